@@ -1,10 +1,12 @@
+use std::any::type_name;
 use std::collections::HashSet;
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, Reply,
-    ReplyOn, Response, StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult, WasmMsg,
+    attr, from_binary, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo,
+    Order, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult,
+    WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
 use cw_utils::parse_instantiate_response_data;
@@ -13,7 +15,8 @@ use astroport::asset::{addr_opt_validate, AssetInfo, PairInfo};
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::factory::{
     Config, ConfigResponse, ExecuteMsg, FeeInfoResponse, InstantiateMsg, MigrateMsg, PairConfig,
-    PairType, PairsResponse, QueryMsg,
+    PairFee, PairMigrationStatus, PairType, PairsMigrationResponse, PairsResponse,
+    PauseInfoResponse, PauseTarget, QueryMsg, UpdateAddr,
 };
 use astroport::generator::ExecuteMsg::DeactivatePool;
 use astroport::pair::InstantiateMsg as PairInstantiateMsg;
@@ -21,11 +24,18 @@ use itertools::Itertools;
 
 use crate::error::ContractError;
 use crate::migration;
-use crate::migration::{migrate_configs, migrate_pair_configs};
+use crate::migration::{
+    migrate_configs, migrate_legacy_pairs, migrate_pair_configs, MIGRATE_LEGACY_PAIRS_LIMIT,
+    PAIRS_V160,
+};
 use crate::querier::query_pair_info;
 use crate::state::{
-    check_asset_infos, pair_key, read_pairs, TmpPairInfo, CONFIG, OWNERSHIP_PROPOSAL, PAIRS,
-    PAIR_CONFIGS, TMP_PAIR_INFO,
+    check_asset_infos, is_paused, load_pair, load_pair_fee, pair_key, pair_type_key,
+    read_failed_migrations, read_pairs, read_pairs_by_asset, read_pending_migrations,
+    PairsMigration, TmpPairInfo, CONFIG, GLOBAL_PAUSE, GUARDIAN, OWNERSHIP_PROPOSAL, PAIRS,
+    PAIRS_BY_ASSET, PAIRS_BY_ASSET_TYPE, PAIRS_BY_TYPE, PAIRS_MIGRATION, PAIRS_TO_MIGRATE,
+    PAIR_CONFIGS, PAIR_FEES, PAIR_FEE_TIERS, PAIR_MIGRATION_STATUSES, PAUSED_PAIRS,
+    PAUSED_PAIR_TYPES, TMP_PAIR_INFO,
 };
 
 /// Contract name that is used for migration.
@@ -34,6 +44,8 @@ const CONTRACT_NAME: &str = "astroport-factory";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// A `reply` call code ID used in a sub-message.
 const INSTANTIATE_PAIR_REPLY_ID: u64 = 1;
+/// A `reply` call code ID used for pair migration sub-messages.
+const MIGRATE_PAIR_REPLY_ID: u64 = 2;
 
 /// Creates a new contract with the specified parameters packed in the `msg` variable.
 ///
@@ -71,10 +83,7 @@ pub fn instantiate(
     }
 
     for pc in msg.pair_configs.iter() {
-        // Validate total and maker fee bps
-        if !pc.valid_fee_bps() {
-            return Err(ContractError::PairConfigInvalidFeeBps {});
-        }
+        check_pair_config(pc)?;
         PAIR_CONFIGS.save(deps.storage, pc.pair_type.to_string(), pc)?;
     }
     CONFIG.save(deps.storage, &config)?;
@@ -112,9 +121,25 @@ pub struct UpdateConfig {
 ///             pair_type,
 ///             asset_infos,
 ///             init_params,
+///             fee,
+///             fee_tier,
 ///         }** Creates a new pair with the specified input parameters.
 ///
-/// * **ExecuteMsg::Deregister { asset_infos }** Removes an existing pair from the factory.
+/// * **ExecuteMsg::UpdatePairFee { pair_addr, fee }** Sets or removes fees for a specific pair.
+///
+/// * **ExecuteMsg::UpdateGuardian { guardian }** Sets or removes the guardian which can pause pairs.
+///
+/// * **ExecuteMsg::Pause { target }** Halts swaps and liquidity provision for the specified pairs.
+///
+/// * **ExecuteMsg::Unpause { target }** Resumes swaps and liquidity provision for the specified pairs.
+///
+/// * **ExecuteMsg::MigratePairs { pair_type, new_code_id, msg, limit }** Migrates the next batch of pairs
+/// of a specific type to a new code ID.
+///
+/// * **ExecuteMsg::MigrateLegacyPairs { limit }** Moves the next batch of pairs registered before
+/// version 1.7.0 to the current storage layout.
+///
+/// * **ExecuteMsg::Deregister { asset_infos, pair_type, fee_tier }** Removes an existing pair from the factory.
 /// * The asset information is for the assets that are traded in the pair.
 ///
 /// * **ExecuteMsg::ProposeNewOwner { owner, expires_in }** Creates a request to change contract ownership.
@@ -152,8 +177,36 @@ pub fn execute(
             pair_type,
             asset_infos,
             init_params,
-        } => execute_create_pair(deps, env, pair_type, asset_infos, init_params),
-        ExecuteMsg::Deregister { asset_infos } => deregister(deps, info, asset_infos),
+            fee,
+            fee_tier,
+        } => execute_create_pair(
+            deps,
+            env,
+            info,
+            pair_type,
+            asset_infos,
+            init_params,
+            fee,
+            fee_tier,
+        ),
+        ExecuteMsg::UpdatePairFee { pair_addr, fee } => {
+            execute_update_pair_fee(deps, info, pair_addr, fee)
+        }
+        ExecuteMsg::UpdateGuardian { guardian } => execute_update_guardian(deps, info, guardian),
+        ExecuteMsg::Pause { target } => execute_set_paused(deps, info, target, true),
+        ExecuteMsg::Unpause { target } => execute_set_paused(deps, info, target, false),
+        ExecuteMsg::MigratePairs {
+            pair_type,
+            new_code_id,
+            msg,
+            limit,
+        } => execute_migrate_pairs(deps, info, pair_type, new_code_id, msg, limit),
+        ExecuteMsg::MigrateLegacyPairs { limit } => execute_migrate_legacy_pairs(deps, info, limit),
+        ExecuteMsg::Deregister {
+            asset_infos,
+            pair_type,
+            fee_tier,
+        } => deregister(deps, info, asset_infos, pair_type, fee_tier),
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config = CONFIG.load(deps.storage)?;
 
@@ -251,10 +304,7 @@ pub fn execute_update_pair_config(
         return Err(ContractError::Unauthorized {});
     }
 
-    // Validate total and maker fee bps
-    if !pair_config.valid_fee_bps() {
-        return Err(ContractError::PairConfigInvalidFeeBps {});
-    }
+    check_pair_config(&pair_config)?;
 
     PAIR_CONFIGS.save(
         deps.storage,
@@ -265,6 +315,27 @@ pub fn execute_update_pair_config(
     Ok(Response::new().add_attribute("action", "update_pair_config"))
 }
 
+/// Validates a pair type configuration.
+fn check_pair_config(pair_config: &PairConfig) -> Result<(), ContractError> {
+    // Validate total and maker fee bps
+    if !pair_config.valid_fee_bps() {
+        return Err(ContractError::PairConfigInvalidFeeBps {});
+    }
+
+    if !pair_config.valid_fee_tiers() {
+        return Err(ContractError::PairConfigInvalidFeeTiers {});
+    }
+
+    // The separator is reserved for fee tier pair keys
+    if let PairType::Custom(name) = &pair_config.pair_type {
+        if name.contains('/') {
+            return Err(ContractError::InvalidPairTypeName(name.clone()));
+        }
+    }
+
+    Ok(())
+}
+
 /// Creates a new pair of `pair_type` with the assets specified in `asset_infos`.
 ///
 /// * **pair_type** is the pair type of the newly created pair.
@@ -272,18 +343,48 @@ pub fn execute_update_pair_config(
 /// * **asset_infos** is a vector with assets for which we create a pair.
 ///
 /// * **init_params** These are packed params used for custom pair types that need extra data to be instantiated.
+///
+/// * **fee** are the fees which override the pair type fees for the new pair. Only the owner can set them.
+///
+/// * **fee_tier** is the fee tier of the new pair. It must be one of the pair type fee tiers.
+#[allow(clippy::too_many_arguments)]
 pub fn execute_create_pair(
     deps: DepsMut,
     env: Env,
+    info: MessageInfo,
     pair_type: PairType,
     asset_infos: Vec<AssetInfo>,
     init_params: Option<Binary>,
+    fee: Option<PairFee>,
+    fee_tier: Option<u16>,
 ) -> Result<Response, ContractError> {
     check_asset_infos(deps.api, &asset_infos)?;
 
     let config = CONFIG.load(deps.storage)?;
 
-    if PAIRS.has(deps.storage, &pair_key(&asset_infos)) {
+    if let Some(fee) = &fee {
+        // Permission check
+        if info.sender != config.owner {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if !fee.valid_fee_bps() {
+            return Err(ContractError::PairConfigInvalidFeeBps {});
+        }
+    }
+
+    // Legacy pairs can't be checked for duplicates before they are moved to the new storage layout
+    if !PAIRS_V160.is_empty(deps.storage) {
+        return Err(ContractError::LegacyPairsNotMigrated {});
+    }
+
+    if PAIRS.has(
+        deps.storage,
+        (
+            pair_key(&asset_infos).as_slice(),
+            pair_type_key(&pair_type, fee_tier),
+        ),
+    ) {
         return Err(ContractError::PairWasCreated {});
     }
 
@@ -297,13 +398,28 @@ pub fn execute_create_pair(
         return Err(ContractError::PairConfigDisabled {});
     }
 
+    if let Some(fee_tier) = fee_tier {
+        if !pair_config.fee_tiers.contains(&fee_tier) {
+            return Err(ContractError::FeeTierNotFound(fee_tier));
+        }
+    }
+
     let pair_key = pair_key(&asset_infos);
-    TMP_PAIR_INFO.save(deps.storage, &TmpPairInfo { pair_key })?;
+    TMP_PAIR_INFO.save(
+        deps.storage,
+        &TmpPairInfo {
+            pair_key,
+            asset_infos: asset_infos.clone(),
+            pair_type,
+            fee,
+            fee_tier,
+        },
+    )?;
 
     let sub_msg: Vec<SubMsg> = vec![SubMsg {
         id: INSTANTIATE_PAIR_REPLY_ID,
         msg: WasmMsg::Instantiate {
-            admin: Some(config.owner.to_string()),
+            admin: Some(env.contract.address.to_string()),
             code_id: pair_config.code_id,
             msg: to_binary(&PairInstantiateMsg {
                 asset_infos: asset_infos.clone(),
@@ -327,6 +443,266 @@ pub fn execute_create_pair(
         ]))
 }
 
+/// Sets or removes fees which override the pair type fees for a specific pair.
+///
+/// * **pair_addr** is the address of the pair.
+///
+/// * **fee** are the new pair fees. If not specified, the pair falls back to its pair type fees.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn execute_update_pair_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_addr: String,
+    fee: Option<PairFee>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+
+    if let Some(fee) = fee {
+        if !fee.valid_fee_bps() {
+            return Err(ContractError::PairConfigInvalidFeeBps {});
+        }
+
+        let pair_info = query_pair_info(&deps.querier, &pair_addr)?;
+        let (_, registered_pair) = load_pair(
+            deps.as_ref(),
+            &pair_info.asset_infos,
+            Some(pair_info.pair_type),
+            PAIR_FEE_TIERS.may_load(deps.storage, &pair_addr)?,
+        )
+        .map_err(|_| ContractError::PairNotRegistered(pair_addr.to_string()))?;
+        if registered_pair != pair_addr {
+            return Err(ContractError::PairNotRegistered(pair_addr.to_string()));
+        }
+
+        PAIR_FEES.save(deps.storage, &pair_addr, &fee)?;
+    } else {
+        PAIR_FEES.remove(deps.storage, &pair_addr);
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_pair_fee"),
+        attr("pair_contract_addr", pair_addr),
+    ]))
+}
+
+/// Sets or removes the guardian which can pause pairs.
+///
+/// * **guardian** is the new guardian.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn execute_update_guardian(
+    deps: DepsMut,
+    info: MessageInfo,
+    guardian: UpdateAddr,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match guardian {
+        UpdateAddr::Set(guardian) => {
+            GUARDIAN.save(deps.storage, &deps.api.addr_validate(&guardian)?)?;
+        }
+        UpdateAddr::Remove {} => GUARDIAN.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_attribute("action", "update_guardian"))
+}
+
+/// Pauses or unpauses swaps and liquidity provision for the pairs specified in `target`.
+/// A pair stays paused while it is paused globally, by its pair type or by itself.
+///
+/// * **target** describes the pairs to pause or unpause.
+///
+/// * **paused** is whether the pairs are paused.
+///
+/// ## Executor
+/// The owner can pause and unpause pairs. The guardian can only pause them.
+pub fn execute_set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    target: PauseTarget,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        if GUARDIAN.may_load(deps.storage)?.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+        if !paused {
+            return Err(ContractError::UnpauseUnauthorized {});
+        }
+    }
+
+    let target = match target {
+        PauseTarget::Global {} => {
+            GLOBAL_PAUSE.save(deps.storage, &paused)?;
+            "global".to_string()
+        }
+        PauseTarget::PairType(pair_type) => {
+            if paused {
+                PAUSED_PAIR_TYPES.save(deps.storage, pair_type.to_string(), &pair_type)?;
+            } else {
+                PAUSED_PAIR_TYPES.remove(deps.storage, pair_type.to_string());
+            }
+            pair_type.to_string()
+        }
+        PauseTarget::Pair(pair_addr) => {
+            let pair_addr = deps.api.addr_validate(&pair_addr)?;
+            if paused {
+                PAUSED_PAIRS.save(deps.storage, &pair_addr, &Empty {})?;
+            } else {
+                PAUSED_PAIRS.remove(deps.storage, &pair_addr);
+            }
+            pair_addr.to_string()
+        }
+    };
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", if paused { "pause" } else { "unpause" }),
+        attr("target", target),
+    ]))
+}
+
+/// Migrates the next batch of pairs of `pair_type` to `new_code_id`. Pairs which were not migrated
+/// yet are migrated first. Once all pairs were tried, pairs which failed to migrate are retried.
+/// Calling this with other parameters than the latest migration starts a new migration.
+///
+/// * **pair_type** is the type of pairs to migrate.
+///
+/// * **new_code_id** is the code ID to migrate the pairs to.
+///
+/// * **msg** is the migration message sent to each pair.
+///
+/// * **limit** is the number of pairs to migrate in this batch.
+///
+/// ## Executor
+/// Only the owner can execute this. The factory must be the admin of the pairs. Pairs created
+/// by factory versions before 1.7.0 are administered by the owner, who has to hand them over
+/// to the factory with `MsgUpdateAdmin` first.
+pub fn execute_migrate_pairs(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_type: PairType,
+    new_code_id: u64,
+    msg: Binary,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut migration = match PAIRS_MIGRATION.may_load(deps.storage)? {
+        Some(migration)
+            if migration.pair_type == pair_type
+                && migration.new_code_id == new_code_id
+                && migration.msg == msg =>
+        {
+            migration
+        }
+        latest => PairsMigration {
+            id: latest.map_or(0, |migration| migration.id + 1),
+            pair_type,
+            new_code_id,
+            msg,
+            cursor: None,
+            finished: false,
+        },
+    };
+
+    let pending = read_pending_migrations(deps.storage, &migration, limit)?;
+    let pairs: Vec<Addr> = if pending.is_empty() {
+        migration.finished = true;
+        read_failed_migrations(deps.storage, &migration, limit)?
+            .into_iter()
+            .map(|(pair_addr, _)| pair_addr)
+            .collect()
+    } else {
+        migration.cursor = pending.last().map(|(key, _)| key.clone());
+        pending
+            .into_iter()
+            .map(|(_, pair_addr)| pair_addr)
+            .collect()
+    };
+
+    if pairs.is_empty() {
+        return Err(ContractError::NoPairsToMigrate {});
+    }
+
+    PAIRS_MIGRATION.save(deps.storage, &migration)?;
+    PAIRS_TO_MIGRATE.save(deps.storage, &pairs)?;
+
+    let messages = pairs.iter().map(|pair_addr| {
+        SubMsg::reply_always(
+            WasmMsg::Migrate {
+                contract_addr: pair_addr.to_string(),
+                new_code_id: migration.new_code_id,
+                msg: migration.msg.clone(),
+            },
+            MIGRATE_PAIR_REPLY_ID,
+        )
+    });
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            attr("action", "migrate_pairs"),
+            attr("pair_type", migration.pair_type.to_string()),
+            attr("new_code_id", migration.new_code_id.to_string()),
+            attr("pairs", pairs.len().to_string()),
+        ]))
+}
+
+/// Moves the next batch of pairs registered before version 1.7.0 to the current storage layout.
+/// The contract migration moves only the first [`MIGRATE_LEGACY_PAIRS_LIMIT`] pairs to bound its gas usage.
+///
+/// * **limit** is the number of pairs to move in this batch.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn execute_migrate_legacy_pairs(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let limit = limit.map_or(MIGRATE_LEGACY_PAIRS_LIMIT, |limit| limit as usize);
+    let migrated = migrate_legacy_pairs(deps.branch(), limit)?;
+    if migrated == 0 {
+        return Err(ContractError::NoPairsToMigrate {});
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "migrate_legacy_pairs"),
+        attr("pairs", migrated.to_string()),
+        attr("finished", PAIRS_V160.is_empty(deps.storage).to_string()),
+    ]))
+}
+
 /// The entry point to the contract for processing replies from submessages.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
@@ -339,7 +715,11 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
                 }),
         } => {
             let tmp = TMP_PAIR_INFO.load(deps.storage)?;
-            if PAIRS.has(deps.storage, &tmp.pair_key) {
+            let key = (
+                tmp.pair_key.as_slice(),
+                pair_type_key(&tmp.pair_type, tmp.fee_tier),
+            );
+            if PAIRS.has(deps.storage, key.clone()) {
                 return Err(ContractError::PairWasRegistered {});
             }
 
@@ -348,13 +728,68 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 
             let pair_contract = deps.api.addr_validate(&init_response.contract_address)?;
 
-            PAIRS.save(deps.storage, &tmp.pair_key, &pair_contract)?;
+            PAIRS.save(deps.storage, key.clone(), &pair_contract)?;
+            PAIRS_BY_TYPE.save(
+                deps.storage,
+                (tmp.pair_type.to_string().as_str(), key.0, key.1),
+                &pair_contract,
+            )?;
+            if let Some(fee) = &tmp.fee {
+                PAIR_FEES.save(deps.storage, &pair_contract, fee)?;
+            }
+            if let Some(fee_tier) = tmp.fee_tier {
+                PAIR_FEE_TIERS.save(deps.storage, &pair_contract, &fee_tier)?;
+            }
+            for asset_info in &tmp.asset_infos {
+                PAIRS_BY_ASSET.save(
+                    deps.storage,
+                    (asset_info.as_bytes(), &pair_contract),
+                    &tmp.pair_type,
+                )?;
+                PAIRS_BY_ASSET_TYPE.save(
+                    deps.storage,
+                    (
+                        asset_info.as_bytes(),
+                        tmp.pair_type.to_string().as_str(),
+                        &pair_contract,
+                    ),
+                    &Empty {},
+                )?;
+            }
 
             Ok(Response::new().add_attributes(vec![
                 attr("action", "register"),
                 attr("pair_contract_addr", pair_contract),
             ]))
         }
+        Reply {
+            id: MIGRATE_PAIR_REPLY_ID,
+            result,
+        } => {
+            // Replies come in the order of the migration messages
+            let mut pairs = PAIRS_TO_MIGRATE.load(deps.storage)?;
+            if pairs.is_empty() {
+                return Err(ContractError::FailedToParseReply {});
+            }
+            let pair_addr = pairs.remove(0);
+            PAIRS_TO_MIGRATE.save(deps.storage, &pairs)?;
+
+            let migration = PAIRS_MIGRATION.load(deps.storage)?;
+            let status = match result {
+                SubMsgResult::Ok(_) => PairMigrationStatus::Migrated {},
+                SubMsgResult::Err(error) => PairMigrationStatus::Failed { error },
+            };
+            PAIR_MIGRATION_STATUSES.save(deps.storage, (migration.id, &pair_addr), &status)?;
+
+            Ok(Response::new().add_attributes(vec![
+                attr("action", "migrate_pair"),
+                attr("pair_contract_addr", pair_addr),
+                attr(
+                    "migrated",
+                    matches!(status, PairMigrationStatus::Migrated {}).to_string(),
+                ),
+            ]))
+        }
         _ => Err(ContractError::FailedToParseReply {}),
     }
 }
@@ -363,12 +798,18 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///
 /// * **asset_infos** is a vector with assets for which we deregister the pair.
 ///
+/// * **pair_type** is the type of the pair to deregister.
+///
+/// * **fee_tier** is the fee tier of the pair to deregister.
+///
 /// ## Executor
 /// Only the owner can execute this.
 pub fn deregister(
     deps: DepsMut,
     info: MessageInfo,
     asset_infos: Vec<AssetInfo>,
+    pair_type: PairType,
+    fee_tier: Option<u16>,
 ) -> Result<Response, ContractError> {
     check_asset_infos(deps.api, &asset_infos)?;
 
@@ -378,8 +819,36 @@ pub fn deregister(
         return Err(ContractError::Unauthorized {});
     }
 
-    let pair_addr = PAIRS.load(deps.storage, &pair_key(&asset_infos))?;
-    PAIRS.remove(deps.storage, &pair_key(&asset_infos));
+    let (pair_type_key, pair_addr) = load_pair(
+        deps.as_ref(),
+        &asset_infos,
+        Some(pair_type.clone()),
+        fee_tier,
+    )?;
+    let key = pair_key(&asset_infos);
+    PAIRS.remove(deps.storage, (key.as_slice(), pair_type_key.clone()));
+    PAIRS_BY_TYPE.remove(
+        deps.storage,
+        (
+            pair_type.to_string().as_str(),
+            key.as_slice(),
+            pair_type_key,
+        ),
+    );
+    PAIRS_V160.remove(deps.storage, &key);
+    PAIR_FEES.remove(deps.storage, &pair_addr);
+    PAIR_FEE_TIERS.remove(deps.storage, &pair_addr);
+    for asset_info in &asset_infos {
+        PAIRS_BY_ASSET.remove(deps.storage, (asset_info.as_bytes(), &pair_addr));
+        PAIRS_BY_ASSET_TYPE.remove(
+            deps.storage,
+            (
+                asset_info.as_bytes(),
+                pair_type.to_string().as_str(),
+                &pair_addr,
+            ),
+        );
+    }
 
     let mut messages: Vec<CosmosMsg> = vec![];
     if let Some(generator) = config.generator_address {
@@ -406,27 +875,85 @@ pub fn deregister(
 /// ## Queries
 /// * **QueryMsg::Config {}** Returns general contract parameters using a custom [`ConfigResponse`] structure.
 ///
-/// * **QueryMsg::Pair { asset_infos }** Returns a [`PairInfo`] object with information about a specific Astroport pair.
+/// * **QueryMsg::Pair { asset_infos, pair_type, fee_tier }** Returns a [`PairInfo`] object with information about a specific Astroport pair.
 ///
-/// * **QueryMsg::Pairs { start_after, limit }** Returns an array that contains items of type [`PairInfo`].
+/// * **QueryMsg::Pairs { start_after, start_after_pair_type, start_after_fee_tier, limit }** Returns an array that contains items of type [`PairInfo`].
 /// This returns information about multiple Astroport pairs
 ///
-/// * **QueryMsg::FeeInfo { pair_type }** Returns the fee structure (total and maker fees) for a specific pair type.
+/// * **QueryMsg::FeeInfo { pair_type, pair_addr }** Returns the fee structure (total and maker fees) for a specific pair
+/// or pair type.
 ///
 /// * **QueryMsg::BlacklistedPairTypes {}** Returns a vector that contains blacklisted pair types (pair types that cannot get ASTRO emissions).
+///
+/// * **QueryMsg::FeeTiers { pair_type }** Returns a vector with the fee tiers available for a pair type.
+///
+/// * **QueryMsg::PauseInfo {}** Returns the current state of the pause registry using a [`PauseInfoResponse`] object.
+///
+/// * **QueryMsg::IsPaused { pair_type, pair_addr }** Returns whether a specific pair is paused.
+///
+/// * **QueryMsg::PairsMigration { limit }** Returns the pending and failed pairs of the latest pairs migration.
+///
+/// * **QueryMsg::PairsByAsset { asset_info, pair_type, start_after, limit }** Returns an array that contains
+/// items of type [`PairInfo`] for pairs which contain a specific asset.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Pair { asset_infos } => to_binary(&query_pair(deps, asset_infos)?),
-        QueryMsg::Pairs { start_after, limit } => {
-            to_binary(&query_pairs(deps, start_after, limit)?)
-        }
-        QueryMsg::FeeInfo { pair_type } => to_binary(&query_fee_info(deps, pair_type)?),
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type,
+            fee_tier,
+        } => to_binary(&query_pair(deps, asset_infos, pair_type, fee_tier)?),
+        QueryMsg::Pairs {
+            start_after,
+            start_after_pair_type,
+            start_after_fee_tier,
+            limit,
+        } => to_binary(&query_pairs(
+            deps,
+            start_after,
+            start_after_pair_type,
+            start_after_fee_tier,
+            limit,
+        )?),
+        QueryMsg::FeeInfo {
+            pair_type,
+            pair_addr,
+        } => to_binary(&query_fee_info(deps, pair_type, pair_addr)?),
         QueryMsg::BlacklistedPairTypes {} => to_binary(&query_blacklisted_pair_types(deps)?),
+        QueryMsg::FeeTiers { pair_type } => to_binary(&query_fee_tiers(deps, pair_type)?),
+        QueryMsg::PauseInfo {} => to_binary(&query_pause_info(deps)?),
+        QueryMsg::IsPaused {
+            pair_type,
+            pair_addr,
+        } => to_binary(&is_paused(
+            deps.storage,
+            &pair_type,
+            &deps.api.addr_validate(&pair_addr)?,
+        )?),
+        QueryMsg::PairsMigration { limit } => to_binary(&query_pairs_migration(deps, limit)?),
+        QueryMsg::PairsByAsset {
+            asset_info,
+            pair_type,
+            start_after,
+            limit,
+        } => to_binary(&query_pairs_by_asset(
+            deps,
+            asset_info,
+            pair_type,
+            start_after,
+            limit,
+        )?),
     }
 }
 
+/// Returns a vector with the fee tiers (total fees in bps) available for a pair type
+pub fn query_fee_tiers(deps: Deps, pair_type: PairType) -> StdResult<Vec<u16>> {
+    let pair_config = PAIR_CONFIGS.load(deps.storage, pair_type.to_string())?;
+
+    Ok(pair_config.fee_tiers)
+}
+
 /// Returns a vector that contains blacklisted pair types
 pub fn query_blacklisted_pair_types(deps: Deps) -> StdResult<Vec<PairType>> {
     PAIR_CONFIGS
@@ -465,39 +992,140 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
 
 /// Returns a pair's data using the assets in `asset_infos` as input (those being the assets that are traded in the pair).
 /// * **asset_infos** is a vector with assets traded in the pair.
-pub fn query_pair(deps: Deps, asset_infos: Vec<AssetInfo>) -> StdResult<PairInfo> {
-    let pair_addr = PAIRS.load(deps.storage, &pair_key(&asset_infos))?;
-    query_pair_info(&deps.querier, pair_addr)
+///
+/// * **pair_type** is the type of the pair. If not specified, the pair with the lowest pair type key is returned.
+///
+/// * **fee_tier** is the fee tier of the pair.
+pub fn query_pair(
+    deps: Deps,
+    asset_infos: Vec<AssetInfo>,
+    pair_type: Option<PairType>,
+    fee_tier: Option<u16>,
+) -> StdResult<PairInfo> {
+    let (_, pair_addr) = load_pair(deps, &asset_infos, pair_type, fee_tier)?;
+    query_pair_with_fee_tier(deps, &pair_addr)
+}
+
+/// Returns a pair's data together with its fee tier.
+fn query_pair_with_fee_tier(deps: Deps, pair_addr: &Addr) -> StdResult<PairInfo> {
+    let mut pair_info = query_pair_info(&deps.querier, pair_addr)?;
+    pair_info.fee_tier = PAIR_FEE_TIERS.may_load(deps.storage, pair_addr)?;
+
+    Ok(pair_info)
 }
 
 /// Returns a vector with pair data that contains items of type [`PairInfo`]. Querying starts at `start_after` and returns `limit` pairs.
 /// * **start_after** is a field which accepts a vector with items of type [`AssetInfo`].
 /// This is the pair from which we start a query.
 ///
+/// * **start_after_pair_type** is the type of the `start_after` pair.
+///
+/// * **start_after_fee_tier** is the fee tier of the `start_after` pair.
+///
 /// * **limit** sets the number of pairs to be retrieved.
 pub fn query_pairs(
     deps: Deps,
     start_after: Option<Vec<AssetInfo>>,
+    start_after_pair_type: Option<PairType>,
+    start_after_fee_tier: Option<u16>,
+    limit: Option<u32>,
+) -> StdResult<PairsResponse> {
+    let pairs = read_pairs(
+        deps,
+        start_after,
+        start_after_pair_type,
+        start_after_fee_tier,
+        limit,
+    )?
+    .iter()
+    .map(|pair_addr| query_pair_with_fee_tier(deps, pair_addr))
+    .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PairsResponse { pairs })
+}
+
+/// Returns a vector with data of pairs which contain `asset_info`. Querying starts after the pair
+/// `start_after` and returns `limit` pairs.
+/// * **asset_info** is the asset which the returned pairs contain.
+///
+/// * **pair_type** is the type of pairs to return. If not specified, pairs of all types are returned.
+///
+/// * **start_after** is the pair contract address from which we start a query.
+///
+/// * **limit** sets the number of pairs to be retrieved.
+pub fn query_pairs_by_asset(
+    deps: Deps,
+    asset_info: AssetInfo,
+    pair_type: Option<PairType>,
+    start_after: Option<String>,
     limit: Option<u32>,
 ) -> StdResult<PairsResponse> {
-    let pairs = read_pairs(deps, start_after, limit)?
+    let start_after = addr_opt_validate(deps.api, &start_after)?;
+    let pairs = read_pairs_by_asset(deps, &asset_info, pair_type, start_after, limit)?
         .iter()
-        .map(|pair_addr| query_pair_info(&deps.querier, pair_addr))
+        .map(|pair_addr| query_pair_with_fee_tier(deps, pair_addr))
         .collect::<StdResult<Vec<_>>>()?;
 
     Ok(PairsResponse { pairs })
 }
 
-/// Returns the fee setup for a specific pair type using a [`FeeInfoResponse`] struct.
+/// Returns the current state of the pause registry using a [`PauseInfoResponse`] object.
+pub fn query_pause_info(deps: Deps) -> StdResult<PauseInfoResponse> {
+    Ok(PauseInfoResponse {
+        guardian: GUARDIAN.may_load(deps.storage)?,
+        global: GLOBAL_PAUSE.may_load(deps.storage)?.unwrap_or_default(),
+        pair_types: PAUSED_PAIR_TYPES
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| Ok(item?.1))
+            .collect::<StdResult<Vec<_>>>()?,
+        pairs: PAUSED_PAIRS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?,
+    })
+}
+
+/// Returns the state of the latest pairs migration using a [`PairsMigrationResponse`] object.
+/// If no pairs were migrated yet, it returns `None`.
+///
+/// * **limit** sets the maximum number of pending and failed pairs to be retrieved.
+pub fn query_pairs_migration(
+    deps: Deps,
+    limit: Option<u32>,
+) -> StdResult<Option<PairsMigrationResponse>> {
+    PAIRS_MIGRATION
+        .may_load(deps.storage)?
+        .map(|migration| {
+            Ok(PairsMigrationResponse {
+                pending: read_pending_migrations(deps.storage, &migration, limit)?
+                    .into_iter()
+                    .map(|(_, pair_addr)| pair_addr)
+                    .collect(),
+                failed: read_failed_migrations(deps.storage, &migration, limit)?,
+                pair_type: migration.pair_type,
+                new_code_id: migration.new_code_id,
+            })
+        })
+        .transpose()
+}
+
+/// Returns the fee setup for a specific pair or pair type using a [`FeeInfoResponse`] struct.
 /// * **pair_type** is a struct that represents the fee information (total and maker fees) for a specific pair type.
-pub fn query_fee_info(deps: Deps, pair_type: PairType) -> StdResult<FeeInfoResponse> {
+///
+/// * **pair_addr** is the address of the pair. Fees set for the pair take precedence over the pair type fees.
+pub fn query_fee_info(
+    deps: Deps,
+    pair_type: PairType,
+    pair_addr: Option<String>,
+) -> StdResult<FeeInfoResponse> {
     let config = CONFIG.load(deps.storage)?;
-    let pair_config = PAIR_CONFIGS.load(deps.storage, pair_type.to_string())?;
+    let pair_addr = addr_opt_validate(deps.api, &pair_addr)?;
+    let fee = load_pair_fee(deps.storage, pair_addr.as_ref(), &pair_type)?
+        .ok_or_else(|| StdError::not_found(type_name::<PairConfig>()))?;
 
     Ok(FeeInfoResponse {
         fee_address: config.fee_address,
-        total_fee_bps: pair_config.total_fee_bps,
-        maker_fee_bps: pair_config.maker_fee_bps,
+        total_fee_bps: fee.total_fee_bps,
+        maker_fee_bps: fee.maker_fee_bps,
     })
 }
 
@@ -511,10 +1139,14 @@ pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response
             "1.2.0" | "1.2.1" => {
                 let msg: migration::MigrationMsg = from_binary(&msg.params)?;
                 migrate_configs(&mut deps, &msg)?;
+                migrate_legacy_pairs(deps.branch(), MIGRATE_LEGACY_PAIRS_LIMIT)?;
+            }
+            "1.3.0" | "1.5.1" | "1.6.0" => {
+                migrate_legacy_pairs(deps.branch(), MIGRATE_LEGACY_PAIRS_LIMIT)?;
             }
-            "1.3.0" | "1.5.1" => {}
             "1.3.1" | "1.5.0" => {
                 migrate_pair_configs(deps.storage)?;
+                migrate_legacy_pairs(deps.branch(), MIGRATE_LEGACY_PAIRS_LIMIT)?;
             }
             _ => return Err(ContractError::MigrationError {}),
         },
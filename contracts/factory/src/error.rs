@@ -22,15 +22,36 @@ pub enum ContractError {
     #[error("Fee bps in pair config must be smaller than or equal to 10,000")]
     PairConfigInvalidFeeBps {},
 
+    #[error("Fee tiers in pair config must be unique and smaller than or equal to 10,000 bps")]
+    PairConfigInvalidFeeTiers {},
+
+    #[error("Custom pair type name {0} must not contain '/'")]
+    InvalidPairTypeName(String),
+
+    #[error("Fee tier {0} bps is not available for the pair type")]
+    FeeTierNotFound(u16),
+
     #[error("Pair config not found")]
     PairConfigNotFound {},
 
+    #[error("Pair {0} is not registered in the factory")]
+    PairNotRegistered(String),
+
     #[error("Pair config disabled")]
     PairConfigDisabled {},
 
     #[error("Doubling assets in asset infos")]
     DoublingAssets {},
 
+    #[error("Only the owner can unpause pairs")]
+    UnpauseUnauthorized {},
+
+    #[error("No pairs left to migrate")]
+    NoPairsToMigrate {},
+
+    #[error("Pairs registered before version 1.7.0 must be migrated first")]
+    LegacyPairsNotMigrated {},
+
     #[error("Contract can't be migrated!")]
     MigrationError {},
 
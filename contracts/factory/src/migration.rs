@@ -1,7 +1,10 @@
-use crate::state::{CONFIG, PAIR_CONFIGS};
+use crate::querier::query_pair_info;
+use crate::state::{
+    pair_key, CONFIG, PAIRS, PAIRS_BY_ASSET, PAIRS_BY_ASSET_TYPE, PAIRS_BY_TYPE, PAIR_CONFIGS,
+};
 use astroport::factory::{Config, PairConfig, PairType};
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, DepsMut, StdError, StdResult, Storage};
+use cosmwasm_std::{Addr, DepsMut, Empty, Order, StdError, StdResult, Storage};
 use cw_storage_plus::{Item, Map};
 
 /// This structure describes a contract migration message.
@@ -92,6 +95,7 @@ pub fn migrate_pair_configs(storage: &mut dyn Storage) -> Result<(), StdError> {
             maker_fee_bps: old_pair_configs.maker_fee_bps,
             is_disabled: old_pair_configs.is_disabled,
             is_generator_disabled: old_pair_configs.is_generator_disabled,
+            fee_tiers: vec![],
         };
 
         if key != pair_type.to_string() {
@@ -104,3 +108,55 @@ pub fn migrate_pair_configs(storage: &mut dyn Storage) -> Result<(), StdError> {
 
     Ok(())
 }
+
+/// Pairs keyed by their asset set only. Used before several pairs of different types
+/// could be created for the same assets.
+pub const PAIRS_V160: Map<&[u8], Addr> = Map::new("pair_info");
+
+/// The number of pairs moved to the new storage layout during the contract migration.
+/// The rest of them are moved with [`ExecuteMsg::MigrateLegacyPairs`](astroport::factory::ExecuteMsg::MigrateLegacyPairs).
+pub const MIGRATE_LEGACY_PAIRS_LIMIT: usize = 50;
+
+/// Moves at most `limit` pairs from [`PAIRS_V160`] to [`PAIRS`] keying them by asset set and pair type.
+/// The pairs are also indexed by their assets in [`PAIRS_BY_ASSET`] and [`PAIRS_BY_ASSET_TYPE`]
+/// and by their type in [`PAIRS_BY_TYPE`].
+/// Returns the number of moved pairs.
+pub fn migrate_legacy_pairs(deps: DepsMut, limit: usize) -> StdResult<usize> {
+    let old_pairs = PAIRS_V160
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let migrated = old_pairs.len();
+
+    for (key, pair_addr) in old_pairs {
+        let pair_info = query_pair_info(&deps.querier, &pair_addr)?;
+
+        let pair_type = pair_info.pair_type.to_string();
+        let asset_key = pair_key(&pair_info.asset_infos);
+        PAIRS.save(
+            deps.storage,
+            (asset_key.as_slice(), pair_type.clone()),
+            &pair_addr,
+        )?;
+        PAIRS_BY_TYPE.save(
+            deps.storage,
+            (pair_type.as_str(), asset_key.as_slice(), pair_type.clone()),
+            &pair_addr,
+        )?;
+        for asset_info in &pair_info.asset_infos {
+            PAIRS_BY_ASSET.save(
+                deps.storage,
+                (asset_info.as_bytes(), &pair_addr),
+                &pair_info.pair_type,
+            )?;
+            PAIRS_BY_ASSET_TYPE.save(
+                deps.storage,
+                (asset_info.as_bytes(), pair_type.as_str(), &pair_addr),
+                &Empty {},
+            )?;
+        }
+        PAIRS_V160.remove(deps.storage, &key);
+    }
+
+    Ok(migrated)
+}
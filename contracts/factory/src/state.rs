@@ -1,16 +1,22 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Api, Deps, Order, StdResult};
-use cw_storage_plus::{Bound, Item, Map};
+use cosmwasm_std::{Addr, Api, Binary, Deps, Empty, Order, StdError, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map, PrefixBound};
 use itertools::Itertools;
 
 use crate::error::ContractError;
+use crate::migration::PAIRS_V160;
+use crate::querier::query_pair_info;
 use astroport::asset::AssetInfo;
 use astroport::common::OwnershipProposal;
-use astroport::factory::{Config, PairConfig};
+use astroport::factory::{Config, PairConfig, PairFee, PairMigrationStatus, PairType};
 /// This is an intermediate structure for storing a pair's key. It is used in a submessage response.
 #[cw_serde]
 pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
+    pub asset_infos: Vec<AssetInfo>,
+    pub pair_type: PairType,
+    pub fee: Option<PairFee>,
+    pub fee_tier: Option<u16>,
 }
 
 /// Saves a pair's key
@@ -19,8 +25,27 @@ pub const TMP_PAIR_INFO: Item<TmpPairInfo> = Item::new("tmp_pair_info");
 /// Saves factory settings
 pub const CONFIG: Item<Config> = Item::new("config");
 
-/// Saves created pairs (from olders to latest)
-pub const PAIRS: Map<&[u8], Addr> = Map::new("pair_info");
+/// Saves created pairs by their asset set key and pair type key (see [`pair_type_key`])
+pub const PAIRS: Map<(&[u8], String), Addr> = Map::new("pairs");
+
+/// Indexes pairs in [`PAIRS`] by their pair type regardless of the fee tier. Pairs of one type are
+/// migrated by ranging over it, so pairs of other types are never read
+pub const PAIRS_BY_TYPE: Map<(&str, &[u8], String), Addr> = Map::new("pairs_by_type");
+
+/// Returns the key which identifies pairs of the specified type and fee tier in [`PAIRS`].
+/// Pairs created without a fee tier are keyed by their pair type only.
+pub fn pair_type_key(pair_type: &PairType, fee_tier: Option<u16>) -> String {
+    match fee_tier {
+        Some(fee_tier) => format!("{pair_type}/{fee_tier}"),
+        None => pair_type.to_string(),
+    }
+}
+
+/// Saves the types of created pairs by their assets and pair addresses
+pub const PAIRS_BY_ASSET: Map<(&[u8], &Addr), PairType> = Map::new("pairs_by_asset");
+
+/// Indexes pairs by their assets and pair type, so pairs of one type are read without skipping other pairs
+pub const PAIRS_BY_ASSET_TYPE: Map<(&[u8], &str, &Addr), Empty> = Map::new("pairs_by_asset_type");
 
 /// Calculates a pair key from the specified parameters in the `asset_infos` variable.
 ///
@@ -38,61 +63,189 @@ pub fn pair_key(asset_infos: &[AssetInfo]) -> Vec<u8> {
 /// Saves pair type configurations
 pub const PAIR_CONFIGS: Map<String, PairConfig> = Map::new("pair_configs");
 
+/// Saves fees which override the pair type fees for specific pairs
+pub const PAIR_FEES: Map<&Addr, PairFee> = Map::new("pair_fees");
+
+/// Saves the fee tiers of pairs which were created with one
+pub const PAIR_FEE_TIERS: Map<&Addr, u16> = Map::new("pair_fee_tiers");
+
+/// Returns the fees charged by a pair. Fees set for the pair take precedence over its fee tier,
+/// which in turn takes precedence over its pair type fees. Pairs with a fee tier share
+/// the pair type maker fee.
+///
+/// `pair_addr` is the pair address. If it is not specified, the pair type fees are returned.
+pub fn load_pair_fee(
+    storage: &dyn Storage,
+    pair_addr: Option<&Addr>,
+    pair_type: &PairType,
+) -> StdResult<Option<PairFee>> {
+    if let Some(fee) = pair_addr
+        .map(|addr| PAIR_FEES.may_load(storage, addr))
+        .transpose()?
+        .flatten()
+    {
+        return Ok(Some(fee));
+    }
+
+    let fee_tier = pair_addr
+        .map(|addr| PAIR_FEE_TIERS.may_load(storage, addr))
+        .transpose()?
+        .flatten();
+
+    Ok(PAIR_CONFIGS
+        .may_load(storage, pair_type.to_string())?
+        .map(|pair_config| PairFee {
+            total_fee_bps: fee_tier.unwrap_or(pair_config.total_fee_bps),
+            maker_fee_bps: pair_config.maker_fee_bps,
+        }))
+}
+
 /// ## Pagination settings
 /// The maximum limit for reading pairs from [`PAIRS`]
 const MAX_LIMIT: u32 = 30;
 /// The default limit for reading pairs from [`PAIRS`]
 const DEFAULT_LIMIT: u32 = 10;
 
-/// Reads pairs from the [`PAIRS`] vector according to the `start_after` and `limit` variables.
-/// Otherwise, it returns the default number of pairs, starting from the oldest one.
+/// Reads pairs from the [`PAIRS`] map according to the `start_after`, `start_after_pair_type`,
+/// `start_after_fee_tier` and `limit` variables.
+/// Otherwise, it returns the default number of pairs, starting from the first one.
 ///
 /// `start_after` is the pair from which the function starts to fetch results.
 ///
+/// `start_after_pair_type` is the type of the `start_after` pair. If it is not specified,
+/// all pairs for the `start_after` assets are skipped.
+///
+/// `start_after_fee_tier` is the fee tier of the `start_after` pair.
+///
 /// `limit` is the number of items to retrieve.
 pub fn read_pairs(
     deps: Deps,
     start_after: Option<Vec<AssetInfo>>,
+    start_after_pair_type: Option<PairType>,
+    start_after_fee_tier: Option<u16>,
     limit: Option<u32>,
 ) -> StdResult<Vec<Addr>> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_key = start_after.as_deref().map(pair_key);
 
-    if let Some(start) = calc_range_start(start_after) {
-        PAIRS
-            .range(
-                deps.storage,
-                Some(Bound::exclusive(start.as_slice())),
-                None,
-                Order::Ascending,
-            )
+    let pairs = match (&start_key, start_after_pair_type) {
+        (Some(start), Some(pair_type)) => PAIRS.range(
+            deps.storage,
+            Some(Bound::exclusive((
+                start.as_slice(),
+                pair_type_key(&pair_type, start_after_fee_tier),
+            ))),
+            None,
+            Order::Ascending,
+        ),
+        (Some(start), None) => PAIRS.prefix_range(
+            deps.storage,
+            Some(PrefixBound::exclusive(start.as_slice())),
+            None,
+            Order::Ascending,
+        ),
+        (None, _) => PAIRS.range(deps.storage, None, None, Order::Ascending),
+    };
+
+    // Pairs which were not moved from the legacy storage yet have no other pairs for their assets
+    let legacy_pairs = PAIRS_V160.range(
+        deps.storage,
+        start_key.as_deref().map(Bound::exclusive),
+        None,
+        Order::Ascending,
+    );
+
+    pairs
+        .map(|item| item.map(|((key, _), pair_addr)| (key, pair_addr)))
+        .merge_by(legacy_pairs, |a, b| match (a, b) {
+            (Ok((a, _)), Ok((b, _))) => a <= b,
+            _ => true,
+        })
+        .take(limit)
+        .map(|item| {
+            let (_, pair_addr) = item?;
+            Ok(pair_addr)
+        })
+        .collect()
+}
+
+/// Reads the addresses of pairs which contain `asset_info` from the [`PAIRS_BY_ASSET`] map
+/// according to the `pair_type`, `start_after` and `limit` variables.
+///
+/// `pair_type` is the type of pairs to return. If it is not specified, pairs of all types are returned.
+///
+/// `start_after` is the pair address from which the function starts to fetch results.
+///
+/// `limit` is the number of items to retrieve.
+pub fn read_pairs_by_asset(
+    deps: Deps,
+    asset_info: &AssetInfo,
+    pair_type: Option<PairType>,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Addr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    match pair_type {
+        Some(pair_type) => PAIRS_BY_ASSET_TYPE
+            .prefix((asset_info.as_bytes(), pair_type.to_string().as_str()))
+            .keys(deps.storage, start, None, Order::Ascending)
             .take(limit)
-            .map(|item| {
-                let (_, pair_addr) = item?;
-                Ok(pair_addr)
-            })
-            .collect()
-    } else {
-        PAIRS
-            .range(deps.storage, None, None, Order::Ascending)
+            .collect(),
+        None => PAIRS_BY_ASSET
+            .prefix(asset_info.as_bytes())
+            .keys(deps.storage, start, None, Order::Ascending)
             .take(limit)
-            .map(|item| {
-                let (_, pair_addr) = item?;
-                Ok(pair_addr)
-            })
-            .collect()
+            .collect(),
     }
 }
 
-/// Calculates the key of a pair from which to start reading data.
+/// Returns the pair type key and the address of a pair with the specified assets.
+///
+/// `pair_type` is the type of the pair to look for. If it is not specified, the pair with
+/// the lowest pair type key (see [`pair_type_key`]) among the pairs registered for the given
+/// assets is returned, so the result does not depend on the order pairs were created in.
+///
+/// `fee_tier` is the fee tier of the pair to look for. It is only used together with `pair_type`.
 ///
-/// `start_after` is an [`Option`] type that accepts [`AssetInfo`] elements.
-/// It is the token pair which we use to determine the start index for a range when returning data for multiple pairs
-fn calc_range_start(start_after: Option<Vec<AssetInfo>>) -> Option<Vec<u8>> {
-    start_after.map(|ref asset| {
-        let mut key = pair_key(asset);
-        key.push(1);
-        key
-    })
+/// Pairs registered before version 1.7.0 which were not moved from [`PAIRS_V160`] yet are also found.
+pub fn load_pair(
+    deps: Deps,
+    asset_infos: &[AssetInfo],
+    pair_type: Option<PairType>,
+    fee_tier: Option<u16>,
+) -> StdResult<(String, Addr)> {
+    let key = pair_key(asset_infos);
+
+    let pair = if let Some(pair_type) = &pair_type {
+        let pair_type = pair_type_key(pair_type, fee_tier);
+        PAIRS
+            .may_load(deps.storage, (key.as_slice(), pair_type.clone()))?
+            .map(|pair_addr| (pair_type, pair_addr))
+    } else {
+        PAIRS
+            .prefix(key.as_slice())
+            .range(deps.storage, None, None, Order::Ascending)
+            .next()
+            .transpose()?
+    };
+    if let Some(pair) = pair {
+        return Ok(pair);
+    }
+
+    // Legacy pairs are keyed by their assets only, so their pair type is queried from the pair itself
+    let pair_addr = PAIRS_V160
+        .may_load(deps.storage, &key)?
+        .ok_or_else(|| StdError::not_found("Pair"))?;
+    let legacy_pair_type = query_pair_info(&deps.querier, &pair_addr)?.pair_type;
+    match pair_type {
+        Some(pair_type) if pair_type != legacy_pair_type || fee_tier.is_some() => {
+            Err(StdError::not_found("Pair"))
+        }
+        _ => Ok((legacy_pair_type.to_string(), pair_addr)),
+    }
 }
 
 pub(crate) fn check_asset_infos(
@@ -109,12 +262,105 @@ pub(crate) fn check_asset_infos(
         .map_err(Into::into)
 }
 
+/// Saves the address which can pause pairs besides the owner
+pub const GUARDIAN: Item<Addr> = Item::new("guardian");
+
+/// Saves whether all pairs are paused
+pub const GLOBAL_PAUSE: Item<bool> = Item::new("global_pause");
+
+/// Saves paused pair types by their string representation
+pub const PAUSED_PAIR_TYPES: Map<String, PairType> = Map::new("paused_pair_types");
+
+/// Saves paused pairs
+pub const PAUSED_PAIRS: Map<&Addr, Empty> = Map::new("paused_pairs");
+
+/// Returns whether a pair is paused either globally, by its pair type or by itself.
+pub fn is_paused(storage: &dyn Storage, pair_type: &PairType, pair_addr: &Addr) -> StdResult<bool> {
+    Ok(GLOBAL_PAUSE.may_load(storage)?.unwrap_or_default()
+        || PAUSED_PAIR_TYPES.has(storage, pair_type.to_string())
+        || PAUSED_PAIRS.has(storage, pair_addr))
+}
+
 /// Stores the latest contract ownership transfer proposal
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 
-/// This state key isn't used anymore but left for backward compatability with old pairs
+/// Saves the pairs which are being migrated in the current batch in the order of their migration messages.
+/// It is empty outside of a batch as old pairs read it
 pub const PAIRS_TO_MIGRATE: Item<Vec<Addr>> = Item::new("pairs_to_migrate");
 
+/// This structure describes the latest pairs migration.
+#[cw_serde]
+pub struct PairsMigration {
+    /// The migration identifier. Used to tell migration statuses of different migrations apart
+    pub id: u64,
+    /// The type of pairs to migrate
+    pub pair_type: PairType,
+    /// The code ID to migrate the pairs to
+    pub new_code_id: u64,
+    /// The migration message sent to each pair
+    pub msg: Binary,
+    /// The key of the last pair in [`PAIRS`] which was migrated. `None` if no pairs were migrated yet
+    pub cursor: Option<(Vec<u8>, String)>,
+    /// Whether all pairs were tried to be migrated at least once
+    pub finished: bool,
+}
+
+/// Saves the latest pairs migration
+pub const PAIRS_MIGRATION: Item<PairsMigration> = Item::new("pairs_migration");
+
+/// Saves migration statuses of pairs by migration identifier and pair address
+pub const PAIR_MIGRATION_STATUSES: Map<(u64, &Addr), PairMigrationStatus> =
+    Map::new("pair_migration_statuses");
+
+/// Reads the keys and addresses of pairs which were not migrated yet by `migration`.
+///
+/// `limit` is the number of items to retrieve.
+pub fn read_pending_migrations(
+    storage: &dyn Storage,
+    migration: &PairsMigration,
+    limit: Option<u32>,
+) -> StdResult<Vec<((Vec<u8>, String), Addr)>> {
+    if migration.finished {
+        return Ok(vec![]);
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let pair_type = migration.pair_type.to_string();
+    let start = migration
+        .cursor
+        .as_ref()
+        .map(|(key, pair_type)| Bound::exclusive((key.as_slice(), pair_type.clone())));
+
+    // Pairs created with a fee tier are migrated together with their pair type
+    PAIRS_BY_TYPE
+        .sub_prefix(pair_type.as_str())
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+/// Reads the addresses of pairs which failed to be migrated by `migration` together with their errors.
+///
+/// `limit` is the number of items to retrieve.
+pub fn read_failed_migrations(
+    storage: &dyn Storage,
+    migration: &PairsMigration,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, String)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    PAIR_MIGRATION_STATUSES
+        .prefix(migration.id)
+        .range(storage, None, None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((pair_addr, PairMigrationStatus::Failed { error })) => Some(Ok((pair_addr, error))),
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+        .take(limit)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use astroport::asset::{native_asset_info, token_asset_info};
@@ -151,28 +397,4 @@ mod tests {
             assert_eq!(legacy_pair_key(&asset_infos), pair_key(&asset_infos));
         }
     }
-
-    #[test]
-    fn test_legacy_start_after() {
-        fn legacy_calc_range_start(start_after: Option<[AssetInfo; 2]>) -> Option<Vec<u8>> {
-            start_after.map(|asset_infos| {
-                let mut asset_infos = asset_infos.to_vec();
-                asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
-
-                let mut v = [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()]
-                    .concat()
-                    .as_slice()
-                    .to_vec();
-                v.push(1);
-                v
-            })
-        }
-
-        for asset_infos in get_test_case() {
-            assert_eq!(
-                legacy_calc_range_start(Some(asset_infos.clone())),
-                calc_range_start(Some(asset_infos.to_vec()))
-            );
-        }
-    }
 }
@@ -1,18 +1,21 @@
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Reply, ReplyOn, SubMsg, SubMsgResponse, SubMsgResult,
-    WasmMsg,
+    attr, from_binary, to_binary, Addr, Binary, Reply, ReplyOn, SubMsg, SubMsgResponse,
+    SubMsgResult, WasmMsg,
 };
+use cw2::set_contract_version;
 
+use crate::migration::PAIRS_V160;
 use crate::mock_querier::mock_dependencies;
-use crate::state::CONFIG;
+use crate::state::pair_key;
 use crate::{
-    contract::{execute, instantiate, query},
+    contract::{execute, instantiate, migrate, query},
     error::ContractError,
 };
 
 use astroport::asset::{AssetInfo, PairInfo};
 use astroport::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, PairConfig, PairType, PairsResponse, QueryMsg,
+    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, PairConfig, PairType, PairsResponse,
+    QueryMsg,
 };
 
 use crate::contract::reply;
@@ -50,6 +53,7 @@ fn proper_initialization() {
                 maker_fee_bps: 10,
                 is_disabled: false,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             },
             PairConfig {
                 code_id: 325u64,
@@ -58,6 +62,7 @@ fn proper_initialization() {
                 maker_fee_bps: 10,
                 is_disabled: false,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             },
         ],
         token_code_id: 123u64,
@@ -82,6 +87,7 @@ fn proper_initialization() {
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         }],
         token_code_id: 123u64,
         fee_address: None,
@@ -108,6 +114,7 @@ fn proper_initialization() {
                 maker_fee_bps: 10,
                 is_disabled: false,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             },
             PairConfig {
                 code_id: 123u64,
@@ -116,6 +123,7 @@ fn proper_initialization() {
                 maker_fee_bps: 10,
                 is_disabled: false,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             },
         ],
         token_code_id: 123u64,
@@ -150,6 +158,7 @@ fn update_config() {
         maker_fee_bps: 166,
         is_disabled: false,
         is_generator_disabled: false,
+        fee_tiers: vec![],
     }];
 
     let msg = InstantiateMsg {
@@ -301,6 +310,7 @@ fn update_pair_config() {
         maker_fee_bps: 10,
         is_disabled: false,
         is_generator_disabled: false,
+        fee_tiers: vec![],
     }];
 
     let msg = InstantiateMsg {
@@ -332,6 +342,7 @@ fn update_pair_config() {
         maker_fee_bps: 2,
         is_disabled: false,
         is_generator_disabled: false,
+        fee_tiers: vec![],
     };
 
     // Unauthorized err
@@ -355,12 +366,39 @@ fn update_pair_config() {
             maker_fee_bps: 10_001,
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         },
     };
 
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
     assert_eq!(res, ContractError::PairConfigInvalidFeeBps {});
 
+    // Check duplicate and invalid fee tiers
+    for fee_tiers in [vec![5, 100, 5], vec![5, 10_001]] {
+        let info = mock_info(owner.clone(), &[]);
+        let msg = ExecuteMsg::UpdatePairConfig {
+            config: PairConfig {
+                fee_tiers,
+                ..pair_config.clone()
+            },
+        };
+
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(res, ContractError::PairConfigInvalidFeeTiers {});
+    }
+
+    // The fee tier separator is not allowed in custom pair type names
+    let info = mock_info(owner.clone(), &[]);
+    let msg = ExecuteMsg::UpdatePairConfig {
+        config: PairConfig {
+            pair_type: PairType::Custom("xyk/5".to_string()),
+            ..pair_config.clone()
+        },
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidPairTypeName("xyk/5".to_string()));
+
     let info = mock_info(owner.clone(), &[]);
     let msg = ExecuteMsg::UpdatePairConfig {
         config: pair_config.clone(),
@@ -382,6 +420,7 @@ fn update_pair_config() {
         maker_fee_bps: 20,
         is_disabled: false,
         is_generator_disabled: false,
+        fee_tiers: vec![],
     };
 
     let info = mock_info(owner.clone(), &[]);
@@ -411,6 +450,7 @@ fn create_pair() {
         maker_fee_bps: 10,
         is_disabled: false,
         is_generator_disabled: false,
+        fee_tiers: vec![],
     };
 
     let msg = InstantiateMsg {
@@ -438,7 +478,6 @@ fn create_pair() {
         },
     ];
 
-    let config = CONFIG.load(&deps.storage);
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
 
@@ -451,6 +490,8 @@ fn create_pair() {
             pair_type: PairType::Stable {},
             asset_infos: asset_infos.clone(),
             init_params: None,
+            fee: None,
+            fee_tier: None,
         },
     )
     .unwrap_err();
@@ -464,6 +505,8 @@ fn create_pair() {
             pair_type: PairType::Xyk {},
             asset_infos: asset_infos.clone(),
             init_params: None,
+            fee: None,
+            fee_tier: None,
         },
     )
     .unwrap();
@@ -488,7 +531,7 @@ fn create_pair() {
                 .unwrap(),
                 code_id: pair_config.code_id,
                 funds: vec![],
-                admin: Some(config.unwrap().owner.to_string()),
+                admin: Some(MOCK_CONTRACT_ADDR.to_string()),
                 label: String::from("Astroport pair"),
             }
             .into(),
@@ -512,6 +555,7 @@ fn register() {
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         }],
         token_code_id: 123u64,
         fee_address: None,
@@ -538,6 +582,8 @@ fn register() {
         pair_type: PairType::Xyk {},
         asset_infos: asset_infos.clone(),
         init_params: None,
+        fee: None,
+        fee_tier: None,
     };
 
     let env = mock_env();
@@ -550,6 +596,7 @@ fn register() {
         contract_addr: Addr::unchecked("pair0000"),
         liquidity_token: Addr::unchecked("liquidity0000"),
         pair_type: PairType::Xyk {},
+        fee_tier: None,
     };
 
     let mut deployed_pairs = vec![(&pair0_addr, &pair0_info)];
@@ -582,6 +629,8 @@ fn register() {
         env.clone(),
         QueryMsg::Pair {
             asset_infos: asset_infos.clone(),
+            pair_type: None,
+            fee_tier: None,
         },
     )
     .unwrap();
@@ -594,6 +643,7 @@ fn register() {
             contract_addr: Addr::unchecked("pair0000"),
             asset_infos: asset_infos.clone(),
             pair_type: PairType::Xyk {},
+            fee_tier: None,
         }
     );
 
@@ -615,6 +665,8 @@ fn register() {
         pair_type: PairType::Xyk {},
         asset_infos: asset_infos_2.clone(),
         init_params: None,
+        fee: None,
+        fee_tier: None,
     };
 
     let env = mock_env();
@@ -627,6 +679,7 @@ fn register() {
         contract_addr: Addr::unchecked("pair0001"),
         liquidity_token: Addr::unchecked("liquidity0001"),
         pair_type: PairType::Xyk {},
+        fee_tier: None,
     };
 
     deployed_pairs.push((&pair1_addr, &pair1_info));
@@ -657,6 +710,8 @@ fn register() {
     let query_msg = QueryMsg::Pairs {
         start_after: None,
         limit: None,
+        start_after_pair_type: None,
+        start_after_fee_tier: None,
     };
 
     let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
@@ -669,12 +724,14 @@ fn register() {
                 contract_addr: Addr::unchecked("pair0000"),
                 asset_infos: asset_infos.clone(),
                 pair_type: PairType::Xyk {},
+                fee_tier: None,
             },
             PairInfo {
                 liquidity_token: Addr::unchecked("liquidity0001"),
                 contract_addr: Addr::unchecked("pair0001"),
                 asset_infos: asset_infos_2.clone(),
                 pair_type: PairType::Xyk {},
+                fee_tier: None,
             }
         ]
     );
@@ -682,6 +739,8 @@ fn register() {
     let query_msg = QueryMsg::Pairs {
         start_after: None,
         limit: Some(1),
+        start_after_pair_type: None,
+        start_after_fee_tier: None,
     };
 
     let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
@@ -693,12 +752,15 @@ fn register() {
             contract_addr: Addr::unchecked("pair0000"),
             asset_infos: asset_infos.clone(),
             pair_type: PairType::Xyk {},
+            fee_tier: None,
         }]
     );
 
     let query_msg = QueryMsg::Pairs {
         start_after: Some(asset_infos.clone()),
         limit: None,
+        start_after_pair_type: None,
+        start_after_fee_tier: None,
     };
 
     let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
@@ -710,6 +772,7 @@ fn register() {
             contract_addr: Addr::unchecked("pair0001"),
             asset_infos: asset_infos_2.clone(),
             pair_type: PairType::Xyk {},
+            fee_tier: None,
         }]
     );
 
@@ -722,6 +785,8 @@ fn register() {
         info,
         ExecuteMsg::Deregister {
             asset_infos: asset_infos_2.clone(),
+            pair_type: PairType::Xyk {},
+            fee_tier: None,
         },
     )
     .unwrap_err();
@@ -737,6 +802,8 @@ fn register() {
         info,
         ExecuteMsg::Deregister {
             asset_infos: asset_infos_2.clone(),
+            pair_type: PairType::Xyk {},
+            fee_tier: None,
         },
     )
     .unwrap();
@@ -746,6 +813,8 @@ fn register() {
     let query_msg = QueryMsg::Pairs {
         start_after: None,
         limit: None,
+        start_after_pair_type: None,
+        start_after_fee_tier: None,
     };
 
     let res = query(deps.as_ref(), env.clone(), query_msg).unwrap();
@@ -757,6 +826,202 @@ fn register() {
             contract_addr: Addr::unchecked("pair0000"),
             asset_infos: asset_infos.clone(),
             pair_type: PairType::Xyk {},
+            fee_tier: None,
         },]
     );
 }
+
+#[test]
+fn migrate_legacy_pairs() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![PairConfig {
+            code_id: 321u64,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 100,
+            maker_fee_bps: 10,
+            is_disabled: false,
+            is_generator_disabled: false,
+            fee_tiers: vec![],
+        }],
+        token_code_id: 123u64,
+        fee_address: None,
+        owner: "owner0000".to_string(),
+        generator_address: None,
+        whitelist_code_id: 234u64,
+        coin_registry_address: "coin_registry".to_string(),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // Store pairs the way factory versions before 1.7.0 did
+    let pairs = (1..=2)
+        .map(|i| {
+            let pair_info = PairInfo {
+                asset_infos: vec![
+                    AssetInfo::Token {
+                        contract_addr: Addr::unchecked("asset0000"),
+                    },
+                    AssetInfo::Token {
+                        contract_addr: Addr::unchecked(format!("asset000{i}")),
+                    },
+                ],
+                contract_addr: Addr::unchecked(format!("pair000{i}")),
+                liquidity_token: Addr::unchecked(format!("liquidity000{i}")),
+                pair_type: PairType::Xyk {},
+                fee_tier: None,
+            };
+            (pair_info.contract_addr.to_string(), pair_info)
+        })
+        .collect::<Vec<_>>();
+    for (_, pair_info) in &pairs {
+        PAIRS_V160
+            .save(
+                deps.as_mut().storage,
+                pair_key(&pair_info.asset_infos).as_slice(),
+                &pair_info.contract_addr,
+            )
+            .unwrap();
+    }
+    deps.querier.with_astroport_pairs(
+        &pairs
+            .iter()
+            .map(|(addr, pair_info)| (addr, pair_info))
+            .collect::<Vec<_>>(),
+    );
+
+    // New pairs can't be created until legacy pairs are moved
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CreatePair {
+            pair_type: PairType::Xyk {},
+            asset_infos: pairs[0].1.asset_infos.clone(),
+            init_params: None,
+            fee: None,
+            fee_tier: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::LegacyPairsNotMigrated {});
+
+    let migrate_msg = ExecuteMsg::MigrateLegacyPairs { limit: Some(1) };
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        migrate_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        migrate_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_legacy_pairs"),
+            attr("pairs", "1"),
+            attr("finished", "false"),
+        ]
+    );
+
+    // Pairs which were not moved yet are still found by queries
+    for pair_type in [None, Some(PairType::Xyk {})] {
+        for (_, pair_info) in &pairs {
+            let res: PairInfo = from_binary(
+                &query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::Pair {
+                        asset_infos: pair_info.asset_infos.clone(),
+                        pair_type: pair_type.clone(),
+                        fee_tier: None,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(res.contract_addr, pair_info.contract_addr);
+        }
+    }
+    query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos: pairs[1].1.asset_infos.clone(),
+            pair_type: Some(PairType::Stable {}),
+            fee_tier: None,
+        },
+    )
+    .unwrap_err();
+
+    let res: PairsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Pairs {
+                start_after: None,
+                start_after_pair_type: None,
+                start_after_fee_tier: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.pairs
+            .iter()
+            .map(|pair| pair.contract_addr.clone())
+            .collect::<Vec<_>>(),
+        pairs
+            .iter()
+            .map(|(_, pair_info)| pair_info.contract_addr.clone())
+            .collect::<Vec<_>>()
+    );
+
+    // The contract migration moves the rest of the pairs
+    set_contract_version(deps.as_mut().storage, "astroport-factory", "1.6.0").unwrap();
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            params: Binary::default(),
+        },
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        migrate_msg,
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoPairsToMigrate {});
+
+    for (_, pair_info) in &pairs {
+        let res: PairInfo = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Pair {
+                    asset_infos: pair_info.asset_infos.clone(),
+                    pair_type: None,
+                    fee_tier: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.contract_addr, pair_info.contract_addr);
+    }
+}
@@ -77,6 +77,7 @@ impl FactoryHelper {
                 maker_fee_bps: 10,
                 is_disabled: false,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             }],
             token_code_id: cw20_token_code_id,
             fee_address: None,
@@ -147,6 +148,8 @@ impl FactoryHelper {
             pair_type,
             asset_infos,
             init_params,
+            fee: None,
+            fee_tier: None,
         };
 
         router.execute_contract(sender.clone(), self.factory.clone(), &msg, &[])
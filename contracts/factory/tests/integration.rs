@@ -2,12 +2,16 @@
 
 mod factory_helper;
 
-use cosmwasm_std::{attr, Addr};
+use cosmwasm_std::{
+    attr, to_binary, Addr, Decimal, DepsMut, Empty, Env, Response, StdResult, WasmMsg,
+};
 
 use astroport::asset::{AssetInfo, PairInfo};
 use astroport::factory::{
-    ConfigResponse, ExecuteMsg, FeeInfoResponse, InstantiateMsg, PairConfig, PairType, QueryMsg,
+    ConfigResponse, ExecuteMsg, FeeInfoResponse, InstantiateMsg, PairConfig, PairFee, PairType,
+    PairsMigrationResponse, PairsResponse, PauseInfoResponse, PauseTarget, QueryMsg, UpdateAddr,
 };
+use astroport::querier::query_pair_fee_info;
 
 use crate::factory_helper::{instantiate_token, FactoryHelper};
 use cw_multi_test::{App, ContractWrapper, Executor};
@@ -44,6 +48,7 @@ fn proper_initialization() {
         maker_fee_bps: 10,
         is_disabled: false,
         is_generator_disabled: false,
+        fee_tiers: vec![],
     }];
 
     let msg = InstantiateMsg {
@@ -181,6 +186,8 @@ fn test_create_pair() {
                         contract_addr: token2.clone(),
                     },
                 ],
+                pair_type: None,
+                fee_tier: None,
             },
         )
         .unwrap();
@@ -202,6 +209,7 @@ fn test_create_pair() {
                 maker_fee_bps: 40,
                 is_disabled: true,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             },
         },
         &[],
@@ -234,6 +242,7 @@ fn test_create_pair() {
             &helper.factory,
             &QueryMsg::FeeInfo {
                 pair_type: PairType::Custom("Custom".to_string()),
+                pair_addr: None,
             },
         )
         .unwrap();
@@ -354,3 +363,1143 @@ fn check_update_owner() {
 
     assert_eq!(res.owner, new_owner)
 }
+
+#[test]
+fn test_create_pairs_of_different_types() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(
+        &mut app,
+        helper.cw20_token_code_id,
+        &owner,
+        "tokenX",
+        Some(18),
+    );
+    let token2 = instantiate_token(
+        &mut app,
+        helper.cw20_token_code_id,
+        &owner,
+        "tokenY",
+        Some(18),
+    );
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    let custom_pair_type = PairType::Custom("another_xyk".to_string());
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdatePairConfig {
+            config: PairConfig {
+                code_id: config.pair_configs[0].code_id,
+                pair_type: custom_pair_type.clone(),
+                total_fee_bps: 30,
+                maker_fee_bps: 10,
+                is_disabled: false,
+                is_generator_disabled: false,
+                fee_tiers: vec![],
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            custom_pair_type.clone(),
+            [&token2, &token1],
+            None,
+        )
+        .unwrap();
+
+    let err = helper
+        .create_pair(
+            &mut app,
+            &owner,
+            custom_pair_type.clone(),
+            [&token1, &token2],
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Pair was already created");
+
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: token1.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: token2.clone(),
+        },
+    ];
+
+    let xyk_pair: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: Some(PairType::Xyk {}),
+                fee_tier: None,
+            },
+        )
+        .unwrap();
+    let custom_pair: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: Some(custom_pair_type.clone()),
+                fee_tier: None,
+            },
+        )
+        .unwrap();
+    assert_ne!(xyk_pair.contract_addr, custom_pair.contract_addr);
+
+    // Without the pair type the pair with the lowest pair type key is returned
+    let default_pair: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: None,
+                fee_tier: None,
+            },
+        )
+        .unwrap();
+    assert!(custom_pair_type.to_string() < PairType::Xyk {}.to_string());
+    assert_eq!(default_pair.contract_addr, custom_pair.contract_addr);
+
+    let pairs: PairsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pairs {
+                start_after: None,
+                start_after_pair_type: None,
+                start_after_fee_tier: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(pairs.pairs.len(), 1);
+    let first_pair = pairs.pairs[0].contract_addr.clone();
+
+    // Continue pagination from the first pair
+    let first_pair_type = if first_pair == xyk_pair.contract_addr {
+        PairType::Xyk {}
+    } else {
+        custom_pair_type.clone()
+    };
+    let pairs: PairsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pairs {
+                start_after: Some(asset_infos.clone()),
+                start_after_pair_type: Some(first_pair_type),
+                start_after_fee_tier: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(pairs.pairs.len(), 1);
+    assert_ne!(pairs.pairs[0].contract_addr, first_pair);
+
+    // Skipping the whole asset set returns nothing
+    let pairs: PairsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pairs {
+                start_after: Some(asset_infos.clone()),
+                start_after_pair_type: None,
+                start_after_fee_tier: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(pairs.pairs.is_empty());
+
+    // Deregister the custom pair only
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Deregister {
+            asset_infos: asset_infos.clone(),
+            pair_type: custom_pair_type.clone(),
+            fee_tier: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let res: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: None,
+                fee_tier: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.contract_addr, xyk_pair.contract_addr);
+
+    app.wrap()
+        .query_wasm_smart::<PairInfo>(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos,
+                pair_type: Some(custom_pair_type),
+                fee_tier: None,
+            },
+        )
+        .unwrap_err();
+}
+
+#[test]
+fn test_pair_fee_overrides() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(
+        &mut app,
+        helper.cw20_token_code_id,
+        &owner,
+        "tokenX",
+        Some(18),
+    );
+    let token2 = instantiate_token(
+        &mut app,
+        helper.cw20_token_code_id,
+        &owner,
+        "tokenY",
+        Some(18),
+    );
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: token1.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: token2.clone(),
+        },
+    ];
+    let pair_fee = PairFee {
+        total_fee_bps: 50,
+        maker_fee_bps: 20,
+    };
+
+    // Only the owner can set pair fees
+    let err = app
+        .execute_contract(
+            Addr::unchecked("someone"),
+            helper.factory.clone(),
+            &ExecuteMsg::CreatePair {
+                pair_type: PairType::Xyk {},
+                asset_infos: asset_infos.clone(),
+                init_params: None,
+                fee: Some(pair_fee.clone()),
+                fee_tier: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::CreatePair {
+                pair_type: PairType::Xyk {},
+                asset_infos: asset_infos.clone(),
+                init_params: None,
+                fee: Some(PairFee {
+                    total_fee_bps: 10_001,
+                    maker_fee_bps: 0,
+                }),
+                fee_tier: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Fee bps in pair config must be smaller than or equal to 10,000"
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::CreatePair {
+            pair_type: PairType::Xyk {},
+            asset_infos: asset_infos.clone(),
+            init_params: None,
+            fee: Some(pair_fee.clone()),
+            fee_tier: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let pair: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos,
+                pair_type: None,
+                fee_tier: None,
+            },
+        )
+        .unwrap();
+
+    let query_fee = |app: &App, pair_addr: Option<String>| -> FeeInfoResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &helper.factory,
+                &QueryMsg::FeeInfo {
+                    pair_type: PairType::Xyk {},
+                    pair_addr,
+                },
+            )
+            .unwrap()
+    };
+
+    let fee_info = query_fee(&app, Some(pair.contract_addr.to_string()));
+    assert_eq!(fee_info.total_fee_bps, 50);
+    assert_eq!(fee_info.maker_fee_bps, 20);
+
+    // Pair type fees are not affected
+    let fee_info = query_fee(&app, None);
+    assert_eq!(fee_info.total_fee_bps, 100);
+    assert_eq!(fee_info.maker_fee_bps, 10);
+
+    // The pair itself reads the overridden fees
+    let pair_fee_info = query_pair_fee_info(&app.wrap(), &helper.factory, &pair).unwrap();
+    assert_eq!(
+        pair_fee_info.total_fee_rate,
+        Decimal::from_ratio(50u8, 10000u16)
+    );
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("someone"),
+            helper.factory.clone(),
+            &ExecuteMsg::UpdatePairFee {
+                pair_addr: pair.contract_addr.to_string(),
+                fee: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    // Only registered pairs can get fees
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdatePairFee {
+            pair_addr: token1.to_string(),
+            fee: Some(pair_fee),
+        },
+        &[],
+    )
+    .unwrap_err();
+
+    let new_fee = PairFee {
+        total_fee_bps: 10,
+        maker_fee_bps: 0,
+    };
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdatePairFee {
+            pair_addr: pair.contract_addr.to_string(),
+            fee: Some(new_fee.clone()),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let fee_info = query_fee(&app, Some(pair.contract_addr.to_string()));
+    assert_eq!(fee_info.total_fee_bps, new_fee.total_fee_bps);
+    assert_eq!(fee_info.maker_fee_bps, new_fee.maker_fee_bps);
+
+    // Removing the override falls back to the pair type fees
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdatePairFee {
+            pair_addr: pair.contract_addr.to_string(),
+            fee: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let fee_info = query_fee(&app, Some(pair.contract_addr.to_string()));
+    assert_eq!(fee_info.total_fee_bps, 100);
+    assert_eq!(fee_info.maker_fee_bps, 10);
+}
+
+#[test]
+fn test_fee_tiers() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(
+        &mut app,
+        helper.cw20_token_code_id,
+        &owner,
+        "tokenX",
+        Some(18),
+    );
+    let token2 = instantiate_token(
+        &mut app,
+        helper.cw20_token_code_id,
+        &owner,
+        "tokenY",
+        Some(18),
+    );
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: token1.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: token2.clone(),
+        },
+    ];
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdatePairConfig {
+            config: PairConfig {
+                fee_tiers: vec![5, 30],
+                ..config.pair_configs[0].clone()
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let fee_tiers: Vec<u16> = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::FeeTiers {
+                pair_type: PairType::Xyk {},
+            },
+        )
+        .unwrap();
+    assert_eq!(fee_tiers, vec![5, 30]);
+
+    let factory = helper.factory.clone();
+    let create_tier_pair = |app: &mut App, fee_tier: u16| {
+        app.execute_contract(
+            Addr::unchecked("someone"),
+            factory.clone(),
+            &ExecuteMsg::CreatePair {
+                pair_type: PairType::Xyk {},
+                asset_infos: asset_infos.clone(),
+                init_params: None,
+                fee: None,
+                fee_tier: Some(fee_tier),
+            },
+            &[],
+        )
+    };
+
+    let err = create_tier_pair(&mut app, 100).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Fee tier 100 bps is not available for the pair type"
+    );
+
+    // Pairs with the same assets but different fee tiers are separate pools
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+    create_tier_pair(&mut app, 5).unwrap();
+
+    let err = create_tier_pair(&mut app, 5).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Pair was already created");
+
+    let query_pair = |app: &App, fee_tier: Option<u16>| {
+        app.wrap().query_wasm_smart::<PairInfo>(
+            &factory,
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: Some(PairType::Xyk {}),
+                fee_tier,
+            },
+        )
+    };
+
+    let query_fee = |app: &App, pair: &PairInfo| -> FeeInfoResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &factory,
+                &QueryMsg::FeeInfo {
+                    pair_type: PairType::Xyk {},
+                    pair_addr: Some(pair.contract_addr.to_string()),
+                },
+            )
+            .unwrap()
+    };
+
+    let default_pair = query_pair(&app, None).unwrap();
+    assert_eq!(default_pair.fee_tier, None);
+    let fee_info = query_fee(&app, &default_pair);
+    assert_eq!(fee_info.total_fee_bps, 100);
+    assert_eq!(fee_info.maker_fee_bps, 10);
+
+    let tier_pair = query_pair(&app, Some(5)).unwrap();
+    assert_ne!(tier_pair.contract_addr, default_pair.contract_addr);
+    assert_eq!(tier_pair.fee_tier, Some(5));
+    let fee_info = query_fee(&app, &tier_pair);
+    assert_eq!(fee_info.total_fee_bps, 5);
+    assert_eq!(fee_info.maker_fee_bps, 10);
+
+    // The pair itself charges its fee tier
+    let pair_fee_info = query_pair_fee_info(&app.wrap(), &factory, &tier_pair).unwrap();
+    assert_eq!(
+        pair_fee_info.total_fee_rate,
+        Decimal::from_ratio(5u8, 10000u16)
+    );
+
+    query_pair(&app, Some(30)).unwrap_err();
+
+    // Paginate through pairs of all fee tiers
+    let query_pairs = |app: &App, start_after: Option<&PairInfo>| -> PairsResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &factory,
+                &QueryMsg::Pairs {
+                    start_after: start_after.map(|pair| pair.asset_infos.clone()),
+                    start_after_pair_type: start_after.map(|pair| pair.pair_type.clone()),
+                    start_after_fee_tier: start_after.and_then(|pair| pair.fee_tier),
+                    limit: Some(1),
+                },
+            )
+            .unwrap()
+    };
+
+    let first = query_pairs(&app, None).pairs;
+    assert_eq!(first[0].contract_addr, default_pair.contract_addr);
+    let second = query_pairs(&app, Some(&first[0])).pairs;
+    assert_eq!(second[0].contract_addr, tier_pair.contract_addr);
+    assert!(query_pairs(&app, Some(&second[0])).pairs.is_empty());
+
+    // Deregister the fee tier pair only
+    app.execute_contract(
+        owner.clone(),
+        factory.clone(),
+        &ExecuteMsg::Deregister {
+            asset_infos: asset_infos.clone(),
+            pair_type: PairType::Xyk {},
+            fee_tier: Some(5),
+        },
+        &[],
+    )
+    .unwrap();
+
+    query_pair(&app, Some(5)).unwrap_err();
+    assert_eq!(
+        query_pair(&app, None).unwrap().contract_addr,
+        default_pair.contract_addr
+    );
+}
+
+#[test]
+fn test_pairs_by_asset() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let tokens = ["tokenX", "tokenY", "tokenZ"]
+        .map(|name| instantiate_token(&mut app, helper.cw20_token_code_id, &owner, name, Some(18)));
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    let custom_pair_type = PairType::Custom("another_xyk".to_string());
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdatePairConfig {
+            config: PairConfig {
+                pair_type: custom_pair_type.clone(),
+                ..config.pair_configs[0].clone()
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&tokens[0], &tokens[1]],
+            None,
+        )
+        .unwrap();
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&tokens[0], &tokens[2]],
+            None,
+        )
+        .unwrap();
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            custom_pair_type.clone(),
+            [&tokens[1], &tokens[0]],
+            None,
+        )
+        .unwrap();
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&tokens[1], &tokens[2]],
+            None,
+        )
+        .unwrap();
+
+    let query_pairs_by_asset = |app: &App,
+                                token: &Addr,
+                                pair_type: Option<PairType>,
+                                start_after: Option<String>,
+                                limit: Option<u32>|
+     -> Vec<PairInfo> {
+        app.wrap()
+            .query_wasm_smart::<PairsResponse>(
+                &helper.factory,
+                &QueryMsg::PairsByAsset {
+                    asset_info: AssetInfo::Token {
+                        contract_addr: token.clone(),
+                    },
+                    pair_type,
+                    start_after,
+                    limit,
+                },
+            )
+            .unwrap()
+            .pairs
+    };
+
+    let pairs = query_pairs_by_asset(&app, &tokens[0], None, None, None);
+    assert_eq!(pairs.len(), 3);
+    assert!(pairs
+        .iter()
+        .all(|pair| pair.asset_infos.contains(&AssetInfo::Token {
+            contract_addr: tokens[0].clone(),
+        })));
+
+    // Filter by pair type
+    let pairs = query_pairs_by_asset(&app, &tokens[0], Some(PairType::Xyk {}), None, None);
+    assert_eq!(pairs.len(), 2);
+    assert!(pairs.iter().all(|pair| pair.pair_type == PairType::Xyk {}));
+
+    let pairs = query_pairs_by_asset(&app, &tokens[2], Some(custom_pair_type.clone()), None, None);
+    assert!(pairs.is_empty());
+
+    // Paginate through pairs
+    let first = query_pairs_by_asset(&app, &tokens[1], None, None, Some(2));
+    assert_eq!(first.len(), 2);
+    let rest = query_pairs_by_asset(
+        &app,
+        &tokens[1],
+        None,
+        Some(first[1].contract_addr.to_string()),
+        None,
+    );
+    assert_eq!(rest.len(), 1);
+    assert!(!first.contains(&rest[0]));
+
+    // Deregistered pairs are removed from the index
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Deregister {
+            asset_infos: vec![
+                AssetInfo::Token {
+                    contract_addr: tokens[0].clone(),
+                },
+                AssetInfo::Token {
+                    contract_addr: tokens[1].clone(),
+                },
+            ],
+            pair_type: custom_pair_type,
+            fee_tier: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_pairs_by_asset(&app, &tokens[0], None, None, None).len(),
+        2
+    );
+    assert_eq!(
+        query_pairs_by_asset(&app, &tokens[1], None, None, None).len(),
+        2
+    );
+}
+
+fn migrate_pair(_deps: DepsMut, _env: Env, _msg: Empty) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+#[test]
+fn test_migrate_pairs() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let tokens = ["tokenX", "tokenY", "tokenZ"]
+        .map(|name| instantiate_token(&mut app, helper.cw20_token_code_id, &owner, name, Some(18)));
+    for (first, second) in [(0, 1), (0, 2), (1, 2)] {
+        helper
+            .create_pair(
+                &mut app,
+                &owner,
+                PairType::Xyk {},
+                [&tokens[first], &tokens[second]],
+                None,
+            )
+            .unwrap();
+    }
+
+    // Pairs are migrated in the same order as they are returned by the factory
+    let pairs: Vec<Addr> = app
+        .wrap()
+        .query_wasm_smart::<PairsResponse>(
+            &helper.factory,
+            &QueryMsg::Pairs {
+                start_after: None,
+                start_after_pair_type: None,
+                start_after_fee_tier: None,
+                limit: None,
+            },
+        )
+        .unwrap()
+        .pairs
+        .into_iter()
+        .map(|pair| pair.contract_addr)
+        .collect();
+
+    // Pairs of other types are not migrated
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    let custom_pair_type = PairType::Custom("another_xyk".to_string());
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdatePairConfig {
+            config: PairConfig {
+                pair_type: custom_pair_type.clone(),
+                ..config.pair_configs[0].clone()
+            },
+        },
+        &[],
+    )
+    .unwrap();
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            custom_pair_type.clone(),
+            [&tokens[0], &tokens[1]],
+            None,
+        )
+        .unwrap();
+    let custom_pair = app
+        .wrap()
+        .query_wasm_smart::<PairInfo>(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: vec![
+                    AssetInfo::Token {
+                        contract_addr: tokens[0].clone(),
+                    },
+                    AssetInfo::Token {
+                        contract_addr: tokens[1].clone(),
+                    },
+                ],
+                pair_type: Some(custom_pair_type),
+                fee_tier: None,
+            },
+        )
+        .unwrap()
+        .contract_addr;
+
+    let new_pair_code_id = app.store_code(Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_pair::contract::execute,
+            astroport_pair::contract::instantiate,
+            astroport_pair::contract::query,
+        )
+        .with_reply_empty(astroport_pair::contract::reply)
+        .with_migrate(migrate_pair),
+    ));
+
+    // The factory is the admin of the pairs it creates. Pairs handed over to another admin can't be migrated
+    app.execute(
+        helper.factory.clone(),
+        WasmMsg::UpdateAdmin {
+            contract_addr: pairs[1].to_string(),
+            admin: owner.to_string(),
+        }
+        .into(),
+    )
+    .unwrap();
+
+    let migrate_msg = |limit: Option<u32>| ExecuteMsg::MigratePairs {
+        pair_type: PairType::Xyk {},
+        new_code_id: new_pair_code_id,
+        msg: to_binary(&Empty {}).unwrap(),
+        limit,
+    };
+    let query_migration = |app: &App| -> PairsMigrationResponse {
+        app.wrap()
+            .query_wasm_smart::<Option<PairsMigrationResponse>>(
+                &helper.factory,
+                &QueryMsg::PairsMigration { limit: None },
+            )
+            .unwrap()
+            .unwrap()
+    };
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("someone"),
+            helper.factory.clone(),
+            &migrate_msg(None),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &migrate_msg(Some(2)),
+        &[],
+    )
+    .unwrap();
+
+    let migration = query_migration(&app);
+    assert_eq!(migration.new_code_id, new_pair_code_id);
+    assert_eq!(migration.pending, vec![pairs[2].clone()]);
+    assert_eq!(migration.failed.len(), 1);
+    assert_eq!(migration.failed[0].0, pairs[1]);
+    assert_eq!(
+        app.contract_data(&pairs[0]).unwrap().code_id as u64,
+        new_pair_code_id
+    );
+    assert_ne!(
+        app.contract_data(&pairs[1]).unwrap().code_id as u64,
+        new_pair_code_id
+    );
+
+    // Continue the migration from the cursor
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &migrate_msg(Some(2)),
+        &[],
+    )
+    .unwrap();
+
+    let migration = query_migration(&app);
+    assert!(migration.pending.is_empty());
+    assert_eq!(migration.failed.len(), 1);
+
+    // Failed pairs are retried once there are no pending pairs left
+    app.execute(
+        owner.clone(),
+        WasmMsg::UpdateAdmin {
+            contract_addr: pairs[1].to_string(),
+            admin: helper.factory.to_string(),
+        }
+        .into(),
+    )
+    .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &migrate_msg(None),
+        &[],
+    )
+    .unwrap();
+
+    let migration = query_migration(&app);
+    assert!(migration.pending.is_empty());
+    assert!(migration.failed.is_empty());
+    for pair in &pairs {
+        assert_eq!(
+            app.contract_data(pair).unwrap().code_id as u64,
+            new_pair_code_id
+        );
+    }
+    assert_ne!(
+        app.contract_data(&custom_pair).unwrap().code_id as u64,
+        new_pair_code_id
+    );
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &migrate_msg(None),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "No pairs left to migrate");
+}
+
+#[test]
+fn test_pause() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let guardian = Addr::unchecked("guardian");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(
+        &mut app,
+        helper.cw20_token_code_id,
+        &owner,
+        "tokenX",
+        Some(18),
+    );
+    let token2 = instantiate_token(
+        &mut app,
+        helper.cw20_token_code_id,
+        &owner,
+        "tokenY",
+        Some(18),
+    );
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+    let pair = app
+        .wrap()
+        .query_wasm_smart::<PairInfo>(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: vec![
+                    AssetInfo::Token {
+                        contract_addr: token1.clone(),
+                    },
+                    AssetInfo::Token {
+                        contract_addr: token2.clone(),
+                    },
+                ],
+                pair_type: None,
+                fee_tier: None,
+            },
+        )
+        .unwrap()
+        .contract_addr;
+
+    let is_paused = |app: &App, pair_type: PairType| -> bool {
+        app.wrap()
+            .query_wasm_smart(
+                &helper.factory,
+                &QueryMsg::IsPaused {
+                    pair_type,
+                    pair_addr: pair.to_string(),
+                },
+            )
+            .unwrap()
+    };
+    let pause_info = |app: &App| -> PauseInfoResponse {
+        app.wrap()
+            .query_wasm_smart(&helper.factory, &QueryMsg::PauseInfo {})
+            .unwrap()
+    };
+
+    let err = app
+        .execute_contract(
+            guardian.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::UpdateGuardian {
+                guardian: UpdateAddr::Set(guardian.to_string()),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdateGuardian {
+            guardian: UpdateAddr::Set(guardian.to_string()),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(pause_info(&app).guardian, Some(guardian.clone()));
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("someone"),
+            helper.factory.clone(),
+            &ExecuteMsg::Pause {
+                target: PauseTarget::Global {},
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    // The guardian can pause all pairs but can't unpause them
+    app.execute_contract(
+        guardian.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Pause {
+            target: PauseTarget::Global {},
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(pause_info(&app).global);
+    assert!(is_paused(&app, PairType::Xyk {}));
+
+    let err = app
+        .execute_contract(
+            guardian.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::Unpause {
+                target: PauseTarget::Global {},
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Only the owner can unpause pairs"
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Unpause {
+            target: PauseTarget::Global {},
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(!is_paused(&app, PairType::Xyk {}));
+
+    // Pause a single pair type
+    app.execute_contract(
+        guardian.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Pause {
+            target: PauseTarget::PairType(PairType::Xyk {}),
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(is_paused(&app, PairType::Xyk {}));
+    assert!(!is_paused(&app, PairType::Stable {}));
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Unpause {
+            target: PauseTarget::PairType(PairType::Xyk {}),
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(!is_paused(&app, PairType::Xyk {}));
+
+    // Pause a single pair
+    app.execute_contract(
+        guardian.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Pause {
+            target: PauseTarget::Pair(pair.to_string()),
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(is_paused(&app, PairType::Xyk {}));
+    assert_eq!(
+        pause_info(&app),
+        PauseInfoResponse {
+            guardian: Some(guardian.clone()),
+            global: false,
+            pair_types: vec![],
+            pairs: vec![pair.clone()],
+        }
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Unpause {
+            target: PauseTarget::Pair(pair.to_string()),
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(!is_paused(&app, PairType::Xyk {}));
+
+    // Once removed, the guardian can no longer pause pairs
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UpdateGuardian {
+            guardian: UpdateAddr::Remove {},
+        },
+        &[],
+    )
+    .unwrap();
+    let err = app
+        .execute_contract(
+            guardian,
+            helper.factory.clone(),
+            &ExecuteMsg::Pause {
+                target: PauseTarget::Global {},
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+}
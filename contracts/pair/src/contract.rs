@@ -6,8 +6,8 @@ use std::vec;
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     attr, from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Decimal256, Deps, DepsMut, Env,
-    Fraction, MessageInfo, QuerierWrapper, Reply, ReplyOn, Response, StdError, StdResult, SubMsg,
-    SubMsgResponse, SubMsgResult, Uint128, Uint256, Uint64, WasmMsg,
+    Fraction, MessageInfo, QuerierWrapper, Reply, ReplyOn, Response, StdError, StdResult, Storage,
+    SubMsg, SubMsgResponse, SubMsgResult, Uint128, Uint256, Uint64, WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
@@ -16,22 +16,29 @@ use astroport::asset::{
     addr_opt_validate, check_swap_parameters, format_lp_token_name, Asset, AssetInfo, CoinsExt,
     PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
 };
+use astroport::common::is_deadline_passed;
 use astroport::factory::PairType;
 use astroport::generator::Cw20HookMsg as GeneratorHookMsg;
+use astroport::observation::{query_observation, Observation, OBSERVATIONS_SIZE};
 use astroport::pair::{
-    ConfigResponse, XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams, DEFAULT_SLIPPAGE,
-    MAX_ALLOWED_SLIPPAGE,
+    assert_no_flash_loan, finish_flash_loan, flash_loan_messages, start_flash_loan, ConfigResponse,
+    XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams, DEFAULT_FLASH_LOAN_FEE_BPS,
+    DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MAX_FLASH_LOAN_FEE_BPS,
 };
 use astroport::pair::{
     CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse,
     QueryMsg, ReverseSimulationResponse, SimulationResponse, TWAP_PRECISION,
 };
-use astroport::querier::{query_factory_config, query_fee_info, query_supply};
+use astroport::querier::{
+    query_factory_config, query_pair_fee_info, query_pair_paused, query_supply,
+};
 use astroport::{token::InstantiateMsg as TokenInstantiateMsg, U256};
+use astroport_circular_buffer::error::BufferResult;
+use astroport_circular_buffer::BufferManager;
 use cw_utils::parse_instantiate_response_data;
 
 use crate::error::ContractError;
-use crate::state::{Config, BALANCES, CONFIG};
+use crate::state::{Config, BALANCES, CONFIG, FLASH_LOAN_FEE_BPS, OBSERVATIONS};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "astroport-pair";
@@ -39,6 +46,11 @@ const CONTRACT_NAME: &str = "astroport-pair";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// A `reply` call code ID used for sub-messages.
 const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
+/// A `reply` call code ID used to check that a flash loan was repaid.
+const FLASH_LOAN_REPLY_ID: u64 = 2;
+/// The minimum amount of both offer and ask assets (without decimals) for a swap to be recorded as an observation.
+/// Smaller swaps can seriously mess oracle price due to rounding errors.
+const MIN_OBSERVATION_TRADE_AMOUNT: Uint128 = Uint128::new(1000);
 
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -74,6 +86,7 @@ pub fn instantiate(
             liquidity_token: Addr::unchecked(""),
             asset_infos: msg.asset_infos.clone(),
             pair_type: PairType::Xyk {},
+            fee_tier: None,
         },
         factory_addr: deps.api.addr_validate(msg.factory_addr.as_str())?,
         block_time_last: 0,
@@ -90,6 +103,8 @@ pub fn instantiate(
 
     CONFIG.save(deps.storage, &config)?;
 
+    BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+
     let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
 
     // Create the LP token contract
@@ -130,7 +145,7 @@ pub fn instantiate(
 
 /// The entry point to the contract for processing replies from submessages.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg {
         Reply {
             id: INSTANTIATE_TOKEN_REPLY_ID,
@@ -156,6 +171,10 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
             Ok(Response::new()
                 .add_attribute("liquidity_token_addr", config.pair_info.liquidity_token))
         }
+        Reply {
+            id: FLASH_LOAN_REPLY_ID,
+            result: SubMsgResult::Ok(_),
+        } => check_flash_loan_repaid(deps, env),
         _ => Err(ContractError::FailedToParseReply {}),
     }
 }
@@ -181,6 +200,15 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             max_spread,
 ///             to,
 ///         }** Performs a swap operation with the specified parameters.
+///
+/// * **ExecuteMsg::SwapExactOut {
+///             ask_asset,
+///             max_offer_amount,
+///             to,
+///         }** Swaps a part of the sent coin to the exact amount of the ask asset and refunds the rest.
+///
+/// * **ExecuteMsg::FlashLoan { assets, receiver, data }** Lends pool assets to the receiver contract
+/// which must return them together with the flash loan fee by the end of the receiver call.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -188,6 +216,9 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // The pair is locked while a flash loan is in progress
+    assert_no_flash_loan(deps.storage)?;
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ProvideLiquidity {
@@ -195,41 +226,86 @@ pub fn execute(
             slippage_tolerance,
             auto_stake,
             receiver,
-        } => provide_liquidity(
-            deps,
-            env,
-            info,
-            assets,
-            slippage_tolerance,
-            auto_stake,
-            receiver,
-        ),
+            deadline,
+        } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            provide_liquidity(
+                deps,
+                env,
+                info,
+                assets,
+                slippage_tolerance,
+                auto_stake,
+                receiver,
+            )
+        }
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            deadline,
             ..
         } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
             }
+            offer_asset.assert_sent_native_token_balance(&info)?;
 
             let to_addr = addr_opt_validate(deps.api, &to)?;
 
             swap(
                 deps,
                 env,
-                info.clone(),
                 info.sender,
                 offer_asset,
                 belief_price,
                 max_spread,
                 to_addr,
+                Uint128::zero(),
+            )
+        }
+        ExecuteMsg::SwapExactOut {
+            ask_asset,
+            max_offer_amount,
+            to,
+            deadline,
+        } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            let offer_asset = match &info.funds[..] {
+                [coin] => Asset::from(coin),
+                _ => return Err(ContractError::InvalidOfferFunds {}),
+            };
+
+            let to_addr = addr_opt_validate(deps.api, &to)?;
+
+            swap_exact_out(
+                deps,
+                env,
+                info.sender,
+                offer_asset,
+                ask_asset,
+                max_offer_amount,
+                to_addr,
             )
         }
         ExecuteMsg::UpdateConfig { params } => update_config(deps, env, info, params),
+        ExecuteMsg::FlashLoan {
+            assets,
+            receiver,
+            data,
+        } => flash_loan(deps, env, assets, receiver, data),
         _ => Err(ContractError::NonSupported {}),
     }
 }
@@ -248,8 +324,13 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            deadline,
             ..
         } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
             // Only asset contract can execute this message
             let mut authorized = false;
             let config = CONFIG.load(deps.storage)?;
@@ -272,7 +353,6 @@ pub fn receive_cw20(
             swap(
                 deps,
                 env,
-                info,
                 Addr::unchecked(cw20_msg.sender),
                 Asset {
                     info: AssetInfo::Token { contract_addr },
@@ -281,16 +361,59 @@ pub fn receive_cw20(
                 belief_price,
                 max_spread,
                 to_addr,
+                Uint128::zero(),
+            )
+        }
+        Cw20HookMsg::SwapExactOut {
+            ask_asset,
+            max_offer_amount,
+            to,
+            deadline,
+        } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            let config = CONFIG.load(deps.storage)?;
+
+            // Only asset contract can execute this message
+            if !config.pair_info.asset_infos.iter().any(|asset_info| {
+                matches!(asset_info, AssetInfo::Token { contract_addr } if *contract_addr == info.sender)
+            }) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = addr_opt_validate(deps.api, &to)?;
+
+            swap_exact_out(
+                deps,
+                env,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: info.sender,
+                    },
+                    amount: cw20_msg.amount,
+                },
+                ask_asset,
+                max_offer_amount,
+                to_addr,
+            )
+        }
+        Cw20HookMsg::WithdrawLiquidity { assets, deadline } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            withdraw_liquidity(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                cw20_msg.amount,
+                assets,
             )
         }
-        Cw20HookMsg::WithdrawLiquidity { assets } => withdraw_liquidity(
-            deps,
-            env,
-            info,
-            Addr::unchecked(cw20_msg.sender),
-            cw20_msg.amount,
-            assets,
-        ),
     }
 }
 
@@ -326,6 +449,11 @@ pub fn provide_liquidity(
     let auto_stake = auto_stake.unwrap_or(false);
 
     let mut config = CONFIG.load(deps.storage)?;
+
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
     info.funds
         .assert_coins_properly_sent(&assets, &config.pair_info.asset_infos)?;
     let mut pools = config
@@ -511,7 +639,11 @@ fn mint_liquidity_token_message(
 /// Withdraw liquidity from the pool.
 /// * **sender** is the address that will receive assets back from the pair contract.
 ///
-/// * **amount** is the amount of LP tokens to burn.
+/// * **amount** is the amount of LP tokens sent for burning.
+///
+/// * **assets** is the amount of each pool asset to withdraw. If empty, the withdrawal is balanced
+/// and all sent LP tokens are burned. Otherwise only the LP tokens needed to withdraw the requested
+/// assets are burned and the rest is sent back to the sender.
 pub fn withdraw_liquidity(
     deps: DepsMut,
     env: Env,
@@ -528,11 +660,37 @@ pub fn withdraw_liquidity(
 
     let (pools, total_share) = pool_info(deps.querier, &config)?;
 
-    let refund_assets = if assets.is_empty() {
+    let (refund_assets, burn_amount) = if assets.is_empty() {
         // Usual withdraw (balanced)
-        get_share_in_assets(&pools, amount, total_share)
+        (get_share_in_assets(&pools, amount, total_share), amount)
     } else {
-        return Err(StdError::generic_err("Imbalanced withdraw is currently disabled").into());
+        // Imbalanced withdraw
+        let withdrawals = get_withdrawal_amounts(&pools, &assets)?;
+        let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
+        let burn_amount = compute_imbalanced_withdraw_share(
+            &pools,
+            &withdrawals,
+            total_share,
+            fee_info.total_fee_rate,
+        )?;
+        if burn_amount > amount {
+            return Err(StdError::generic_err(format!(
+                "Not enough LP tokens. You need {} LP tokens.",
+                burn_amount
+            ))
+            .into());
+        }
+
+        let refund_assets = pools
+            .iter()
+            .zip(withdrawals)
+            .map(|(pool, amount)| Asset {
+                info: pool.info.clone(),
+                amount,
+            })
+            .collect();
+
+        (refund_assets, burn_amount)
     };
 
     if config.track_asset_balances {
@@ -557,20 +715,36 @@ pub fn withdraw_liquidity(
     }
 
     // Update the pool info
-    let messages: Vec<CosmosMsg> = vec![
-        refund_assets[0].clone().into_msg(sender.clone())?,
-        refund_assets[1].clone().into_msg(sender.clone())?,
-        CosmosMsg::Wasm(WasmMsg::Execute {
+    let mut messages = refund_assets
+        .iter()
+        .filter(|asset| !asset.amount.is_zero())
+        .map(|asset| asset.clone().into_msg(sender.clone()))
+        .collect::<StdResult<Vec<_>>>()?;
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair_info.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn {
+            amount: burn_amount,
+        })?,
+        funds: vec![],
+    }));
+
+    // Send unused LP tokens back
+    let unused_amount = amount - burn_amount;
+    if !unused_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: config.pair_info.liquidity_token.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: sender.to_string(),
+                amount: unused_amount,
+            })?,
             funds: vec![],
-        }),
-    ];
+        }));
+    }
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "withdraw_liquidity"),
         attr("sender", sender),
-        attr("withdrawn_share", amount),
+        attr("withdrawn_share", burn_amount),
         attr(
             "refund_assets",
             format!("{}, {}", refund_assets[0], refund_assets[1]),
@@ -578,6 +752,98 @@ pub fn withdraw_liquidity(
     ]))
 }
 
+/// Returns the amounts of pool assets requested for an imbalanced withdrawal.
+/// The amounts are ordered the same way as the pool assets.
+///
+/// * **pools** is the array with assets in the pool.
+///
+/// * **assets** is the array with assets to withdraw. Omitted pool assets are not withdrawn.
+pub fn get_withdrawal_amounts(
+    pools: &[Asset],
+    assets: &[Asset],
+) -> Result<Vec<Uint128>, ContractError> {
+    let mut withdrawals = vec![None; pools.len()];
+    for asset in assets {
+        let ind = pools
+            .iter()
+            .position(|pool| pool.info.equal(&asset.info))
+            .ok_or(ContractError::AssetMismatch {})?;
+        if withdrawals[ind].is_some() {
+            return Err(ContractError::DoublingAssets {});
+        }
+        if asset.amount >= pools[ind].amount {
+            return Err(StdError::generic_err(format!(
+                "Not enough {} in the pool to withdraw {}",
+                asset.info, asset.amount
+            ))
+            .into());
+        }
+        withdrawals[ind] = Some(asset.amount);
+    }
+
+    let withdrawals = withdrawals
+        .into_iter()
+        .map(Option::unwrap_or_default)
+        .collect::<Vec<_>>();
+    if withdrawals.iter().all(Uint128::is_zero) {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(withdrawals)
+}
+
+/// Returns the amount of LP tokens that must be burned to withdraw the specified amounts of pool assets.
+/// The imbalanced part of the withdrawal is charged with half of the swap fee (as if half of it was swapped).
+/// The fee stays in the pool.
+///
+/// * **pools** is the array with assets in the pool.
+///
+/// * **withdrawals** is the amount of each pool asset to withdraw.
+///
+/// * **total_share** is the total amount of LP tokens currently minted.
+///
+/// * **fee_rate** is the total fee rate of the pair.
+pub fn compute_imbalanced_withdraw_share(
+    pools: &[Asset],
+    withdrawals: &[Uint128],
+    total_share: Uint128,
+    fee_rate: Decimal,
+) -> Result<Uint128, ContractError> {
+    let old_balances = pools
+        .iter()
+        .map(|pool| U256::from(pool.amount.u128()))
+        .collect::<Vec<_>>();
+    let mut new_balances = pools
+        .iter()
+        .zip(withdrawals)
+        .map(|(pool, amount)| Ok(U256::from(pool.amount.checked_sub(*amount)?.u128())))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // The pool invariant is the geometric mean of the pool balances
+    let d0 = (old_balances[0] * old_balances[1]).integer_sqrt();
+    let d1 = (new_balances[0] * new_balances[1]).integer_sqrt();
+
+    let fee_numerator = U256::from(fee_rate.numerator().u128());
+    let fee_denominator = U256::from(fee_rate.denominator().u128()) * U256::from(2u8);
+    for (old_balance, new_balance) in old_balances.iter().zip(new_balances.iter_mut()) {
+        let ideal_balance = *old_balance * d1 / d0;
+        let difference = if ideal_balance > *new_balance {
+            ideal_balance - *new_balance
+        } else {
+            *new_balance - ideal_balance
+        };
+        let fee = (difference * fee_numerator + fee_denominator - U256::one()) / fee_denominator;
+        *new_balance = new_balance.saturating_sub(fee);
+    }
+    let d2 = (new_balances[0] * new_balances[1]).integer_sqrt();
+
+    // Round up so the withdrawal never dilutes other liquidity providers
+    let total_share = U256::from(total_share.u128());
+    let burn_amount = (total_share * (d0 - d2) + d0 - U256::one()) / d0;
+
+    Ok(Uint128::new(burn_amount.as_u128()))
+}
+
 /// Returns the amount of pool assets that correspond to an amount of LP tokens.
 ///
 /// * **pools** is the array with assets in the pool.
@@ -613,22 +879,26 @@ pub fn get_share_in_assets(pools: &[Asset], amount: Uint128, total_share: Uint12
 ///
 /// * **to** sets the recipient of the swap operation.
 ///
+/// * **refund_amount** part of the deposit which is not swapped and is sent back to the sender.
+///
 /// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
 #[allow(clippy::too_many_arguments)]
 pub fn swap(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
     sender: Addr,
     offer_asset: Asset,
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
     to: Option<Addr>,
+    refund_amount: Uint128,
 ) -> Result<Response, ContractError> {
-    offer_asset.assert_sent_native_token_balance(&info)?;
-
     let mut config = CONFIG.load(deps.storage)?;
 
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
     // If the asset balance is already increased, we should subtract the user deposit from the pool amount
     let pools = config
         .pair_info
@@ -636,7 +906,7 @@ pub fn swap(
         .into_iter()
         .map(|mut p| {
             if p.info.equal(&offer_asset.info) {
-                p.amount = p.amount.checked_sub(offer_asset.amount)?;
+                p.amount = p.amount.checked_sub(offer_asset.amount + refund_amount)?;
             }
             Ok(p)
         })
@@ -656,11 +926,7 @@ pub fn swap(
     }
 
     // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        &config.factory_addr,
-        config.pair_info.pair_type.clone(),
-    )?;
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
 
     let offer_amount = offer_asset.amount;
 
@@ -691,6 +957,14 @@ pub fn swap(
         messages.push(return_asset.into_msg(receiver.clone())?)
     }
 
+    if !refund_amount.is_zero() {
+        let refund_asset = Asset {
+            info: offer_pool.info.clone(),
+            amount: refund_amount,
+        };
+        messages.push(refund_asset.into_msg(sender.clone())?)
+    }
+
     // Compute the Maker fee
     let mut maker_fee_amount = Uint128::zero();
     if let Some(fee_address) = fee_info.fee_address {
@@ -717,6 +991,18 @@ pub fn swap(
         )?;
     }
 
+    // Store time series data.
+    // Skipping small unsafe values which can seriously mess oracle price due to rounding errors
+    if offer_amount >= MIN_OBSERVATION_TRADE_AMOUNT && return_amount >= MIN_OBSERVATION_TRADE_AMOUNT
+    {
+        let (base_amount, quote_amount) = if offer_asset.info.equal(&pools[0].info) {
+            (offer_amount, return_amount)
+        } else {
+            (return_amount, offer_amount)
+        };
+        accumulate_swap_sizes(deps.storage, &env, base_amount, quote_amount)?;
+    }
+
     // Accumulate prices for the assets in the pool
     if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
         accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
@@ -744,9 +1030,127 @@ pub fn swap(
             attr("spread_amount", spread_amount),
             attr("commission_amount", commission_amount),
             attr("maker_fee_amount", maker_fee_amount),
+            attr("refund_amount", refund_amount),
         ]))
 }
 
+/// Swaps a part of the deposit to the exact amount of the ask asset and sends the rest back to the sender.
+///
+/// * **sender** is the sender of the swap operation.
+///
+/// * **deposit** the offer asset sent to the pair.
+///
+/// * **ask_asset** the asset and the exact amount to receive.
+///
+/// * **max_offer_amount** the maximum amount of the offer asset which can be spent.
+///
+/// * **to** sets the recipient of the swap operation.
+pub fn swap_exact_out(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    deposit: Asset,
+    ask_asset: Asset,
+    max_offer_amount: Uint128,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    /// Upper bound of attempts to compensate rounding errors of the reverse simulation
+    const MAX_ITERATIONS: u8 = 8;
+
+    ask_asset.info.check(deps.api)?;
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?
+        .into_iter()
+        .map(|mut p| {
+            if p.info.equal(&deposit.info) {
+                p.amount = p.amount.checked_sub(deposit.amount)?;
+            }
+            Ok(p)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let (offer_pool, ask_pool) =
+        if deposit.info.equal(&pools[0].info) && ask_asset.info.equal(&pools[1].info) {
+            (&pools[0], &pools[1])
+        } else if deposit.info.equal(&pools[1].info) && ask_asset.info.equal(&pools[0].info) {
+            (&pools[1], &pools[0])
+        } else {
+            return Err(ContractError::AssetMismatch {});
+        };
+
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
+
+    let (mut offer_amount, ..) = compute_offer_amount(
+        offer_pool.amount,
+        ask_pool.amount,
+        ask_asset.amount,
+        fee_info.total_fee_rate,
+    )?;
+
+    // The reverse simulation rounds down, so we adjust the offer amount until the forward simulation
+    // returns at least the requested amount
+    let mut iterations = 0;
+    loop {
+        let (return_amount, ..) = compute_swap(
+            offer_pool.amount,
+            ask_pool.amount,
+            offer_amount,
+            fee_info.total_fee_rate,
+        )?;
+        if return_amount >= ask_asset.amount {
+            break;
+        }
+
+        iterations += 1;
+        if iterations > MAX_ITERATIONS {
+            return Err(ContractError::ExactOutNotFound {});
+        }
+
+        let deficit = ask_asset.amount - return_amount;
+        offer_amount += offer_amount
+            .multiply_ratio(deficit, return_amount.max(Uint128::one()))
+            .max(Uint128::one());
+    }
+
+    if offer_amount > max_offer_amount {
+        return Err(ContractError::MaxOfferAmountAssertion {
+            required: offer_amount,
+            max_offer_amount,
+        });
+    }
+    if offer_amount > deposit.amount {
+        return Err(ContractError::InsufficientOfferAmount {
+            required: offer_amount,
+            provided: deposit.amount,
+        });
+    }
+
+    swap(
+        deps,
+        env,
+        sender,
+        Asset {
+            info: deposit.info,
+            amount: offer_amount,
+        },
+        None,
+        Some(Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?),
+        to,
+        deposit.amount - offer_amount,
+    )
+}
+
 /// Updates the pool configuration with the specified parameters in the `params` variable.
 ///
 /// * **params** new parameter values.
@@ -787,11 +1191,125 @@ pub fn update_config(
                 "enabled".to_owned(),
             ));
         }
+        XYKPoolUpdateParams::UpdateFlashLoanFee { fee_bps } => {
+            if fee_bps > MAX_FLASH_LOAN_FEE_BPS {
+                return Err(ContractError::FlashLoanFeeTooHigh {});
+            }
+            FLASH_LOAN_FEE_BPS.save(deps.storage, &fee_bps)?;
+
+            response
+                .attributes
+                .push(attr("flash_loan_fee_bps", fee_bps.to_string()));
+        }
     }
 
     Ok(response)
 }
 
+/// Sends the specified pool assets to the receiver contract and calls it with `FlashLoanReceiverMsg`.
+/// The pair is locked until the call is finished and the loan is checked in [`check_flash_loan_repaid`].
+///
+/// * **assets** are the assets to borrow.
+///
+/// * **receiver** is the contract which receives borrowed assets.
+///
+/// * **data** is arbitrary data passed to the receiver.
+pub fn flash_loan(
+    deps: DepsMut,
+    env: Env,
+    assets: Vec<Asset>,
+    receiver: String,
+    data: Binary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
+    let receiver = deps.api.addr_validate(&receiver)?;
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &env.contract.address)?;
+    let fee_bps = FLASH_LOAN_FEE_BPS
+        .may_load(deps.storage)?
+        .unwrap_or(DEFAULT_FLASH_LOAN_FEE_BPS);
+
+    let loan = start_flash_loan(
+        deps.storage,
+        &config.pair_info,
+        &receiver,
+        &pools,
+        &assets,
+        fee_bps,
+    )?;
+    let attributes = vec![
+        attr("action", "flash_loan"),
+        attr("receiver", &receiver),
+        attr(
+            "assets",
+            assets
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        attr(
+            "fees",
+            loan.fees
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+    ];
+    let (messages, callback) = flash_loan_messages(&receiver, assets, loan, data)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessage(SubMsg::reply_on_success(callback, FLASH_LOAN_REPLY_ID))
+        .add_attributes(attributes))
+}
+
+/// Checks that the flash loan in progress was repaid together with the fee,
+/// sends the Maker share of the fee and unlocks the pair.
+pub fn check_flash_loan_repaid(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let (loan, balances) = finish_flash_loan(deps.storage, &deps.querier, &env.contract.address)?;
+
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
+
+    let mut messages = vec![];
+    for ((before, fee), actual) in loan
+        .balances_before
+        .iter()
+        .zip(loan.fees.iter())
+        .zip(balances)
+    {
+        let mut maker_fee_amount = Uint128::zero();
+        if let Some(fee_address) = &fee_info.fee_address {
+            if let Some(f) = calculate_maker_fee(&fee.info, fee.amount, fee_info.maker_fee_rate) {
+                maker_fee_amount = f.amount;
+                messages.push(f.into_msg(fee_address)?);
+            }
+        }
+
+        if config.track_asset_balances {
+            BALANCES.save(
+                deps.storage,
+                &before.info,
+                &(actual - maker_fee_amount),
+                env.block.height,
+            )?;
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "flash_loan_repaid"))
+}
+
 /// Accumulate token prices for the assets in the pool.
 /// Note that this function shifts **block_time** when any of the token prices is zero in order to not
 /// fill an accumulator with a null price for that period.
@@ -833,6 +1351,80 @@ pub fn accumulate_prices(
     Ok(Some((pcl0, pcl1, block_time)))
 }
 
+/// Calculate and save moving averages of swap sizes.
+pub fn accumulate_swap_sizes(
+    storage: &mut dyn Storage,
+    env: &Env,
+    base_amount: Uint128,
+    quote_amount: Uint128,
+) -> BufferResult<()> {
+    let mut buffer = BufferManager::new(storage, OBSERVATIONS)?;
+
+    let new_observation;
+    if let Some(last_obs) = buffer.read_last(storage)? {
+        // Since this is circular buffer the next index contains the oldest value
+        let count = buffer.capacity();
+        if let Some(oldest_obs) = buffer.read_single(storage, buffer.head() + 1)? {
+            let new_base_sma = safe_sma_calculation(
+                last_obs.base_sma,
+                oldest_obs.base_amount,
+                count,
+                base_amount,
+            )?;
+            let new_quote_sma = safe_sma_calculation(
+                last_obs.quote_sma,
+                oldest_obs.quote_amount,
+                count,
+                quote_amount,
+            )?;
+            new_observation = Observation {
+                base_amount,
+                quote_amount,
+                base_sma: new_base_sma,
+                quote_sma: new_quote_sma,
+                timestamp: env.block.time.seconds(),
+            };
+        } else {
+            // Buffer is not full yet
+            let count = Uint128::from(buffer.head());
+            let new_base_sma = (last_obs.base_sma * count + base_amount) / (count + Uint128::one());
+            let new_quote_sma =
+                (last_obs.quote_sma * count + quote_amount) / (count + Uint128::one());
+            new_observation = Observation {
+                base_amount,
+                quote_amount,
+                base_sma: new_base_sma,
+                quote_sma: new_quote_sma,
+                timestamp: env.block.time.seconds(),
+            };
+        }
+    } else {
+        // Buffer is empty
+        new_observation = Observation {
+            timestamp: env.block.time.seconds(),
+            base_sma: base_amount,
+            base_amount,
+            quote_sma: quote_amount,
+            quote_amount,
+        };
+    }
+
+    buffer.instant_push(storage, &new_observation)
+}
+
+/// Internal function to calculate new moving average using Uint256.
+/// Overflow is possible only if new average order size is greater than 2^128 - 1 which is unlikely.
+fn safe_sma_calculation(
+    sma: Uint128,
+    oldest_amount: Uint128,
+    count: u32,
+    new_amount: Uint128,
+) -> StdResult<Uint128> {
+    let res = (sma.full_mul(count) + Uint256::from(new_amount) - Uint256::from(oldest_amount))
+        .checked_div(count.into())?;
+    res.try_into().map_err(StdError::from)
+}
+
 /// Calculates the amount of fees the Maker contract gets according to specified pair parameters.
 /// Returns a [`None`] if the Maker fee is zero, otherwise returns a [`Asset`] struct with the specified attributes.
 ///
@@ -880,6 +1472,10 @@ pub fn calculate_maker_fee(
 ///
 /// * **QueryMsg::AssetBalanceAt { asset_info, block_height }** Returns the balance of the specified asset that was in the pool
 /// just preceeding the moment of the specified block height creation.
+///
+/// * **QueryMsg::Observe { seconds_ago }** Returns the price observation at the point that was `seconds_ago` seconds ago.
+///
+/// * **QueryMsg::FlashLoanFee {}** Returns the flash loan fee (in bps).
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -898,6 +1494,14 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             asset_info,
             block_height,
         } => to_binary(&query_asset_balances_at(deps, asset_info, block_height)?),
+        QueryMsg::Observe { seconds_ago } => {
+            to_binary(&query_observation(deps, env, OBSERVATIONS, seconds_ago)?)
+        }
+        QueryMsg::FlashLoanFee {} => to_binary(
+            &FLASH_LOAN_FEE_BPS
+                .may_load(deps.storage)?
+                .unwrap_or(DEFAULT_FLASH_LOAN_FEE_BPS),
+        ),
         _ => Err(StdError::generic_err("Query is not supported")),
     }
 }
@@ -953,11 +1557,7 @@ pub fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationR
     }
 
     // Get fee info from the factory contract
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr,
-        config.pair_info.pair_type,
-    )?;
+    let fee_info = query_pair_fee_info(&deps.querier, config.factory_addr, &config.pair_info)?;
 
     let (return_amount, spread_amount, commission_amount) = compute_swap(
         offer_pool.amount,
@@ -1002,11 +1602,7 @@ pub fn query_reverse_simulation(
     }
 
     // Get fee info from factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr,
-        config.pair_info.pair_type,
-    )?;
+    let fee_info = query_pair_fee_info(&deps.querier, config.factory_addr, &config.pair_info)?;
 
     let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
         offer_pool.amount,
@@ -1265,12 +1861,15 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
             "1.0.0" | "1.0.1" | "1.1.0" | "1.2.0" => {
                 migration::add_asset_balances_tracking_flag(deps.storage)?;
             }
-            "1.3.0" | "1.3.1" => {}
+            "1.3.0" | "1.3.1" | "1.4.0" => {}
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
     }
 
+    // Observations buffer was introduced in v1.5.0
+    BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::default().add_attributes([
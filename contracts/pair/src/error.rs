@@ -1,5 +1,7 @@
 use astroport::asset::MINIMUM_LIQUIDITY_AMOUNT;
-use cosmwasm_std::{OverflowError, StdError};
+use astroport::pair::{FlashLoanError, MAX_FLASH_LOAN_FEE_BPS};
+use astroport_circular_buffer::error::BufferError;
+use cosmwasm_std::{OverflowError, StdError, Uint128};
 use thiserror::Error;
 
 /// This enum describes pair contract errors
@@ -8,9 +10,15 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    CircularBuffer(#[from] BufferError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Swaps and liquidity provision are paused")]
+    PairPaused {},
+
     #[error("CW20 tokens can be swapped via Cw20::Send message only")]
     Cw20DirectSwap {},
 
@@ -50,8 +58,38 @@ pub enum ContractError {
     #[error("Asset balances tracking is already enabled")]
     AssetBalancesTrackingIsAlreadyEnabled {},
 
+    #[error("Transaction deadline has passed")]
+    DeadlineExpired {},
+
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
+
+    #[error("{0}")]
+    FlashLoan(#[from] FlashLoanError),
+
+    #[error(
+        "Flash loan fee must be less than or equal to {} bps",
+        MAX_FLASH_LOAN_FEE_BPS
+    )]
+    FlashLoanFeeTooHigh {},
+
+    #[error("Exactly one offer coin must be sent")]
+    InvalidOfferFunds {},
+
+    #[error("Operation exceeds max offer amount: {required} > {max_offer_amount}")]
+    MaxOfferAmountAssertion {
+        required: Uint128,
+        max_offer_amount: Uint128,
+    },
+
+    #[error("Insufficient offer amount: {required} required, {provided} provided")]
+    InsufficientOfferAmount {
+        required: Uint128,
+        provided: Uint128,
+    },
+
+    #[error("Failed to find an offer amount for the requested ask amount")]
+    ExactOutNotFound {},
 }
 
 impl From<OverflowError> for ContractError {
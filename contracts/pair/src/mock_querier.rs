@@ -6,7 +6,7 @@ use cosmwasm_std::{
 use std::collections::HashMap;
 
 use astroport::factory::FeeInfoResponse;
-use astroport::factory::QueryMsg::FeeInfo;
+use astroport::factory::QueryMsg::{FeeInfo, IsPaused};
 use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies.
@@ -89,6 +89,7 @@ impl WasmMockQuerier {
                             })
                             .into(),
                         ),
+                        IsPaused { .. } => SystemResult::Ok(to_binary(&false).into()),
                         _ => panic!("DO NOT ENTER HERE"),
                     }
                 } else {
@@ -1,4 +1,6 @@
 use astroport::asset::{AssetInfo, PairInfo};
+use astroport::observation::Observation;
+use astroport_circular_buffer::CircularBuffer;
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, Uint128};
 use cw_storage_plus::{Item, SnapshotMap};
@@ -30,3 +32,10 @@ pub const BALANCES: SnapshotMap<&AssetInfo, Uint128> = SnapshotMap::new(
     "balances_change",
     cw_storage_plus::Strategy::EveryBlock,
 );
+
+/// Stores the flash loan fee (in bps). If not set, [`astroport::pair::DEFAULT_FLASH_LOAN_FEE_BPS`] is used
+pub const FLASH_LOAN_FEE_BPS: Item<u16> = Item::new("flash_loan_fee_bps");
+
+/// Circular buffer to store trade size observations
+pub const OBSERVATIONS: CircularBuffer<Observation> =
+    CircularBuffer::new("observations_state", "observations_buffer");
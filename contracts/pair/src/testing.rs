@@ -190,6 +190,7 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -305,6 +306,7 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(50)),
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -377,6 +379,7 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -440,6 +443,7 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -481,6 +485,7 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -522,6 +527,7 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -562,6 +568,7 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -592,6 +599,7 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
     let info = mock_info(
         "addr0001",
@@ -621,6 +629,7 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(51)),
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
     let info = mock_info(
         "addr0001",
@@ -677,7 +686,11 @@ fn withdraw_liquidity() {
     // Withdraw liquidity
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: String::from("addr0000"),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { assets: vec![] }).unwrap(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            assets: vec![],
+            deadline: None,
+        })
+        .unwrap(),
         amount: Uint128::new(100u128),
     });
 
@@ -807,6 +820,7 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
         to: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -1013,6 +1027,7 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
@@ -1028,6 +1043,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: Some(Decimal::percent(50)),
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1150,6 +1166,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1412,6 +1429,7 @@ fn test_accumulate_prices() {
                     contract_addr: Addr::unchecked("pair"),
                     liquidity_token: Addr::unchecked("lp_token"),
                     pair_type: PairType::Xyk {}, // Implemented in mock querier
+                    fee_tier: None,
                 },
                 factory_addr: Addr::unchecked("factory"),
                 block_time_last: case.block_time_last,
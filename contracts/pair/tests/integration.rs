@@ -3,20 +3,26 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use astroport::asset::{native_asset_info, Asset, AssetInfo, AssetInfoExt, PairInfo};
+use astroport::asset::{
+    native_asset_info, Asset, AssetInfo, AssetInfoExt, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
+};
 use astroport::factory::{
     ExecuteMsg as FactoryExecuteMsg, InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType,
-    QueryMsg as FactoryQueryMsg,
+    PauseTarget, QueryMsg as FactoryQueryMsg,
 };
+use astroport::observation::OracleObservation;
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams, TWAP_PRECISION,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FlashLoanError,
+    InstantiateMsg, QueryMsg, XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams,
+    DEFAULT_FLASH_LOAN_FEE_BPS, TWAP_PRECISION,
 };
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport_mocks::cw_multi_test::{App, BasicApp, ContractWrapper, Executor};
-use astroport_mocks::{astroport_address, MockGeneratorBuilder, MockXykPairBuilder};
+use astroport_mocks::{
+    astroport_address, borrower_contract, BorrowerAction, MockGeneratorBuilder, MockXykPairBuilder,
+};
 use astroport_pair::error::ContractError;
-use cosmwasm_std::{attr, to_binary, Addr, Coin, Decimal, Uint128};
+use cosmwasm_std::{attr, to_binary, Addr, Binary, Coin, Decimal, Empty, Uint128};
 use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
 
 const OWNER: &str = "owner";
@@ -79,6 +85,7 @@ fn instantiate_pair(mut router: &mut App, owner: &Addr) -> Addr {
             total_fee_bps: 0,
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         }],
         token_code_id: token_contract_code_id,
         generator_address: Some(String::from("generator")),
@@ -289,7 +296,11 @@ fn test_provide_and_withdraw_liquidity() {
     let msg = Cw20ExecuteMsg::Send {
         contract: pair_instance.to_string(),
         amount: Uint128::from(50u8),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { assets: vec![] }).unwrap(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            assets: vec![],
+            deadline: None,
+        })
+        .unwrap(),
     };
     // Try to send withdraw liquidity with FOO token
     let err = router
@@ -316,6 +327,7 @@ fn test_provide_and_withdraw_liquidity() {
                 belief_price: None,
                 max_spread: None,
                 to: None,
+                deadline: None,
             },
             &[Coin {
                 denom: "cny".to_string(),
@@ -373,6 +385,7 @@ fn provide_liquidity_msg(
         slippage_tolerance: Option::from(slippage_tolerance),
         auto_stake: None,
         receiver,
+        deadline: None,
     };
 
     let coins = [
@@ -482,6 +495,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             total_fee_bps: 0,
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         }],
         token_code_id,
         generator_address: Some(String::from("generator")),
@@ -512,6 +526,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
         ],
         pair_type: PairType::Xyk {},
         init_params: None,
+        fee: None,
+        fee_tier: None,
     };
 
     app.execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
@@ -526,6 +542,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
                 contract_addr: token_y_instance.clone(),
             },
         ],
+        pair_type: None,
+        fee_tier: None,
     };
 
     let res: PairInfo = app
@@ -562,6 +580,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            deadline: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -593,6 +612,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -607,6 +627,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            deadline: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -872,6 +893,7 @@ fn asset_balances_tracking_works_correctly() {
             total_fee_bps: 0,
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         }],
         token_code_id,
         generator_address: Some(String::from("generator")),
@@ -903,6 +925,8 @@ fn asset_balances_tracking_works_correctly() {
         ],
         pair_type: PairType::Xyk {},
         init_params: None,
+        fee: None,
+        fee_tier: None,
     };
 
     app.execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
@@ -917,6 +941,8 @@ fn asset_balances_tracking_works_correctly() {
                 denom: "test2".to_string(),
             },
         ],
+        pair_type: None,
+        fee_tier: None,
     };
 
     let res: PairInfo = app
@@ -1043,6 +1069,7 @@ fn asset_balances_tracking_works_correctly() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let send_funds = [
@@ -1106,6 +1133,8 @@ fn asset_balances_tracking_works_correctly() {
             })
             .unwrap(),
         ),
+        fee: None,
+        fee_tier: None,
     };
 
     app.execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
@@ -1120,6 +1149,8 @@ fn asset_balances_tracking_works_correctly() {
                 denom: "uusd".to_string(),
             },
         ],
+        pair_type: None,
+        fee_tier: None,
     };
 
     let res: PairInfo = app
@@ -1295,6 +1326,7 @@ fn asset_balances_tracking_works_correctly() {
         belief_price: None,
         max_spread: None,
         to: None,
+        deadline: None,
     };
     let send_funds = vec![Coin {
         denom: "uusd".to_owned(),
@@ -1337,7 +1369,11 @@ fn asset_balances_tracking_works_correctly() {
     let msg = Cw20ExecuteMsg::Send {
         contract: pair_instance.to_string(),
         amount: Uint128::new(500_000000),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { assets: vec![] }).unwrap(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            assets: vec![],
+            deadline: None,
+        })
+        .unwrap(),
     };
 
     app.execute_contract(owner.clone(), lp_token_address, &msg, &[])
@@ -1503,6 +1539,107 @@ fn update_pair_config() {
     );
 }
 
+#[test]
+fn check_observe_queries() {
+    let owner = Addr::unchecked("owner");
+    let user1 = Addr::unchecked("user1");
+
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+        ],
+    );
+
+    // Set Alice's balances
+    app.send_tokens(
+        owner.clone(),
+        user1.clone(),
+        &[
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(4000000_000000),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(2000000_000000),
+            },
+        ],
+    )
+    .unwrap();
+
+    // Instantiate pair
+    let pair_instance = instantiate_pair(&mut app, &user1);
+
+    // No swaps were made yet
+    app.wrap()
+        .query_wasm_smart::<OracleObservation>(
+            pair_instance.to_string(),
+            &QueryMsg::Observe { seconds_ago: 0 },
+        )
+        .unwrap_err();
+
+    // Provide liquidity
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1000000_000000),
+        Uint128::new(1000000_000000),
+        None,
+        None,
+    );
+    app.execute_contract(user1.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    // Swap
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_owned(),
+            },
+            amount: Uint128::new(100_000000),
+        },
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let send_funds = vec![Coin {
+        denom: "uusd".to_owned(),
+        amount: Uint128::new(100_000000),
+    }];
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
+        .unwrap();
+
+    app.update_block(|b| {
+        b.height += 1;
+        b.time = b.time.plus_seconds(1000);
+    });
+
+    let res: OracleObservation = app
+        .wrap()
+        .query_wasm_smart(
+            pair_instance.to_string(),
+            &QueryMsg::Observe { seconds_ago: 0 },
+        )
+        .unwrap();
+
+    // 100 uusd were swapped for 99.99 uluna
+    assert_eq!(
+        res,
+        OracleObservation {
+            timestamp: app.block_info().time.seconds(),
+            price: Decimal::from_ratio(100_000000u128, 99_990000u128)
+        }
+    );
+}
+
 #[test]
 fn provide_liquidity_with_autostaking_to_generator() {
     let astroport = astroport_address();
@@ -1550,7 +1687,7 @@ fn provide_liquidity_with_autostaking_to_generator() {
 }
 
 #[test]
-fn test_imbalanced_withdraw_is_disabled() {
+fn test_imbalanced_withdraw() {
     let owner = Addr::unchecked("owner");
     let alice_address = Addr::unchecked("alice");
     let mut router = mock_app(
@@ -1628,26 +1765,792 @@ fn test_imbalanced_withdraw_is_disabled() {
         .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
         .unwrap();
 
-    // Check that imbalanced withdraw is currently disabled
+    // Not enough LP tokens to withdraw the requested assets
     let msg_imbalance = Cw20ExecuteMsg::Send {
         contract: pair_instance.to_string(),
-        amount: Uint128::from(50u8),
+        amount: Uint128::new(500_000),
         msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
             assets: vec![Asset {
                 info: AssetInfo::NativeToken {
                     denom: "uusd".to_string(),
                 },
-                amount: Uint128::from(100u8),
+                amount: Uint128::new(1_000_000),
             }],
+            deadline: None,
         })
         .unwrap(),
     };
+    let err = router
+        .execute_contract(alice_address.clone(), lp_token.clone(), &msg_imbalance, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Not enough LP tokens. You need 501257 LP tokens."
+    );
 
+    // Assets which are not in the pool can not be withdrawn
+    let msg_imbalance = Cw20ExecuteMsg::Send {
+        contract: pair_instance.to_string(),
+        amount: Uint128::new(1_000_000),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            assets: vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "ibc/uatom".to_string(),
+                },
+                amount: Uint128::new(1_000_000),
+            }],
+            deadline: None,
+        })
+        .unwrap(),
+    };
     let err = router
         .execute_contract(alice_address.clone(), lp_token.clone(), &msg_imbalance, &[])
         .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ContractError>().unwrap(),
+        &ContractError::AssetMismatch {}
+    );
+
+    // Withdraw uusd only. Unused LP tokens are sent back
+    let msg_imbalance = Cw20ExecuteMsg::Send {
+        contract: pair_instance.to_string(),
+        amount: Uint128::new(1_000_000),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            assets: vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::new(1_000_000),
+            }],
+            deadline: None,
+        })
+        .unwrap(),
+    };
+    let res = router
+        .execute_contract(alice_address.clone(), lp_token.clone(), &msg_imbalance, &[])
+        .unwrap();
+    assert_eq!(
+        res.events[3].attributes[3],
+        attr("withdrawn_share", "501257")
+    );
+
+    let res: BalanceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &lp_token,
+            &Cw20QueryMsg::Balance {
+                address: alice_address.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.balance, Uint128::new(99_999_000 - 501_257));
+
+    let uusd_balance = router.wrap().query_balance(&alice_address, "uusd").unwrap();
+    assert_eq!(uusd_balance.amount, Uint128::new(133_999_900));
+    let uluna_balance = router
+        .wrap()
+        .query_balance(&alice_address, "uluna")
+        .unwrap();
+    assert_eq!(uluna_balance.amount, Uint128::new(99_999_900));
+}
+
+#[test]
+fn test_flash_loan() {
+    let owner = Addr::unchecked("owner");
+    let alice_address = Addr::unchecked("alice");
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+        ],
+    );
+
+    router
+        .send_tokens(
+            owner.clone(),
+            alice_address.clone(),
+            &[
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(200_000_000u128),
+                },
+                Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(200_000_000u128),
+                },
+            ],
+        )
+        .unwrap();
+
+    let pair_instance = instantiate_pair(&mut router, &owner);
+
+    // Send half of the flash loan fees to the Maker
+    let factory = router
+        .wrap()
+        .query_wasm_smart(pair_instance.to_string(), &QueryMsg::Config {})
+        .map(|config: ConfigResponse| config.factory_addr)
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            factory.clone(),
+            &FactoryExecuteMsg::UpdateConfig {
+                token_code_id: None,
+                fee_address: Some("maker".to_string()),
+                generator_address: None,
+                whitelist_code_id: None,
+                coin_registry_address: None,
+            },
+            &[],
+        )
+        .unwrap();
+    let factory_config: astroport::factory::ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(factory.to_string(), &FactoryQueryMsg::Config {})
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            factory,
+            &FactoryExecuteMsg::UpdatePairConfig {
+                config: PairConfig {
+                    maker_fee_bps: 5000,
+                    ..factory_config.pair_configs[0].clone()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(100_000_000),
+        Uint128::new(100_000_000),
+        None,
+        None,
+    );
+    router
+        .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let borrower_code_id = router.store_code(borrower_contract());
+    let borrower = router
+        .instantiate_contract(
+            borrower_code_id,
+            owner.clone(),
+            &Empty {},
+            &[],
+            "BORROWER",
+            None,
+        )
+        .unwrap();
+    // The borrower needs some funds to pay the flash loan fee
+    router
+        .send_tokens(
+            owner.clone(),
+            borrower.clone(),
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(10_000u128),
+            }],
+        )
+        .unwrap();
+
+    let fee_bps: u16 = router
+        .wrap()
+        .query_wasm_smart(pair_instance.to_string(), &QueryMsg::FlashLoanFee {})
+        .unwrap();
+    assert_eq!(fee_bps, DEFAULT_FLASH_LOAN_FEE_BPS);
+
+    let uusd = native_asset_info("uusd".to_string());
+    let flash_loan_msg = |repay_amount: u128| ExecuteMsg::FlashLoan {
+        assets: vec![uusd.with_balance(1_000_000u128)],
+        receiver: borrower.to_string(),
+        data: to_binary(&BorrowerAction::Repay {
+            assets: vec![uusd.with_balance(repay_amount)],
+        })
+        .unwrap(),
+    };
+
+    // The loan is repaid without the fee
+    let err = router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &flash_loan_msg(1_000_000),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::FlashLoan(FlashLoanError::NotRepaid {
+            asset: "uusd".to_string(),
+            expected: Uint128::new(100_000_900),
+            actual: Uint128::new(100_000_000),
+        })
+        .to_string()
+    );
+
+    // The fee is rounded up, so small loans are not free
+    let err = router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::FlashLoan {
+                assets: vec![uusd.with_balance(100u128)],
+                receiver: borrower.to_string(),
+                data: to_binary(&BorrowerAction::Repay {
+                    assets: vec![uusd.with_balance(100u128)],
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
     assert_eq!(
         err.root_cause().to_string(),
-        "Generic error: Imbalanced withdraw is currently disabled"
+        ContractError::FlashLoan(FlashLoanError::NotRepaid {
+            asset: "uusd".to_string(),
+            expected: Uint128::new(100_000_001),
+            actual: Uint128::new(100_000_000),
+        })
+        .to_string()
     );
+
+    // The pool can not be used during the flash loan
+    let err = router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::FlashLoan {
+                assets: vec![uusd.with_balance(1_000_000u128)],
+                receiver: borrower.to_string(),
+                data: to_binary(&BorrowerAction::Swap {
+                    offer_asset: uusd.with_balance(1_000_000u128),
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::FlashLoan(FlashLoanError::InProgress {}).to_string()
+    );
+
+    // It is impossible to borrow more than the pool has
+    let err = router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::FlashLoan {
+                assets: vec![uusd.with_balance(100_000_001u128)],
+                receiver: borrower.to_string(),
+                data: Binary::default(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Invalid flash loan assets: 100000001uusd exceeds the pool balance"
+    );
+
+    // The loan is repaid with the fee
+    router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &flash_loan_msg(1_000_900),
+            &[],
+        )
+        .unwrap();
+
+    let maker_balance = router.wrap().query_balance("maker", "uusd").unwrap();
+    assert_eq!(maker_balance.amount, Uint128::new(450));
+    let pool_balance = router.wrap().query_balance(&pair_instance, "uusd").unwrap();
+    assert_eq!(pool_balance.amount, Uint128::new(100_000_450));
+    let borrower_balance = router.wrap().query_balance(&borrower, "uusd").unwrap();
+    assert_eq!(borrower_balance.amount, Uint128::new(9_100));
+
+    // The pair is unlocked after the loan is repaid
+    let (msg, coins) = provide_liquidity_msg(Uint128::new(100), Uint128::new(100), None, None);
+    router
+        .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    // Only the factory owner can update the flash loan fee
+    let msg = ExecuteMsg::UpdateConfig {
+        params: to_binary(&XYKPoolUpdateParams::UpdateFlashLoanFee { fee_bps: 30 }).unwrap(),
+    };
+    let err = router
+        .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::UpdateConfig {
+                params: to_binary(&XYKPoolUpdateParams::UpdateFlashLoanFee { fee_bps: 1001 })
+                    .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::FlashLoanFeeTooHigh {}.to_string()
+    );
+
+    router
+        .execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
+        .unwrap();
+    let fee_bps: u16 = router
+        .wrap()
+        .query_wasm_smart(pair_instance.to_string(), &QueryMsg::FlashLoanFee {})
+        .unwrap();
+    assert_eq!(fee_bps, 30);
+}
+
+#[test]
+fn test_flash_loan_cannot_drain_locked_lp() {
+    let owner = Addr::unchecked("owner");
+    let alice_address = Addr::unchecked("alice");
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+        ],
+    );
+
+    let pair_instance = instantiate_pair(&mut router, &owner);
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(100_000_000),
+        Uint128::new(100_000_000),
+        None,
+        None,
+    );
+    router
+        .execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let lp_token = router
+        .wrap()
+        .query_wasm_smart::<PairInfo>(&pair_instance, &QueryMsg::Pair {})
+        .unwrap()
+        .liquidity_token;
+    let locked_lp = |router: &App| {
+        router
+            .wrap()
+            .query_wasm_smart::<BalanceResponse>(
+                &lp_token,
+                &Cw20QueryMsg::Balance {
+                    address: pair_instance.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+    };
+    assert_eq!(locked_lp(&router), MINIMUM_LIQUIDITY_AMOUNT);
+
+    // Neither the LP token nor the pair itself can receive a flash loan,
+    // otherwise the pair would call them on the borrower's behalf
+    for receiver in [&lp_token, &pair_instance] {
+        let err = router
+            .execute_contract(
+                alice_address.clone(),
+                pair_instance.clone(),
+                &ExecuteMsg::FlashLoan {
+                    assets: vec![native_asset_info("uusd".to_string()).with_balance(1_000u128)],
+                    receiver: receiver.to_string(),
+                    data: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: alice_address.to_string(),
+                        amount: MINIMUM_LIQUIDITY_AMOUNT,
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::FlashLoan(FlashLoanError::InvalidReceiver(receiver.to_string()))
+                .to_string()
+        );
+    }
+
+    assert_eq!(locked_lp(&router), MINIMUM_LIQUIDITY_AMOUNT);
+}
+
+#[test]
+fn test_swap_exact_out() {
+    let owner = Addr::unchecked("owner");
+    let alice_address = Addr::unchecked("alice");
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+        ],
+    );
+
+    router
+        .send_tokens(
+            owner.clone(),
+            alice_address.clone(),
+            &[
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(200_000_000u128),
+                },
+                Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(200_000_000u128),
+                },
+            ],
+        )
+        .unwrap();
+
+    let pair_instance = instantiate_pair(&mut router, &owner);
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(100_000_000),
+        Uint128::new(100_000_000),
+        None,
+        None,
+    );
+    router
+        .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let uusd = native_asset_info("uusd".to_string());
+    let swap_msg = |max_offer_amount: u128| ExecuteMsg::SwapExactOut {
+        ask_asset: uusd.with_balance(1_000_000u128),
+        max_offer_amount: Uint128::new(max_offer_amount),
+        to: None,
+        deadline: None,
+    };
+
+    let err = router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &swap_msg(2_000_000),
+            &[Coin::new(1_000_000, "uluna"), Coin::new(1_000_000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::InvalidOfferFunds {}, err.downcast().unwrap());
+
+    let err = router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &swap_msg(1_000_000),
+            &[Coin::new(2_000_000, "uluna")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::MaxOfferAmountAssertion {
+            required: Uint128::new(1_010_102),
+            max_offer_amount: Uint128::new(1_000_000)
+        },
+        err.downcast().unwrap()
+    );
+
+    let err = router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &swap_msg(2_000_000),
+            &[Coin::new(1_000_000, "uluna")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InsufficientOfferAmount {
+            required: Uint128::new(1_010_102),
+            provided: Uint128::new(1_000_000)
+        },
+        err.downcast().unwrap()
+    );
+
+    router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &swap_msg(2_000_000),
+            &[Coin::new(2_000_000, "uluna")],
+        )
+        .unwrap();
+
+    // Alice received the exact ask amount and got the unused offer coins back
+    let alice_balances = router.wrap().query_all_balances(&alice_address).unwrap();
+    assert_eq!(
+        alice_balances,
+        vec![
+            Coin::new(98_989_898, "uluna"),
+            Coin::new(101_000_000, "uusd")
+        ]
+    );
+
+    let pair_balances = router.wrap().query_all_balances(&pair_instance).unwrap();
+    assert_eq!(
+        pair_balances,
+        vec![
+            Coin::new(101_010_102, "uluna"),
+            Coin::new(99_000_000, "uusd")
+        ]
+    );
+}
+
+#[test]
+fn test_deadline() {
+    let owner = Addr::unchecked("owner");
+    let alice_address = Addr::unchecked("alice");
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+        ],
+    );
+
+    router
+        .send_tokens(
+            owner.clone(),
+            alice_address.clone(),
+            &[
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(200_000_000u128),
+                },
+                Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(200_000_000u128),
+                },
+            ],
+        )
+        .unwrap();
+
+    let pair_instance = instantiate_pair(&mut router, &owner);
+    let now = router.block_info().time.seconds();
+
+    let coins = [
+        Coin::new(100_000_000, "uluna"),
+        Coin::new(100_000_000, "uusd"),
+    ];
+    let provide_msg = |deadline: u64| ExecuteMsg::ProvideLiquidity {
+        assets: coins.iter().map(Asset::from).collect(),
+        slippage_tolerance: None,
+        auto_stake: None,
+        receiver: None,
+        deadline: Some(deadline),
+    };
+
+    let err = router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &provide_msg(now - 1),
+            &coins,
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::DeadlineExpired {}, err.downcast().unwrap());
+
+    // The deadline is inclusive
+    router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &provide_msg(now),
+            &coins,
+        )
+        .unwrap();
+
+    let swap_msg = |deadline: u64| ExecuteMsg::Swap {
+        offer_asset: native_asset_info("uluna".to_string()).with_balance(1_000u128),
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: Some(deadline),
+    };
+    let err = router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &swap_msg(now - 1),
+            &[Coin::new(1_000, "uluna")],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::DeadlineExpired {}, err.downcast().unwrap());
+    router
+        .execute_contract(
+            alice_address.clone(),
+            pair_instance.clone(),
+            &swap_msg(now + 100),
+            &[Coin::new(1_000, "uluna")],
+        )
+        .unwrap();
+
+    let res: PairInfo = router
+        .wrap()
+        .query_wasm_smart(pair_instance.to_string(), &QueryMsg::Pair {})
+        .unwrap();
+    let withdraw_msg = |deadline: u64| Cw20ExecuteMsg::Send {
+        contract: pair_instance.to_string(),
+        amount: Uint128::new(1_000),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            assets: vec![],
+            deadline: Some(deadline),
+        })
+        .unwrap(),
+    };
+    let err = router
+        .execute_contract(
+            alice_address.clone(),
+            res.liquidity_token.clone(),
+            &withdraw_msg(now - 1),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::DeadlineExpired {}, err.downcast().unwrap());
+    router
+        .execute_contract(
+            alice_address.clone(),
+            res.liquidity_token,
+            &withdraw_msg(now),
+            &[],
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_paused_pair() {
+    let owner = Addr::unchecked(OWNER);
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+        ],
+    );
+
+    let pair_instance = instantiate_pair(&mut router, &owner);
+    let factory = router
+        .wrap()
+        .query_wasm_smart::<ConfigResponse>(&pair_instance, &QueryMsg::Config {})
+        .unwrap()
+        .factory_addr;
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(100_000_000),
+        Uint128::new(100_000_000),
+        None,
+        None,
+    );
+    router
+        .execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let set_paused = |router: &mut App, paused: bool| {
+        let target = PauseTarget::Pair(pair_instance.to_string());
+        let msg = if paused {
+            FactoryExecuteMsg::Pause { target }
+        } else {
+            FactoryExecuteMsg::Unpause { target }
+        };
+        router
+            .execute_contract(owner.clone(), factory.clone(), &msg, &[])
+            .unwrap();
+    };
+    set_paused(&mut router, true);
+
+    let err = router
+        .execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap_err();
+    assert_eq!(ContractError::PairPaused {}, err.downcast().unwrap());
+
+    let swap_msg = ExecuteMsg::Swap {
+        offer_asset: native_asset_info("uluna".to_string()).with_balance(1_000u128),
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &swap_msg,
+            &[Coin::new(1_000, "uluna")],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::PairPaused {}, err.downcast().unwrap());
+
+    // Liquidity can still be withdrawn from a paused pair
+    let res: PairInfo = router
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pair {})
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            res.liquidity_token,
+            &Cw20ExecuteMsg::Send {
+                contract: pair_instance.to_string(),
+                amount: Uint128::new(1_000),
+                msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                    assets: vec![],
+                    deadline: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    set_paused(&mut router, false);
+    router
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &swap_msg,
+            &[Coin::new(1_000, "uluna")],
+        )
+        .unwrap();
 }
@@ -93,6 +93,7 @@ fn instantiate_factory_contract(app: &mut App, owner: Addr, pair_code_id: u64) -
             pair_type: PairType::Custom("bonded".to_string()),
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         }],
         token_code_id: 0,
         fee_address: None,
@@ -414,6 +415,7 @@ fn test_pair_swap() {
                     belief_price: None,
                     max_spread: None,
                     to: None,
+                    deadline: None,
                 })
                 .unwrap(),
             },
@@ -434,6 +436,7 @@ fn test_pair_swap() {
                     belief_price: None,
                     max_spread: None,
                     to: None,
+                    deadline: None,
                 })
                 .unwrap(),
             },
@@ -547,6 +550,7 @@ fn test_pair_swap() {
                     belief_price: None,
                     max_spread: None,
                     to: None,
+                    deadline: None,
                 })
                 .unwrap(),
             },
@@ -567,6 +571,7 @@ fn test_pair_swap() {
                     belief_price: None,
                     max_spread: None,
                     to: None,
+                    deadline: None,
                 })
                 .unwrap(),
             },
@@ -607,6 +612,7 @@ fn test_unsupported_methods() {
                 slippage_tolerance: None,
                 auto_stake: None,
                 receiver: None,
+                deadline: None,
             },
             &[],
         )
@@ -663,6 +669,7 @@ fn test_unsupported_methods() {
                 belief_price: None,
                 max_spread: None,
                 to: None,
+                deadline: None,
             },
             &[],
         )
@@ -712,7 +719,8 @@ fn test_queries() {
             ],
             contract_addr: contracts.pair_instance.clone(),
             liquidity_token: Addr::unchecked(""),
-            pair_type: PairType::Custom("Bonded".to_string())
+            pair_type: PairType::Custom("Bonded".to_string()),
+            fee_tier: None
         }
     );
 }
@@ -15,17 +15,24 @@ use itertools::Itertools;
 use astroport::asset::AssetInfoExt;
 use astroport::asset::{
     addr_opt_validate, format_lp_token_name, token_asset, Asset, AssetInfo, CoinsExt,
-    Decimal256Ext, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
+    Decimal256Ext, DecimalAsset, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
+};
+use astroport::common::{
+    claim_ownership, drop_ownership_proposal, is_deadline_passed, propose_new_owner,
 };
-use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::cosmwasm_ext::{AbsDiff, DecimalToInteger, IntegerToDecimal};
 use astroport::factory::PairType;
 use astroport::observation::{MIN_TRADE_SIZE, OBSERVATIONS_SIZE};
-use astroport::pair::{Cw20HookMsg, ExecuteMsg, InstantiateMsg};
+use astroport::pair::{
+    assert_no_flash_loan, finish_flash_loan, flash_loan_messages, start_flash_loan, Cw20HookMsg,
+    ExecuteMsg, InstantiateMsg, DEFAULT_FLASH_LOAN_FEE_BPS, MAX_FLASH_LOAN_FEE_BPS,
+};
 use astroport::pair_concentrated::{
     ConcentratedPoolParams, ConcentratedPoolUpdateParams, MigrateMsg, UpdatePoolParams,
 };
-use astroport::querier::{query_factory_config, query_fee_info, query_supply};
+use astroport::querier::{
+    query_factory_config, query_pair_fee_info, query_pair_paused, query_supply,
+};
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport_circular_buffer::BufferManager;
 
@@ -34,12 +41,13 @@ use crate::math::{calc_d, get_xcp};
 use crate::migration::migrate_config;
 use crate::state::{
     store_precisions, AmpGamma, Config, PoolParams, PoolState, Precisions, PriceState, BALANCES,
-    CONFIG, OBSERVATIONS, OWNERSHIP_PROPOSAL,
+    CONFIG, FLASH_LOAN_FEE_BPS, OBSERVATIONS, OWNERSHIP_PROPOSAL,
 };
 use crate::utils::{
     accumulate_swap_sizes, assert_max_spread, assert_slippage_tolerance, before_swap_check,
     calc_provide_fee, check_asset_infos, check_assets, check_cw20_in_pool, check_pair_registered,
-    compute_swap, get_share_in_assets, mint_liquidity_token_message, query_pools,
+    compute_offer_amount, compute_swap, get_share_in_assets, maker_fee_share,
+    mint_liquidity_token_message, query_pools,
 };
 
 /// Contract name that is used for migration.
@@ -48,6 +56,8 @@ const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// A `reply` call code ID used for sub-messages.
 const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
+/// A `reply` call code ID used to check that a flash loan was repaid.
+const FLASH_LOAN_REPLY_ID: u64 = 2;
 /// An LP token's precision.
 pub(crate) const LP_TOKEN_PRECISION: u8 = 6;
 
@@ -111,6 +121,7 @@ pub fn instantiate(
             liquidity_token: Addr::unchecked(""),
             asset_infos: msg.asset_infos.clone(),
             pair_type: PairType::Custom("concentrated".to_string()),
+            fee_tier: None,
         },
         factory_addr,
         block_time_last: env.block.time.seconds(),
@@ -166,7 +177,7 @@ pub fn instantiate(
 
 /// The entry point to the contract for processing replies from submessages.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg {
         Reply {
             id: INSTANTIATE_TOKEN_REPLY_ID,
@@ -189,6 +200,10 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
             Ok(Response::new()
                 .add_attribute("liquidity_token_addr", config.pair_info.liquidity_token))
         }
+        Reply {
+            id: FLASH_LOAN_REPLY_ID,
+            result: SubMsgResult::Ok(_),
+        } => check_flash_loan_repaid(deps, env),
         _ => Err(ContractError::FailedToParseReply {}),
     }
 }
@@ -214,6 +229,15 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             max_spread,
 ///             to,
 ///         }** Performs a swap operation with the specified parameters.
+///
+/// * **ExecuteMsg::SwapExactOut {
+///             ask_asset,
+///             max_offer_amount,
+///             to,
+///         }** Swaps a part of the sent coin to the exact amount of the ask asset and refunds the rest.
+///
+/// * **ExecuteMsg::FlashLoan { assets, receiver, data }** Lends pool assets to the receiver contract
+/// which must return them together with the flash loan fee by the end of the receiver call.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -221,6 +245,9 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // The pair is locked while a flash loan is in progress
+    assert_no_flash_loan(deps.storage)?;
+
     let config = CONFIG.load(deps.storage)?;
 
     match msg {
@@ -230,22 +257,34 @@ pub fn execute(
             slippage_tolerance,
             auto_stake,
             receiver,
-        } => provide_liquidity(
-            deps,
-            env,
-            info,
-            assets,
-            slippage_tolerance,
-            auto_stake,
-            receiver,
-        ),
+            deadline,
+        } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            provide_liquidity(
+                deps,
+                env,
+                info,
+                assets,
+                slippage_tolerance,
+                auto_stake,
+                receiver,
+            )
+        }
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            deadline,
             ..
         } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
@@ -266,9 +305,42 @@ pub fn execute(
                 belief_price,
                 max_spread,
                 to_addr,
+                Uint128::zero(),
+            )
+        }
+        ExecuteMsg::SwapExactOut {
+            ask_asset,
+            max_offer_amount,
+            to,
+            deadline,
+        } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            let offer_asset = match &info.funds[..] {
+                [coin] => Asset::from(coin),
+                _ => return Err(ContractError::InvalidOfferFunds {}),
+            };
+
+            let to_addr = addr_opt_validate(deps.api, &to)?;
+
+            swap_exact_out(
+                deps,
+                env,
+                info.sender,
+                offer_asset,
+                ask_asset,
+                max_offer_amount,
+                to_addr,
             )
         }
         ExecuteMsg::UpdateConfig { params } => update_config(deps, env, info, params),
+        ExecuteMsg::FlashLoan {
+            assets,
+            receiver,
+            data,
+        } => flash_loan(deps, env, config, assets, receiver, data),
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let factory_config = query_factory_config(&deps.querier, config.factory_addr)?;
 
@@ -322,8 +394,13 @@ fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            deadline,
             ..
         } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
             let config = CONFIG.load(deps.storage)?;
 
             // Only asset contract can execute this message
@@ -338,16 +415,49 @@ fn receive_cw20(
                 belief_price,
                 max_spread,
                 to_addr,
+                Uint128::zero(),
+            )
+        }
+        Cw20HookMsg::SwapExactOut {
+            ask_asset,
+            max_offer_amount,
+            to,
+            deadline,
+        } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            let config = CONFIG.load(deps.storage)?;
+
+            // Only asset contract can execute this message
+            check_cw20_in_pool(&config, &info.sender)?;
+
+            let to_addr = addr_opt_validate(deps.api, &to)?;
+            swap_exact_out(
+                deps,
+                env,
+                Addr::unchecked(cw20_msg.sender),
+                token_asset(info.sender, cw20_msg.amount),
+                ask_asset,
+                max_offer_amount,
+                to_addr,
+            )
+        }
+        Cw20HookMsg::WithdrawLiquidity { assets, deadline } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            withdraw_liquidity(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                cw20_msg.amount,
+                assets,
             )
         }
-        Cw20HookMsg::WithdrawLiquidity { assets } => withdraw_liquidity(
-            deps,
-            env,
-            info,
-            Addr::unchecked(cw20_msg.sender),
-            cw20_msg.amount,
-            assets,
-        ),
     }
 }
 
@@ -376,10 +486,15 @@ pub fn provide_liquidity(
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
 
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
     if !check_pair_registered(
         deps.querier,
         &config.factory_addr,
         &config.pair_info.asset_infos,
+        &config.pair_info.pair_type,
     )? {
         return Err(ContractError::PairIsNotRegistered {});
     }
@@ -613,24 +728,51 @@ fn withdraw_liquidity(
     let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
     let mut messages = vec![];
 
-    let refund_assets = if assets.is_empty() {
+    let (refund_assets, burn_amount) = if assets.is_empty() {
         // Usual withdraw (balanced)
-        get_share_in_assets(&pools, amount.saturating_sub(Uint128::one()), total_share)
+        let refund_assets =
+            get_share_in_assets(&pools, amount.saturating_sub(Uint128::one()), total_share);
+
+        // decrease XCP
+        let mut xs = pools.iter().map(|a| a.amount).collect_vec();
+
+        xs[0] -= refund_assets[0].amount;
+        xs[1] -= refund_assets[1].amount;
+        xs[1] *= config.pool_state.price_state.price_scale;
+        let amp_gamma = config.pool_state.get_amp_gamma(&env);
+        let d = calc_d(&xs, &amp_gamma)?;
+        config.pool_state.price_state.xcp_profit_real =
+            get_xcp(d, config.pool_state.price_state.price_scale)
+                / (total_share - amount).to_decimal256(LP_TOKEN_PRECISION)?;
+
+        (refund_assets, amount)
     } else {
-        return Err(StdError::generic_err("Imbalanced withdraw is currently disabled").into());
-    };
+        let (refund_assets, burn_amount) =
+            imbalanced_withdraw(&env, &mut config, &precisions, &pools, &assets, total_share)?;
 
-    // decrease XCP
-    let mut xs = pools.iter().map(|a| a.amount).collect_vec();
+        if burn_amount > amount {
+            return Err(StdError::generic_err(format!(
+                "Not enough LP tokens. You need {} LP tokens.",
+                burn_amount
+            ))
+            .into());
+        } else if burn_amount < amount {
+            // Returning unused LP tokens back to the user
+            messages.push(
+                wasm_execute(
+                    &config.pair_info.liquidity_token,
+                    &Cw20ExecuteMsg::Transfer {
+                        recipient: sender.to_string(),
+                        amount: amount - burn_amount,
+                    },
+                    vec![],
+                )?
+                .into(),
+            );
+        }
 
-    xs[0] -= refund_assets[0].amount;
-    xs[1] -= refund_assets[1].amount;
-    xs[1] *= config.pool_state.price_state.price_scale;
-    let amp_gamma = config.pool_state.get_amp_gamma(&env);
-    let d = calc_d(&xs, &amp_gamma)?;
-    config.pool_state.price_state.xcp_profit_real =
-        get_xcp(d, config.pool_state.price_state.price_scale)
-            / (total_share - amount).to_decimal256(LP_TOKEN_PRECISION)?;
+        (refund_assets, burn_amount)
+    };
 
     let refund_assets = refund_assets
         .into_iter()
@@ -647,6 +789,7 @@ fn withdraw_liquidity(
     messages.extend(
         refund_assets
             .iter()
+            .filter(|asset| !asset.amount.is_zero())
             .cloned()
             .map(|asset| asset.into_msg(&sender))
             .collect::<StdResult<Vec<_>>>()?,
@@ -654,7 +797,9 @@ fn withdraw_liquidity(
     messages.push(
         wasm_execute(
             &config.pair_info.liquidity_token,
-            &Cw20ExecuteMsg::Burn { amount },
+            &Cw20ExecuteMsg::Burn {
+                amount: burn_amount,
+            },
             vec![],
         )?
         .into(),
@@ -679,11 +824,112 @@ fn withdraw_liquidity(
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "withdraw_liquidity"),
         attr("sender", sender),
-        attr("withdrawn_share", amount),
+        attr("withdrawn_share", burn_amount),
         attr("refund_assets", refund_assets.iter().join(", ")),
     ]))
 }
 
+/// Imbalanced withdraw liquidity from the pool. Updates the pool state and returns the assets to withdraw
+/// together with the amount of LP tokens to burn.
+/// The imbalanced part of the withdrawal is charged with the same fee as an imbalanced provide.
+///
+/// * **pools** internal representation of the pool assets.
+///
+/// * **assets** specifies the assets amount to withdraw.
+///
+/// * **total_share** total amount of LP tokens currently minted.
+fn imbalanced_withdraw(
+    env: &Env,
+    config: &mut Config,
+    precisions: &Precisions,
+    pools: &[DecimalAsset],
+    assets: &[Asset],
+    total_share: Uint128,
+) -> Result<(Vec<DecimalAsset>, Uint128), ContractError> {
+    if assets.len() > pools.len() {
+        return Err(ContractError::InvalidNumberOfAssets(pools.len()));
+    }
+
+    if !assets.iter().map(|asset| &asset.info).all_unique() {
+        return Err(ContractError::DoublingAssets {});
+    }
+
+    let mut withdrawals = [Decimal256::zero(), Decimal256::zero()];
+    for asset in assets {
+        let ind = pools
+            .iter()
+            .position(|pool| pool.info.equal(&asset.info))
+            .ok_or_else(|| ContractError::InvalidAsset(asset.info.to_string()))?;
+        withdrawals[ind] =
+            Decimal256::with_precision(asset.amount, precisions.get_precision(&asset.info)?)?;
+        if withdrawals[ind] >= pools[ind].amount {
+            return Err(StdError::generic_err(format!(
+                "Not enough {} in the pool to withdraw {}",
+                asset.info, asset.amount
+            ))
+            .into());
+        }
+    }
+
+    if withdrawals.iter().all(|amount| amount.is_zero()) {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let total_share = total_share.to_decimal256(LP_TOKEN_PRECISION)?;
+    let price_scale = config.pool_state.price_state.price_scale;
+    let amp_gamma = config.pool_state.get_amp_gamma(env);
+
+    let mut old_xp = pools.iter().map(|a| a.amount).collect_vec();
+    old_xp[1] *= price_scale;
+    let old_d = calc_d(&old_xp, &amp_gamma)?;
+
+    let mut new_xp = pools
+        .iter()
+        .zip(withdrawals)
+        .map(|(pool, withdrawal)| pool.amount - withdrawal)
+        .collect_vec();
+    new_xp[1] *= price_scale;
+    let new_d = calc_d(&new_xp, &amp_gamma)?;
+
+    let mut iwithdrawals = withdrawals;
+    iwithdrawals[1] *= price_scale;
+
+    let burn_amount = total_share * old_d.saturating_sub(new_d) / old_d
+        * (Decimal256::one() + calc_provide_fee(&iwithdrawals, &new_xp, &config.pool_params));
+    // Round up to make it unfavorable for the "attacker"
+    let burn_amount = burn_amount.to_uint(LP_TOKEN_PRECISION)? + Uint128::one();
+    let total_lp = total_share.checked_sub(burn_amount.to_decimal256(LP_TOKEN_PRECISION)?)?;
+
+    // calculate withdrawn share
+    let share_ratio = (total_share - total_lp) / total_share;
+    let balanced_share = vec![pools[0].amount * share_ratio, pools[1].amount * share_ratio];
+    let assets_diff = vec![
+        withdrawals[0].diff(balanced_share[0]),
+        withdrawals[1].diff(balanced_share[1]),
+    ];
+
+    // if assets_diff[1] is zero then withdrawals are balanced thus no need to update price
+    if !assets_diff[1].is_zero() {
+        let last_price = assets_diff[0] / assets_diff[1];
+        config
+            .pool_state
+            .update_price(&config.pool_params, env, total_lp, &new_xp, last_price)?;
+    } else {
+        config.pool_state.price_state.xcp_profit_real = get_xcp(new_d, price_scale) / total_lp;
+    }
+
+    let refund_assets = pools
+        .iter()
+        .zip(withdrawals)
+        .map(|(pool, amount)| DecimalAsset {
+            info: pool.info.clone(),
+            amount,
+        })
+        .collect();
+
+    Ok((refund_assets, burn_amount))
+}
+
 /// Performs an swap operation with the specified parameters. The trader must approve the
 /// pool contract to transfer offer assets from their wallet.
 ///
@@ -696,6 +942,9 @@ fn withdraw_liquidity(
 /// * **max_spread** sets the maximum spread of the swap operation.
 ///
 /// * **to** sets the recipient of the swap operation.
+///
+/// * **refund_amount** part of the deposit which is not swapped and is sent back to the sender.
+#[allow(clippy::too_many_arguments)]
 fn swap(
     deps: DepsMut,
     env: Env,
@@ -704,12 +953,17 @@ fn swap(
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
     to: Option<Addr>,
+    refund_amount: Uint128,
 ) -> Result<Response, ContractError> {
     let precisions = Precisions::new(deps.storage)?;
     let offer_asset_prec = precisions.get_precision(&offer_asset.info)?;
     let offer_asset_dec = offer_asset.to_decimal_asset(offer_asset_prec)?;
     let mut config = CONFIG.load(deps.storage)?;
 
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
     let mut pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
 
     let (offer_ind, _) = pools
@@ -719,22 +973,16 @@ fn swap(
     let ask_ind = 1 ^ offer_ind;
     let ask_asset_prec = precisions.get_precision(&pools[ask_ind].info)?;
 
-    pools[offer_ind].amount -= offer_asset_dec.amount;
+    pools[offer_ind].amount -=
+        offer_asset_dec.amount + refund_amount.to_decimal256(offer_asset_prec)?;
 
     before_swap_check(&pools, offer_asset_dec.amount)?;
 
     let mut xs = pools.iter().map(|asset| asset.amount).collect_vec();
 
     // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        &config.factory_addr,
-        config.pair_info.pair_type.clone(),
-    )?;
-    let mut maker_fee_share = Decimal256::zero();
-    if fee_info.fee_address.is_some() {
-        maker_fee_share = fee_info.maker_fee_rate.into();
-    }
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
+    let maker_fee_share = maker_fee_share(&fee_info);
 
     let swap_result = compute_swap(
         &xs,
@@ -776,6 +1024,11 @@ fn swap(
     }
     .into_msg(&receiver)?];
 
+    if !refund_amount.is_zero() {
+        let refund = offer_asset.info.with_balance(refund_amount);
+        messages.push(refund.into_msg(&sender)?);
+    }
+
     let mut maker_fee = Uint128::zero();
     if let Some(fee_address) = fee_info.fee_address {
         maker_fee = swap_result.maker_fee.to_uint(ask_asset_prec)?;
@@ -827,9 +1080,127 @@ fn swap(
             swap_result.total_fee.to_uint(ask_asset_prec)?,
         ),
         attr("maker_fee_amount", maker_fee),
+        attr("refund_amount", refund_amount),
     ]))
 }
 
+/// Swaps a part of the deposit to the exact amount of the ask asset and sends the rest back to the sender.
+///
+/// * **sender** is the sender of the swap operation.
+///
+/// * **deposit** the offer asset sent to the pair.
+///
+/// * **ask_asset** the asset and the exact amount to receive.
+///
+/// * **max_offer_amount** the maximum amount of the offer asset which can be spent.
+///
+/// * **to** sets the recipient of the swap operation.
+fn swap_exact_out(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    deposit: Asset,
+    ask_asset: Asset,
+    max_offer_amount: Uint128,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    /// Upper bound of attempts to compensate rounding errors of the reverse simulation
+    const MAX_ITERATIONS: u8 = 8;
+
+    ask_asset.info.check(deps.api)?;
+    if ask_asset.amount.is_zero() {
+        return Err(StdError::generic_err("Ask amount must not be zero").into());
+    }
+
+    let precisions = Precisions::new(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
+    let mut pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+
+    let (offer_ind, _) = pools
+        .iter()
+        .find_position(|asset| asset.info == deposit.info)
+        .ok_or_else(|| ContractError::InvalidAsset(deposit.info.to_string()))?;
+    let ask_ind = 1 ^ offer_ind;
+    if pools[ask_ind].info != ask_asset.info {
+        return Err(ContractError::InvalidAsset(ask_asset.info.to_string()));
+    }
+    let offer_asset_prec = precisions.get_precision(&deposit.info)?;
+    let ask_asset_prec = precisions.get_precision(&ask_asset.info)?;
+
+    pools[offer_ind].amount -= deposit.amount.to_decimal256(offer_asset_prec)?;
+
+    let xs = pools.iter().map(|asset| asset.amount).collect_vec();
+
+    let (offer_amount, ..) = compute_offer_amount(
+        &xs,
+        ask_asset.amount.to_decimal256(ask_asset_prec)?,
+        ask_ind,
+        &config,
+        &env,
+    )?;
+    // Round up to the offer asset precision
+    let mut offer_amount = offer_amount.to_uint(offer_asset_prec)? + Uint128::one();
+
+    // The reverse simulation is approximate, so we adjust the offer amount until the forward simulation
+    // returns at least the requested amount
+    let mut iterations = 0;
+    loop {
+        let return_amount = compute_swap(
+            &xs,
+            offer_amount.to_decimal256(offer_asset_prec)?,
+            ask_ind,
+            &config,
+            &env,
+            Decimal256::zero(),
+        )?
+        .dy
+        .to_uint(ask_asset_prec)?;
+        if return_amount >= ask_asset.amount {
+            break;
+        }
+
+        iterations += 1;
+        if iterations > MAX_ITERATIONS {
+            return Err(ContractError::ExactOutNotFound {});
+        }
+
+        let deficit = ask_asset.amount - return_amount;
+        offer_amount += offer_amount
+            .multiply_ratio(deficit, return_amount.max(Uint128::one()))
+            .max(Uint128::one());
+    }
+
+    if offer_amount > max_offer_amount {
+        return Err(ContractError::MaxOfferAmountAssertion {
+            required: offer_amount,
+            max_offer_amount,
+        });
+    }
+    if offer_amount > deposit.amount {
+        return Err(ContractError::InsufficientOfferAmount {
+            required: offer_amount,
+            provided: deposit.amount,
+        });
+    }
+
+    swap(
+        deps,
+        env,
+        sender,
+        deposit.info.with_balance(offer_amount),
+        None,
+        // The trader is protected by max_offer_amount thus we allow the maximum spread
+        Some(Decimal::percent(50)),
+        to,
+        deposit.amount - offer_amount,
+    )
+}
+
 /// Updates the pool configuration with the specified parameters in the `params` variable.
 ///
 /// * **params** new parameter values in [`Binary`] form.
@@ -876,12 +1247,114 @@ fn update_config(
 
             "enable_asset_balances_tracking"
         }
+        ConcentratedPoolUpdateParams::UpdateFlashLoanFee { fee_bps } => {
+            if fee_bps > MAX_FLASH_LOAN_FEE_BPS {
+                return Err(ContractError::FlashLoanFeeTooHigh {});
+            }
+            FLASH_LOAN_FEE_BPS.save(deps.storage, &fee_bps)?;
+
+            "update_flash_loan_fee"
+        }
     };
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attribute("action", action))
 }
 
+/// Sends the specified pool assets to the receiver contract and calls it with `FlashLoanReceiverMsg`.
+/// The pair is locked until the call is finished and the loan is checked in [`check_flash_loan_repaid`].
+///
+/// * **assets** are the assets to borrow.
+///
+/// * **receiver** is the contract which receives borrowed assets.
+///
+/// * **data** is arbitrary data passed to the receiver.
+fn flash_loan(
+    deps: DepsMut,
+    env: Env,
+    config: Config,
+    assets: Vec<Asset>,
+    receiver: String,
+    data: Binary,
+) -> Result<Response, ContractError> {
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
+    let receiver = deps.api.addr_validate(&receiver)?;
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &env.contract.address)?;
+    let fee_bps = FLASH_LOAN_FEE_BPS
+        .may_load(deps.storage)?
+        .unwrap_or(DEFAULT_FLASH_LOAN_FEE_BPS);
+
+    let loan = start_flash_loan(
+        deps.storage,
+        &config.pair_info,
+        &receiver,
+        &pools,
+        &assets,
+        fee_bps,
+    )?;
+    let attributes = vec![
+        attr("action", "flash_loan"),
+        attr("receiver", &receiver),
+        attr("assets", assets.iter().join(",")),
+        attr("fees", loan.fees.iter().join(",")),
+    ];
+    let (messages, callback) = flash_loan_messages(&receiver, assets, loan, data)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessage(SubMsg::reply_on_success(callback, FLASH_LOAN_REPLY_ID))
+        .add_attributes(attributes))
+}
+
+/// Checks that the flash loan in progress was repaid together with the fee,
+/// sends the Maker share of the fee and unlocks the pair.
+fn check_flash_loan_repaid(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let (loan, balances) = finish_flash_loan(deps.storage, &deps.querier, &env.contract.address)?;
+
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
+    let maker_fee_share = maker_fee_share(&fee_info);
+    let precisions = Precisions::new(deps.storage)?;
+
+    let mut messages = vec![];
+    for ((before, fee), actual) in loan
+        .balances_before
+        .iter()
+        .zip(loan.fees.iter())
+        .zip(balances)
+    {
+        let mut maker_fee = Uint128::zero();
+        if let Some(fee_address) = &fee_info.fee_address {
+            // The Maker's share is calculated the same way as for swap fees
+            let precision = precisions.get_precision(&fee.info)?;
+            maker_fee =
+                (fee.amount.to_decimal256(precision)? * maker_fee_share).to_uint(precision)?;
+            if !maker_fee.is_zero() {
+                messages.push(fee.info.with_balance(maker_fee).into_msg(fee_address)?);
+            }
+        }
+
+        if config.track_asset_balances {
+            BALANCES.save(
+                deps.storage,
+                &before.info,
+                &(actual - maker_fee),
+                env.block.height,
+            )?;
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "flash_loan_repaid"))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     let contract_version = get_contract_version(deps.storage)?;
@@ -892,7 +1365,7 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
             "1.2.4" => {
                 BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
             }
-            "2.0.3" | "2.0.4" => {}
+            "2.0.3" | "2.0.4" | "2.0.5" => {}
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
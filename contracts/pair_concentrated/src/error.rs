@@ -1,7 +1,8 @@
 use crate::consts::MIN_AMP_CHANGING_TIME;
 use astroport::asset::MINIMUM_LIQUIDITY_AMOUNT;
+use astroport::pair::{FlashLoanError, MAX_FLASH_LOAN_FEE_BPS};
 use astroport_circular_buffer::error::BufferError;
-use cosmwasm_std::{ConversionOverflowError, Decimal, OverflowError, StdError};
+use cosmwasm_std::{ConversionOverflowError, Decimal, OverflowError, StdError, Uint128};
 use thiserror::Error;
 
 /// This enum describes pair contract errors
@@ -22,6 +23,9 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Swaps and liquidity provision are paused")]
+    PairPaused {},
+
     #[error("CW20 tokens can be swapped via Cw20::Send message only")]
     Cw20DirectSwap {},
 
@@ -60,6 +64,9 @@ pub enum ContractError {
     #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY_AMOUNT)]
     MinimumLiquidityAmountError {},
 
+    #[error("Transaction deadline has passed")]
+    DeadlineExpired {},
+
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
 
@@ -77,4 +84,31 @@ pub enum ContractError {
 
     #[error("Asset balances tracking is already enabled")]
     AssetBalancesTrackingIsAlreadyEnabled {},
+
+    #[error("{0}")]
+    FlashLoan(#[from] FlashLoanError),
+
+    #[error(
+        "Flash loan fee must be less than or equal to {} bps",
+        MAX_FLASH_LOAN_FEE_BPS
+    )]
+    FlashLoanFeeTooHigh {},
+
+    #[error("Exactly one offer coin must be sent")]
+    InvalidOfferFunds {},
+
+    #[error("Operation exceeds max offer amount: {required} > {max_offer_amount}")]
+    MaxOfferAmountAssertion {
+        required: Uint128,
+        max_offer_amount: Uint128,
+    },
+
+    #[error("Insufficient offer amount: {required} required, {provided} provided")]
+    InsufficientOfferAmount {
+        required: Uint128,
+        provided: Uint128,
+    },
+
+    #[error("Failed to find an offer amount for the requested ask amount")]
+    ExactOutNotFound {},
 }
@@ -62,6 +62,7 @@ pub(crate) fn migrate_config(storage: &mut dyn Storage) -> Result<(), StdError>
         contract_addr: old_config.pair_info.contract_addr,
         liquidity_token: old_config.pair_info.liquidity_token,
         pair_type: PairType::Custom("concentrated".to_string()),
+        fee_tier: None,
     };
 
     let new_config = Config {
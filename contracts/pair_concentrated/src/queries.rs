@@ -10,20 +10,21 @@ use astroport::cosmwasm_ext::{DecimalToInteger, IntegerToDecimal};
 use astroport::observation::query_observation;
 use astroport::pair::{
     ConfigResponse, PoolResponse, ReverseSimulationResponse, SimulationResponse,
+    DEFAULT_FLASH_LOAN_FEE_BPS,
 };
 
 use astroport::pair_concentrated::{ConcentratedPoolConfig, QueryMsg};
-use astroport::querier::{query_factory_config, query_fee_info, query_supply};
+use astroport::querier::{query_factory_config, query_pair_fee_info, query_supply};
 
 use crate::contract::LP_TOKEN_PRECISION;
 use crate::error::ContractError;
 use crate::math::{calc_d, get_xcp};
 
-use crate::state::{Precisions, BALANCES, CONFIG, OBSERVATIONS};
+use crate::state::{Precisions, BALANCES, CONFIG, FLASH_LOAN_FEE_BPS, OBSERVATIONS};
 
 use crate::utils::{
-    before_swap_check, compute_offer_amount, compute_swap, get_share_in_assets, pool_info,
-    query_pools,
+    before_swap_check, compute_offer_amount, compute_swap, get_share_in_assets, maker_fee_share,
+    pool_info, query_pools,
 };
 
 /// Exposes all the queries available in the contract.
@@ -49,6 +50,8 @@ use crate::utils::{
 ///
 /// * **QueryMsg::AssetBalanceAt { asset_info, block_height }** Returns the balance of the specified
 /// asset that was in the pool just preceding the moment of the specified block height creation.
+///
+/// * **QueryMsg::FlashLoanFee {}** Returns the flash loan fee (in bps).
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -78,6 +81,11 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             asset_info,
             block_height,
         } => to_binary(&query_asset_balances_at(deps, asset_info, block_height)?),
+        QueryMsg::FlashLoanFee {} => to_binary(
+            &FLASH_LOAN_FEE_BPS
+                .may_load(deps.storage)?
+                .unwrap_or(DEFAULT_FLASH_LOAN_FEE_BPS),
+        ),
     }
 }
 
@@ -152,15 +160,8 @@ pub fn query_simulation(
     let xs = pools.iter().map(|asset| asset.amount).collect_vec();
 
     // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        &config.factory_addr,
-        config.pair_info.pair_type.clone(),
-    )?;
-    let mut maker_fee_share = Decimal256::zero();
-    if fee_info.fee_address.is_some() {
-        maker_fee_share = fee_info.maker_fee_rate.into();
-    }
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
+    let maker_fee_share = maker_fee_share(&fee_info);
 
     let swap_result = compute_swap(
         &xs,
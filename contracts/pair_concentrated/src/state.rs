@@ -423,6 +423,9 @@ pub const BALANCES: SnapshotMap<&AssetInfo, Uint128> = SnapshotMap::new(
     cw_storage_plus::Strategy::EveryBlock,
 );
 
+/// Stores the flash loan fee (in bps). If not set, [`astroport::pair::DEFAULT_FLASH_LOAN_FEE_BPS`] is used
+pub const FLASH_LOAN_FEE_BPS: Item<u16> = Item::new("flash_loan_fee_bps");
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
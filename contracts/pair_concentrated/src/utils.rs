@@ -7,8 +7,9 @@ use itertools::Itertools;
 
 use astroport::asset::{Asset, AssetInfo, DecimalAsset};
 use astroport::cosmwasm_ext::AbsDiff;
+use astroport::factory::PairType;
 use astroport::observation::Observation;
-use astroport::querier::{query_factory_config, query_supply};
+use astroport::querier::{query_factory_config, query_supply, FeeInfo};
 use astroport_circular_buffer::error::BufferResult;
 use astroport_circular_buffer::BufferManager;
 use astroport_factory::state::pair_key;
@@ -266,6 +267,15 @@ pub fn calc_last_prices(xs: &[Decimal256], config: &Config, env: &Env) -> StdRes
     Ok(last_price)
 }
 
+/// Returns the share of fees which is sent to the Maker. It is zero if the fee address is not set.
+pub fn maker_fee_share(fee_info: &FeeInfo) -> Decimal256 {
+    if fee_info.fee_address.is_some() {
+        fee_info.maker_fee_rate.into()
+    } else {
+        Decimal256::zero()
+    }
+}
+
 /// Calculate swap result.
 pub fn compute_swap(
     xs: &[Decimal256],
@@ -405,9 +415,14 @@ pub fn check_pair_registered(
     querier: QuerierWrapper,
     factory: &Addr,
     asset_infos: &[AssetInfo],
+    pair_type: &PairType,
 ) -> StdResult<bool> {
     astroport_factory::state::PAIRS
-        .query(&querier, factory.clone(), &pair_key(asset_infos))
+        .query(
+            &querier,
+            factory.clone(),
+            (pair_key(asset_infos).as_slice(), pair_type.to_string()),
+        )
         .map(|inner| inner.is_some())
 }
 
@@ -27,6 +27,7 @@ use astroport::pair_concentrated::{
     ConcentratedPoolParams, ConcentratedPoolUpdateParams, QueryMsg,
 };
 use astroport_mocks::cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+use astroport_mocks::{borrower_contract, BorrowerAction};
 use astroport_pair_concentrated::contract::{execute, instantiate, reply};
 use astroport_pair_concentrated::queries::query;
 use astroport_pair_concentrated::state::Config;
@@ -113,6 +114,7 @@ fn coin_registry_contract() -> Box<dyn Contract<Empty>> {
         astroport_native_coin_registry::contract::query,
     ))
 }
+
 fn factory_contract() -> Box<dyn Contract<Empty>> {
     Box::new(
         ContractWrapper::new_with_empty(
@@ -212,6 +214,7 @@ impl Helper {
                 pair_type: pair_type.clone(),
                 is_disabled: false,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             }],
             token_code_id,
             generator_address: None,
@@ -238,13 +241,19 @@ impl Helper {
             pair_type,
             asset_infos: asset_infos.clone(),
             init_params: Some(to_binary(&params).unwrap()),
+            fee: None,
+            fee_tier: None,
         };
 
         app.execute_contract(owner.clone(), factory.clone(), &init_pair_msg, &[])?;
 
         let resp: PairInfo = app.wrap().query_wasm_smart(
             &factory,
-            &astroport::factory::QueryMsg::Pair { asset_infos },
+            &astroport::factory::QueryMsg::Pair {
+                asset_infos,
+                pair_type: None,
+                fee_tier: None,
+            },
         )?;
 
         Ok(Self {
@@ -280,6 +289,7 @@ impl Helper {
             slippage_tolerance,
             auto_stake: None,
             receiver: None,
+            deadline: None,
         };
 
         self.app
@@ -295,7 +305,11 @@ impl Helper {
         let msg = Cw20ExecuteMsg::Send {
             contract: self.pair_addr.to_string(),
             amount: Uint128::from(amount),
-            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { assets }).unwrap(),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                assets,
+                deadline: None,
+            })
+            .unwrap(),
         };
 
         self.app
@@ -318,6 +332,7 @@ impl Helper {
                         belief_price: None,
                         max_spread,
                         to: None,
+                        deadline: None,
                     })
                     .unwrap(),
                 };
@@ -339,6 +354,7 @@ impl Helper {
                     belief_price: None,
                     max_spread,
                     to: None,
+                    deadline: None,
                 };
 
                 self.app
@@ -347,6 +363,81 @@ impl Helper {
         }
     }
 
+    pub fn swap_exact_out(
+        &mut self,
+        sender: &Addr,
+        deposit: &Asset,
+        ask_asset: &Asset,
+        max_offer_amount: u128,
+    ) -> AnyResult<AppResponse> {
+        match &deposit.info {
+            AssetInfo::Token { contract_addr } => {
+                let msg = Cw20ExecuteMsg::Send {
+                    contract: self.pair_addr.to_string(),
+                    amount: deposit.amount,
+                    msg: to_binary(&Cw20HookMsg::SwapExactOut {
+                        ask_asset: ask_asset.clone(),
+                        max_offer_amount: max_offer_amount.into(),
+                        to: None,
+                        deadline: None,
+                    })
+                    .unwrap(),
+                };
+
+                self.app
+                    .execute_contract(sender.clone(), contract_addr.clone(), &msg, &[])
+            }
+            AssetInfo::NativeToken { .. } => {
+                let funds =
+                    deposit.mock_coin_sent(&mut self.app, sender, &self.pair_addr, SendType::None);
+
+                let msg = ExecuteMsg::SwapExactOut {
+                    ask_asset: ask_asset.clone(),
+                    max_offer_amount: max_offer_amount.into(),
+                    to: None,
+                    deadline: None,
+                };
+
+                self.app
+                    .execute_contract(sender.clone(), self.pair_addr.clone(), &msg, &funds)
+            }
+        }
+    }
+
+    /// Instantiates a mock contract which borrows assets via flash loans.
+    pub fn init_borrower(&mut self) -> Addr {
+        let code_id = self.app.store_code(borrower_contract());
+        self.app
+            .instantiate_contract(
+                code_id,
+                self.owner.clone(),
+                &Empty {},
+                &[],
+                "Borrower",
+                None,
+            )
+            .unwrap()
+    }
+
+    pub fn flash_loan(
+        &mut self,
+        sender: &Addr,
+        assets: &[Asset],
+        borrower: &Addr,
+        action: &BorrowerAction,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            sender.clone(),
+            self.pair_addr.clone(),
+            &ExecuteMsg::FlashLoan {
+                assets: assets.to_vec(),
+                receiver: borrower.to_string(),
+                data: to_binary(action).unwrap(),
+            },
+            &[],
+        )
+    }
+
     pub fn simulate_swap(
         &self,
         offer_asset: &Asset,
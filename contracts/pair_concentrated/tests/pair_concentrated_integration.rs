@@ -1,6 +1,8 @@
 #![cfg(not(tarpaulin_include))]
 
-use astroport_mocks::{astroport_address, MockConcentratedPairBuilder, MockGeneratorBuilder};
+use astroport_mocks::{
+    astroport_address, BorrowerAction, MockConcentratedPairBuilder, MockGeneratorBuilder,
+};
 use cosmwasm_std::{Addr, Coin, Decimal, StdError, Uint128};
 
 use astroport_mocks::cw_multi_test::{BasicApp, Executor};
@@ -14,7 +16,7 @@ use astroport::asset::{
 use astroport::cosmwasm_ext::AbsDiff;
 use astroport::observation::OracleObservation;
 
-use astroport::pair::{ExecuteMsg, PoolResponse};
+use astroport::pair::{ExecuteMsg, FlashLoanError, PoolResponse, DEFAULT_FLASH_LOAN_FEE_BPS};
 use astroport::pair_concentrated::{
     ConcentratedPoolParams, ConcentratedPoolUpdateParams, PromoteParams, QueryMsg, UpdatePoolParams,
 };
@@ -358,19 +360,6 @@ fn provide_and_withdraw() {
     helper.provide_liquidity(&user5, &assets).unwrap();
     assert_eq!(57271_023590, helper.token_balance(&helper.lp_token, &user5));
 
-    // check that imbalanced withdraw is currently disabled
-    let withdraw_assets = vec![
-        helper.assets[&test_coins[0]].with_balance(10_000_000000u128),
-        helper.assets[&test_coins[1]].with_balance(5_000_000000u128),
-    ];
-    let err = helper
-        .withdraw_liquidity(&user1, 7071_067711, withdraw_assets)
-        .unwrap_err();
-    assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Imbalanced withdraw is currently disabled"
-    );
-
     // user1 withdraws 1/10 of his LP tokens
     helper
         .withdraw_liquidity(&user1, 7071_067711, vec![])
@@ -459,6 +448,88 @@ fn check_imbalanced_provide() {
     assert_eq!(0, helper.coin_balance(&test_coins[1], &user1));
 }
 
+#[test]
+fn check_imbalanced_withdraw() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: Decimal::from_ratio(2u8, 1u8),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let user1 = Addr::unchecked("user1");
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000_000000u128),
+    ];
+    helper.give_me_money(&assets, &user1);
+    helper.provide_liquidity(&user1, &assets).unwrap();
+
+    let lp_amount = helper.token_balance(&helper.lp_token, &user1);
+
+    // Withdraw asset which does not belong to the pair
+    let random_coin = native_asset_info("random-coin".to_string()).with_balance(100u8);
+    let err = helper
+        .withdraw_liquidity(&user1, lp_amount / 10, vec![random_coin])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidAsset("random-coin".to_string()),
+        err.downcast().unwrap()
+    );
+
+    // Withdraw the same asset twice
+    let withdraw_assets = vec![
+        helper.assets[&test_coins[0]].with_balance(10_000_000000u128),
+        helper.assets[&test_coins[0]].with_balance(10_000_000000u128),
+    ];
+    let err = helper
+        .withdraw_liquidity(&user1, lp_amount / 10, withdraw_assets)
+        .unwrap_err();
+    assert_eq!(ContractError::DoublingAssets {}, err.downcast().unwrap());
+
+    // Not enough LP tokens to withdraw the requested assets
+    let withdraw_assets = vec![helper.assets[&test_coins[0]].with_balance(10_000_000000u128)];
+    let err = helper
+        .withdraw_liquidity(&user1, lp_amount / 100, withdraw_assets.clone())
+        .unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .starts_with("Generic error: Not enough LP tokens. You need"));
+
+    // Single-sided withdraw. Unused LP tokens are sent back
+    helper
+        .withdraw_liquidity(&user1, lp_amount / 2, withdraw_assets)
+        .unwrap();
+    assert_eq!(10_000_000000, helper.coin_balance(&test_coins[0], &user1));
+    assert_eq!(0, helper.coin_balance(&test_coins[1], &user1));
+
+    // 10_000 uluna is 1/20 of the pool value. Imbalanced withdraw is charged with fees
+    // thus a bit more LP tokens are burned compared to the balanced withdraw
+    let burned_amount = lp_amount - helper.token_balance(&helper.lp_token, &user1);
+    assert!(burned_amount > lp_amount / 20);
+    assert!(burned_amount < lp_amount / 20 * 101 / 100);
+
+    // Balanced withdraw of the rest still works
+    let lp_amount = helper.token_balance(&helper.lp_token, &user1);
+    helper
+        .withdraw_liquidity(&user1, lp_amount, vec![])
+        .unwrap();
+    assert_eq!(0, helper.token_balance(&helper.lp_token, &user1));
+}
+
 #[test]
 fn provide_with_different_precision() {
     let owner = Addr::unchecked("owner");
@@ -1539,3 +1610,254 @@ fn provide_withdraw_slippage() {
         .provide_liquidity_with_slip_tolerance(&owner, &assets, Some(f64_to_dec(0.5)))
         .unwrap();
 }
+
+#[test]
+fn check_flash_loan() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: Decimal::one(),
+        ma_half_time: 600,
+        track_asset_balances: Some(true),
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let fee_bps: u16 = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::FlashLoanFee {})
+        .unwrap();
+    assert_eq!(fee_bps, DEFAULT_FLASH_LOAN_FEE_BPS);
+
+    let user = Addr::unchecked("user");
+    let borrower = helper.init_borrower();
+    // The borrower needs some funds to pay the flash loan fee
+    helper.give_me_money(
+        &[helper.assets[&test_coins[1]].with_balance(10_000u128)],
+        &borrower,
+    );
+
+    let loan = vec![helper.assets[&test_coins[1]].with_balance(10_000000u128)];
+    let repay_msg = |amount: u128| BorrowerAction::Repay {
+        assets: vec![helper.assets[&test_coins[1]].with_balance(amount)],
+    };
+    let msg_without_fee = repay_msg(10_000000);
+    let msg = repay_msg(10_009000);
+
+    // The loan is repaid without the fee
+    let err = helper
+        .flash_loan(&user, &loan, &borrower, &msg_without_fee)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::FlashLoan(FlashLoanError::NotRepaid {
+            asset: helper.assets[&test_coins[1]].to_string(),
+            expected: Uint128::new(100_000_009000),
+            actual: Uint128::new(100_000_000000),
+        })
+        .to_string()
+    );
+
+    // Zero amounts can not be borrowed
+    let err = helper
+        .flash_loan(
+            &user,
+            &[helper.assets[&test_coins[1]].with_balance(0u128)],
+            &borrower,
+            &msg,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::FlashLoan(FlashLoanError::InvalidAssets(format!(
+            "zero amount of {}",
+            helper.assets[&test_coins[1]]
+        ))),
+        err.downcast().unwrap()
+    );
+
+    // The pool can not be used during the flash loan
+    helper.give_me_money(
+        &[helper.assets[&test_coins[0]].with_balance(1_000000u128)],
+        &borrower,
+    );
+    let swap_msg = BorrowerAction::Swap {
+        offer_asset: helper.assets[&test_coins[0]].with_balance(1_000000u128),
+    };
+    let err = helper
+        .flash_loan(&user, &loan, &borrower, &swap_msg)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::FlashLoan(FlashLoanError::InProgress {}).to_string()
+    );
+
+    // The loan is repaid with the fee
+    helper.flash_loan(&user, &loan, &borrower, &msg).unwrap();
+
+    let maker = helper.fake_maker.clone();
+    assert_eq!(helper.coin_balance(&test_coins[1], &maker), 4500);
+    assert_eq!(
+        helper.coin_balance(&test_coins[1], &helper.pair_addr),
+        100_000_004500
+    );
+    assert_eq!(helper.coin_balance(&test_coins[1], &borrower), 1000);
+
+    helper.app.next_block(10);
+    assert_eq!(
+        helper
+            .query_asset_balance_at(
+                &helper.assets[&test_coins[1]],
+                helper.app.block_info().height
+            )
+            .unwrap(),
+        Some(Uint128::new(100_000_004500))
+    );
+
+    // The pair is unlocked after the loan is repaid
+    helper
+        .swap(
+            &borrower,
+            &helper.assets[&test_coins[0]].with_balance(1_000000u128),
+            None,
+        )
+        .unwrap();
+
+    let err = helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::UpdateFlashLoanFee { fee_bps: 1001 },
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::FlashLoanFeeTooHigh {},
+        err.downcast().unwrap()
+    );
+
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::UpdateFlashLoanFee { fee_bps: 30 },
+        )
+        .unwrap();
+    let fee_bps: u16 = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::FlashLoanFee {})
+        .unwrap();
+    assert_eq!(fee_bps, 30);
+}
+
+#[test]
+fn check_swap_exact_out() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: Decimal::one(),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    let deposit = helper.assets[&test_coins[0]].with_balance(2_000000u128);
+    let ask_asset = helper.assets[&test_coins[1]].with_balance(1_000000u128);
+    helper.give_me_money(&[deposit.clone()], &user);
+
+    let err = helper
+        .swap_exact_out(&user, &deposit, &ask_asset, 900_000)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MaxOfferAmountAssertion { .. }
+    ));
+
+    let err = helper
+        .swap_exact_out(
+            &user,
+            &helper.assets[&test_coins[0]].with_balance(900_000u128),
+            &ask_asset,
+            2_000000,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientOfferAmount { .. }
+    ));
+
+    let err = helper
+        .swap_exact_out(
+            &user,
+            &deposit,
+            &helper.assets[&test_coins[0]].with_balance(1_000000u128),
+            2_000000,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::InvalidAsset(helper.assets[&test_coins[0]].to_string()).to_string()
+    );
+
+    let pair_luna_before = helper.coin_balance(&test_coins[0], &helper.pair_addr);
+    helper
+        .swap_exact_out(&user, &deposit, &ask_asset, 1_100000)
+        .unwrap();
+
+    // The user received at least the ask amount and the rest of the deposit was refunded
+    let received = helper.coin_balance(&test_coins[1], &user);
+    assert!(received >= 1_000000);
+    assert!(received < 1_005000, "Received too much: {received}");
+    let spent = 2_000000 - helper.coin_balance(&test_coins[0], &user);
+    assert!(spent > 1_000000 && spent <= 1_100000, "Spent: {spent}");
+    assert_eq!(
+        helper.coin_balance(&test_coins[0], &helper.pair_addr),
+        pair_luna_before + spent
+    );
+
+    // Exact output swap via the CW20 hook
+    let deposit = helper.assets[&test_coins[1]].with_balance(2_000000u128);
+    let ask_asset = helper.assets[&test_coins[0]].with_balance(1_000000u128);
+    helper.give_me_money(&[deposit.clone()], &user);
+    let usdc_before = helper.coin_balance(&test_coins[1], &user);
+    let luna_before = helper.coin_balance(&test_coins[0], &user);
+    helper
+        .swap_exact_out(&user, &deposit, &ask_asset, 1_100000)
+        .unwrap();
+
+    let received = helper.coin_balance(&test_coins[0], &user) - luna_before;
+    assert!(
+        received >= 1_000000 && received < 1_005000,
+        "Received: {received}"
+    );
+    let spent = usdc_before - helper.coin_balance(&test_coins[1], &user);
+    assert!(spent > 1_000000 && spent <= 1_100000, "Spent: {spent}");
+}
@@ -15,7 +15,9 @@ use astroport::asset::{
     addr_opt_validate, format_lp_token_name, Asset, AssetInfo, AssetInfoExt, CoinsExt,
     Decimal256Ext, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
 };
-use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
+use astroport::common::{
+    claim_ownership, drop_ownership_proposal, is_deadline_passed, propose_new_owner,
+};
 use astroport::cosmwasm_ext::{AbsDiff, DecimalToInteger, IntegerToDecimal};
 use astroport::factory::PairType;
 use astroport::observation::{MIN_TRADE_SIZE, OBSERVATIONS_SIZE};
@@ -24,7 +26,9 @@ use astroport::pair_concentrated::UpdatePoolParams;
 use astroport::pair_concentrated_inj::{
     ConcentratedInjObParams, ConcentratedObPoolUpdateParams, ExecuteMsg,
 };
-use astroport::querier::{query_factory_config, query_fee_info, query_supply};
+use astroport::querier::{
+    query_factory_config, query_pair_fee_info, query_pair_paused, query_supply,
+};
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport_circular_buffer::BufferManager;
 
@@ -130,6 +134,7 @@ pub fn instantiate(
             liquidity_token: Addr::unchecked(""),
             asset_infos: msg.asset_infos.clone(),
             pair_type: PairType::Custom("concentrated_inj_orderbook".to_string()),
+            fee_tier: None,
         },
         factory_addr,
         pool_params,
@@ -237,22 +242,34 @@ pub fn execute(
             slippage_tolerance,
             auto_stake,
             receiver,
-        } => provide_liquidity(
-            deps,
-            env,
-            info,
-            assets,
-            slippage_tolerance,
-            auto_stake,
-            receiver,
-        ),
+            deadline,
+        } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            provide_liquidity(
+                deps,
+                env,
+                info,
+                assets,
+                slippage_tolerance,
+                auto_stake,
+                receiver,
+            )
+        }
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            deadline,
             ..
         } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
             offer_asset.info.check(deps.api)?;
             if !config.pair_info.asset_infos.contains(&offer_asset.info) {
                 return Err(ContractError::InvalidAsset(offer_asset.info.to_string()));
@@ -323,14 +340,20 @@ fn receive_cw20(
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response<InjectiveMsgWrapper>, ContractError> {
     match from_binary(&cw20_msg.msg)? {
-        Cw20HookMsg::WithdrawLiquidity { assets } => withdraw_liquidity(
-            deps,
-            env,
-            info,
-            Addr::unchecked(cw20_msg.sender),
-            cw20_msg.amount,
-            assets,
-        ),
+        Cw20HookMsg::WithdrawLiquidity { assets, deadline } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            withdraw_liquidity(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                cw20_msg.amount,
+                assets,
+            )
+        }
         _ => Err(ContractError::NotSupported {}),
     }
 }
@@ -361,10 +384,15 @@ where
 {
     let mut config = CONFIG.load(deps.storage)?;
 
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
     if !check_pair_registered(
         deps.querier,
         &config.factory_addr,
         &config.pair_info.asset_infos,
+        &config.pair_info.pair_type,
     )? {
         return Err(ContractError::PairIsNotRegistered {});
     }
@@ -714,6 +742,11 @@ where
     let offer_asset_prec = precisions.get_precision(&offer_asset.info)?;
     let offer_asset_dec = offer_asset.to_decimal_asset(offer_asset_prec)?;
     let mut config = CONFIG.load(deps.storage)?;
+
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
     let mut ob_state = OrderbookState::load(deps.storage)?;
 
     let mut pools = query_pools(
@@ -739,11 +772,7 @@ where
     let mut xs = pools.iter().map(|asset| asset.amount).collect_vec();
 
     // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        &config.factory_addr,
-        config.pair_info.pair_type.clone(),
-    )?;
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
     let mut maker_fee_share = Decimal256::zero();
     if fee_info.fee_address.is_some() {
         maker_fee_share = fee_info.maker_fee_rate.into();
@@ -22,6 +22,9 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Swaps and liquidity provision are paused")]
+    PairPaused {},
+
     #[error("You need to provide init params")]
     InitParamsNotFound {},
 
@@ -57,6 +60,9 @@ pub enum ContractError {
     #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY_AMOUNT)]
     MinimumLiquidityAmountError {},
 
+    #[error("Transaction deadline has passed")]
+    DeadlineExpired {},
+
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
 
@@ -13,7 +13,7 @@ use astroport_pair_concentrated::state::Config as CLConfig;
 use crate::state::{AmpGamma, Config, PoolParams, PoolState, PriceState, CONFIG};
 
 const MIGRATE_FROM: &str = "astroport-pair-concentrated";
-const MIGRATION_VERSION: &str = "2.0.5";
+const MIGRATION_VERSIONS: &[&str] = &["2.0.5", "2.1.0"];
 
 /// Manages the contract migration.
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -27,7 +27,8 @@ pub fn migrate(
     let contract_info = CONTRACT.load(deps.storage)?;
     match msg {
         MigrateMsg::MigrateToOrderbook { params } => {
-            if contract_info.contract != MIGRATE_FROM || contract_info.version != MIGRATION_VERSION
+            if contract_info.contract != MIGRATE_FROM
+                || !MIGRATION_VERSIONS.contains(&contract_info.version.as_str())
             {
                 return Err(StdError::generic_err(format!(
                     "Can't migrate from {} {}",
@@ -57,7 +58,7 @@ pub fn migrate(
             let contract_info = cw2::get_contract_version(deps.storage)?;
             match contract_info.contract.as_str() {
                 CONTRACT_NAME => match contract_info.version.as_str() {
-                    "2.0.3" | "2.0.4" => {}
+                    "2.0.3" | "2.0.4" | "2.0.5" => {}
                     _ => {
                         return Err(StdError::generic_err(format!(
                             "Can't migrate from {} {}",
@@ -16,7 +16,7 @@ use crate::orderbook::error::OrderbookError;
 use crate::orderbook::state::OrderbookState;
 use crate::state::{AmpGamma, Config, Precisions};
 use astroport::cosmwasm_ext::{AbsDiff, ConvertInto, IntegerToDecimal};
-use astroport::querier::{query_fee_info, query_supply};
+use astroport::querier::{query_pair_fee_info, query_supply};
 use injective_cosmwasm::{
     checked_address_to_subaccount_id, create_batch_update_orders_msg, create_withdraw_msg,
     FundingMode, InjectiveMsgWrapper, InjectiveQuerier, MarketId, OrderType, SpotOrder,
@@ -419,11 +419,7 @@ where
     // converting into internal representation
     ixs[1] *= config.pool_state.price_state.price_scale;
 
-    let fee_info = query_fee_info(
-        &querier,
-        &config.factory_addr,
-        config.pair_info.pair_type.clone(),
-    )?;
+    let fee_info = query_pair_fee_info(&querier, &config.factory_addr, &config.pair_info)?;
 
     let mut messages = vec![];
     if let Some(fee_addr) = fee_info.fee_address {
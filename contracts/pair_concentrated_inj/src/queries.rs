@@ -13,7 +13,7 @@ use astroport::pair::{
 };
 use astroport::pair_concentrated::ConcentratedPoolParams;
 use astroport::pair_concentrated_inj::{OrderbookStateResponse, QueryMsg};
-use astroport::querier::{query_factory_config, query_fee_info, query_supply};
+use astroport::querier::{query_factory_config, query_pair_fee_info, query_supply};
 
 use crate::contract::LP_TOKEN_PRECISION;
 use crate::error::ContractError;
@@ -180,11 +180,7 @@ pub fn query_simulation(
     let xs = pools.iter().map(|asset| asset.amount).collect_vec();
 
     // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        &config.factory_addr,
-        config.pair_info.pair_type.clone(),
-    )?;
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
     let mut maker_fee_share = Decimal256::zero();
     if fee_info.fee_address.is_some() {
         maker_fee_share = fee_info.maker_fee_rate.into();
@@ -8,6 +8,7 @@ use itertools::Itertools;
 
 use astroport::asset::{Asset, AssetInfo, DecimalAsset};
 use astroport::cosmwasm_ext::{AbsDiff, IntegerToDecimal};
+use astroport::factory::PairType;
 use astroport::observation::Observation;
 use astroport::querier::query_factory_config;
 use astroport_circular_buffer::error::BufferResult;
@@ -507,12 +508,17 @@ pub fn check_pair_registered<C>(
     querier: QuerierWrapper<C>,
     factory: &Addr,
     asset_infos: &[AssetInfo],
+    pair_type: &PairType,
 ) -> StdResult<bool>
 where
     C: CustomQuery,
 {
     astroport_factory::state::PAIRS
-        .query(&querier, factory.clone(), &pair_key(asset_infos))
+        .query(
+            &querier,
+            factory.clone(),
+            (pair_key(asset_infos).as_slice(), pair_type.to_string()),
+        )
         .map(|inner| inner.is_some())
 }
 
@@ -54,7 +54,8 @@ where
     T: CustomMsg,
 {
     let tmp = TMP_PAIR_INFO.load(deps.storage)?;
-    if PAIRS.has(deps.storage, &tmp.pair_key) {
+    let key = (tmp.pair_key.as_slice(), tmp.pair_type.to_string());
+    if PAIRS.has(deps.storage, key.clone()) {
         return Err(ContractError::PairWasRegistered {});
     }
 
@@ -64,7 +65,7 @@ where
 
     let pair_contract = deps.api.addr_validate(&res.contract_address)?;
 
-    PAIRS.save(deps.storage, &tmp.pair_key, &pair_contract)?;
+    PAIRS.save(deps.storage, key, &pair_contract)?;
 
     Ok(Response::new().add_attributes(vec![
         attr("action", "register"),
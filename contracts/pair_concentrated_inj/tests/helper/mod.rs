@@ -272,6 +272,7 @@ impl Helper {
                     pair_type: PairType::Custom("concentrated".to_string()),
                     is_disabled: false,
                     is_generator_disabled: false,
+                    fee_tiers: vec![],
                 },
                 PairConfig {
                     code_id: app.store_code(orderbook_pair_contract()),
@@ -280,6 +281,7 @@ impl Helper {
                     pair_type: PairType::Custom("concentrated_inj_orderbook".to_string()),
                     is_disabled: false,
                     is_generator_disabled: false,
+                    fee_tiers: vec![],
                 },
             ],
             token_code_id,
@@ -324,13 +326,19 @@ impl Helper {
             pair_type,
             asset_infos: asset_infos.clone(),
             init_params: Some(params),
+            fee: None,
+            fee_tier: None,
         };
 
         app.execute_contract(owner.clone(), factory.clone(), &init_pair_msg, &[])?;
 
         let resp: PairInfo = app.wrap().query_wasm_smart(
             &factory,
-            &astroport::factory::QueryMsg::Pair { asset_infos },
+            &astroport::factory::QueryMsg::Pair {
+                asset_infos,
+                pair_type: None,
+                fee_tier: None,
+            },
         )?;
 
         Ok(Self {
@@ -389,6 +397,7 @@ impl Helper {
             slippage_tolerance,
             auto_stake: None,
             receiver: None,
+            deadline: None,
         };
 
         self.app
@@ -404,7 +413,11 @@ impl Helper {
         let msg = Cw20ExecuteMsg::Send {
             contract: self.pair_addr.to_string(),
             amount: Uint128::from(amount),
-            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { assets }).unwrap(),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                assets,
+                deadline: None,
+            })
+            .unwrap(),
         };
 
         self.app
@@ -427,6 +440,7 @@ impl Helper {
                         belief_price: None,
                         max_spread,
                         to: None,
+                        deadline: None,
                     })
                     .unwrap(),
                 };
@@ -448,6 +462,7 @@ impl Helper {
                     belief_price: None,
                     max_spread,
                     to: None,
+                    deadline: None,
                 };
 
                 self.app
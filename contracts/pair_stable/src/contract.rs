@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::vec;
@@ -19,36 +20,46 @@ use astroport::asset::{
     Decimal256Ext, DecimalAsset, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
 };
 
-use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
-use astroport::cosmwasm_ext::IntegerToDecimal;
+use astroport::common::{
+    claim_ownership, drop_ownership_proposal, is_deadline_passed, propose_new_owner,
+};
+use astroport::cosmwasm_ext::{AbsDiff, IntegerToDecimal};
 use astroport::factory::PairType;
 use astroport::pair::{
-    ConfigResponse, InstantiateMsg, StablePoolParams, StablePoolUpdateParams, DEFAULT_SLIPPAGE,
-    MAX_ALLOWED_SLIPPAGE,
+    assert_no_flash_loan, finish_flash_loan, flash_loan_messages, start_flash_loan,
+    AssetRateSource, ConfigResponse, InstantiateMsg, StablePoolParams, StablePoolUpdateParams,
+    DEFAULT_FLASH_LOAN_FEE_BPS, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MAX_FLASH_LOAN_FEE_BPS,
 };
 
-use crate::migration::{migrate_config_from_v21, migrate_config_to_v210};
-use astroport::observation::{query_observation, MIN_TRADE_SIZE, OBSERVATIONS_SIZE};
+use crate::migration::{migrate_config_from_v21, migrate_config_to_v210, migrate_config_to_v320};
+use astroport::observation::{
+    query_observation, OracleObservation, MIN_TRADE_SIZE, OBSERVATIONS_SIZE,
+};
 use astroport::pair::{
-    Cw20HookMsg, ExecuteMsg, MigrateMsg, PoolResponse, QueryMsg, ReverseSimulationResponse,
-    SimulationResponse, StablePoolConfig,
+    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, MigrateMsg, PoolResponse, QueryMsg,
+    ReverseSimulationResponse, SimulationResponse, StablePoolConfig,
+};
+use astroport::querier::{
+    query_factory_config, query_pair_fee_info, query_pair_paused, query_supply,
 };
-use astroport::querier::{query_factory_config, query_fee_info, query_supply};
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport::DecimalCheckedOps;
 use astroport_circular_buffer::BufferManager;
 
 use crate::error::ContractError;
 use crate::math::{
-    calc_y, compute_d, AMP_PRECISION, MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME,
+    calc_y, compute_d, AMP_PRECISION, MAX_AMP, MAX_AMP_CHANGE, MAX_N_COINS, MIN_AMP_CHANGING_TIME,
+    MIN_N_COINS,
 };
 use crate::state::{
-    get_precision, store_precisions, Config, CONFIG, OBSERVATIONS, OWNERSHIP_PROPOSAL,
+    get_precision, init_cumulative_prices, store_precisions, Config, ObservationsKeys,
+    CACHED_RATES, CONFIG, FLASH_LOAN_FEE_BPS, OBSERVATIONS, OWNERSHIP_PROPOSAL, RATE_SOURCES,
 };
 use crate::utils::{
-    accumulate_swap_sizes, adjust_precision, check_asset_infos, check_assets, check_cw20_in_pool,
-    compute_current_amp, compute_swap, determine_base_quote_amount, get_share_in_assets,
-    mint_liquidity_token_message, select_pools, SwapResult,
+    accumulate_prices, accumulate_swap_sizes, adjust_precision, check_asset_infos, check_assets,
+    check_cw20_in_pool, check_rate_sources, compute_current_amp, compute_swap,
+    determine_base_quote_amount, get_share_in_assets, mint_liquidity_token_message, select_pools,
+    Rates, SwapResult,
 };
 
 /// Contract name that is used for migration.
@@ -57,8 +68,8 @@ const CONTRACT_NAME: &str = "astroport-pair-stable";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// A `reply` call code ID of sub-message.
 const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
-/// Number of assets in the pool.
-const N_COINS: usize = 2;
+/// A `reply` call code ID used to check that a flash loan was repaid.
+const FLASH_LOAN_REPLY_ID: u64 = 2;
 
 /// Creates a new contract with the specified parameters in [`InstantiateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -70,8 +81,8 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     check_asset_infos(deps.api, &msg.asset_infos)?;
 
-    if msg.asset_infos.len() != N_COINS {
-        return Err(ContractError::InvalidNumberOfAssets(N_COINS));
+    if !(MIN_N_COINS..=MAX_N_COINS).contains(&msg.asset_infos.len()) {
+        return Err(ContractError::InvalidNumberOfPoolAssets {});
     }
 
     if msg.init_params.is_none() {
@@ -84,6 +95,9 @@ pub fn instantiate(
         return Err(ContractError::IncorrectAmp {});
     }
 
+    let rate_sources = params.rate_sources.unwrap_or_default();
+    check_rate_sources(deps.api, &msg.asset_infos, &rate_sources)?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     let factory_addr = deps.api.addr_validate(&msg.factory_addr)?;
@@ -96,6 +110,7 @@ pub fn instantiate(
             liquidity_token: Addr::unchecked(""),
             asset_infos: msg.asset_infos.clone(),
             pair_type: PairType::Stable {},
+            fee_tier: None,
         },
         factory_addr,
         block_time_last: 0,
@@ -104,10 +119,29 @@ pub fn instantiate(
         next_amp: params.amp * AMP_PRECISION,
         next_amp_time: env.block.time.seconds(),
         greatest_precision,
+        cumulative_prices: init_cumulative_prices(&msg.asset_infos),
     };
 
     CONFIG.save(deps.storage, &config)?;
-    BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+
+    for rate_source in rate_sources {
+        RATE_SOURCES.save(
+            deps.storage,
+            rate_source.asset_info.to_string(),
+            &rate_source.source,
+        )?;
+    }
+    // Make sure rate providers respond and cache their initial rates
+    Rates::load(deps.as_ref(), &env, &config)?.save(deps.storage)?;
+
+    // Observations are stored for each pair of assets in the pool
+    for (base_ind, quote_ind) in (0..msg.asset_infos.len()).tuple_combinations() {
+        BufferManager::init(
+            deps.storage,
+            ObservationsKeys::new(base_ind, quote_ind).buffer(),
+            OBSERVATIONS_SIZE,
+        )?;
+    }
 
     let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
 
@@ -137,7 +171,7 @@ pub fn instantiate(
 
 /// The entry point to the contract for processing replies from submessages.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg {
         Reply {
             id: INSTANTIATE_TOKEN_REPLY_ID,
@@ -160,6 +194,10 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
             Ok(Response::new()
                 .add_attribute("liquidity_token_addr", config.pair_info.liquidity_token))
         }
+        Reply {
+            id: FLASH_LOAN_REPLY_ID,
+            result: SubMsgResult::Ok(_),
+        } => check_flash_loan_repaid(deps, env),
         _ => Err(ContractError::FailedToParseReply {}),
     }
 }
@@ -186,6 +224,9 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             max_spread,
 ///             to,
 ///         }** Performs an swap using the specified parameters.
+///
+/// * **ExecuteMsg::FlashLoan { assets, receiver, data }** Lends pool assets to the receiver contract
+/// which must return them together with the flash loan fee by the end of the receiver call.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -193,6 +234,9 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // The pair is locked while a flash loan is in progress
+    assert_no_flash_loan(deps.storage)?;
+
     match msg {
         ExecuteMsg::UpdateConfig { params } => update_config(deps, env, info, params),
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
@@ -200,16 +244,28 @@ pub fn execute(
             assets,
             auto_stake,
             receiver,
+            deadline,
             ..
-        } => provide_liquidity(deps, env, info, assets, auto_stake, receiver),
+        } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            provide_liquidity(deps, env, info, assets, auto_stake, receiver)
+        }
         ExecuteMsg::Swap {
             offer_asset,
             ask_asset_info,
             belief_price,
             max_spread,
             to,
+            deadline,
             ..
         } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
@@ -229,6 +285,12 @@ pub fn execute(
                 to_addr,
             )
         }
+        ExecuteMsg::SwapExactOut { .. } => Err(ContractError::NonSupported {}),
+        ExecuteMsg::FlashLoan {
+            assets,
+            receiver,
+            data,
+        } => flash_loan(deps, env, assets, receiver, data),
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let cfg = CONFIG.load(deps.storage)?;
             let factory_config = query_factory_config(&deps.querier, cfg.factory_addr.clone())?;
@@ -285,7 +347,12 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            deadline,
         } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
             let config = CONFIG.load(deps.storage)?;
 
             // Only asset contract can execute this message
@@ -308,13 +375,21 @@ pub fn receive_cw20(
                 to_addr,
             )
         }
-        Cw20HookMsg::WithdrawLiquidity { assets } => withdraw_liquidity(
-            deps,
-            info,
-            Addr::unchecked(cw20_msg.sender),
-            cw20_msg.amount,
-            assets,
-        ),
+        Cw20HookMsg::SwapExactOut { .. } => Err(ContractError::NonSupported {}),
+        Cw20HookMsg::WithdrawLiquidity { assets, deadline } => {
+            if is_deadline_passed(&env.block, deadline) {
+                return Err(ContractError::DeadlineExpired {});
+            }
+
+            withdraw_liquidity(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                cw20_msg.amount,
+                assets,
+            )
+        }
     }
 }
 
@@ -339,7 +414,12 @@ pub fn provide_liquidity(
     check_assets(deps.api, &assets)?;
 
     let auto_stake = auto_stake.unwrap_or(false);
-    let config = CONFIG.load(deps.storage)?;
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
     info.funds
         .assert_coins_properly_sent(&assets, &config.pair_info.asset_infos)?;
 
@@ -434,6 +514,30 @@ pub fn provide_liquidity(
         })
         .collect::<StdResult<Vec<(DecimalAsset, Decimal256)>>>()?;
 
+    // Accumulate prices using the pool balances before the deposit
+    let old_pools = assets_collection
+        .iter()
+        .map(|(deposit, pool)| DecimalAsset {
+            info: deposit.info.clone(),
+            amount: *pool,
+        })
+        .collect_vec();
+    let rates = Rates::load(deps.as_ref(), &env, &config)?;
+    accumulate_prices(deps.as_ref(), &env, &mut config, &rates, &old_pools)?;
+    CONFIG.save(deps.storage, &config)?;
+    rates.save(deps.storage)?;
+
+    // Pool balances and deposits are scaled by asset rates
+    let assets_collection = assets_collection
+        .iter()
+        .map(|(deposit, pool)| {
+            Ok((
+                rates.scale_asset(deposit)?,
+                rates.scale(&deposit.info, *pool)?,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
     let amp = compute_current_amp(&config, &env)?;
 
     // Invariant (D) after deposit added
@@ -508,14 +612,18 @@ pub fn provide_liquidity(
 /// * **sender** is the address that will receive assets back from the pair contract.
 ///
 /// * **amount** is the amount of LP tokens to burn.
+///
+/// * **assets** is the amount of each pool asset to withdraw. If empty, the withdrawal is balanced.
+/// Otherwise only the needed amount of LP tokens is burned and the rest is sent back to the sender.
 pub fn withdraw_liquidity(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     sender: Addr,
     amount: Uint128,
     assets: Vec<Asset>,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
+    let mut config = CONFIG.load(deps.storage)?;
 
     if info.sender != config.pair_info.liquidity_token {
         return Err(ContractError::Unauthorized {});
@@ -523,22 +631,68 @@ pub fn withdraw_liquidity(
 
     let (pools, total_share) = pool_info(deps.querier, &config)?;
 
-    let refund_assets = if assets.is_empty() {
+    let decimal_pools = pools
+        .iter()
+        .cloned()
+        .map(|pool| {
+            let precision = get_precision(deps.storage, &pool.info)?;
+            pool.to_decimal_asset(precision)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let rates = match Rates::load(deps.as_ref(), &env, &config) {
+        Ok(rates) => Some(rates),
+        // Balanced withdrawal doesn't depend on asset rates thus it is allowed even if rates are stale
+        Err(ContractError::StaleRate(_)) if assets.is_empty() => None,
+        Err(err) => return Err(err),
+    };
+    if let Some(rates) = &rates {
+        accumulate_prices(deps.as_ref(), &env, &mut config, rates, &decimal_pools)?;
+        CONFIG.save(deps.storage, &config)?;
+        rates.save(deps.storage)?;
+    }
+
+    let mut messages = vec![];
+
+    let (refund_assets, burn_amount) = match &rates {
+        // Imbalanced withdraw. Rates are always loaded in this case
+        Some(rates) if !assets.is_empty() => {
+            let burn_amount =
+                imbalanced_withdraw(deps.as_ref(), &env, &config, rates, amount, &assets)?;
+            if burn_amount < amount {
+                // Returning unused LP tokens back to the user
+                messages.push(
+                    wasm_execute(
+                        &config.pair_info.liquidity_token,
+                        &Cw20ExecuteMsg::Transfer {
+                            recipient: sender.to_string(),
+                            amount: amount - burn_amount,
+                        },
+                        vec![],
+                    )?
+                    .into(),
+                )
+            }
+
+            (assets, burn_amount)
+        }
         // Usual withdraw (balanced)
-        get_share_in_assets(&pools, amount, total_share)
-    } else {
-        return Err(StdError::generic_err("Imbalanced withdraw is currently disabled").into());
+        _ => (get_share_in_assets(&pools, amount, total_share), amount),
     };
 
-    let mut messages = refund_assets
-        .clone()
-        .into_iter()
-        .map(|asset| asset.into_msg(&sender))
-        .collect::<StdResult<Vec<_>>>()?;
+    messages.extend(
+        refund_assets
+            .iter()
+            .filter(|asset| !asset.amount.is_zero())
+            .map(|asset| asset.clone().into_msg(&sender))
+            .collect::<StdResult<Vec<_>>>()?,
+    );
     messages.push(
         wasm_execute(
             &config.pair_info.liquidity_token,
-            &Cw20ExecuteMsg::Burn { amount },
+            &Cw20ExecuteMsg::Burn {
+                amount: burn_amount,
+            },
             vec![],
         )?
         .into(),
@@ -547,11 +701,143 @@ pub fn withdraw_liquidity(
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "withdraw_liquidity"),
         attr("sender", sender),
-        attr("withdrawn_share", amount),
+        attr("withdrawn_share", burn_amount),
         attr("refund_assets", refund_assets.iter().join(", ")),
     ]))
 }
 
+/// Imbalanced withdraw liquidity from the pool. Returns a [`ContractError`] on failure,
+/// otherwise returns the number of LP tokens to burn.
+/// The imbalanced part of the withdrawal is charged with a fee the same way as in Curve.
+/// The fee stays in the pool.
+///
+/// * **rates** redemption rates of the pool assets.
+///
+/// * **provided_amount** amount of provided LP tokens to withdraw liquidity with.
+///
+/// * **assets** specifies the assets amount to withdraw.
+fn imbalanced_withdraw(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    rates: &Rates,
+    provided_amount: Uint128,
+    assets: &[Asset],
+) -> Result<Uint128, ContractError> {
+    if assets.len() > config.pair_info.asset_infos.len() {
+        return Err(ContractError::InvalidNumberOfAssets(
+            config.pair_info.asset_infos.len(),
+        ));
+    }
+
+    if !assets.iter().map(|asset| &asset.info).all_unique() {
+        return Err(ContractError::DoublingAssets {});
+    }
+
+    if assets.iter().all(|asset| asset.amount.is_zero()) {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let pools: HashMap<_, _> = config
+        .pair_info
+        .query_pools(&deps.querier, &env.contract.address)?
+        .into_iter()
+        .map(|pool| (pool.info, pool.amount))
+        .collect();
+
+    let mut assets_collection = assets
+        .iter()
+        .cloned()
+        .map(|asset| {
+            let precision = get_precision(deps.storage, &asset.info)?;
+            // Get appropriate pool
+            let pool = pools
+                .get(&asset.info)
+                .copied()
+                .ok_or_else(|| ContractError::InvalidAsset(asset.info.to_string()))?;
+
+            Ok((
+                asset.to_decimal_asset(precision)?,
+                Decimal256::with_precision(pool, precision)?,
+            ))
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    // If some assets are omitted then add them explicitly with 0 withdraw amount
+    pools
+        .into_iter()
+        .try_for_each(|(pool_info, pool_amount)| -> StdResult<()> {
+            if !assets.iter().any(|asset| asset.info == pool_info) {
+                let precision = get_precision(deps.storage, &pool_info)?;
+
+                assets_collection.push((
+                    DecimalAsset {
+                        amount: Decimal256::zero(),
+                        info: pool_info,
+                    },
+                    Decimal256::with_precision(pool_amount, precision)?,
+                ));
+            }
+            Ok(())
+        })?;
+
+    let n_coins = config.pair_info.asset_infos.len() as u8;
+
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
+    // Curve charges fee * N / (4 * (N - 1)) on the imbalanced part of the withdrawal
+    let fee = Decimal256::new(fee_info.total_fee_rate.atomics().into())
+        .checked_mul(Decimal256::from_ratio(n_coins, 4 * (n_coins - 1)))?;
+
+    let amp = compute_current_amp(config, env)?;
+
+    // Initial invariant (D)
+    let old_balances = assets_collection
+        .iter()
+        .map(|(withdraw, pool)| rates.scale(&withdraw.info, *pool))
+        .collect::<StdResult<Vec<_>>>()?;
+    let init_d = compute_d(amp, &old_balances)?;
+
+    // Invariant (D) after assets withdrawn
+    let mut new_balances = assets_collection
+        .iter()
+        .map(|(withdraw, pool)| {
+            if withdraw.amount >= *pool {
+                return Err(StdError::generic_err(format!(
+                    "Not enough {} in the pool to withdraw {}",
+                    withdraw.info, withdraw.amount
+                )));
+            }
+            rates.scale(&withdraw.info, *pool - withdraw.amount)
+        })
+        .collect::<StdResult<Vec<Decimal256>>>()?;
+    let withdraw_d = compute_d(amp, &new_balances)?;
+
+    for i in 0..n_coins as usize {
+        let ideal_balance = withdraw_d.checked_multiply_ratio(old_balances[i], init_d)?;
+        let difference = ideal_balance.diff(new_balances[i]);
+        new_balances[i] = new_balances[i].saturating_sub(fee * difference);
+    }
+
+    let after_fee_d = compute_d(amp, &new_balances)?;
+
+    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    // How many tokens do we need to burn to withdraw asked assets?
+    let burn_amount = Decimal256::with_precision(total_share, config.greatest_precision)?
+        .checked_multiply_ratio(init_d.saturating_sub(after_fee_d), init_d)?
+        .to_uint128_with_precision(config.greatest_precision)?
+        .checked_add(Uint128::one())?; // In case of rounding errors - make it unfavorable for the "attacker"
+
+    if burn_amount > provided_amount {
+        return Err(StdError::generic_err(format!(
+            "Not enough LP tokens. You need {} LP tokens.",
+            burn_amount
+        ))
+        .into());
+    }
+
+    Ok(burn_amount)
+}
+
 /// Performs an swap operation with the specified parameters.
 ///
 /// * **sender** is the sender of the swap operation.
@@ -576,7 +862,11 @@ pub fn swap(
     max_spread: Option<Decimal>,
     to: Option<Addr>,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
 
     // If the asset balance already increased
     // We should subtract the user deposit from the pool offer asset amount
@@ -615,6 +905,11 @@ pub fn swap(
 
     let offer_asset_dec = offer_asset.to_decimal_asset(offer_precision)?;
 
+    let rates = Rates::load(deps.as_ref(), &env, &config)?;
+    accumulate_prices(deps.as_ref(), &env, &mut config, &rates, &pools)?;
+    CONFIG.save(deps.storage, &config)?;
+    rates.save(deps.storage)?;
+
     let SwapResult {
         return_amount,
         spread_amount,
@@ -622,6 +917,7 @@ pub fn swap(
         deps.storage,
         &env,
         &config,
+        &rates,
         &offer_asset_dec,
         &offer_pool,
         &ask_pool,
@@ -629,11 +925,7 @@ pub fn swap(
     )?;
 
     // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        &config.factory_addr,
-        config.pair_info.pair_type.clone(),
-    )?;
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
     let commission_amount = fee_info.total_fee_rate.checked_mul_uint128(return_amount)?;
     let return_amount = return_amount.saturating_sub(commission_amount);
 
@@ -676,9 +968,15 @@ pub fn swap(
         && return_amount.to_decimal256(ask_precision)? >= MIN_TRADE_SIZE
     {
         // Store time series data
-        let (base_amount, quote_amount) =
-            determine_base_quote_amount(&pools, &offer_asset, return_amount)?;
-        accumulate_swap_sizes(deps.storage, &env, base_amount, quote_amount)?;
+        let (observations_keys, base_amount, quote_amount) =
+            determine_base_quote_amount(&pools, &offer_asset, &ask_pool.info, return_amount)?;
+        accumulate_swap_sizes(
+            deps.storage,
+            &env,
+            observations_keys.buffer(),
+            base_amount,
+            quote_amount,
+        )?;
     }
 
     Ok(Response::new()
@@ -725,6 +1023,80 @@ pub fn calculate_maker_fee(
     })
 }
 
+/// Sends the specified pool assets to the receiver contract and calls it with `FlashLoanReceiverMsg`.
+/// The pair is locked until the call is finished and the loan is checked in [`check_flash_loan_repaid`].
+///
+/// * **assets** are the assets to borrow.
+///
+/// * **receiver** is the contract which receives borrowed assets.
+///
+/// * **data** is arbitrary data passed to the receiver.
+pub fn flash_loan(
+    deps: DepsMut,
+    env: Env,
+    assets: Vec<Asset>,
+    receiver: String,
+    data: Binary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if query_pair_paused(&deps.querier, &config.factory_addr, &config.pair_info)? {
+        return Err(ContractError::PairPaused {});
+    }
+
+    let receiver = deps.api.addr_validate(&receiver)?;
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &env.contract.address)?;
+    let fee_bps = FLASH_LOAN_FEE_BPS
+        .may_load(deps.storage)?
+        .unwrap_or(DEFAULT_FLASH_LOAN_FEE_BPS);
+
+    let loan = start_flash_loan(
+        deps.storage,
+        &config.pair_info,
+        &receiver,
+        &pools,
+        &assets,
+        fee_bps,
+    )?;
+    let attributes = vec![
+        attr("action", "flash_loan"),
+        attr("receiver", &receiver),
+        attr("assets", assets.iter().join(",")),
+        attr("fees", loan.fees.iter().join(",")),
+    ];
+    let (messages, callback) = flash_loan_messages(&receiver, assets, loan, data)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessage(SubMsg::reply_on_success(callback, FLASH_LOAN_REPLY_ID))
+        .add_attributes(attributes))
+}
+
+/// Checks that the flash loan in progress was repaid together with the fee,
+/// sends the Maker share of the fee and unlocks the pair.
+pub fn check_flash_loan_repaid(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let (loan, _) = finish_flash_loan(deps.storage, &deps.querier, &env.contract.address)?;
+
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
+
+    let mut messages = vec![];
+    for fee in &loan.fees {
+        if let Some(fee_address) = &fee_info.fee_address {
+            if let Some(f) = calculate_maker_fee(&fee.info, fee.amount, fee_info.maker_fee_rate) {
+                messages.push(f.into_msg(fee_address)?);
+            }
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "flash_loan_repaid"))
+}
+
 /// Exposes all the queries available in the contract.
 /// ## Queries
 /// * **QueryMsg::Pair {}** Returns information about the pair in an object of type [`PairInfo`].
@@ -740,10 +1112,18 @@ pub fn calculate_maker_fee(
 /// * **QueryMsg::ReverseSimulation { ask_asset }** Returns the result of a reverse swap simulation using
 /// a [`ReverseSimulationResponse`] object.
 ///
-/// * **QueryMsg::CumulativePrices {}** Returns information about cumulative prices for the assets in the
-/// pool using a [`CumulativePricesResponse`] object.
+/// * **QueryMsg::CumulativePrices {}** Returns information about cumulative prices for each directed pair
+/// of assets in the pool using a [`CumulativePricesResponse`] object.
+///
+/// * **QueryMsg::Observe { seconds_ago }** Returns the price observation for the first two assets in the pool
+/// at the point that was `seconds_ago` seconds ago.
+///
+/// * **QueryMsg::ObservePair { base_asset, quote_asset, seconds_ago }** Returns the price observation
+/// for the specified pair of assets at the point that was `seconds_ago` seconds ago.
 ///
 /// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
+///
+/// * **QueryMsg::FlashLoanFee {}** Returns the flash loan fee (in bps).
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -763,14 +1143,28 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             ask_asset,
             offer_asset_info,
         )?),
-        QueryMsg::CumulativePrices {} => Err(StdError::generic_err(
-            stringify!(Not implemented. Use {"observe": {"seconds_ago": ... }} instead.),
-        )),
+        QueryMsg::CumulativePrices {} => to_binary(&query_cumulative_prices(deps, env)?),
         QueryMsg::Observe { seconds_ago } => {
             to_binary(&query_observation(deps, env, OBSERVATIONS, seconds_ago)?)
         }
+        QueryMsg::ObservePair {
+            base_asset,
+            quote_asset,
+            seconds_ago,
+        } => to_binary(&query_observe_pair(
+            deps,
+            env,
+            base_asset,
+            quote_asset,
+            seconds_ago,
+        )?),
         QueryMsg::Config {} => to_binary(&query_config(deps, env)?),
         QueryMsg::QueryComputeD {} => to_binary(&query_compute_d(deps, env)?),
+        QueryMsg::FlashLoanFee {} => to_binary(
+            &FLASH_LOAN_FEE_BPS
+                .may_load(deps.storage)?
+                .unwrap_or(DEFAULT_FLASH_LOAN_FEE_BPS),
+        ),
         _ => Err(StdError::generic_err("Query is not supported")),
     }
 }
@@ -842,6 +1236,9 @@ pub fn query_simulation(
         });
     }
 
+    let rates =
+        Rates::load(deps, &env, &config).map_err(|err| StdError::generic_err(format!("{err}")))?;
+
     let SwapResult {
         return_amount,
         spread_amount,
@@ -849,6 +1246,7 @@ pub fn query_simulation(
         deps.storage,
         &env,
         &config,
+        &rates,
         &offer_asset.to_decimal_asset(offer_precision)?,
         &offer_pool,
         &ask_pool,
@@ -857,11 +1255,7 @@ pub fn query_simulation(
     .map_err(|err| StdError::generic_err(format!("{err}")))?;
 
     // Get fee info from factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        &config.factory_addr,
-        config.pair_info.pair_type.clone(),
-    )?;
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
 
     let commission_amount = fee_info.total_fee_rate.checked_mul_uint128(return_amount)?;
     let return_amount = return_amount.saturating_sub(commission_amount);
@@ -920,42 +1314,136 @@ pub fn query_reverse_simulation(
     }
 
     // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        &config.factory_addr,
-        config.pair_info.pair_type.clone(),
-    )?;
+    let fee_info = query_pair_fee_info(&deps.querier, &config.factory_addr, &config.pair_info)?;
     let before_commission = (Decimal256::one()
         - Decimal256::new(fee_info.total_fee_rate.atomics().into()))
     .inv()
     .ok_or_else(|| StdError::generic_err("The pool must have less than 100% fee!"))?
     .checked_mul(Decimal256::with_precision(ask_asset.amount, ask_precision)?)?;
 
-    let xp = pools.into_iter().map(|pool| pool.amount).collect_vec();
+    let rates =
+        Rates::load(deps, &env, &config).map_err(|err| StdError::generic_err(format!("{err}")))?;
+
     let new_offer_pool_amount = calc_y(
         compute_current_amp(&config, &env)?,
-        ask_pool.amount - before_commission,
-        &xp,
+        &ask_pool.info,
+        &offer_pool.info,
+        rates.scale(&ask_pool.info, ask_pool.amount - before_commission)?,
+        &rates.scale_pools(&pools)?,
         config.greatest_precision,
     )?;
 
     let offer_amount = new_offer_pool_amount.checked_sub(
-        offer_pool
-            .amount
+        rates
+            .scale(&offer_pool.info, offer_pool.amount)?
             .to_uint128_with_precision(config.greatest_precision)?,
     )?;
+    let offer_amount = rates
+        .unscale(
+            &offer_pool.info,
+            Decimal256::with_precision(offer_amount, config.greatest_precision)?,
+        )?
+        .to_uint128_with_precision(config.greatest_precision)?;
     let offer_amount = adjust_precision(offer_amount, config.greatest_precision, offer_precision)?;
 
+    // Amount of offer assets which would be swapped at 1:1 rate between scaled amounts
+    let ideal_offer_amount = rates
+        .unscale(
+            &offer_pool.info,
+            rates.scale(&ask_pool.info, before_commission)?,
+        )?
+        .to_uint128_with_precision(offer_precision)?;
+
     Ok(ReverseSimulationResponse {
         offer_amount,
-        spread_amount: offer_amount
-            .saturating_sub(before_commission.to_uint128_with_precision(offer_precision)?),
+        spread_amount: offer_amount.saturating_sub(ideal_offer_amount),
         commission_amount: fee_info
             .total_fee_rate
             .checked_mul_uint128(before_commission.to_uint128_with_precision(ask_precision)?)?,
     })
 }
 
+/// Returns information about cumulative prices for each directed pair of assets
+/// in the pool using a [`CumulativePricesResponse`] object.
+pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePricesResponse> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps.querier, &config)?;
+
+    let decimal_assets = assets
+        .iter()
+        .cloned()
+        .map(|asset| {
+            let precision = get_precision(deps.storage, &asset.info)?;
+            asset.to_decimal_asset(precision)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Rates::load(deps, &env, &config)
+        .and_then(|rates| accumulate_prices(deps, &env, &mut config, &rates, &decimal_assets))
+        .map_err(|err| StdError::generic_err(format!("{err}")))?;
+
+    Ok(CumulativePricesResponse {
+        assets,
+        total_share,
+        cumulative_prices: config.cumulative_prices,
+    })
+}
+
+/// Returns the price observation for the given pair of assets in an [`OracleObservation`] object.
+/// The price is the amount of `base_asset` per one `quote_asset`.
+///
+/// * **seconds_ago** how many seconds ago the observation was made.
+pub fn query_observe_pair(
+    deps: Deps,
+    env: Env,
+    base_asset: AssetInfo,
+    quote_asset: AssetInfo,
+    seconds_ago: u64,
+) -> StdResult<OracleObservation> {
+    let config = CONFIG.load(deps.storage)?;
+    let position = |info: &AssetInfo| {
+        config
+            .pair_info
+            .asset_infos
+            .iter()
+            .position(|asset_info| asset_info == info)
+            .ok_or_else(|| {
+                StdError::generic_err(format!("{}", ContractError::InvalidAsset(info.to_string())))
+            })
+    };
+    let base_ind = position(&base_asset)?;
+    let quote_ind = position(&quote_asset)?;
+
+    match base_ind.cmp(&quote_ind) {
+        Ordering::Less => query_observation(
+            deps,
+            env,
+            ObservationsKeys::new(base_ind, quote_ind).buffer(),
+            seconds_ago,
+        ),
+        Ordering::Greater => {
+            // Observations are stored only for one direction thus we need to invert the price
+            let observation = query_observation(
+                deps,
+                env,
+                ObservationsKeys::new(quote_ind, base_ind).buffer(),
+                seconds_ago,
+            )?;
+            Ok(OracleObservation {
+                timestamp: observation.timestamp,
+                price: observation
+                    .price
+                    .inv()
+                    .ok_or_else(|| StdError::generic_err("Observed price is zero"))?,
+            })
+        }
+        Ordering::Equal => Err(StdError::generic_err(format!(
+            "{}",
+            ContractError::SameAssets {}
+        ))),
+    }
+}
+
 /// Returns the pair contract configuration in a [`ConfigResponse`] object.
 pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
@@ -1032,7 +1520,10 @@ pub fn migrate(mut deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Respons
             "2.1.1" | "2.1.2" => {
                 migrate_config_from_v21(deps.branch())?;
             }
-            "3.0.0" | "3.1.0" => {}
+            "3.0.0" | "3.1.0" | "3.1.1" => {
+                migrate_config_to_v320(deps.branch())?;
+            }
+            "3.2.0" => {}
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
@@ -1084,11 +1575,49 @@ pub fn update_config(
             next_amp_time,
         } => start_changing_amp(config, deps, env, next_amp, next_amp_time)?,
         StablePoolUpdateParams::StopChangingAmp {} => stop_changing_amp(config, deps, env)?,
+        StablePoolUpdateParams::UpdateFlashLoanFee { fee_bps } => {
+            if fee_bps > MAX_FLASH_LOAN_FEE_BPS {
+                return Err(ContractError::FlashLoanFeeTooHigh {});
+            }
+            FLASH_LOAN_FEE_BPS.save(deps.storage, &fee_bps)?;
+        }
+        StablePoolUpdateParams::UpdateRateSources { rate_sources } => {
+            update_rate_sources(config, deps, env, rate_sources)?
+        }
     }
 
     Ok(Response::default())
 }
 
+/// Replaces the redemption rate sources of the pool assets.
+/// Rates cached from the previous sources are dropped.
+///
+/// * **rate_sources** new rate sources. Assets which are not listed get a rate of 1.
+fn update_rate_sources(
+    config: Config,
+    deps: DepsMut,
+    env: Env,
+    rate_sources: Vec<AssetRateSource>,
+) -> Result<(), ContractError> {
+    check_rate_sources(deps.api, &config.pair_info.asset_infos, &rate_sources)?;
+
+    for asset_info in &config.pair_info.asset_infos {
+        RATE_SOURCES.remove(deps.storage, asset_info.to_string());
+        CACHED_RATES.remove(deps.storage, asset_info.to_string());
+    }
+    for rate_source in rate_sources {
+        RATE_SOURCES.save(
+            deps.storage,
+            rate_source.asset_info.to_string(),
+            &rate_source.source,
+        )?;
+    }
+    // Make sure the new rate providers respond and cache their rates
+    Rates::load(deps.as_ref(), &env, &config)?.save(deps.storage)?;
+
+    Ok(())
+}
+
 /// Start changing the AMP value.
 ///
 /// * **next_amp** new value for AMP.
@@ -1153,12 +1682,14 @@ fn query_compute_d(deps: Deps, env: Env) -> StdResult<Uint128> {
     let config = CONFIG.load(deps.storage)?;
 
     let amp = compute_current_amp(&config, &env)?;
+    let rates =
+        Rates::load(deps, &env, &config).map_err(|err| StdError::generic_err(format!("{err}")))?;
     let pools = config
         .pair_info
-        .query_pools_decimal(&deps.querier, env.contract.address, &config.factory_addr)?
-        .into_iter()
-        .map(|pool| pool.amount)
-        .collect::<Vec<_>>();
+        .query_pools_decimal(&deps.querier, &env.contract.address, &config.factory_addr)?
+        .iter()
+        .map(|pool| rates.scale(&pool.info, pool.amount))
+        .collect::<StdResult<Vec<_>>>()?;
 
     compute_d(amp, &pools)
         .map_err(|_| StdError::generic_err("Failed to calculate the D"))?
@@ -2,9 +2,10 @@ use cosmwasm_std::{CheckedMultiplyRatioError, ConversionOverflowError, OverflowE
 use thiserror::Error;
 
 use astroport::asset::MINIMUM_LIQUIDITY_AMOUNT;
+use astroport::pair::{FlashLoanError, MAX_FLASH_LOAN_FEE_BPS};
 use astroport_circular_buffer::error::BufferError;
 
-use crate::math::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME};
+use crate::math::{MAX_AMP, MAX_AMP_CHANGE, MAX_N_COINS, MIN_AMP_CHANGING_TIME, MIN_N_COINS};
 
 /// This enum describes stableswap pair contract errors
 #[derive(Error, Debug, PartialEq)]
@@ -21,6 +22,9 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Swaps and liquidity provision are paused")]
+    PairPaused {},
+
     #[error("CW20 tokens can be swapped via Cw20::Send message only")]
     Cw20DirectSwap {},
 
@@ -81,14 +85,45 @@ pub enum ContractError {
     #[error("Invalid number of assets. This pair support only {0} assets")]
     InvalidNumberOfAssets(usize),
 
+    #[error(
+        "Invalid number of assets. Stableswap pool supports from {} to {} assets",
+        MIN_N_COINS,
+        MAX_N_COINS
+    )]
+    InvalidNumberOfPoolAssets {},
+
+    #[error("Invalid rate of the asset {0}. Rate must be greater than zero")]
+    InvalidRate(String),
+
+    #[error("Rate cache period must not exceed the max staleness period")]
+    InvalidRateCachePeriod {},
+
+    #[error("Failed to fetch the rate of the asset {0} and the cached rate is too old")]
+    StaleRate(String),
+
     #[error("Contract can't be migrated!")]
     MigrationError {},
 
     #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY_AMOUNT)]
     MinimumLiquidityAmountError {},
 
+    #[error("Transaction deadline has passed")]
+    DeadlineExpired {},
+
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
+
+    #[error("Operation non supported")]
+    NonSupported {},
+
+    #[error("{0}")]
+    FlashLoan(#[from] FlashLoanError),
+
+    #[error(
+        "Flash loan fee must be less than or equal to {} bps",
+        MAX_FLASH_LOAN_FEE_BPS
+    )]
+    FlashLoanFeeTooHigh {},
 }
 
 impl From<OverflowError> for ContractError {
@@ -1,6 +1,6 @@
 use cosmwasm_std::{Decimal256, StdError, StdResult, Uint128, Uint64};
 
-use astroport::asset::Decimal256Ext;
+use astroport::asset::{AssetInfo, Decimal256Ext, DecimalAsset};
 
 /// The maximum number of calculation steps for Newton's method.
 const ITERATIONS: u8 = 64;
@@ -9,8 +9,10 @@ pub const MAX_AMP: u64 = 1_000_000;
 pub const MAX_AMP_CHANGE: u64 = 10;
 pub const MIN_AMP_CHANGING_TIME: u64 = 86400;
 pub const AMP_PRECISION: u64 = 100;
-/// N = 2
-pub const N_COINS: Decimal256 = Decimal256::raw(2000000000000000000);
+/// The minimum number of assets in the pool
+pub const MIN_N_COINS: usize = 2;
+/// The maximum number of assets in the pool
+pub const MAX_N_COINS: usize = 5;
 /// 1e-6
 pub const TOL: Decimal256 = Decimal256::raw(1000000000000);
 
@@ -21,11 +23,12 @@ pub const TOL: Decimal256 = Decimal256::raw(1000000000000);
 /// A * sum(x_i) * n**n + D = A * D * n**n + D**(n+1) / (n**n * prod(x_i))
 ///
 pub fn compute_d(amp: Uint64, pools: &[Decimal256]) -> StdResult<Decimal256> {
-    let leverage = Decimal256::from_ratio(amp, AMP_PRECISION) * N_COINS;
-    let amount_a_times_coins = pools[0] * N_COINS;
-    let amount_b_times_coins = pools[1] * N_COINS;
+    let n_coins = Decimal256::from_integer(pools.len() as u128);
+    let leverage = Decimal256::from_ratio(amp, AMP_PRECISION) * n_coins;
 
-    let sum_x = pools[0].checked_add(pools[1])?; // sum(x_i), a.k.a S
+    let sum_x = pools
+        .iter()
+        .try_fold(Decimal256::zero(), |acc, x| acc.checked_add(*x))?; // sum(x_i), a.k.a S
     if sum_x.is_zero() {
         Ok(Decimal256::zero())
     } else {
@@ -34,9 +37,12 @@ pub fn compute_d(amp: Uint64, pools: &[Decimal256]) -> StdResult<Decimal256> {
 
         // Newton's method to approximate D
         for _ in 0..ITERATIONS {
-            let d_product = d.pow(3) / (amount_a_times_coins * amount_b_times_coins);
+            // d_product = D**(n+1) / (n**n * prod(x_i))
+            let d_product = pools.iter().try_fold(d, |acc, x| -> StdResult<_> {
+                acc.checked_multiply_ratio(d, x.checked_mul(n_coins)?)
+            })?;
             d_previous = d;
-            d = calculate_step(d, leverage, sum_x, d_product)?;
+            d = calculate_step(d, leverage, sum_x, d_product, n_coins)?;
             // Equality with the precision of 1e-6
             if d.abs_diff(d_previous) <= TOL {
                 return Ok(d);
@@ -59,14 +65,15 @@ fn calculate_step(
     leverage: Decimal256,
     sum_x: Decimal256,
     d_product: Decimal256,
+    n_coins: Decimal256,
 ) -> StdResult<Decimal256> {
     let leverage_mul = leverage.checked_mul(sum_x)?;
-    let d_p_mul = d_product.checked_mul(N_COINS)?;
+    let d_p_mul = d_product.checked_mul(n_coins)?;
 
     let l_val = leverage_mul.checked_add(d_p_mul)?.checked_mul(initial_d)?;
 
     let leverage_sub = initial_d.checked_mul(leverage - Decimal256::one())?;
-    let n_coins_sum = d_product.checked_mul(N_COINS.checked_add(Decimal256::one())?)?;
+    let n_coins_sum = d_product.checked_mul(n_coins.checked_add(Decimal256::one())?)?;
 
     let r_val = leverage_sub.checked_add(n_coins_sum)?;
 
@@ -75,29 +82,44 @@ fn calculate_step(
         .map_err(|e| StdError::generic_err(e.to_string()))
 }
 
-/// Compute the swap amount `y` in proportion to `x`.
+/// Compute the new amount of the `ask_info` pool `y` after the `offer_info` pool changes to `new_amount`.
 ///
 /// * **Solve for y**
 ///
 /// y**2 + y * (sum' - (A*n**n - 1) * D / (A * n**n)) = D ** (n + 1) / (n ** (2 * n) * prod' * A)
 ///
 /// y**2 + b*y = c
+///
+/// where sum' and prod' are the sum and the product of all pool amounts except `y`
+/// (the offer pool amount is replaced with `new_amount`).
 pub(crate) fn calc_y(
     amp: Uint64,
+    offer_info: &AssetInfo,
+    ask_info: &AssetInfo,
     new_amount: Decimal256,
-    xp: &[Decimal256],
+    pools: &[DecimalAsset],
     target_precision: u8,
 ) -> StdResult<Uint128> {
-    let d = compute_d(amp, xp)?;
-    let leverage = Decimal256::from_ratio(amp, 1u8) * N_COINS;
+    let n_coins = Decimal256::from_integer(pools.len() as u128);
+    let xp = pools.iter().map(|pool| pool.amount).collect::<Vec<_>>();
+    let d = compute_d(amp, &xp)?;
+    let leverage = Decimal256::from_ratio(amp, 1u8) * n_coins;
     let amp_prec = Decimal256::from_ratio(AMP_PRECISION, 1u8);
 
-    let c = d.checked_pow(3)?.checked_mul(amp_prec)?
-        / new_amount
-            .checked_mul(N_COINS * N_COINS)?
-            .checked_mul(leverage)?;
+    let mut c = d;
+    let mut sum = Decimal256::zero();
+    for pool in pools.iter().filter(|pool| pool.info != *ask_info) {
+        let amount = if pool.info == *offer_info {
+            new_amount
+        } else {
+            pool.amount
+        };
+        sum = sum.checked_add(amount)?;
+        c = c.checked_multiply_ratio(d, amount.checked_mul(n_coins)?)?;
+    }
+    let c = c.checked_multiply_ratio(d.checked_mul(amp_prec)?, leverage.checked_mul(n_coins)?)?;
 
-    let b = new_amount.checked_add(d.checked_mul(amp_prec)? / leverage)?;
+    let b = sum.checked_add(d.checked_mul(amp_prec)? / leverage)?;
 
     // Solve for y by approximating: y**2 + b*y = c
     let mut y_prev;
@@ -107,7 +129,11 @@ pub(crate) fn calc_y(
         y = y
             .checked_pow(2)?
             .checked_add(c)?
-            .checked_div(y.checked_mul(N_COINS)?.checked_add(b)?.checked_sub(d)?)
+            .checked_div(
+                y.checked_mul(Decimal256::from_integer(2u8))?
+                    .checked_add(b)?
+                    .checked_sub(d)?,
+            )
             .map_err(|e| StdError::generic_err(e.to_string()))?;
         if y.abs_diff(y_prev) <= TOL {
             return y.to_uint128_with_precision(target_precision);
@@ -8,7 +8,7 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, DepsMut, QuerierWrapper, StdResult, Uint128};
 use cw_storage_plus::Item;
 
-use crate::state::{store_precisions, Config, CONFIG, OBSERVATIONS};
+use crate::state::{init_cumulative_prices, store_precisions, Config, CONFIG, OBSERVATIONS};
 
 /// This structure stores the main stableswap pair parameters.
 #[cw_serde]
@@ -64,6 +64,8 @@ pub fn migrate_config_to_v210(mut deps: DepsMut) -> StdResult<Config> {
         &cfg_v100.factory_addr,
     )?;
 
+    let cumulative_prices = init_cumulative_prices(&cfg_v100.pair_info.asset_infos);
+
     let cfg = Config {
         owner: None,
         pair_info: cfg_v100.pair_info,
@@ -74,6 +76,7 @@ pub fn migrate_config_to_v210(mut deps: DepsMut) -> StdResult<Config> {
         next_amp: cfg_v100.next_amp,
         next_amp_time: cfg_v100.next_amp_time,
         greatest_precision,
+        cumulative_prices,
     };
 
     CONFIG.save(deps.storage, &cfg)?;
@@ -123,6 +126,7 @@ pub fn migrate_config_from_v21(deps: DepsMut) -> StdResult<()> {
         next_amp: cfg_v212.next_amp,
         next_amp_time: cfg_v212.next_amp_time,
         greatest_precision: cfg_v212.greatest_precision,
+        cumulative_prices: cfg_v212.cumulative_prices,
     };
 
     CONFIG.save(deps.storage, &cfg)?;
@@ -131,3 +135,50 @@ pub fn migrate_config_from_v21(deps: DepsMut) -> StdResult<()> {
 
     Ok(())
 }
+
+pub fn migrate_config_to_v320(deps: DepsMut) -> StdResult<()> {
+    /// This structure stores the main stableswap pair parameters.
+    #[cw_serde]
+    pub struct OldConfig {
+        /// The contract owner
+        pub owner: Option<Addr>,
+        /// The pair information stored in a [`PairInfo`] struct
+        pub pair_info: PairInfo,
+        /// The factory contract address
+        pub factory_addr: Addr,
+        /// The last timestamp when the pair contract update the asset cumulative prices
+        pub block_time_last: u64,
+        /// This is the current amplification used in the pool
+        pub init_amp: u64,
+        /// This is the start time when amplification starts to scale up or down
+        pub init_amp_time: u64,
+        /// This is the target amplification to reach at `next_amp_time`
+        pub next_amp: u64,
+        /// This is the timestamp when the current pool amplification should be `next_amp`
+        pub next_amp_time: u64,
+        /// The greatest precision of assets in the pool
+        pub greatest_precision: u8,
+    }
+
+    const CONFIG_V310: Item<OldConfig> = Item::new("config");
+
+    let cfg_v310 = CONFIG_V310.load(deps.storage)?;
+
+    let cumulative_prices = init_cumulative_prices(&cfg_v310.pair_info.asset_infos);
+
+    let cfg = Config {
+        owner: cfg_v310.owner,
+        pair_info: cfg_v310.pair_info,
+        factory_addr: cfg_v310.factory_addr,
+        block_time_last: cfg_v310.block_time_last,
+        init_amp: cfg_v310.init_amp,
+        init_amp_time: cfg_v310.init_amp_time,
+        next_amp: cfg_v310.next_amp,
+        next_amp_time: cfg_v310.next_amp_time,
+        greatest_precision: cfg_v310.greatest_precision,
+        cumulative_prices,
+    };
+
+    // Observations buffer is already initialized as all previous pools consist of two assets only
+    CONFIG.save(deps.storage, &cfg)
+}
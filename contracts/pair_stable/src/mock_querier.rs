@@ -1,4 +1,4 @@
-use astroport::factory::QueryMsg::{Config, FeeInfo};
+use astroport::factory::QueryMsg::{Config, FeeInfo, IsPaused};
 use astroport::factory::{Config as FactoryConfig, ConfigResponse, FeeInfoResponse};
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
@@ -100,6 +100,7 @@ impl WasmMockQuerier {
                             })
                             .into(),
                         ),
+                        IsPaused { .. } => SystemResult::Ok(to_binary(&false).into()),
                         _ => panic!("DO NOT ENTER HERE"),
                     }
                 } else {
@@ -1,9 +1,10 @@
 use astroport::asset::{AssetInfo, PairInfo};
 use astroport::common::OwnershipProposal;
 use astroport::observation::Observation;
+use astroport::pair::RateSource;
 use astroport_circular_buffer::CircularBuffer;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, DepsMut, StdResult, Storage};
+use cosmwasm_std::{Addr, Decimal256, DepsMut, StdResult, Storage, Uint128};
 use cw_storage_plus::{Item, Map};
 
 /// This structure stores the main stableswap pair parameters.
@@ -27,20 +28,69 @@ pub struct Config {
     pub next_amp_time: u64,
     /// The greatest precision of assets in the pool
     pub greatest_precision: u8,
+    /// The vector contains cumulative prices for each pair of assets in the pool
+    pub cumulative_prices: Vec<(AssetInfo, AssetInfo, Uint128)>,
 }
 
-/// Circular buffer to store trade size observations
+/// Circular buffer to store trade size observations for the first two assets in the pool
 pub const OBSERVATIONS: CircularBuffer<Observation> =
     CircularBuffer::new("observations_state", "observations_buffer");
 
+/// Storage keys of the circular buffer which stores trade size observations for a pair of pool assets.
+/// Assets are referenced by their position in the pool and the base asset is the one that goes first.
+/// The first two assets use [`OBSERVATIONS`] keys which were used before pools with more than two assets were introduced.
+pub(crate) struct ObservationsKeys {
+    state_key: String,
+    array_namespace: String,
+}
+
+impl ObservationsKeys {
+    pub fn new(base_ind: usize, quote_ind: usize) -> Self {
+        if (base_ind, quote_ind) == (0, 1) {
+            Self {
+                state_key: "observations_state".to_string(),
+                array_namespace: "observations_buffer".to_string(),
+            }
+        } else {
+            Self {
+                state_key: format!("observations_state_{base_ind}_{quote_ind}"),
+                array_namespace: format!("observations_buffer_{base_ind}_{quote_ind}"),
+            }
+        }
+    }
+
+    pub fn buffer(&self) -> CircularBuffer<Observation> {
+        CircularBuffer::new(&self.state_key, &self.array_namespace)
+    }
+}
+
 pub const CONFIG: Item<Config> = Item::new("config");
 
 /// Stores map of AssetInfo (as String) -> precision
 pub const PRECISIONS: Map<String, u8> = Map::new("precisions");
 
+/// Stores map of AssetInfo (as String) -> redemption rate source.
+/// Assets without a rate source have a rate of 1
+pub const RATE_SOURCES: Map<String, RateSource> = Map::new("rate_sources");
+
+/// This structure stores a redemption rate fetched from a rate provider contract.
+#[cw_serde]
+pub struct CachedRate {
+    /// The fetched redemption rate
+    pub rate: Decimal256,
+    /// The timestamp when the rate was fetched
+    pub updated_at: u64,
+}
+
+/// Stores map of AssetInfo (as String) -> the latest rate fetched from a rate provider contract
+pub const CACHED_RATES: Map<String, CachedRate> = Map::new("cached_rates");
+
 /// Stores the latest contract ownership transfer proposal
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 
+/// Stores the flash loan fee (in bps). If not set, [`astroport::pair::DEFAULT_FLASH_LOAN_FEE_BPS`] is used
+pub const FLASH_LOAN_FEE_BPS: Item<u16> = Item::new("flash_loan_fee_bps");
+
 /// Store all token precisions and return the greatest one.
 pub(crate) fn store_precisions(
     deps: DepsMut,
@@ -62,3 +112,18 @@ pub(crate) fn store_precisions(
 pub(crate) fn get_precision(storage: &dyn Storage, asset_info: &AssetInfo) -> StdResult<u8> {
     PRECISIONS.load(storage, asset_info.to_string())
 }
+
+/// Returns the initial (zero) cumulative prices for each directed pair of assets in the pool.
+pub(crate) fn init_cumulative_prices(
+    asset_infos: &[AssetInfo],
+) -> Vec<(AssetInfo, AssetInfo, Uint128)> {
+    asset_infos
+        .iter()
+        .flat_map(|from| {
+            asset_infos
+                .iter()
+                .filter(move |to| *to != from)
+                .map(move |to| (from.clone(), to.clone(), Uint128::zero()))
+        })
+        .collect()
+}
@@ -77,6 +77,7 @@ fn proper_initialization() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
@@ -167,6 +168,7 @@ fn provide_liquidity() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
@@ -199,6 +201,7 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -316,6 +319,7 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -387,6 +391,7 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -469,6 +474,7 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -509,6 +515,7 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -555,6 +562,7 @@ fn withdraw_liquidity() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
@@ -571,7 +579,11 @@ fn withdraw_liquidity() {
     // Withdraw liquidity
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: String::from("addr0000"),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { assets: vec![] }).unwrap(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            assets: vec![],
+            deadline: None,
+        })
+        .unwrap(),
         amount: Uint128::new(100u128),
     });
 
@@ -682,6 +694,7 @@ fn try_native_to_token() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
@@ -707,6 +720,7 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
         to: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -838,6 +852,7 @@ fn try_token_to_native() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
@@ -863,6 +878,7 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
@@ -878,6 +894,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -975,6 +992,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1059,6 +1077,7 @@ fn test_query_pool() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
@@ -1130,6 +1149,7 @@ fn test_query_share() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
@@ -1336,7 +1356,7 @@ fn mock_env_with_block_time(time: u64) -> Env {
     env
 }
 
-use crate::utils::{compute_swap, select_pools};
+use crate::utils::{compute_swap, select_pools, Rates};
 use proptest::prelude::*;
 use sim::StableSwapModel;
 
@@ -1357,7 +1377,7 @@ proptest! {
             factory_addr: String::from("factory"),
             asset_infos: vec![offer_asset.info.clone(), ask_asset.clone()],
             token_code_id: 10u64,
-            init_params: Some(to_binary(&StablePoolParams { amp, owner: None }).unwrap()),
+            init_params: Some(to_binary(&StablePoolParams { amp, owner: None, rate_sources: None }).unwrap()),
         };
 
         let env = mock_env();
@@ -1377,6 +1397,7 @@ proptest! {
             deps.as_ref().storage,
             &env,
             &config,
+            &Rates::load(deps.as_ref(), &env, &config).unwrap(),
             &offer_asset.to_decimal_asset(offer_asset.info.decimals(&deps.as_ref().querier, &config.factory_addr).unwrap()).unwrap(),
             &offer_pool,
             &ask_pool,
@@ -1447,6 +1468,7 @@ fn update_owner() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: Some(owner.to_owned()),
+                rate_sources: None,
             })
             .unwrap(),
         ),
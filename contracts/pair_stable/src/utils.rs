@@ -1,20 +1,25 @@
 use cosmwasm_std::{
-    to_binary, wasm_execute, Addr, Api, CosmosMsg, Decimal, Env, QuerierWrapper, StdError,
-    StdResult, Storage, Uint128, Uint256, Uint64,
+    to_binary, wasm_execute, Addr, Api, Binary, CosmosMsg, Decimal, Decimal256, Deps, Env,
+    QuerierWrapper, QueryRequest, StdError, StdResult, Storage, Uint128, Uint256, Uint64,
+    WasmQuery,
 };
 use cw20::Cw20ExecuteMsg;
 use itertools::Itertools;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 
 use astroport::asset::{Asset, AssetInfo, Decimal256Ext, DecimalAsset};
 use astroport::observation::Observation;
+use astroport::pair::{AssetRateSource, RateResponse, RateSource};
 use astroport::querier::query_factory_config;
 use astroport_circular_buffer::error::BufferResult;
-use astroport_circular_buffer::BufferManager;
+use astroport_circular_buffer::{BufferManager, CircularBuffer};
 
 use crate::error::ContractError;
 use crate::math::calc_y;
-use crate::state::{get_precision, Config, OBSERVATIONS};
+use crate::state::{
+    get_precision, CachedRate, Config, ObservationsKeys, CACHED_RATES, RATE_SOURCES,
+};
 
 /// Helper function to check if the given asset infos are valid.
 pub(crate) fn check_asset_infos(
@@ -37,6 +42,49 @@ pub(crate) fn check_assets(api: &dyn Api, assets: &[Asset]) -> Result<(), Contra
     check_asset_infos(api, &asset_infos)
 }
 
+/// Helper function to check that the given rate sources are valid.
+/// Every rate source must refer to a pool asset and each asset may have only one rate source.
+pub(crate) fn check_rate_sources(
+    api: &dyn Api,
+    asset_infos: &[AssetInfo],
+    rate_sources: &[AssetRateSource],
+) -> Result<(), ContractError> {
+    if !rate_sources
+        .iter()
+        .map(|rate_source| &rate_source.asset_info)
+        .all_unique()
+    {
+        return Err(ContractError::DoublingAssets {});
+    }
+
+    for AssetRateSource { asset_info, source } in rate_sources {
+        if !asset_infos.contains(asset_info) {
+            return Err(ContractError::InvalidAsset(asset_info.to_string()));
+        }
+
+        match source {
+            RateSource::Constant { rate } => {
+                if rate.is_zero() {
+                    return Err(ContractError::InvalidRate(asset_info.to_string()));
+                }
+            }
+            RateSource::Contract {
+                contract_addr,
+                cache_period,
+                max_staleness,
+                ..
+            } => {
+                api.addr_validate(contract_addr)?;
+                if cache_period > max_staleness {
+                    return Err(ContractError::InvalidRateCachePeriod {});
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Checks that cw20 token is part of the pool.
 ///
 /// * **cw20_sender** is cw20 token address which is being checked.
@@ -242,6 +290,161 @@ pub(crate) fn get_share_in_assets(
         .collect()
 }
 
+/// Redemption rates of the pool assets.
+/// Pool balances are multiplied by asset rates before they are used in the stableswap invariant.
+pub struct Rates {
+    rates: HashMap<AssetInfo, Decimal256>,
+    /// Rates which were fetched from rate provider contracts and must be cached
+    fetched: Vec<(AssetInfo, CachedRate)>,
+}
+
+impl Rates {
+    /// Loads rates of the pool assets using the pair contract storage.
+    pub fn load(deps: Deps, env: &Env, config: &Config) -> Result<Self, ContractError> {
+        Self::collect(
+            deps.querier,
+            env,
+            &config.pair_info.asset_infos,
+            |asset_info| RATE_SOURCES.may_load(deps.storage, asset_info.to_string()),
+            |asset_info| CACHED_RATES.may_load(deps.storage, asset_info.to_string()),
+        )
+    }
+
+    /// Loads rates of the pool assets from outside of the pair contract.
+    pub fn query(
+        querier: QuerierWrapper,
+        env: &Env,
+        config: &Config,
+    ) -> Result<Self, ContractError> {
+        let pair_addr = &config.pair_info.contract_addr;
+        Self::collect(
+            querier,
+            env,
+            &config.pair_info.asset_infos,
+            |asset_info| RATE_SOURCES.query(&querier, pair_addr.clone(), asset_info.to_string()),
+            |asset_info| CACHED_RATES.query(&querier, pair_addr.clone(), asset_info.to_string()),
+        )
+    }
+
+    /// Resolves the rate of every pool asset.
+    /// A rate provider contract is queried only if the cached rate is older than the cache period.
+    /// If the query fails the cached rate is used as long as it is not older than the max staleness period.
+    fn collect(
+        querier: QuerierWrapper,
+        env: &Env,
+        asset_infos: &[AssetInfo],
+        load_source: impl Fn(&AssetInfo) -> StdResult<Option<RateSource>>,
+        load_cached: impl Fn(&AssetInfo) -> StdResult<Option<CachedRate>>,
+    ) -> Result<Self, ContractError> {
+        let block_time = env.block.time.seconds();
+        let mut rates = HashMap::with_capacity(asset_infos.len());
+        let mut fetched = vec![];
+
+        for asset_info in asset_infos {
+            let rate = match load_source(asset_info)? {
+                None => Decimal256::one(),
+                Some(RateSource::Constant { rate }) => Decimal256::new(rate.atomics().into()),
+                Some(RateSource::Contract {
+                    contract_addr,
+                    query_msg,
+                    cache_period,
+                    max_staleness,
+                }) => {
+                    let cached = load_cached(asset_info)?;
+                    let age = |cached: &CachedRate| block_time.saturating_sub(cached.updated_at);
+
+                    match &cached {
+                        Some(cached) if age(cached) < cache_period => cached.rate,
+                        _ => {
+                            if let Some(rate) = query_rate(querier, contract_addr, query_msg) {
+                                fetched.push((
+                                    asset_info.clone(),
+                                    CachedRate {
+                                        rate,
+                                        updated_at: block_time,
+                                    },
+                                ));
+                                rate
+                            } else {
+                                cached
+                                    .filter(|cached| age(cached) <= max_staleness)
+                                    .map(|cached| cached.rate)
+                                    .ok_or_else(|| {
+                                        ContractError::StaleRate(asset_info.to_string())
+                                    })?
+                            }
+                        }
+                    }
+                }
+            };
+
+            rates.insert(asset_info.clone(), rate);
+        }
+
+        Ok(Self { rates, fetched })
+    }
+
+    /// Returns the rate of the given asset.
+    pub fn get(&self, asset_info: &AssetInfo) -> Decimal256 {
+        self.rates
+            .get(asset_info)
+            .copied()
+            .unwrap_or_else(Decimal256::one)
+    }
+
+    /// Multiplies the given amount of an asset by its rate.
+    pub fn scale(&self, asset_info: &AssetInfo, amount: Decimal256) -> StdResult<Decimal256> {
+        Ok(amount.checked_mul(self.get(asset_info))?)
+    }
+
+    /// Divides the given scaled amount of an asset by its rate.
+    pub fn unscale(&self, asset_info: &AssetInfo, amount: Decimal256) -> StdResult<Decimal256> {
+        let rate = self.get(asset_info);
+        if rate == Decimal256::one() {
+            Ok(amount)
+        } else {
+            amount.checked_multiply_ratio(Decimal256::one(), rate)
+        }
+    }
+
+    /// Returns the asset with the amount multiplied by the asset rate.
+    pub fn scale_asset(&self, asset: &DecimalAsset) -> StdResult<DecimalAsset> {
+        Ok(DecimalAsset {
+            info: asset.info.clone(),
+            amount: self.scale(&asset.info, asset.amount)?,
+        })
+    }
+
+    /// Returns pool balances multiplied by asset rates.
+    pub fn scale_pools(&self, pools: &[DecimalAsset]) -> StdResult<Vec<DecimalAsset>> {
+        pools.iter().map(|pool| self.scale_asset(pool)).collect()
+    }
+
+    /// Caches the rates which were fetched from rate provider contracts.
+    pub fn save(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        self.fetched.iter().try_for_each(|(asset_info, cached)| {
+            CACHED_RATES.save(storage, asset_info.to_string(), cached)
+        })
+    }
+}
+
+/// Queries an asset rate from a rate provider contract.
+/// Returns [`None`] if the query failed or the returned rate is zero.
+fn query_rate(
+    querier: QuerierWrapper,
+    contract_addr: String,
+    query_msg: Binary,
+) -> Option<Decimal256> {
+    querier
+        .query::<RateResponse>(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr,
+            msg: query_msg,
+        }))
+        .ok()
+        .filter(|response| !response.rate.is_zero())
+        .map(|response| Decimal256::new(response.rate.atomics().into()))
+}
+
 /// Structure for internal use which represents swap result.
 pub(crate) struct SwapResult {
     pub return_amount: Uint128,
@@ -249,6 +452,9 @@ pub(crate) struct SwapResult {
 }
 
 /// Returns the result of a swap in form of a [`SwapResult`] object.
+/// The swap is performed on pool balances scaled by asset rates.
+///
+/// * **rates** redemption rates of the pool assets.
 ///
 /// * **offer_asset** asset that is being offered.
 ///
@@ -257,31 +463,46 @@ pub(crate) struct SwapResult {
 /// * **ask_pool** asked asset.
 ///
 /// * **pools** array with assets available in the pool.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn compute_swap(
     storage: &dyn Storage,
     env: &Env,
     config: &Config,
+    rates: &Rates,
     offer_asset: &DecimalAsset,
     offer_pool: &DecimalAsset,
     ask_pool: &DecimalAsset,
     pools: &[DecimalAsset],
 ) -> Result<SwapResult, ContractError> {
     let token_precision = get_precision(storage, &ask_pool.info)?;
-    let xp = pools.iter().map(|p| p.amount).collect_vec();
 
     let new_ask_pool = calc_y(
         compute_current_amp(config, env)?,
-        offer_pool.amount + offer_asset.amount,
-        &xp,
+        &offer_pool.info,
+        &ask_pool.info,
+        rates.scale(&offer_pool.info, offer_pool.amount + offer_asset.amount)?,
+        &rates.scale_pools(pools)?,
         token_precision,
     )?;
 
-    let return_amount = ask_pool.amount.to_uint128_with_precision(token_precision)? - new_ask_pool;
-    let offer_asset_amount = offer_asset
-        .amount
+    let return_amount = rates
+        .scale(&ask_pool.info, ask_pool.amount)?
+        .to_uint128_with_precision(token_precision)?
+        - new_ask_pool;
+    let return_amount = rates
+        .unscale(
+            &ask_pool.info,
+            Decimal256::with_precision(return_amount, token_precision)?,
+        )?
+        .to_uint128_with_precision(token_precision)?;
+    let offer_asset_amount = rates
+        .unscale(
+            &ask_pool.info,
+            rates.scale(&offer_asset.info, offer_asset.amount)?,
+        )?
         .to_uint128_with_precision(token_precision)?;
 
-    // We consider swap rate 1:1 in stable swap thus any difference is considered as spread.
+    // We consider swap rate 1:1 between scaled amounts thus any difference is considered as spread.
     let spread_amount = offer_asset_amount.saturating_sub(return_amount);
 
     Ok(SwapResult {
@@ -291,13 +512,16 @@ pub(crate) fn compute_swap(
 }
 
 /// Calculate and save moving averages of swap sizes.
+///
+/// * **observations** circular buffer of the pair of assets which were swapped.
 pub fn accumulate_swap_sizes(
     storage: &mut dyn Storage,
     env: &Env,
+    observations: CircularBuffer<Observation>,
     base_amount: Uint128,
     quote_amount: Uint128,
 ) -> BufferResult<()> {
-    let mut buffer = BufferManager::new(storage, OBSERVATIONS)?;
+    let mut buffer = BufferManager::new(storage, observations)?;
 
     let new_observation;
     if let Some(last_obs) = buffer.read_last(storage)? {
@@ -364,23 +588,84 @@ fn safe_sma_calculation(
     res.try_into().map_err(StdError::from)
 }
 
-/// Internal function to determine which asset is base one, which is quote one
+/// Internal function to determine which asset is base one, which is quote one.
+/// The base asset is the one that goes first in the pool.
+/// Returns the observations buffer keys for the pair of swapped assets as well as base and quote amounts.
 pub(crate) fn determine_base_quote_amount(
     pools: &[DecimalAsset],
     offer_asset: &Asset,
+    ask_asset_info: &AssetInfo,
     return_amount: Uint128,
-) -> Result<(Uint128, Uint128), ContractError> {
-    let offer_index = pools
-        .iter()
-        .find_position(|asset| asset.info == offer_asset.info)
-        .ok_or_else(|| ContractError::InvalidAsset(offer_asset.info.to_string()))?
-        .0;
-
-    let (base_amount, quote_amount) = if offer_index == 0 {
-        (offer_asset.amount, return_amount)
-    } else {
-        (return_amount, offer_asset.amount)
+) -> Result<(ObservationsKeys, Uint128, Uint128), ContractError> {
+    let position = |info: &AssetInfo| {
+        pools
+            .iter()
+            .position(|asset| asset.info == *info)
+            .ok_or_else(|| ContractError::InvalidAsset(info.to_string()))
     };
+    let offer_index = position(&offer_asset.info)?;
+    let ask_index = position(ask_asset_info)?;
+
+    if offer_index < ask_index {
+        Ok((
+            ObservationsKeys::new(offer_index, ask_index),
+            offer_asset.amount,
+            return_amount,
+        ))
+    } else {
+        Ok((
+            ObservationsKeys::new(ask_index, offer_index),
+            return_amount,
+            offer_asset.amount,
+        ))
+    }
+}
+
+/// Accumulate token prices for the assets in the pool.
+/// The price of every directed pair of assets is the amount of ask tokens returned for one offer token.
+///
+/// * **rates** redemption rates of the pool assets.
+///
+/// * **pools** array with assets available in the pool *before* the operation.
+pub fn accumulate_prices(
+    deps: Deps,
+    env: &Env,
+    config: &mut Config,
+    rates: &Rates,
+    pools: &[DecimalAsset],
+) -> Result<(), ContractError> {
+    let block_time = env.block.time.seconds();
+    if block_time <= config.block_time_last {
+        return Ok(());
+    }
+
+    let time_elapsed = Uint128::from(block_time - config.block_time_last);
+
+    if pools.iter().all(|pool| !pool.amount.is_zero()) {
+        let immut_config = config.clone();
+        for (from, to, value) in config.cumulative_prices.iter_mut() {
+            let offer_asset = DecimalAsset {
+                info: from.clone(),
+                amount: Decimal256::one(),
+            };
+
+            let (offer_pool, ask_pool) = select_pools(Some(from), Some(to), pools)?;
+            let SwapResult { return_amount, .. } = compute_swap(
+                deps.storage,
+                env,
+                &immut_config,
+                rates,
+                &offer_asset,
+                &offer_pool,
+                &ask_pool,
+                pools,
+            )?;
+
+            *value = value.wrapping_add(time_elapsed.checked_mul(return_amount)?);
+        }
+    }
+
+    config.block_time_last = block_time;
 
-    Ok((base_amount, quote_amount))
+    Ok(())
 }
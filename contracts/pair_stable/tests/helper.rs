@@ -6,8 +6,13 @@ use std::str::FromStr;
 
 use anyhow::Result as AnyResult;
 use astroport_mocks::cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
-use cosmwasm_std::{coin, to_binary, Addr, Coin, Decimal, Empty, StdResult, Uint128};
+use astroport_mocks::{borrower_contract, BorrowerAction};
+use cosmwasm_std::{
+    coin, to_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Response,
+    StdError, StdResult, Uint128,
+};
 use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw_storage_plus::Item;
 use derivative::Derivative;
 use itertools::Itertools;
 
@@ -15,8 +20,8 @@ use astroport::asset::{native_asset_info, token_asset_info, Asset, AssetInfo, Pa
 use astroport::factory::{PairConfig, PairType};
 use astroport::observation::OracleObservation;
 use astroport::pair::{
-    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, QueryMsg, ReverseSimulationResponse,
-    SimulationResponse, StablePoolParams,
+    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, QueryMsg, RateResponse,
+    ReverseSimulationResponse, SimulationResponse, StablePoolParams,
 };
 pub const NATIVE_TOKEN_PRECISION: u8 = 6;
 use astroport_pair_stable::contract::{execute, instantiate, query, reply};
@@ -95,6 +100,29 @@ fn factory_contract() -> Box<dyn Contract<Empty>> {
     )
 }
 
+/// Mock rate provider which responds with the rate set by the last [`Helper::set_rate`] call.
+/// If the rate is not set the provider fails to respond.
+const MOCK_RATE: Item<Option<Decimal>> = Item::new("rate");
+
+fn rate_provider_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new_with_empty(
+        |deps: DepsMut, _: Env, _: MessageInfo, rate: Option<Decimal>| -> StdResult<Response> {
+            MOCK_RATE.save(deps.storage, &rate)?;
+            Ok(Response::new())
+        },
+        |deps: DepsMut, _: Env, _: MessageInfo, rate: Option<Decimal>| -> StdResult<Response> {
+            MOCK_RATE.save(deps.storage, &rate)?;
+            Ok(Response::new())
+        },
+        |deps: Deps, _: Env, _: Empty| -> StdResult<Binary> {
+            let rate = MOCK_RATE
+                .load(deps.storage)?
+                .ok_or_else(|| StdError::generic_err("Rate provider is down"))?;
+            to_binary(&RateResponse { rate })
+        },
+    ))
+}
+
 fn store_coin_registry_code() -> Box<dyn Contract<Empty>> {
     Box::new(ContractWrapper::new_with_empty(
         astroport_native_coin_registry::contract::execute,
@@ -122,6 +150,20 @@ impl Helper {
         test_coins: Vec<TestCoin>,
         amp: u64,
         swap_fee: Option<u16>,
+    ) -> AnyResult<Self> {
+        Self::new_with_params(owner, test_coins, swap_fee, |_, _| StablePoolParams {
+            amp,
+            owner: None,
+            rate_sources: None,
+        })
+    }
+
+    /// Creates a pair with custom init params which may depend on contracts deployed before the pair.
+    pub fn new_with_params(
+        owner: &Addr,
+        test_coins: Vec<TestCoin>,
+        swap_fee: Option<u16>,
+        init_params: impl FnOnce(&mut App, &HashMap<TestCoin, AssetInfo>) -> StablePoolParams,
     ) -> AnyResult<Self> {
         let mut app = App::new(|router, _, storage| {
             router
@@ -188,6 +230,7 @@ impl Helper {
                 pair_type: PairType::Stable {},
                 is_disabled: false,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             }],
             token_code_id,
             generator_address: None,
@@ -210,17 +253,25 @@ impl Helper {
             .into_iter()
             .map(|(_, asset_info)| asset_info)
             .collect_vec();
+        let params = init_params(&mut app, &asset_infos_vec.iter().cloned().collect());
+        let amp = params.amp;
         let init_pair_msg = astroport::factory::ExecuteMsg::CreatePair {
             pair_type: PairType::Stable {},
             asset_infos: asset_infos.clone(),
-            init_params: Some(to_binary(&StablePoolParams { amp, owner: None }).unwrap()),
+            init_params: Some(to_binary(&params).unwrap()),
+            fee: None,
+            fee_tier: None,
         };
 
         app.execute_contract(owner.clone(), factory.clone(), &init_pair_msg, &[])?;
 
         let resp: PairInfo = app.wrap().query_wasm_smart(
             &factory,
-            &astroport::factory::QueryMsg::Pair { asset_infos },
+            &astroport::factory::QueryMsg::Pair {
+                asset_infos,
+                pair_type: None,
+                fee_tier: None,
+            },
         )?;
 
         Ok(Self {
@@ -234,6 +285,27 @@ impl Helper {
         })
     }
 
+    /// Instantiates a mock rate provider which responds with the given rate.
+    pub fn init_rate_provider(app: &mut App, owner: &Addr, rate: Decimal) -> Addr {
+        let code_id = app.store_code(rate_provider_contract());
+        app.instantiate_contract(
+            code_id,
+            owner.clone(),
+            &Some(rate),
+            &[],
+            "Rate provider",
+            None,
+        )
+        .unwrap()
+    }
+
+    /// Sets the rate of a mock rate provider. [`None`] makes the provider fail.
+    pub fn set_rate(&mut self, rate_provider: &Addr, rate: Option<Decimal>) {
+        self.app
+            .execute_contract(self.owner.clone(), rate_provider.clone(), &rate, &[])
+            .unwrap();
+    }
+
     pub fn provide_liquidity(&mut self, sender: &Addr, assets: &[Asset]) -> AnyResult<AppResponse> {
         let funds =
             assets.mock_coins_sent(&mut self.app, sender, &self.pair_addr, SendType::Allowance);
@@ -243,6 +315,7 @@ impl Helper {
             slippage_tolerance: None,
             auto_stake: None,
             receiver: None,
+            deadline: None,
         };
 
         self.app
@@ -258,7 +331,11 @@ impl Helper {
         let msg = Cw20ExecuteMsg::Send {
             contract: self.pair_addr.to_string(),
             amount: Uint128::from(amount),
-            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { assets }).unwrap(),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                assets,
+                deadline: None,
+            })
+            .unwrap(),
         };
 
         self.app
@@ -281,6 +358,7 @@ impl Helper {
                         belief_price: None,
                         max_spread: None,
                         to: None,
+                        deadline: None,
                     })
                     .unwrap(),
                 };
@@ -302,6 +380,7 @@ impl Helper {
                     belief_price: None,
                     max_spread: None,
                     to: None,
+                    deadline: None,
                 };
 
                 self.app
@@ -310,6 +389,40 @@ impl Helper {
         }
     }
 
+    /// Instantiates a mock contract which borrows assets via flash loans.
+    pub fn init_borrower(&mut self) -> Addr {
+        let code_id = self.app.store_code(borrower_contract());
+        self.app
+            .instantiate_contract(
+                code_id,
+                self.owner.clone(),
+                &Empty {},
+                &[],
+                "Borrower",
+                None,
+            )
+            .unwrap()
+    }
+
+    pub fn flash_loan(
+        &mut self,
+        sender: &Addr,
+        assets: &[Asset],
+        borrower: &Addr,
+        action: &BorrowerAction,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            sender.clone(),
+            self.pair_addr.clone(),
+            &ExecuteMsg::FlashLoan {
+                assets: assets.to_vec(),
+                receiver: borrower.to_string(),
+                data: to_binary(action).unwrap(),
+            },
+            &[],
+        )
+    }
+
     pub fn simulate_swap(
         &self,
         offer_asset: &Asset,
@@ -350,6 +463,22 @@ impl Helper {
             .query_wasm_smart(&self.pair_addr, &QueryMsg::Observe { seconds_ago })
     }
 
+    pub fn query_observe_pair(
+        &self,
+        base_asset: &TestCoin,
+        quote_asset: &TestCoin,
+        seconds_ago: u64,
+    ) -> StdResult<OracleObservation> {
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::ObservePair {
+                base_asset: self.assets[base_asset].clone(),
+                quote_asset: self.assets[quote_asset].clone(),
+                seconds_ago,
+            },
+        )
+    }
+
     fn init_token(
         app: &mut App,
         token_code: u64,
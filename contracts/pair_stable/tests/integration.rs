@@ -128,6 +128,7 @@ fn instantiate_pair(mut router: &mut App, owner: &Addr) -> Addr {
             pair_type: PairType::Stable {},
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         }],
         token_code_id: token_contract_code_id,
         generator_address: None,
@@ -191,6 +192,7 @@ fn instantiate_pair(mut router: &mut App, owner: &Addr) -> Addr {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
@@ -383,6 +385,7 @@ fn provide_liquidity_msg(
         slippage_tolerance: None,
         auto_stake: None,
         receiver,
+        deadline: None,
     };
 
     let coins = [
@@ -491,6 +494,7 @@ fn provide_lp_for_single_token() {
             pair_type: PairType::Stable {},
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         }],
         token_code_id,
         generator_address: Some(String::from("generator")),
@@ -524,9 +528,12 @@ fn provide_lp_for_single_token() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
+        fee: None,
+        fee_tier: None,
     };
 
     app.execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
@@ -541,6 +548,8 @@ fn provide_lp_for_single_token() {
                 contract_addr: token_y_instance.clone(),
             },
         ],
+        pair_type: None,
+        fee_tier: None,
     };
 
     let res: PairInfo = app
@@ -575,6 +584,7 @@ fn provide_lp_for_single_token() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
         amount: swap_amount,
@@ -606,6 +616,7 @@ fn provide_lp_for_single_token() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     let err = app
@@ -634,6 +645,7 @@ fn provide_lp_for_single_token() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -658,6 +670,7 @@ fn provide_lp_for_single_token() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -690,6 +703,7 @@ fn provide_lp_for_single_token() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -703,6 +717,7 @@ fn provide_lp_for_single_token() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
         amount: swap_amount,
@@ -719,6 +734,7 @@ fn provide_lp_for_single_token() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
         amount: swap_amount,
@@ -825,6 +841,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             pair_type: PairType::Stable {},
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         }],
         token_code_id,
         generator_address: Some(String::from("generator")),
@@ -858,9 +875,12 @@ fn test_compatibility_of_tokens_with_different_precision() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
+        fee: None,
+        fee_tier: None,
     };
 
     app.execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
@@ -875,6 +895,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
                 contract_addr: token_y_instance.clone(),
             },
         ],
+        pair_type: None,
+        fee_tier: None,
     };
 
     let res: PairInfo = app
@@ -920,6 +942,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -940,6 +963,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            deadline: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -1080,6 +1104,7 @@ fn update_pair_config() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
@@ -1324,6 +1349,7 @@ fn check_observe_queries() {
         belief_price: None,
         max_spread: None,
         to: None,
+        deadline: None,
     };
     let send_funds = vec![Coin {
         denom: "uusd".to_owned(),
@@ -1401,7 +1427,7 @@ fn provide_liquidity_with_autostaking_to_generator() {
 }
 
 #[test]
-fn test_imbalance_withdraw_is_disabled() {
+fn test_imbalanced_withdraw() {
     let owner = Addr::unchecked("owner");
     let alice_address = Addr::unchecked("alice");
 
@@ -1486,7 +1512,7 @@ fn test_imbalance_withdraw_is_disabled() {
         .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
         .unwrap();
 
-    // Check that imbalanced withdraw is currently disabled
+    // Not enough LP tokens to withdraw the requested assets
     let msg_imbalance = Cw20ExecuteMsg::Send {
         contract: pair_instance.to_string(),
         amount: Uint128::from(50u8),
@@ -1497,6 +1523,7 @@ fn test_imbalance_withdraw_is_disabled() {
                 },
                 amount: Uint128::from(100u8),
             }],
+            deadline: None,
         })
         .unwrap(),
     };
@@ -1506,6 +1533,44 @@ fn test_imbalance_withdraw_is_disabled() {
         .unwrap_err();
     assert_eq!(
         err.root_cause().to_string(),
-        "Generic error: Imbalanced withdraw is currently disabled"
+        "Generic error: Not enough LP tokens. You need 101 LP tokens."
     );
+
+    // Withdraw uusd only. Unused LP tokens are sent back
+    let msg_imbalance = Cw20ExecuteMsg::Send {
+        contract: pair_instance.to_string(),
+        amount: Uint128::from(150u8),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            assets: vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(100u8),
+            }],
+            deadline: None,
+        })
+        .unwrap(),
+    };
+    router
+        .execute_contract(alice_address.clone(), lp_token.clone(), &msg_imbalance, &[])
+        .unwrap();
+
+    let res: BalanceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &lp_token,
+            &Cw20QueryMsg::Balance {
+                address: alice_address.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.balance, Uint128::new(199_000 - 101));
+
+    let uusd_balance = router.wrap().query_balance(&alice_address, "uusd").unwrap();
+    assert_eq!(uusd_balance.amount, Uint128::new(33_100));
+    let uluna_balance = router
+        .wrap()
+        .query_balance(&alice_address, "uluna")
+        .unwrap();
+    assert_eq!(uluna_balance.amount, Uint128::zero());
 }
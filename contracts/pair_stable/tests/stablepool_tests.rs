@@ -1,11 +1,17 @@
 #![cfg(not(tarpaulin_include))]
 
-use cosmwasm_std::{Addr, Decimal, StdError};
+use cosmwasm_std::{to_binary, Addr, Decimal, Empty, Fraction, StdError};
 use std::str::FromStr;
 
-use astroport::asset::AssetInfoExt;
+use astroport::asset::{native_asset_info, AssetInfoExt};
 use astroport::cosmwasm_ext::AbsDiff;
 use astroport::observation::OracleObservation;
+use astroport::pair::{
+    AssetRateSource, ExecuteMsg, FlashLoanError, QueryMsg, RateSource, StablePoolParams,
+    StablePoolUpdateParams, DEFAULT_FLASH_LOAN_FEE_BPS,
+};
+use astroport_mocks::cw_multi_test::Executor;
+use astroport_mocks::BorrowerAction;
 use astroport_pair_stable::error::ContractError;
 use helper::AppExtension;
 
@@ -224,7 +230,6 @@ fn swap_different_precisions() {
     assert_eq!(99_94902, helper.coin_balance(&test_coins[1], &user));
 }
 
-#[ignore]
 #[test]
 fn check_swaps() {
     let owner = Addr::unchecked("owner");
@@ -281,7 +286,7 @@ fn check_wrong_initializations() {
     let err = Helper::new(&owner, vec![TestCoin::native("uluna")], 100u64, None).unwrap_err();
 
     assert_eq!(
-        ContractError::InvalidNumberOfAssets(2),
+        ContractError::InvalidNumberOfPoolAssets {},
         err.downcast().unwrap()
     );
 
@@ -301,7 +306,7 @@ fn check_wrong_initializations() {
     .unwrap_err();
 
     assert_eq!(
-        ContractError::InvalidNumberOfAssets(2),
+        ContractError::InvalidNumberOfPoolAssets {},
         err.downcast().unwrap()
     );
 
@@ -326,6 +331,21 @@ fn check_wrong_initializations() {
         None,
     )
     .unwrap();
+
+    // As well as 5 assets
+    Helper::new(
+        &owner,
+        vec![
+            TestCoin::native("one"),
+            TestCoin::cw20("two"),
+            TestCoin::native("three"),
+            TestCoin::cw20("four"),
+            TestCoin::native("five"),
+        ],
+        100u64,
+        None,
+    )
+    .unwrap();
 }
 
 #[ignore]
@@ -407,9 +427,6 @@ fn check_pool_prices() {
     let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("USDX")];
 
     let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
-    let err = helper.query_prices().unwrap_err();
-    assert_eq!(StdError::generic_err("Querier contract error: Generic error: Not implemented.Use { \"observe\" : { \"seconds_ago\" : ... } } instead.")
-               , err);
 
     let assets = vec![
         helper.assets[&test_coins[0]].with_balance(100_000_000_000000u128),
@@ -505,3 +522,536 @@ fn check_pool_prices() {
     );
     helper.app.next_block(10);
 }
+
+#[test]
+fn check_three_assets_pool() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::native("uusd"),
+        TestCoin::cw20("USDC"),
+        TestCoin::cw20("USDT"),
+    ];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[2]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let prices = helper.query_prices().unwrap();
+    assert_eq!(prices.cumulative_prices.len(), 6);
+    assert!(prices
+        .cumulative_prices
+        .iter()
+        .all(|(_, _, value)| value.is_zero()));
+
+    helper.app.next_block(1000);
+
+    // Swap uusd -> USDT skipping USDC
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+
+    let sim_resp = helper
+        .simulate_swap(&offer_asset, Some(helper.assets[&test_coins[2]].clone()))
+        .unwrap();
+    let reverse_sim_resp = helper
+        .simulate_reverse_swap(
+            &helper.assets[&test_coins[2]].with_balance(sim_resp.return_amount.u128()),
+            Some(helper.assets[&test_coins[0]].clone()),
+        )
+        .unwrap();
+    assert!(
+        reverse_sim_resp
+            .offer_amount
+            .diff(offer_asset.amount)
+            .u128()
+            <= 1,
+        "reverse simulation offer amount {} differs from {}",
+        reverse_sim_resp.offer_amount,
+        offer_asset.amount
+    );
+
+    helper
+        .swap(
+            &user,
+            &offer_asset,
+            Some(helper.assets[&test_coins[2]].clone()),
+        )
+        .unwrap();
+    assert_eq!(0, helper.coin_balance(&test_coins[0], &user));
+    assert_eq!(0, helper.coin_balance(&test_coins[1], &user));
+    assert_eq!(
+        sim_resp.return_amount.u128(),
+        helper.coin_balance(&test_coins[2], &user)
+    );
+
+    // Cumulative prices are accumulated for every directed pair of assets
+    let prices = helper.query_prices().unwrap();
+    assert!(prices
+        .cumulative_prices
+        .iter()
+        .all(|(_, _, value)| !value.is_zero()));
+
+    helper.app.next_block(86400);
+
+    // Observations are stored for the swapped pair only
+    let observation = helper
+        .query_observe_pair(&test_coins[0], &test_coins[2], 0)
+        .unwrap();
+    assert_eq!(
+        observation.price,
+        Decimal::from_ratio(offer_asset.amount, sim_resp.return_amount)
+    );
+
+    // Price in the opposite direction is inverted
+    let observation = helper
+        .query_observe_pair(&test_coins[2], &test_coins[0], 0)
+        .unwrap();
+    assert_eq!(
+        observation.price,
+        Decimal::from_ratio(offer_asset.amount, sim_resp.return_amount)
+            .inv()
+            .unwrap()
+    );
+
+    let err = helper
+        .query_observe_pair(&test_coins[0], &test_coins[1], 0)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Querier contract error: Generic error: Buffer is empty")
+    );
+    let err = helper.query_observe(0).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Querier contract error: Generic error: Buffer is empty")
+    );
+
+    let err = helper
+        .query_observe_pair(&test_coins[1], &test_coins[1], 0)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "Querier contract error: Generic error: Source and target assets are the same"
+        )
+    );
+}
+
+#[test]
+fn check_rate_scaled_pool() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("STLUNA")];
+
+    let rate_source = |contract_addr: &Addr, cache_period, max_staleness| RateSource::Contract {
+        contract_addr: contract_addr.to_string(),
+        query_msg: to_binary(&Empty {}).unwrap(),
+        cache_period,
+        max_staleness,
+    };
+
+    // Rate source must refer to a pool asset
+    let err = Helper::new_with_params(&owner, test_coins.clone(), None, |_, _| StablePoolParams {
+        amp: 100,
+        owner: None,
+        rate_sources: Some(vec![AssetRateSource {
+            asset_info: native_asset_info("uusd".to_string()),
+            source: RateSource::Constant {
+                rate: Decimal::one(),
+            },
+        }]),
+    })
+    .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidAsset("uusd".to_string()),
+        err.downcast().unwrap()
+    );
+
+    // Rate must not be cached longer than the max staleness period
+    let err = Helper::new_with_params(&owner, test_coins.clone(), None, |app, assets| {
+        let rate_provider = Helper::init_rate_provider(app, &owner, Decimal::one());
+        StablePoolParams {
+            amp: 100,
+            owner: None,
+            rate_sources: Some(vec![AssetRateSource {
+                asset_info: assets[&test_coins[1]].clone(),
+                source: rate_source(&rate_provider, 3600, 60),
+            }]),
+        }
+    })
+    .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidRateCachePeriod {},
+        err.downcast().unwrap()
+    );
+
+    let mut rate_provider = Addr::unchecked("");
+    let mut helper = Helper::new_with_params(&owner, test_coins.clone(), None, |app, assets| {
+        rate_provider = Helper::init_rate_provider(app, &owner, Decimal::from_str("1.2").unwrap());
+        StablePoolParams {
+            amp: 100,
+            owner: None,
+            rate_sources: Some(vec![AssetRateSource {
+                asset_info: assets[&test_coins[1]].clone(),
+                source: rate_source(&rate_provider, 60, 3600),
+            }]),
+        }
+    })
+    .unwrap();
+
+    // The pool is balanced according to the rate
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(120_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+
+    // 100 STLUNA are worth 120 LUNA minus fees
+    let sim_resp = helper
+        .simulate_swap(&offer_asset, Some(helper.assets[&test_coins[0]].clone()))
+        .unwrap();
+    assert!(
+        (119_900000..=119_940000).contains(&sim_resp.return_amount.u128()),
+        "unexpected return amount {}",
+        sim_resp.return_amount
+    );
+
+    // Reverse simulation uses the same scaled math
+    let reverse_sim_resp = helper
+        .simulate_reverse_swap(
+            &helper.assets[&test_coins[0]].with_balance(sim_resp.return_amount.u128()),
+            Some(helper.assets[&test_coins[1]].clone()),
+        )
+        .unwrap();
+    assert!(
+        reverse_sim_resp
+            .offer_amount
+            .diff(offer_asset.amount)
+            .u128()
+            <= 2,
+        "reverse simulation offer amount {} differs from {}",
+        reverse_sim_resp.offer_amount,
+        offer_asset.amount
+    );
+
+    helper
+        .swap(
+            &user,
+            &offer_asset,
+            Some(helper.assets[&test_coins[0]].clone()),
+        )
+        .unwrap();
+    assert_eq!(
+        sim_resp.return_amount.u128(),
+        helper.coin_balance(&test_coins[0], &user)
+    );
+
+    // The new rate is not used until the cached rate expires
+    helper.set_rate(&rate_provider, Some(Decimal::from_str("1.5").unwrap()));
+    helper.app.next_block(30);
+    let sim_resp = helper
+        .simulate_swap(&offer_asset, Some(helper.assets[&test_coins[0]].clone()))
+        .unwrap();
+    assert!(sim_resp.return_amount.u128() < 120_000000);
+
+    helper.app.next_block(31);
+    let sim_resp = helper
+        .simulate_swap(&offer_asset, Some(helper.assets[&test_coins[0]].clone()))
+        .unwrap();
+    assert!(
+        (149_500000..=149_925000).contains(&sim_resp.return_amount.u128()),
+        "unexpected return amount {}",
+        sim_resp.return_amount
+    );
+
+    // Swap caches the new rate
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper
+        .swap(
+            &user,
+            &offer_asset,
+            Some(helper.assets[&test_coins[0]].clone()),
+        )
+        .unwrap();
+
+    // The cached rate is used while the rate provider is down
+    helper.set_rate(&rate_provider, None);
+    helper.app.next_block(100);
+    let sim_resp = helper
+        .simulate_swap(&offer_asset, Some(helper.assets[&test_coins[0]].clone()))
+        .unwrap();
+    assert!(sim_resp.return_amount.u128() > 149_000000);
+
+    // The cached rate is too old
+    helper.app.next_block(3600);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    let err = helper
+        .swap(
+            &user,
+            &offer_asset,
+            Some(helper.assets[&test_coins[0]].clone()),
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::StaleRate(helper.assets[&test_coins[1]].to_string()),
+        err.downcast().unwrap()
+    );
+
+    // Only the owner can replace rate sources
+    let update_rate_sources = |rate_sources| ExecuteMsg::UpdateConfig {
+        params: to_binary(&StablePoolUpdateParams::UpdateRateSources { rate_sources }).unwrap(),
+    };
+    let constant_rate = vec![AssetRateSource {
+        asset_info: helper.assets[&test_coins[1]].clone(),
+        source: RateSource::Constant {
+            rate: Decimal::from_str("1.5").unwrap(),
+        },
+    }];
+    let err = helper
+        .app
+        .execute_contract(
+            user.clone(),
+            helper.pair_addr.clone(),
+            &update_rate_sources(constant_rate.clone()),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    // New rate sources are validated
+    let err = helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &update_rate_sources(vec![AssetRateSource {
+                asset_info: helper.assets[&test_coins[1]].clone(),
+                source: RateSource::Constant {
+                    rate: Decimal::zero(),
+                },
+            }]),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidRate(helper.assets[&test_coins[1]].to_string()),
+        err.downcast().unwrap()
+    );
+
+    // The owner replaces the stale rate provider with a constant rate
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &update_rate_sources(constant_rate),
+            &[],
+        )
+        .unwrap();
+    helper
+        .swap(
+            &user,
+            &offer_asset,
+            Some(helper.assets[&test_coins[0]].clone()),
+        )
+        .unwrap();
+
+    // Removing all rate sources makes the pool 1:1 again
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &update_rate_sources(vec![]),
+            &[],
+        )
+        .unwrap();
+    let sim_resp = helper
+        .simulate_swap(&offer_asset, Some(helper.assets[&test_coins[0]].clone()))
+        .unwrap();
+    assert!(
+        (99_500000..=100_500000).contains(&sim_resp.return_amount.u128()),
+        "unexpected return amount {}",
+        sim_resp.return_amount
+    );
+
+    // Balanced withdrawal is still allowed
+    let lp_balance = helper.token_balance(&helper.lp_token, &owner);
+    helper
+        .withdraw_liquidity(&owner, lp_balance, vec![])
+        .unwrap();
+}
+
+#[test]
+fn check_flash_loan() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("USDC")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    // Send half of the flash loan fees to the Maker
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &astroport::factory::ExecuteMsg::UpdateConfig {
+                token_code_id: None,
+                fee_address: Some("maker".to_string()),
+                generator_address: None,
+                whitelist_code_id: None,
+                coin_registry_address: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let fee_bps: u16 = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::FlashLoanFee {})
+        .unwrap();
+    assert_eq!(fee_bps, DEFAULT_FLASH_LOAN_FEE_BPS);
+
+    let user = Addr::unchecked("user");
+    let borrower = helper.init_borrower();
+    // The borrower needs some funds to pay the flash loan fee
+    helper.give_me_money(
+        &[
+            helper.assets[&test_coins[0]].with_balance(10_000u128),
+            helper.assets[&test_coins[1]].with_balance(10_000u128),
+        ],
+        &borrower,
+    );
+
+    let loan = vec![
+        helper.assets[&test_coins[0]].with_balance(1_000_000u128),
+        helper.assets[&test_coins[1]].with_balance(2_000_000u128),
+    ];
+    let repay_msg = |uusd_amount: u128, usdc_amount: u128| BorrowerAction::Repay {
+        assets: vec![
+            helper.assets[&test_coins[0]].with_balance(uusd_amount),
+            helper.assets[&test_coins[1]].with_balance(usdc_amount),
+        ],
+    };
+    let msg_without_fee = repay_msg(1_000_000, 2_001_800);
+    let msg = repay_msg(1_000_900, 2_001_800);
+
+    // The loan is repaid without the fee
+    let err = helper
+        .flash_loan(&user, &loan, &borrower, &msg_without_fee)
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::FlashLoan(FlashLoanError::NotRepaid {
+            asset: "uusd".to_string(),
+            expected: 100_000_000900u128.into(),
+            actual: 100_000_000000u128.into(),
+        })
+        .to_string()
+    );
+
+    // Assets which are not in the pool can not be borrowed
+    let err = helper
+        .flash_loan(
+            &user,
+            &[native_asset_info("uluna".to_string()).with_balance(1_000_000u128)],
+            &borrower,
+            &msg,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::FlashLoan(FlashLoanError::InvalidAssets(
+            "uluna does not belong to the pool".to_string()
+        )),
+        err.downcast().unwrap()
+    );
+
+    // The pool can not be used during the flash loan
+    let err = helper
+        .flash_loan(
+            &user,
+            &loan,
+            &borrower,
+            &BorrowerAction::Swap {
+                offer_asset: loan[0].clone(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::FlashLoan(FlashLoanError::InProgress {}).to_string()
+    );
+
+    // The loan is repaid with the fee
+    helper.flash_loan(&user, &loan, &borrower, &msg).unwrap();
+
+    let maker = Addr::unchecked("maker");
+    assert_eq!(helper.coin_balance(&test_coins[0], &maker), 450);
+    assert_eq!(helper.coin_balance(&test_coins[1], &maker), 900);
+    assert_eq!(
+        helper.coin_balance(&test_coins[0], &helper.pair_addr),
+        100_000_000450
+    );
+    assert_eq!(
+        helper.coin_balance(&test_coins[1], &helper.pair_addr),
+        100_000_000900
+    );
+
+    // The pair is unlocked after the loan is repaid
+    helper.give_me_money(&[loan[0].clone()], &user);
+    helper.swap(&user, &loan[0], None).unwrap();
+
+    let err = helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::UpdateConfig {
+                params: to_binary(&StablePoolUpdateParams::UpdateFlashLoanFee { fee_bps: 1001 })
+                    .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::FlashLoanFeeTooHigh {},
+        err.downcast().unwrap()
+    );
+
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::UpdateConfig {
+                params: to_binary(&StablePoolUpdateParams::UpdateFlashLoanFee { fee_bps: 30 })
+                    .unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    let fee_bps: u16 = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::FlashLoanFee {})
+        .unwrap();
+    assert_eq!(fee_bps, 30);
+}
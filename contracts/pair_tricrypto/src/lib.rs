@@ -0,0 +1,8 @@
+pub mod contract;
+pub mod state;
+
+pub mod consts;
+pub mod error;
+pub mod math;
+pub mod queries;
+pub mod utils;
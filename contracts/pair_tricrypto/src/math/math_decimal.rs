@@ -0,0 +1,381 @@
+use cosmwasm_std::{Decimal256, Fraction, StdError, StdResult, Uint128};
+use itertools::Itertools;
+
+use astroport::cosmwasm_ext::AbsDiff;
+
+use crate::consts::{HALFPOW_TOL, MAX_ITER, N, N_COINS, N_POW_N, TOL};
+use crate::math::signed_decimal::SignedDecimal256;
+
+/// Calculates the geometric mean of the given values using Newton's method.
+pub fn geometric_mean(x: &[Decimal256]) -> StdResult<Decimal256> {
+    let mul = x.iter().fold(Decimal256::one(), |acc, x| acc * *x);
+    if mul.is_zero() {
+        return Ok(Decimal256::zero());
+    }
+
+    // The arithmetic mean is always greater than or equal to the geometric mean
+    // thus it is used as the first approximation
+    let mut d = x.iter().fold(Decimal256::zero(), |acc, x| acc + *x) / N;
+
+    for _ in 0..MAX_ITER {
+        let d_prev = d;
+        d = (d * (N - Decimal256::one()) + mul / d.pow(N_COINS as u32 - 1)) / N;
+        if d.diff(d_prev) <= TOL {
+            return Ok(d);
+        }
+    }
+
+    Err(StdError::generic_err("geometric_mean is not converging"))
+}
+
+fn product(x: &[SignedDecimal256]) -> SignedDecimal256 {
+    x.iter()
+        .fold(Decimal256::one().into(), |acc: SignedDecimal256, x| {
+            acc * *x
+        })
+}
+
+fn sum(x: &[SignedDecimal256]) -> SignedDecimal256 {
+    x.iter()
+        .fold(Decimal256::zero().into(), |acc: SignedDecimal256, x| {
+            acc + *x
+        })
+}
+
+pub(crate) fn f(
+    d: SignedDecimal256,
+    x: &[SignedDecimal256],
+    a: Decimal256,
+    gamma: Decimal256,
+) -> SignedDecimal256 {
+    let mul = product(x);
+    let d_pow_n_1 = d.pow(N_COINS as u32 - 1);
+    let d_pow_n = d_pow_n_1 * d;
+
+    let k0 = mul * N_POW_N / d_pow_n;
+    let k = a * gamma.pow(2) * k0 / (SignedDecimal256::from(gamma + Decimal256::one()) - k0).pow(2);
+
+    k * d_pow_n_1 * sum(x) + mul - k * d_pow_n - (d / N).pow(N_COINS as u32)
+}
+
+/// df/dD
+pub(crate) fn df_dd(
+    d: SignedDecimal256,
+    x: &[SignedDecimal256],
+    a: Decimal256,
+    gamma: Decimal256,
+) -> SignedDecimal256 {
+    let mul = product(x);
+    let a_gamma_pow_2 = a * gamma.pow(2); // A * gamma^2
+    let d_pow_n_2 = d.pow(N_COINS as u32 - 2);
+    let d_pow_n_1 = d_pow_n_2 * d;
+
+    let k0 = mul * N_POW_N / (d_pow_n_1 * d);
+
+    let gamma_one_k0 = SignedDecimal256::from(gamma + Decimal256::one()) - k0; // gamma + 1 - K0
+    let gamma_one_k0_pow2 = gamma_one_k0.pow(2); // (gamma + 1 - K0)^2
+
+    let k = a_gamma_pow_2 * k0 / gamma_one_k0_pow2;
+
+    // dK/dD * D. dK0/dD = -N * K0 / D thus D is cancelled out which helps to keep precision
+    let k_d = -(a_gamma_pow_2 * (gamma + Decimal256::one() + k0) * k0 * N
+        / (gamma_one_k0_pow2 * gamma_one_k0));
+
+    (k_d + k * (N - Decimal256::one())) * d_pow_n_2 * sum(x)
+        - (k_d + k * N) * d_pow_n_1
+        - (d / N).pow(N_COINS as u32 - 1)
+}
+
+pub(crate) fn newton_d(
+    x: &[Decimal256],
+    a: Decimal256,
+    gamma: Decimal256,
+) -> StdResult<Decimal256> {
+    let mut d_prev: SignedDecimal256 = (N * geometric_mean(x)?).into();
+    let x = x.iter().map(SignedDecimal256::from).collect_vec();
+
+    for _ in 0..MAX_ITER {
+        let d = d_prev - f(d_prev, &x, a, gamma) / df_dd(d_prev, &x, a, gamma);
+        if d.diff(d_prev) <= TOL {
+            return d.try_into();
+        }
+        d_prev = d;
+    }
+
+    Err(StdError::generic_err("newton_d is not converging"))
+}
+
+/// df/dx
+pub(crate) fn df_dx(
+    d: Decimal256,
+    x: &[SignedDecimal256],
+    a: Decimal256,
+    gamma: Decimal256,
+    i: usize,
+) -> SignedDecimal256 {
+    // Product of all volumes except x[i]
+    let mul_r = x
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != i)
+        .fold(Decimal256::one().into(), |acc: SignedDecimal256, (_, x)| {
+            acc * *x
+        });
+    let d_pow_n_2 = d.pow(N_COINS as u32 - 2);
+    let d_pow_n_1 = d_pow_n_2 * d;
+
+    let k0 = mul_r * x[i] * N_POW_N / (d_pow_n_1 * d);
+    let gamma_one_k0 = gamma + Decimal256::one() - k0;
+    let gamma_one_k0_pow2 = gamma_one_k0.pow(2);
+    let a_gamma_pow2 = a * gamma.pow(2);
+
+    let k = a_gamma_pow2 * k0 / gamma_one_k0_pow2;
+    // dK/dx * D
+    let k_x = mul_r * N_POW_N * a_gamma_pow2 * (gamma + Decimal256::one() + k0)
+        / (d_pow_n_1 * gamma_one_k0 * gamma_one_k0_pow2);
+
+    k_x * d_pow_n_2 * (sum(x) - d) + k * d_pow_n_1 + mul_r
+}
+
+pub(crate) fn newton_y(
+    xs: &[Decimal256],
+    a: Decimal256,
+    gamma: Decimal256,
+    d: Decimal256,
+    j: usize,
+) -> StdResult<Decimal256> {
+    let mut x = xs.iter().map(SignedDecimal256::from).collect_vec();
+    let mul_r = xs
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != j)
+        .fold(Decimal256::one(), |acc, (_, x)| acc * *x);
+    let x0 = (d / N).pow(N_COINS as u32) / mul_r;
+    let mut xi_1: SignedDecimal256 = x0.into();
+    x[j] = xi_1;
+
+    for _ in 0..MAX_ITER {
+        let xi = xi_1 - f(d.into(), &x, a, gamma) / df_dx(d, &x, a, gamma, j);
+        if xi.diff(xi_1) <= TOL {
+            return xi.try_into();
+        }
+        x[j] = xi;
+        xi_1 = xi;
+    }
+
+    Err(StdError::generic_err("newton_y is not converging"))
+}
+
+/// Calculates 0.5^power.
+pub fn half_float_pow(power: Decimal256) -> StdResult<Decimal256> {
+    let intpow = power.floor();
+    let intpow_u128: Uint128 = (intpow.numerator() / intpow.denominator()).try_into()?;
+
+    let half = Decimal256::from_ratio(1u8, 2u8);
+    let frac_pow = power - intpow;
+
+    // 0.5 ^ int_power
+    let result = half.pow(intpow_u128.u128() as u32);
+
+    let mut term = Decimal256::one();
+    let mut sum = Decimal256::one();
+
+    for i in 1..(MAX_ITER as u128) {
+        let k = Decimal256::from_atomics(i, 0).unwrap();
+        let mut c = k - Decimal256::one();
+
+        c = frac_pow.diff(c);
+        term = term * c * half / k;
+        sum -= term;
+
+        if term < HALFPOW_TOL {
+            return Ok(result * sum);
+        }
+    }
+
+    Err(StdError::generic_err("halfpow is not converging"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    use anyhow::{anyhow, Result as AnyResult};
+
+    use crate::math::math_f64::newton_d as newton_d_f64;
+    use crate::math::math_f64::newton_y as newton_y_f64;
+
+    use super::*;
+
+    fn f64_to_dec(val: f64) -> Decimal256 {
+        Decimal256::from_str(&val.to_string()).unwrap()
+    }
+
+    fn dec_to_f64(val: impl Display) -> f64 {
+        f64::from_str(&val.to_string()).unwrap()
+    }
+
+    fn assert_values(dec: impl Display, f64_val: f64) {
+        let dec_val = dec_to_f64(dec);
+        if (dec_val - f64_val).abs() > 0.001f64 {
+            assert_eq!(dec_val, f64_val)
+        }
+    }
+
+    fn compute(x: [f64; 3], a: f64, gamma: f64) -> AnyResult<()> {
+        println!("{x:?}, a: {a}");
+
+        let x_dec = x.map(f64_to_dec);
+        let a_dec = f64_to_dec(a);
+        let gamma_dec = f64_to_dec(gamma);
+
+        let d_f64 = newton_d_f64(&x, a, gamma);
+        let d_dec = newton_d(&x_dec, a_dec, gamma_dec).unwrap();
+        assert_values(d_dec, d_f64);
+
+        for (offer_ind, ask_ind) in [(1, 0), (0, 1), (0, 2), (2, 1)] {
+            let mut xp_swap = x;
+            xp_swap[offer_ind] += 3.0;
+            xp_swap[ask_ind] = 0.0;
+            let y_f64 = newton_y_f64(&xp_swap, a, gamma, d_f64, ask_ind);
+
+            let xp_swap_dec = xp_swap.map(f64_to_dec);
+            if let Ok(res) = newton_y(&xp_swap_dec, a_dec, gamma_dec, d_dec, ask_ind) {
+                assert_values(res, y_f64);
+            } else {
+                return Err(anyhow!("newton_y does not converge for i = {ask_ind}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn single_test() {
+        let gamma = 0.000145;
+
+        compute([1000f64, 1000f64, 1000f64], 3500f64, gamma).unwrap();
+        compute([1000f64, 2000f64, 500f64], 100f64, gamma).unwrap();
+    }
+
+    #[test]
+    fn test_geometric_mean() {
+        let res = geometric_mean(&[f64_to_dec(8f64), f64_to_dec(1f64), f64_to_dec(1f64)]).unwrap();
+        assert_values(res, 2f64);
+
+        let res = geometric_mean(&[
+            f64_to_dec(1_000_000f64),
+            f64_to_dec(1_000f64),
+            f64_to_dec(1f64),
+        ])
+        .unwrap();
+        assert_values(res, 1_000f64);
+
+        let res = geometric_mean(&[f64_to_dec(0f64), f64_to_dec(1f64), f64_to_dec(1f64)]).unwrap();
+        assert_eq!(res, Decimal256::zero());
+    }
+
+    #[test]
+    fn test_real_case() {
+        let x0 = 1173700.016159;
+        let x1 = 0.800244312479334221;
+        let x2 = 1173700.016159;
+        let offer_amount = 1.0;
+        let amp = 40.0;
+        let gamma = 0.000145;
+
+        let xs = [f64_to_dec(x0), f64_to_dec(x1), f64_to_dec(x2)];
+        let d = newton_d(&xs, f64_to_dec(amp), f64_to_dec(gamma)).unwrap();
+
+        println!("Pool before [{} {} {}]", x0, x1, x2);
+        let new_x1 = newton_y(
+            &[
+                f64_to_dec(x0 + offer_amount),
+                f64_to_dec(x1),
+                f64_to_dec(x2),
+            ],
+            f64_to_dec(amp),
+            f64_to_dec(gamma),
+            d,
+            1,
+        )
+        .unwrap();
+        let new_x1 = dec_to_f64(new_x1);
+        println!("Pool after [{} {} {}]", x0 + offer_amount, new_x1, x2);
+        println!("Diff [{} {}]", offer_amount, new_x1 - x1);
+        assert!(new_x1 < x1, "new x1 {new_x1} should be less than x1 {x1}");
+    }
+
+    #[test]
+    fn test_derivatives() {
+        let a_f64 = 3500f64;
+        let gamma_f64 = 0.000145;
+        let d_f64 = 3000000f64;
+        let xs = [1_000000f64, 1_000000f64, 1_000000f64];
+
+        let a = f64_to_dec(a_f64);
+        let gamma = f64_to_dec(gamma_f64);
+        let d = f64_to_dec(d_f64);
+        let x = xs.map(|x| SignedDecimal256::from(f64_to_dec(x)));
+
+        let der_f64 = crate::math::math_f64::df_dd(d_f64, &xs, a_f64, gamma_f64);
+        let der = df_dd(d.into(), &x, a, gamma);
+        assert_values(der, der_f64);
+
+        for i in 0..3 {
+            let dx_f64 = crate::math::math_f64::df_dx(d_f64, &xs, a_f64, gamma_f64, i);
+            let dx = df_dx(d, &x, a, gamma, i);
+            assert_values(dx, dx_f64);
+        }
+    }
+
+    #[test]
+    fn test_f() {
+        let a = f64_to_dec(40f64);
+        let gamma = f64_to_dec(0.000145);
+        let d = f64_to_dec(20000000f64);
+        let xs = [1000000f64, 100000000f64, 10000000f64];
+        let x = xs.map(|x| SignedDecimal256::from(f64_to_dec(x)));
+
+        let val = f(d.into(), &x, a, gamma);
+        let val_f64 = crate::math::math_f64::f(20000000f64, &xs, 40f64, 0.000145);
+        let dec_val_f64 = dec_to_f64(val);
+        assert!(
+            ((dec_val_f64 - val_f64) / val_f64).abs() < 1e-6,
+            "Assert failed: {dec_val_f64} !~ {val_f64}"
+        )
+    }
+
+    #[ignore]
+    #[test]
+    fn test_calculations() {
+        let gamma = 0.000145;
+
+        let x_range: Vec<u128> = (1000u128..=100_000).step_by(20000).into_iter().collect();
+        let mut a_range = (100u128..=10000u128).step_by(2000).collect_vec();
+        a_range.push(1);
+
+        for ((&x1, &x2), &x3) in x_range
+            .iter()
+            .cartesian_product(&x_range)
+            .cartesian_product(&x_range)
+        {
+            for a in &a_range {
+                compute([x1 as f64, x2 as f64, x3 as f64], *a as f64, gamma).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_halfpow() {
+        let res = half_float_pow(f64_to_dec(3.231f64)).unwrap();
+        assert_eq!(dec_to_f64(res), 0.10650551189033386);
+
+        let res = half_float_pow(f64_to_dec(0.5012f64)).unwrap();
+        assert_eq!(dec_to_f64(res), 0.7065188709002241);
+
+        let res = half_float_pow(f64_to_dec(59.1f64)).unwrap();
+        assert_eq!(dec_to_f64(res), 0f64);
+    }
+}
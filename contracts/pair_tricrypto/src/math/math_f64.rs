@@ -0,0 +1,92 @@
+use crate::consts::MAX_ITER;
+
+const N: f64 = 3.0;
+const TOL: f64 = 1e-5;
+
+pub fn f(d: f64, x: &[f64], a: f64, gamma: f64) -> f64 {
+    let mul: f64 = x.iter().product();
+    let sum: f64 = x.iter().sum();
+    let k0 = mul * (N / d).powi(3);
+    let k = a * gamma.powi(2) * k0 / (gamma + 1.0f64 - k0).powi(2);
+
+    k * d.powi(2) * sum + mul - k * d.powi(3) - (d / N).powi(3)
+}
+
+/// df/dD
+pub fn df_dd(d: f64, x: &[f64], a: f64, gamma: f64) -> f64 {
+    let mul: f64 = x.iter().product();
+    let sum: f64 = x.iter().sum();
+    let k0 = mul * (N / d).powi(3);
+    let k = a * gamma.powi(2) * k0 / (gamma + 1.0 - k0).powi(2);
+    let k0_d = -N * k0 / d;
+    let k_d = a * gamma.powi(2) * (gamma + 1.0 + k0) / (gamma + 1.0 - k0).powi(3) * k0_d;
+
+    (k_d * d.powi(2) + 2.0 * k * d) * sum - (k_d * d.powi(3) + N * k * d.powi(2)) - (d / N).powi(2)
+}
+
+/// df/dx
+pub fn df_dx(d: f64, x: &[f64], a: f64, gamma: f64, i: usize) -> f64 {
+    let mul_r: f64 = x
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != i)
+        .map(|(_, x)| x)
+        .product();
+    let sum: f64 = x.iter().sum();
+    let k0 = mul_r * x[i] * (N / d).powi(3);
+    let k = a * gamma.powi(2) * k0 / (gamma + 1.0 - k0).powi(2);
+    let k0_x = mul_r * (N / d).powi(3);
+    let k_x = a * gamma.powi(2) * (gamma + 1.0 + k0) / (gamma + 1.0 - k0).powi(3) * k0_x;
+
+    (k_x * sum + k) * d.powi(2) + mul_r - k_x * d.powi(3)
+}
+
+pub fn newton_y(xs: &[f64], a: f64, gamma: f64, d: f64, j: usize) -> f64 {
+    let mut x = xs.to_vec();
+    let mul_r: f64 = x
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != j)
+        .map(|(_, x)| x)
+        .product();
+    let x0 = (d / N).powi(3) / mul_r;
+    let mut xi_1 = x0;
+    x[j] = x0;
+
+    println!("Computing x[{j}]. First approximation {x0}");
+
+    let mut i = 0;
+    let mut diff = 1.0;
+    let mut xi = 0.0;
+
+    while diff > TOL && i < MAX_ITER {
+        xi = xi_1 - f(d, &x, a, gamma) / df_dx(d, &x, a, gamma, j);
+        x[j] = xi;
+
+        diff = (xi - xi_1).abs();
+        println!("{i}, {xi}, {xi_1}");
+        xi_1 = xi;
+        i += 1;
+    }
+
+    xi
+}
+
+pub fn newton_d(x: &[f64], a: f64, gamma: f64) -> f64 {
+    let d0 = N * x.iter().product::<f64>().cbrt();
+    println!("Computing D. First approximation {d0}");
+    let mut di_1 = d0;
+    let mut i = 0;
+    let mut diff = 1.0;
+    let mut di = 0.0;
+
+    while diff > TOL && i < MAX_ITER {
+        di = di_1 - f(di_1, x, a, gamma) / df_dd(di_1, x, a, gamma);
+        diff = (di - di_1).abs();
+        println!("{i}, {di}, {}", f(di, x, a, gamma));
+        di_1 = di;
+        i += 1;
+    }
+
+    di
+}
@@ -0,0 +1,49 @@
+use cosmwasm_std::{Decimal256, StdResult};
+
+use crate::consts::N;
+use crate::math::math_decimal::{geometric_mean, newton_d, newton_y};
+use crate::state::AmpGamma;
+
+mod math_decimal;
+#[cfg(test)]
+mod math_f64;
+mod signed_decimal;
+
+pub use math_decimal::half_float_pow;
+
+/// Calculate D invariant based on known pool volumes.
+///
+/// * **xs** - internal representation of pool volumes.
+/// * **amp_gamma** - an object which represents current Amp and Gamma parameters.
+pub fn calc_d(xs: &[Decimal256], amp_gamma: &AmpGamma) -> StdResult<Decimal256> {
+    newton_d(xs, amp_gamma.amp.into(), amp_gamma.gamma.into())
+}
+
+/// Calculate unknown pool's volume based on the other sides of pools which are known and D.
+///
+/// * **xs** - internal representation of pool volumes.
+/// * **d** - current D invariant.
+/// * **amp_gamma** - an object which represents current Amp and Gamma parameters.
+/// * **ask_ind** - the index of pool which is unknown.
+pub fn calc_y(
+    xs: &[Decimal256],
+    d: Decimal256,
+    amp_gamma: &AmpGamma,
+    ask_ind: usize,
+) -> StdResult<Decimal256> {
+    newton_y(xs, amp_gamma.amp.into(), amp_gamma.gamma.into(), d, ask_ind)
+}
+
+/// Get current XCP.
+/// * **d** - internal D invariant.
+/// * **price_scales** - prices of the 2nd and the 3rd assets denominated in the 1st asset.
+pub fn get_xcp(d: Decimal256, price_scales: &[Decimal256]) -> StdResult<Decimal256> {
+    let xs = std::iter::once(d / N)
+        .chain(
+            price_scales
+                .iter()
+                .map(|price_scale| d / (N * *price_scale)),
+        )
+        .collect::<Vec<_>>();
+    geometric_mean(&xs)
+}
@@ -0,0 +1,699 @@
+#![cfg(not(tarpaulin_include))]
+
+use cosmwasm_std::{Addr, Decimal, Decimal256};
+
+use astroport::asset::{native_asset_info, AssetInfoExt, MINIMUM_LIQUIDITY_AMOUNT};
+use astroport::factory::{ExecuteMsg as FactoryExecuteMsg, PairType, PauseTarget};
+use astroport::pair_concentrated::{ConcentratedPoolUpdateParams, UpdatePoolParams};
+use astroport::pair_tricrypto::TricryptoPoolParams;
+use astroport_mocks::cw_multi_test::Executor;
+use astroport_pair_tricrypto::consts::{AMP_MAX, AMP_MIN};
+use astroport_pair_tricrypto::error::ContractError;
+
+use crate::helper::{dec_to_f64, f64_to_dec, AppExtension, Helper, TestCoin};
+
+mod helper;
+
+fn common_pool_params() -> TricryptoPoolParams {
+    TricryptoPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scales: vec![Decimal::one(), Decimal::one()],
+        ma_half_time: 600,
+        track_asset_balances: None,
+    }
+}
+
+#[test]
+fn check_wrong_initialization() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::native("uluna"),
+        TestCoin::cw20("USDC"),
+        TestCoin::native("uatom"),
+    ];
+    let params = common_pool_params();
+
+    let err = Helper::new(
+        &owner,
+        vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")],
+        params.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidNumberOfAssets(3),
+        err.downcast().unwrap()
+    );
+
+    let mut wrong_params = params.clone();
+    wrong_params.price_scales = vec![Decimal::one()];
+    let err = Helper::new(&owner, test_coins.clone(), wrong_params).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: price_scales must contain exactly 2 elements"
+    );
+
+    let mut wrong_params = params.clone();
+    wrong_params.price_scales = vec![Decimal::one(), Decimal::zero()];
+    let err = Helper::new(&owner, test_coins.clone(), wrong_params).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Initial price scales can not be zero"
+    );
+
+    let mut wrong_params = params.clone();
+    wrong_params.amp = Decimal::zero();
+    let err = Helper::new(&owner, test_coins.clone(), wrong_params).unwrap_err();
+    assert_eq!(
+        ContractError::IncorrectPoolParam(
+            "amp".to_string(),
+            AMP_MIN.to_string(),
+            AMP_MAX.to_string()
+        ),
+        err.downcast().unwrap(),
+    );
+
+    // check instantiation with valid params
+    Helper::new(&owner, test_coins, params).unwrap();
+}
+
+#[test]
+fn provide_and_withdraw() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::native("uluna"),
+        TestCoin::cw20("USDC"),
+        TestCoin::native("uatom"),
+    ];
+
+    let mut params = common_pool_params();
+    // 1 USDC = 2 uluna, 1 uatom = 0.5 uluna
+    params.price_scales = vec![f64_to_dec(2.0), f64_to_dec(0.5)];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    // checking LP token virtual price on an empty pool
+    let lp_price = helper.query_lp_price().unwrap();
+    assert!(
+        lp_price.is_zero(),
+        "LP price must be zero before any provide"
+    );
+
+    let user1 = Addr::unchecked("user1");
+
+    let random_coin = native_asset_info("random-coin".to_string()).with_balance(100u8);
+    let err = helper
+        .provide_liquidity(&user1, &[random_coin])
+        .unwrap_err();
+    assert_eq!(
+        "The asset random-coin does not belong to the pair",
+        err.root_cause().to_string()
+    );
+
+    let err = helper.provide_liquidity(&user1, &[]).unwrap_err();
+    assert_eq!(
+        "Generic error: Nothing to provide",
+        err.root_cause().to_string()
+    );
+
+    // Initial provide must contain all assets
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000_000000u128),
+    ];
+    helper.give_me_money(&assets, &user1);
+    let err = helper.provide_liquidity(&user1, &assets).unwrap_err();
+    assert_eq!(ContractError::InvalidZeroAmount {}, err.downcast().unwrap());
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000_000000u128),
+        helper.assets[&test_coins[2]].with_balance(200_000_000000u128),
+    ];
+    helper.give_me_money(
+        &[helper.assets[&test_coins[2]].with_balance(200_000_000000u128)],
+        &user1,
+    );
+    helper.provide_liquidity(&user1, &assets).unwrap();
+
+    // The pool is balanced according to price scales thus D = 300_000 and xcp = 100_000
+    let d = helper.query_d().unwrap();
+    assert!((dec_to_f64(d) - 300_000f64).abs() < 1e-6, "D = {d}");
+    let user1_lp = helper.token_balance(&helper.lp_token, &user1);
+    assert!(
+        user1_lp.abs_diff(100_000_000000 - MINIMUM_LIQUIDITY_AMOUNT.u128()) <= 1,
+        "user1 LP = {user1_lp}"
+    );
+    assert_eq!(0, helper.coin_balance(&test_coins[0], &user1));
+    assert_eq!(0, helper.coin_balance(&test_coins[1], &user1));
+    assert_eq!(0, helper.coin_balance(&test_coins[2], &user1));
+
+    // Balanced provide in a different order of assets
+    let user2 = Addr::unchecked("user2");
+    let assets = vec![
+        helper.assets[&test_coins[2]].with_balance(200_000_000000u128),
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000_000000u128),
+    ];
+    helper.give_me_money(&assets, &user2);
+    helper.provide_liquidity(&user2, &assets).unwrap();
+    let user2_lp = helper.token_balance(&helper.lp_token, &user2);
+    assert!(
+        user2_lp.abs_diff(user1_lp + MINIMUM_LIQUIDITY_AMOUNT.u128()) <= 1,
+        "user2 LP = {user2_lp}"
+    );
+    // Balanced provide doesn't change price scales
+    assert_eq!(
+        helper.query_price_scales().unwrap(),
+        vec![f64_to_dec::<Decimal256>(2.0), f64_to_dec(0.5)]
+    );
+
+    // After initial provide one-sided provide is allowed
+    let user3 = Addr::unchecked("user3");
+    let assets = vec![helper.assets[&test_coins[1]].with_balance(10_000_000000u128)];
+    helper.give_me_money(&assets, &user3);
+    helper.provide_liquidity(&user3, &assets).unwrap();
+    // 10_000 USDC is worth 20_000 uluna i.e. 1/30 of the pool value which is 2/30 of user1 LP tokens.
+    // LP amount is less than for a balanced provide as it is charged with fees
+    let user3_lp = helper.token_balance(&helper.lp_token, &user3);
+    assert!(user3_lp < user1_lp / 15, "user3 LP = {user3_lp}");
+    assert!(user3_lp > user1_lp / 15 * 98 / 100, "user3 LP = {user3_lp}");
+
+    // user1 withdraws 1/10 of their LP tokens
+    helper
+        .withdraw_liquidity(&user1, user1_lp / 10, vec![])
+        .unwrap();
+    assert_eq!(
+        user1_lp - user1_lp / 10,
+        helper.token_balance(&helper.lp_token, &user1)
+    );
+    let share = helper.query_share(user1_lp / 10).unwrap();
+    for (ind, coin) in test_coins.iter().enumerate() {
+        assert!(
+            helper
+                .coin_balance(coin, &user1)
+                .abs_diff(share[ind].amount.u128())
+                <= 1,
+            "{coin:?}: withdrawn {} expected {}",
+            helper.coin_balance(coin, &user1),
+            share[ind].amount
+        );
+    }
+}
+
+#[test]
+fn check_imbalanced_withdraw() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::native("uluna"),
+        TestCoin::cw20("USDC"),
+        TestCoin::native("uatom"),
+    ];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pool_params()).unwrap();
+
+    let user1 = Addr::unchecked("user1");
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[2]].with_balance(100_000_000000u128),
+    ];
+    helper.give_me_money(&assets, &user1);
+    helper.provide_liquidity(&user1, &assets).unwrap();
+
+    let lp_amount = helper.token_balance(&helper.lp_token, &user1);
+
+    // Withdraw asset which does not belong to the pair
+    let random_coin = native_asset_info("random-coin".to_string()).with_balance(100u8);
+    let err = helper
+        .withdraw_liquidity(&user1, lp_amount / 10, vec![random_coin])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidAsset("random-coin".to_string()),
+        err.downcast().unwrap()
+    );
+
+    // Withdraw the same asset twice
+    let withdraw_assets = vec![
+        helper.assets[&test_coins[2]].with_balance(10_000_000000u128),
+        helper.assets[&test_coins[2]].with_balance(10_000_000000u128),
+    ];
+    let err = helper
+        .withdraw_liquidity(&user1, lp_amount / 10, withdraw_assets)
+        .unwrap_err();
+    assert_eq!(ContractError::DoublingAssets {}, err.downcast().unwrap());
+
+    // Single-sided withdraw of the 3rd asset. Unused LP tokens are sent back
+    let withdraw_assets = vec![helper.assets[&test_coins[2]].with_balance(15_000_000000u128)];
+    helper
+        .withdraw_liquidity(&user1, lp_amount / 2, withdraw_assets)
+        .unwrap();
+    assert_eq!(0, helper.coin_balance(&test_coins[0], &user1));
+    assert_eq!(0, helper.coin_balance(&test_coins[1], &user1));
+    assert_eq!(15_000_000000, helper.coin_balance(&test_coins[2], &user1));
+
+    // 15_000 uatom is 1/20 of the pool value. Imbalanced withdraw is charged with fees
+    // thus a bit more LP tokens are burned compared to the balanced withdraw
+    let burned_amount = lp_amount - helper.token_balance(&helper.lp_token, &user1);
+    assert!(burned_amount > lp_amount / 20);
+    assert!(burned_amount < lp_amount / 20 * 101 / 100);
+
+    // Two-sided withdraw
+    let withdraw_assets = vec![
+        helper.assets[&test_coins[0]].with_balance(5_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(5_000_000000u128),
+    ];
+    let lp_amount = helper.token_balance(&helper.lp_token, &user1);
+    helper
+        .withdraw_liquidity(&user1, lp_amount / 2, withdraw_assets)
+        .unwrap();
+    assert_eq!(5_000_000000, helper.coin_balance(&test_coins[0], &user1));
+    assert_eq!(5_000_000000, helper.coin_balance(&test_coins[1], &user1));
+
+    // Balanced withdraw of the rest still works
+    let lp_amount = helper.token_balance(&helper.lp_token, &user1);
+    helper
+        .withdraw_liquidity(&user1, lp_amount, vec![])
+        .unwrap();
+    assert_eq!(0, helper.token_balance(&helper.lp_token, &user1));
+}
+
+#[test]
+fn check_swaps_between_all_assets() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::native("uluna"),
+        TestCoin::cw20("USDC"),
+        TestCoin::native("uatom"),
+    ];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pool_params()).unwrap();
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+
+    // Check swap does not work if pool is empty
+    let err = helper
+        .swap(
+            &user,
+            &offer_asset,
+            &helper.assets[&test_coins[1]].clone(),
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: One of the pools is empty"
+    );
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[2]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    // Ask asset must be different from the offer asset
+    let err = helper
+        .swap(
+            &user,
+            &offer_asset,
+            &helper.assets[&test_coins[0]].clone(),
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::SameAssets {}, err.downcast().unwrap());
+
+    // Ask asset must belong to the pair
+    let wrong_coin = native_asset_info("random-coin".to_string());
+    let err = helper
+        .swap(&user, &offer_asset, &wrong_coin, None)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidAsset(wrong_coin.to_string()),
+        err.downcast().unwrap()
+    );
+
+    // Ask asset must be specified in simulations
+    let err = helper.simulate_swap(&offer_asset, None).unwrap_err();
+    assert!(err.to_string().contains("Ask or offer asset is missed"));
+    let err = helper
+        .simulate_reverse_swap(&helper.assets[&test_coins[1]].with_balance(100u8), None)
+        .unwrap_err();
+    assert!(err.to_string().contains("Ask or offer asset is missed"));
+
+    for (offer_ind, ask_ind) in [(0, 1), (1, 2), (2, 0), (1, 0), (2, 1), (0, 2)] {
+        let offer_asset = helper.assets[&test_coins[offer_ind]].with_balance(100_000000u128);
+        let ask_asset_info = helper.assets[&test_coins[ask_ind]].clone();
+        let sim_resp = helper
+            .simulate_swap(&offer_asset, Some(ask_asset_info.clone()))
+            .unwrap();
+
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        let ask_balance_before = helper.coin_balance(&test_coins[ask_ind], &user);
+        helper
+            .swap(&user, &offer_asset, &ask_asset_info, None)
+            .unwrap();
+        let received = helper.coin_balance(&test_coins[ask_ind], &user) - ask_balance_before;
+
+        assert_eq!(
+            sim_resp.return_amount.u128(),
+            received,
+            "{offer_ind} -> {ask_ind}"
+        );
+        // The pool is balanced and the swap is small thus only fees are charged
+        assert!(
+            received > 99_600000 && received < 100_000000,
+            "{offer_ind} -> {ask_ind}: received {received}"
+        );
+
+        helper.app.next_block(100);
+    }
+
+    // Large swap exceeds default max spread
+    let offer_asset = helper.assets[&test_coins[2]].with_balance(90_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    let err = helper
+        .swap(
+            &user,
+            &offer_asset,
+            &helper.assets[&test_coins[1]].clone(),
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::MaxSpreadAssertion {},
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn check_reverse_swap() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::cw20("FOO"),
+        TestCoin::cw20("BAR"),
+        TestCoin::cw20("BAZ"),
+    ];
+
+    let mut params = common_pool_params();
+    params.price_scales = vec![f64_to_dec(2.0), f64_to_dec(0.5)];
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000_000000u128),
+        helper.assets[&test_coins[2]].with_balance(200_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(100_000000u128);
+    let ask_asset_info = helper.assets[&test_coins[2]].clone();
+
+    let sim_resp = helper
+        .simulate_swap(&offer_asset, Some(ask_asset_info.clone()))
+        .unwrap();
+    // 1 BAR = 4 BAZ
+    assert!(sim_resp.return_amount.u128() < 400_000000);
+    assert!(sim_resp.return_amount.u128() > 396_000000);
+
+    let reverse_sim_resp = helper
+        .simulate_reverse_swap(
+            &ask_asset_info.with_balance(sim_resp.return_amount.u128()),
+            Some(offer_asset.info.clone()),
+        )
+        .unwrap();
+    // As it is hard to predict dynamic fees reverse swap is not exact
+    let offer_diff = reverse_sim_resp
+        .offer_amount
+        .u128()
+        .abs_diff(offer_asset.amount.u128());
+    assert!(
+        offer_diff < 1_000000,
+        "reverse simulation offer amount {} differs too much from {}",
+        reverse_sim_resp.offer_amount,
+        offer_asset.amount
+    );
+}
+
+#[test]
+fn check_swaps_with_price_update() {
+    let owner = Addr::unchecked("owner");
+    let half = Decimal::from_ratio(1u8, 2u8);
+
+    let test_coins = vec![
+        TestCoin::native("uluna"),
+        TestCoin::cw20("USDC"),
+        TestCoin::native("uatom"),
+    ];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pool_params()).unwrap();
+
+    helper.app.next_block(1000);
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[2]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    helper.app.next_block(1000);
+
+    let initial_price_scales = helper.query_price_scales().unwrap();
+
+    // Buying USDC for uluna makes USDC more expensive
+    let user1 = Addr::unchecked("user1");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(5_000_000000u128);
+    let ask_asset_info = helper.assets[&test_coins[1]].clone();
+    let mut prev_vlp_price = helper.query_lp_price().unwrap();
+
+    for i in 0..8 {
+        helper.give_me_money(&[offer_asset.clone()], &user1);
+        helper
+            .swap(&user1, &offer_asset, &ask_asset_info, Some(half))
+            .unwrap();
+        let new_vlp_price = helper.query_lp_price().unwrap();
+        assert!(
+            new_vlp_price >= prev_vlp_price,
+            "{i}: new_vlp_price <= prev_vlp_price ({new_vlp_price} <= {prev_vlp_price})",
+        );
+        prev_vlp_price = new_vlp_price;
+        helper.app.next_block(1000);
+    }
+
+    let price_scales = helper.query_price_scales().unwrap();
+    assert!(
+        price_scales[0] > initial_price_scales[0],
+        "USDC price scale didn't grow: {price_scales:?}"
+    );
+
+    // Selling uatom for uluna makes uatom cheaper
+    let offer_asset = helper.assets[&test_coins[2]].with_balance(5_000_000000u128);
+    let ask_asset_info = helper.assets[&test_coins[0]].clone();
+    for _i in 0..8 {
+        helper.give_me_money(&[offer_asset.clone()], &user1);
+        helper
+            .swap(&user1, &offer_asset, &ask_asset_info, Some(half))
+            .unwrap();
+        helper.app.next_block(1000);
+    }
+
+    let price_scales = helper.query_price_scales().unwrap();
+    assert!(
+        price_scales[1] < initial_price_scales[1],
+        "uatom price scale didn't decline: {price_scales:?}"
+    );
+
+    // Swaps between the 2nd and the 3rd assets keep working after repegging
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(1_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user1);
+    helper
+        .swap(
+            &user1,
+            &offer_asset,
+            &helper.assets[&test_coins[2]].clone(),
+            Some(half),
+        )
+        .unwrap();
+}
+
+#[test]
+fn check_observe_queries() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::native("uluna"),
+        TestCoin::cw20("USDC"),
+        TestCoin::native("uatom"),
+    ];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pool_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[2]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let uluna = helper.assets[&test_coins[0]].clone();
+    let usdc = helper.assets[&test_coins[1]].clone();
+    let uatom = helper.assets[&test_coins[2]].clone();
+
+    let user = Addr::unchecked("user");
+    let offer_asset = uatom.with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, &usdc, None).unwrap();
+    let usdc_amount = helper.coin_balance(&test_coins[1], &user);
+
+    helper.app.next_block(1000);
+
+    // Observations for USDC/uatom pair were stored
+    let price = helper.observe_pair_price(&usdc, &uatom, 0).unwrap();
+    assert_eq!(price, Decimal::from_ratio(usdc_amount, 100_000000u128));
+    // Price is inverted for the reversed pair
+    let inv_price = helper.observe_pair_price(&uatom, &usdc, 0).unwrap();
+    assert!((dec_to_f64(price * inv_price) - 1.0).abs() < 1e-6);
+
+    // No swaps between uluna and USDC yet
+    let err = helper.observe_price(0).unwrap_err();
+    assert!(err.to_string().contains("Buffer is empty"));
+
+    let err = helper.observe_pair_price(&uluna, &uluna, 0).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Source and target assets are the same"));
+
+    let random_coin = native_asset_info("random-coin".to_string());
+    let err = helper
+        .observe_pair_price(&uluna, &random_coin, 0)
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("The asset random-coin does not belong to the pair"));
+
+    let offer_asset = usdc.with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, &uluna, None).unwrap();
+    let uluna_amount = helper.coin_balance(&test_coins[0], &user);
+
+    helper.app.next_block(1000);
+
+    // Observe query returns the price of the first two assets
+    let price = helper.observe_price(0).unwrap();
+    assert_eq!(price, Decimal::from_ratio(uluna_amount, 100_000000u128));
+    assert_eq!(price, helper.observe_pair_price(&uluna, &usdc, 0).unwrap());
+}
+
+#[test]
+fn update_params() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::native("uluna"),
+        TestCoin::cw20("USDC"),
+        TestCoin::native("uatom"),
+    ];
+
+    let mut helper = Helper::new(&owner, test_coins, common_pool_params()).unwrap();
+
+    let action = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: Some(f64_to_dec(0.002)),
+        out_fee: None,
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+    });
+
+    let err = helper
+        .update_config(&Addr::unchecked("random"), &action)
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    helper.update_config(&owner, &action).unwrap();
+
+    let config = helper.query_config().unwrap();
+    assert_eq!(config.pool_params.mid_fee, f64_to_dec::<Decimal>(0.002));
+    assert_eq!(
+        config.pool_state.price_state.price_scales,
+        vec![Decimal256::one(), Decimal256::one()]
+    );
+    assert!(!config.track_asset_balances);
+}
+
+#[test]
+fn check_paused_pair() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::native("uluna"),
+        TestCoin::cw20("USDC"),
+        TestCoin::native("uatom"),
+    ];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pool_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[2]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let target = PauseTarget::PairType(PairType::Custom("tricrypto".to_string()));
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &FactoryExecuteMsg::Pause {
+                target: target.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let err = helper.provide_liquidity(&owner, &assets).unwrap_err();
+    assert_eq!(ContractError::PairPaused {}, err.downcast().unwrap());
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    let ask_asset_info = helper.assets[&test_coins[2]].clone();
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    let err = helper
+        .swap(&user, &offer_asset, &ask_asset_info, None)
+        .unwrap_err();
+    assert_eq!(ContractError::PairPaused {}, err.downcast().unwrap());
+
+    // Liquidity can still be withdrawn from a paused pair
+    helper
+        .withdraw_liquidity(&owner, 1000_000000, vec![])
+        .unwrap();
+
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &FactoryExecuteMsg::Unpause { target },
+            &[],
+        )
+        .unwrap();
+
+    helper
+        .swap(&user, &offer_asset, &ask_asset_info, None)
+        .unwrap();
+}
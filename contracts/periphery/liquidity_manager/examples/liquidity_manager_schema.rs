@@ -1,11 +1,12 @@
 use cosmwasm_schema::write_api;
 
-use astroport::liquidity_manager::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use astroport::liquidity_manager::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         query: QueryMsg,
         execute: ExecuteMsg,
+        migrate: MigrateMsg,
     }
 }
@@ -1,24 +1,40 @@
+use std::str::FromStr;
+
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    from_binary, to_binary, wasm_execute, Addr, DepsMut, Env, MessageInfo, Reply, ReplyOn,
-    Response, StdError, StdResult, SubMsg, Uint128,
+    coin, from_binary, to_binary, wasm_execute, Addr, Api, CosmosMsg, Decimal, DepsMut, Env,
+    MessageInfo, QuerierWrapper, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128,
 };
+use cw2::{set_contract_version, CONTRACT};
 use cw20::{Cw20ExecuteMsg, Expiration};
 
 use astroport::asset::{addr_opt_validate, Asset, AssetInfo, AssetInfoExt, PairInfo};
+use astroport::common::is_deadline_passed;
 use astroport::factory::PairType;
-use astroport::liquidity_manager::{Cw20HookMsg, ExecuteMsg, InstantiateMsg};
+use astroport::generator::ExecuteMsg as GeneratorExecuteMsg;
+use astroport::liquidity_manager::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, RouterPath,
+};
 use astroport::pair::{
     Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg, QueryMsg as PairQueryMsg,
-    QueryMsg,
+    QueryMsg, MAX_ALLOWED_SLIPPAGE,
 };
-use astroport::querier::query_supply;
+use astroport::querier::{query_pair_fee_info, query_supply};
+use astroport::router::{Cw20HookMsg as RouterCw20HookMsg, ExecuteMsg as RouterExecuteMsg};
 use astroport_pair::contract::get_share_in_assets;
 
 use crate::error::ContractError;
 use crate::state::{ActionParams, Config, ReplyData, CONFIG, REPLY_DATA};
-use crate::utils::{query_cw20_minter, query_lp_amount, xyk_provide_simulation};
+use crate::utils::{
+    query_cw20_minter, query_generator, query_lp_amount, simulated_zap_swap_amount,
+    xyk_deposits_share, xyk_provide_simulation, xyk_zap_swap_amount,
+};
+
+/// Contract name that is used for migration.
+const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+/// Contract version that is used for migration.
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -27,6 +43,8 @@ pub fn instantiate(
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     CONFIG.save(
         deps.storage,
         &Config {
@@ -55,17 +73,73 @@ pub fn execute(
             let pair_addr = deps.api.addr_validate(&pair_addr)?;
             provide_liquidity(deps, info, env, pair_addr, msg, min_lp_to_receive)
         }
+        ExecuteMsg::ZapIn {
+            pair_addr,
+            offer_asset,
+            max_spread,
+            min_lp_to_receive,
+            auto_stake,
+            receiver,
+            deadline,
+        } => {
+            let pair_addr = deps.api.addr_validate(&pair_addr)?;
+            zap_in(
+                deps,
+                env,
+                info,
+                pair_addr,
+                offer_asset,
+                max_spread,
+                min_lp_to_receive,
+                auto_stake.unwrap_or(false),
+                receiver,
+                deadline,
+            )
+        }
+        ExecuteMsg::MigrateLiquidity {
+            from_pair,
+            to_pair,
+            amount,
+            staked,
+            min_lp_out,
+            auto_stake,
+            receiver,
+            deadline,
+        } => {
+            let from_pair = deps.api.addr_validate(&from_pair)?;
+            let to_pair = deps.api.addr_validate(&to_pair)?;
+            migrate_liquidity(
+                deps,
+                env,
+                info,
+                from_pair,
+                to_pair,
+                amount,
+                staked.unwrap_or(false),
+                min_lp_out,
+                auto_stake.unwrap_or(true),
+                receiver,
+                deadline,
+            )
+        }
         ExecuteMsg::Receive(cw20_msg) => match from_binary(&cw20_msg.msg)? {
             Cw20HookMsg::WithdrawLiquidity {
                 pair_msg: msg,
                 min_assets_to_receive,
+                target_asset,
+                min_receive,
+                router_path,
             } if matches!(&msg, PairCw20HookMsg::WithdrawLiquidity { .. }) => withdraw_liquidity(
                 deps,
+                env,
                 info.sender,
                 Addr::unchecked(cw20_msg.sender),
                 cw20_msg.amount,
                 msg,
                 min_assets_to_receive,
+                target_asset,
+                min_receive,
+                router_path,
             ),
             _ => Err(ContractError::UnsupportedCw20HookMsg {}),
         },
@@ -74,6 +148,9 @@ pub fn execute(
 
 const WITHDRAW_LIQUIDITY_REPLY_ID: u64 = 1;
 const PROVIDE_LIQUIDITY_REPLY_ID: u64 = 2;
+const ZAP_IN_REPLY_ID: u64 = 3;
+const ZAP_OUT_REPLY_ID: u64 = 4;
+const MIGRATE_LIQUIDITY_REPLY_ID: u64 = 5;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
@@ -83,15 +160,11 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
                 receiver,
                 params:
                     ActionParams::Withdraw {
-                        pair_addr,
                         min_assets_to_receive,
+                        balances_before,
                     },
             } => {
-                let pair_info: PairInfo = deps
-                    .querier
-                    .query_wasm_smart(pair_addr, &QueryMsg::Pair {})?;
-                let mut withdrawn_assets =
-                    pair_info.query_pools(&deps.querier, env.contract.address)?;
+                let mut withdrawn_assets = received_assets(deps.querier, &env, &balances_before)?;
                 if withdrawn_assets[0].info.ne(&min_assets_to_receive[0].info) {
                     withdrawn_assets.swap(0, 1);
                 }
@@ -116,6 +189,64 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
                     .add_messages(messages)
                     .add_attribute("liquidity_manager", "withdraw_check_passed"))
             }
+            ReplyData {
+                receiver,
+                params:
+                    ActionParams::ZapOut {
+                        pair_addr,
+                        target_asset,
+                        min_receive,
+                        router_path,
+                        balances_before,
+                    },
+            } => {
+                let other_asset = received_assets(deps.querier, &env, &balances_before)?
+                    .into_iter()
+                    .find(|asset| asset.info.ne(&target_asset))
+                    .ok_or_else(|| ContractError::AssetNotInPair(target_asset.to_string()))?;
+
+                if other_asset.amount.is_zero() {
+                    return finish_zap_out(
+                        deps,
+                        env,
+                        receiver,
+                        target_asset,
+                        min_receive,
+                        &balances_before,
+                    );
+                }
+
+                // The resulting amount is checked against `min_receive` after the swap
+                let max_spread = Some(Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?);
+                let swap = match router_path {
+                    Some(router_path) => router_swap_msg(&router_path, other_asset, max_spread)?,
+                    None => swap_msg(&pair_addr, other_asset, &target_asset, max_spread)?,
+                };
+
+                Ok(Response::new()
+                    .add_submessage(SubMsg::reply_on_success(swap, ZAP_OUT_REPLY_ID))
+                    .add_attribute("liquidity_manager", "zap_out_withdrawn"))
+            }
+            _ => Err(ContractError::InvalidReplyData {}),
+        },
+        ZAP_OUT_REPLY_ID => match REPLY_DATA.load(deps.storage)? {
+            ReplyData {
+                receiver,
+                params:
+                    ActionParams::ZapOut {
+                        target_asset,
+                        min_receive,
+                        balances_before,
+                        ..
+                    },
+            } => finish_zap_out(
+                deps,
+                env,
+                receiver,
+                target_asset,
+                min_receive,
+                &balances_before,
+            ),
             _ => Err(ContractError::InvalidReplyData {}),
         },
         PROVIDE_LIQUIDITY_REPLY_ID => match REPLY_DATA.load(deps.storage)? {
@@ -150,6 +281,80 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
             }
             _ => Err(ContractError::InvalidReplyData {}),
         },
+        ZAP_IN_REPLY_ID => match REPLY_DATA.load(deps.storage)? {
+            ReplyData {
+                receiver,
+                params:
+                    ActionParams::ZapIn {
+                        pair_addr,
+                        offer_asset,
+                        ask_asset_info,
+                        ask_balance_before,
+                        auto_stake,
+                        min_lp_to_receive,
+                        sender,
+                    },
+            } => {
+                let ask_balance_after =
+                    ask_asset_info.query_pool(&deps.querier, &env.contract.address)?;
+                let assets = vec![
+                    offer_asset,
+                    ask_asset_info.with_balance(ask_balance_after - ask_balance_before),
+                ];
+
+                let pair_info: PairInfo = deps
+                    .querier
+                    .query_wasm_smart(&pair_addr, &PairQueryMsg::Pair {})?;
+                let submessages = provide_from_contract(
+                    deps,
+                    &env,
+                    &pair_info,
+                    assets,
+                    &sender,
+                    &Addr::unchecked(receiver),
+                    auto_stake,
+                    min_lp_to_receive,
+                )?;
+
+                Ok(Response::new()
+                    .add_submessages(submessages)
+                    .add_attribute("liquidity_manager", "zap_in_swap_done"))
+            }
+            _ => Err(ContractError::InvalidReplyData {}),
+        },
+        MIGRATE_LIQUIDITY_REPLY_ID => match REPLY_DATA.load(deps.storage)? {
+            ReplyData {
+                receiver,
+                params:
+                    ActionParams::MigrateLiquidity {
+                        to_pair,
+                        balances_before,
+                        auto_stake,
+                        min_lp_out,
+                        sender,
+                    },
+            } => {
+                let pair_info: PairInfo = deps
+                    .querier
+                    .query_wasm_smart(&to_pair, &PairQueryMsg::Pair {})?;
+                let withdrawn_assets = received_assets(deps.querier, &env, &balances_before)?;
+                let submessages = provide_from_contract(
+                    deps,
+                    &env,
+                    &pair_info,
+                    withdrawn_assets,
+                    &sender,
+                    &Addr::unchecked(receiver),
+                    auto_stake,
+                    min_lp_out,
+                )?;
+
+                Ok(Response::new()
+                    .add_submessages(submessages)
+                    .add_attribute("liquidity_manager", "migrate_withdrawn"))
+            }
+            _ => Err(ContractError::InvalidReplyData {}),
+        },
         _ => Err(StdError::generic_err(format!("Unsupported reply id {}", msg.id)).into()),
     }
 }
@@ -167,8 +372,13 @@ fn provide_liquidity(
         slippage_tolerance,
         auto_stake,
         receiver,
+        deadline,
     } = exec_msg
     {
+        if is_deadline_passed(&env.block, deadline) {
+            return Err(ContractError::DeadlineExpired {});
+        }
+
         if assets.len() != 2 {
             return Err(ContractError::WrongPoolLength {});
         }
@@ -294,6 +504,7 @@ fn provide_liquidity(
             slippage_tolerance,
             auto_stake,
             receiver: Some(receiver.to_string()),
+            deadline,
         };
         let mut provide_msg = SubMsg::new(wasm_execute(&pair_addr, &tweaked_exec_msg, funds)?);
 
@@ -336,14 +547,376 @@ fn provide_liquidity(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+fn zap_in(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pair_addr: Addr,
+    offer_asset: Asset,
+    max_spread: Option<Decimal>,
+    min_lp_to_receive: Option<Uint128>,
+    auto_stake: bool,
+    receiver: Option<String>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    if is_deadline_passed(&env.block, deadline) {
+        return Err(ContractError::DeadlineExpired {});
+    }
+
+    if offer_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let pair_info: PairInfo = deps
+        .querier
+        .query_wasm_smart(&pair_addr, &PairQueryMsg::Pair {})?;
+    if pair_info.asset_infos.len() != 2 {
+        return Err(ContractError::WrongPoolLength {});
+    }
+    if !pair_info.asset_infos.contains(&offer_asset.info) {
+        return Err(ContractError::AssetNotInPair(offer_asset.info.to_string()));
+    }
+
+    let mut messages = vec![];
+    match &offer_asset.info {
+        AssetInfo::NativeToken { .. } => offer_asset.assert_sent_native_token_balance(&info)?,
+        AssetInfo::Token { contract_addr } => messages.push(SubMsg::new(wasm_execute(
+            contract_addr,
+            &Cw20ExecuteMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: offer_asset.amount,
+            },
+            vec![],
+        )?)),
+    }
+
+    let mut pools = pair_info.query_pools(&deps.querier, &pair_addr)?;
+    if pools[0].info.ne(&offer_asset.info) {
+        pools.swap(0, 1);
+    }
+    if pools[0].amount.is_zero() || pools[1].amount.is_zero() {
+        return Err(ContractError::EmptyPool {});
+    }
+    let ask_asset_info = pools[1].info.clone();
+
+    let swap_amount = match &pair_info.pair_type {
+        PairType::Xyk {} => {
+            let factory_addr = CONFIG.load(deps.storage)?.factory_addr;
+            let fee_info = query_pair_fee_info(&deps.querier, factory_addr, &pair_info)?;
+            xyk_zap_swap_amount(
+                pools[0].amount,
+                offer_asset.amount,
+                fee_info.total_fee_rate,
+                fee_info.maker_fee_rate,
+            )?
+        }
+        _ => simulated_zap_swap_amount(
+            deps.querier,
+            &pair_addr,
+            &offer_asset,
+            pools[0].amount,
+            &ask_asset_info,
+            pools[1].amount,
+        )?,
+    };
+
+    let swap = swap_msg(
+        &pair_addr,
+        offer_asset.info.with_balance(swap_amount),
+        &ask_asset_info,
+        max_spread,
+    )?;
+    messages.push(SubMsg::reply_on_success(swap, ZAP_IN_REPLY_ID));
+
+    let receiver = addr_opt_validate(deps.api, &receiver)?.unwrap_or_else(|| info.sender.clone());
+    REPLY_DATA.save(
+        deps.storage,
+        &ReplyData {
+            receiver: receiver.to_string(),
+            params: ActionParams::ZapIn {
+                pair_addr,
+                offer_asset: offer_asset
+                    .info
+                    .with_balance(offer_asset.amount - swap_amount),
+                ask_balance_before: ask_asset_info
+                    .query_pool(&deps.querier, &env.contract.address)?,
+                ask_asset_info,
+                auto_stake,
+                min_lp_to_receive,
+                sender: info.sender,
+            },
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attribute("contract", "liquidity_manager")
+        .add_attribute("action", "zap_in")
+        .add_attribute("swap_amount", swap_amount))
+}
+
+/// Creates a message swapping `offer_asset` through the pair. The swap output is sent to the sender (this contract).
+fn swap_msg(
+    pair_addr: &Addr,
+    offer_asset: Asset,
+    ask_asset_info: &AssetInfo,
+    max_spread: Option<Decimal>,
+) -> StdResult<CosmosMsg> {
+    match &offer_asset.info {
+        AssetInfo::Token { contract_addr } => Ok(wasm_execute(
+            contract_addr,
+            &Cw20ExecuteMsg::Send {
+                contract: pair_addr.to_string(),
+                amount: offer_asset.amount,
+                msg: to_binary(&PairCw20HookMsg::Swap {
+                    ask_asset_info: Some(ask_asset_info.clone()),
+                    belief_price: None,
+                    max_spread,
+                    to: None,
+                    deadline: None,
+                })?,
+            },
+            vec![],
+        )?
+        .into()),
+        AssetInfo::NativeToken { denom } => {
+            let funds = vec![coin(offer_asset.amount.u128(), denom)];
+            Ok(wasm_execute(
+                pair_addr,
+                &PairExecuteMsg::Swap {
+                    offer_asset,
+                    ask_asset_info: Some(ask_asset_info.clone()),
+                    belief_price: None,
+                    max_spread,
+                    to: None,
+                    deadline: None,
+                },
+                funds,
+            )?
+            .into())
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn migrate_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from_pair: Addr,
+    to_pair: Addr,
+    amount: Uint128,
+    staked: bool,
+    min_lp_out: Option<Uint128>,
+    auto_stake: bool,
+    receiver: Option<String>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    if is_deadline_passed(&env.block, deadline) {
+        return Err(ContractError::DeadlineExpired {});
+    }
+
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let from_pair_info: PairInfo = deps
+        .querier
+        .query_wasm_smart(&from_pair, &PairQueryMsg::Pair {})?;
+    let to_pair_info: PairInfo = deps
+        .querier
+        .query_wasm_smart(&to_pair, &PairQueryMsg::Pair {})?;
+    if to_pair_info.asset_infos.len() != 2 {
+        return Err(ContractError::WrongPoolLength {});
+    }
+    if from_pair == to_pair
+        || from_pair_info.asset_infos.len() != to_pair_info.asset_infos.len()
+        || from_pair_info
+            .asset_infos
+            .iter()
+            .any(|asset_info| !to_pair_info.asset_infos.contains(asset_info))
+    {
+        return Err(ContractError::InvalidMigrationPair {});
+    }
+
+    let transfer_msg = if staked {
+        let factory_addr = CONFIG.load(deps.storage)?.factory_addr;
+        wasm_execute(
+            query_generator(deps.querier, factory_addr)?,
+            &GeneratorExecuteMsg::WithdrawFrom {
+                lp_token: from_pair_info.liquidity_token.to_string(),
+                owner: info.sender.to_string(),
+                amount,
+            },
+            vec![],
+        )?
+    } else {
+        wasm_execute(
+            &from_pair_info.liquidity_token,
+            &Cw20ExecuteMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount,
+            },
+            vec![],
+        )?
+    };
+    let withdraw_msg = wasm_execute(
+        &from_pair_info.liquidity_token,
+        &Cw20ExecuteMsg::Send {
+            contract: from_pair.to_string(),
+            amount,
+            msg: to_binary(&PairCw20HookMsg::WithdrawLiquidity {
+                assets: vec![],
+                deadline: None,
+            })?,
+        },
+        vec![],
+    )?;
+
+    let receiver = addr_opt_validate(deps.api, &receiver)?.unwrap_or_else(|| info.sender.clone());
+    REPLY_DATA.save(
+        deps.storage,
+        &ReplyData {
+            receiver: receiver.to_string(),
+            params: ActionParams::MigrateLiquidity {
+                to_pair,
+                balances_before: from_pair_info
+                    .query_pools(&deps.querier, &env.contract.address)?,
+                auto_stake,
+                min_lp_out,
+                sender: info.sender,
+            },
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_message(transfer_msg)
+        .add_submessage(SubMsg::reply_on_success(
+            withdraw_msg,
+            MIGRATE_LIQUIDITY_REPLY_ID,
+        ))
+        .add_attribute("contract", "liquidity_manager")
+        .add_attribute("action", "migrate_liquidity"))
+}
+
+/// Provides the assets held by this contract to the pair on behalf of `receiver`.
+/// Assets which the XYK pair would keep because of its minting policy are sent back to `sender`.
+#[allow(clippy::too_many_arguments)]
+fn provide_from_contract(
+    deps: DepsMut,
+    env: &Env,
+    pair_info: &PairInfo,
+    assets: Vec<Asset>,
+    sender: &Addr,
+    receiver: &Addr,
+    auto_stake: bool,
+    min_lp_to_receive: Option<Uint128>,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let deposits = match &pair_info.pair_type {
+        PairType::Xyk {} => xyk_deposits_share(deps.querier, pair_info, None, &assets)?,
+        _ => assets.clone(),
+    };
+
+    let mut submessages = vec![];
+    let mut funds = vec![];
+    for (asset, deposit) in assets.iter().zip(deposits.iter()) {
+        let excess = asset.amount - deposit.amount;
+        if !excess.is_zero() {
+            submessages.push(SubMsg::new(
+                asset.info.with_balance(excess).into_msg(sender)?,
+            ));
+        }
+
+        if deposit.amount.is_zero() {
+            continue;
+        }
+        match &deposit.info {
+            AssetInfo::Token { contract_addr } => {
+                submessages.push(SubMsg::new(wasm_execute(
+                    contract_addr,
+                    &Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: pair_info.contract_addr.to_string(),
+                        amount: deposit.amount,
+                        expires: Some(Expiration::AtHeight(env.block.height + 1)),
+                    },
+                    vec![],
+                )?));
+            }
+            AssetInfo::NativeToken { denom } => {
+                funds.push(coin(deposit.amount.u128(), denom));
+            }
+        }
+    }
+    // Native coins must be sorted by denom
+    funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+    let provide_msg = wasm_execute(
+        &pair_info.contract_addr,
+        &PairExecuteMsg::ProvideLiquidity {
+            assets: deposits,
+            slippage_tolerance: None,
+            auto_stake: Some(auto_stake),
+            receiver: Some(receiver.to_string()),
+            deadline: None,
+        },
+        funds,
+    )?;
+
+    if let Some(min_lp_to_receive) = min_lp_to_receive {
+        let config = CONFIG.load(deps.storage)?;
+        let lp_amount_before = query_lp_amount(
+            deps.querier,
+            pair_info.liquidity_token.to_string(),
+            config.factory_addr,
+            auto_stake,
+            &receiver.to_string(),
+        )?;
+
+        REPLY_DATA.save(
+            deps.storage,
+            &ReplyData {
+                receiver: receiver.to_string(),
+                params: ActionParams::Provide {
+                    lp_token_addr: pair_info.liquidity_token.to_string(),
+                    lp_amount_before,
+                    min_lp_to_receive,
+                    staked_in_generator: auto_stake,
+                },
+            },
+        )?;
+        submessages.push(SubMsg::reply_on_success(
+            provide_msg,
+            PROVIDE_LIQUIDITY_REPLY_ID,
+        ));
+    } else {
+        submessages.push(SubMsg::new(provide_msg));
+    }
+
+    Ok(submessages)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn withdraw_liquidity(
     deps: DepsMut,
+    env: Env,
     lp_token_addr: Addr,
     receiver: Addr,
     amount: Uint128,
     inner_msg: PairCw20HookMsg,
     min_assets_to_receive: Vec<Asset>,
+    target_asset: Option<AssetInfo>,
+    min_receive: Option<Uint128>,
+    router_path: Option<RouterPath>,
 ) -> Result<Response, ContractError> {
+    if let PairCw20HookMsg::WithdrawLiquidity { deadline, .. } = &inner_msg {
+        if is_deadline_passed(&env.block, *deadline) {
+            return Err(ContractError::DeadlineExpired {});
+        }
+    }
+
     let pair_addr = query_cw20_minter(deps.querier, lp_token_addr.clone())?;
     let pair_info: PairInfo = deps
         .querier
@@ -353,18 +926,53 @@ fn withdraw_liquidity(
         return Err(ContractError::WrongPoolLength {});
     }
 
-    if pair_info.asset_infos.len() != min_assets_to_receive.len() {
-        return Err(ContractError::WrongAssetLength {
-            expected: pair_info.asset_infos.len(),
-            actual: min_assets_to_receive.len(),
-        });
-    }
-    // Check `min_assets_to_receive` belong to the pair
-    for asset in &min_assets_to_receive {
-        if !pair_info.asset_infos.contains(&asset.info) {
-            return Err(ContractError::AssetNotInPair(asset.info.to_string()));
+    let balances_before = pair_info.query_pools(&deps.querier, &env.contract.address)?;
+    let params = if let Some(target_asset) = target_asset {
+        // Zap-out is protected by `min_receive` only
+        if !min_assets_to_receive.is_empty() {
+            return Err(ContractError::WrongAssetLength {
+                expected: 0,
+                actual: min_assets_to_receive.len(),
+            });
+        }
+        if !pair_info.asset_infos.contains(&target_asset) {
+            return Err(ContractError::AssetNotInPair(target_asset.to_string()));
+        }
+        if let Some(router_path) = &router_path {
+            let other_asset = pair_info
+                .asset_infos
+                .iter()
+                .find(|asset_info| **asset_info != target_asset)
+                .unwrap();
+            validate_router_path(deps.api, router_path, other_asset, &target_asset)?;
         }
-    }
+
+        ActionParams::ZapOut {
+            pair_addr: pair_addr.clone(),
+            target_asset,
+            min_receive: min_receive.unwrap_or_default(),
+            router_path,
+            balances_before,
+        }
+    } else {
+        if pair_info.asset_infos.len() != min_assets_to_receive.len() {
+            return Err(ContractError::WrongAssetLength {
+                expected: pair_info.asset_infos.len(),
+                actual: min_assets_to_receive.len(),
+            });
+        }
+        // Check `min_assets_to_receive` belong to the pair
+        for asset in &min_assets_to_receive {
+            if !pair_info.asset_infos.contains(&asset.info) {
+                return Err(ContractError::AssetNotInPair(asset.info.to_string()));
+            }
+        }
+
+        ActionParams::Withdraw {
+            min_assets_to_receive,
+            balances_before,
+        }
+    };
 
     let withdraw_msg = wasm_execute(
         lp_token_addr,
@@ -381,10 +989,7 @@ fn withdraw_liquidity(
         deps.storage,
         &ReplyData {
             receiver: receiver.to_string(),
-            params: ActionParams::Withdraw {
-                pair_addr,
-                min_assets_to_receive,
-            },
+            params,
         },
     )?;
 
@@ -393,3 +998,129 @@ fn withdraw_liquidity(
         .add_attribute("contract", "liquidity_manager")
         .add_attribute("action", "withdraw_liquidity"))
 }
+
+/// Checks that the router path swaps `offer_asset` into `target_asset`.
+fn validate_router_path(
+    api: &dyn Api,
+    router_path: &RouterPath,
+    offer_asset: &AssetInfo,
+    target_asset: &AssetInfo,
+) -> Result<(), ContractError> {
+    api.addr_validate(&router_path.router)?;
+
+    match (
+        router_path.operations.first(),
+        router_path.operations.last(),
+    ) {
+        (Some(first), Some(last))
+            if first.get_offer_asset_info().eq(offer_asset)
+                && last.get_target_asset_info().eq(target_asset) =>
+        {
+            Ok(())
+        }
+        _ => Err(ContractError::InvalidRouterPath {}),
+    }
+}
+
+/// Creates a message swapping `offer_asset` through the router. The swap output is sent to this contract.
+fn router_swap_msg(
+    router_path: &RouterPath,
+    offer_asset: Asset,
+    max_spread: Option<Decimal>,
+) -> StdResult<CosmosMsg> {
+    match &offer_asset.info {
+        AssetInfo::Token { contract_addr } => Ok(wasm_execute(
+            contract_addr,
+            &Cw20ExecuteMsg::Send {
+                contract: router_path.router.clone(),
+                amount: offer_asset.amount,
+                msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                    operations: router_path.operations.clone(),
+                    minimum_receive: None,
+                    to: None,
+                    max_spread,
+                    deadline: None,
+                    ibc_destination: None,
+                    referral: None,
+                })?,
+            },
+            vec![],
+        )?
+        .into()),
+        AssetInfo::NativeToken { denom } => Ok(wasm_execute(
+            &router_path.router,
+            &RouterExecuteMsg::ExecuteSwapOperations {
+                operations: router_path.operations.clone(),
+                minimum_receive: None,
+                to: None,
+                max_spread,
+                deadline: None,
+                ibc_destination: None,
+                referral: None,
+            },
+            vec![coin(offer_asset.amount.u128(), denom)],
+        )?
+        .into()),
+    }
+}
+
+/// Returns the amounts of the pool assets received by this contract since `balances_before` were queried.
+fn received_assets(
+    querier: QuerierWrapper,
+    env: &Env,
+    balances_before: &[Asset],
+) -> StdResult<Vec<Asset>> {
+    balances_before
+        .iter()
+        .map(|before| {
+            let balance = before.info.query_pool(&querier, &env.contract.address)?;
+            Ok(before
+                .info
+                .with_balance(balance.checked_sub(before.amount)?))
+        })
+        .collect()
+}
+
+/// Sends the `target_asset` received by this contract during the zap-out to the receiver
+/// if it is at least `min_receive`.
+fn finish_zap_out(
+    deps: DepsMut,
+    env: Env,
+    receiver: String,
+    target_asset: AssetInfo,
+    min_receive: Uint128,
+    balances_before: &[Asset],
+) -> Result<Response, ContractError> {
+    let received = received_assets(deps.querier, &env, balances_before)?
+        .into_iter()
+        .find(|asset| asset.info.eq(&target_asset))
+        .ok_or_else(|| ContractError::AssetNotInPair(target_asset.to_string()))?
+        .amount;
+    if received < min_receive {
+        return Err(ContractError::WithdrawSlippageViolation {
+            asset_name: target_asset.to_string(),
+            received,
+            expected: min_receive,
+        });
+    }
+
+    Ok(Response::new()
+        .add_message(target_asset.with_balance(received).into_msg(receiver)?)
+        .add_attribute("liquidity_manager", "zap_out_check_passed"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // Versions before 1.1.0 did not store the contract version
+    if CONTRACT.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::MigrationError {});
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("previous_contract_name", CONTRACT_NAME)
+        .add_attribute("previous_contract_version", "1.0.1")
+        .add_attribute("new_contract_name", CONTRACT_NAME)
+        .add_attribute("new_contract_version", CONTRACT_VERSION))
+}
@@ -38,4 +38,22 @@ pub enum ContractError {
 
     #[error("Liquidity manager supports only pools with 2 assets")]
     WrongPoolLength {},
+
+    #[error("Transaction deadline has passed")]
+    DeadlineExpired {},
+
+    #[error("Offer amount must be greater than zero")]
+    InvalidZeroAmount {},
+
+    #[error("Cannot zap into a pool without liquidity")]
+    EmptyPool {},
+
+    #[error("Router path must swap the other pool asset into the target asset")]
+    InvalidRouterPath {},
+
+    #[error("Liquidity can be migrated only between different pairs with the same assets")]
+    InvalidMigrationPair {},
+
+    #[error("Contract can't be migrated!")]
+    MigrationError {},
 }
@@ -2,15 +2,19 @@
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{to_binary, Binary, Deps, Env, StdError, StdResult, Uint128};
 
-use astroport::asset::{Asset, PairInfo};
+use astroport::asset::{Asset, AssetInfo, PairInfo};
 use astroport::factory::PairType;
-use astroport::liquidity_manager::QueryMsg;
-use astroport::pair::{ExecuteMsg as PairExecuteMsg, QueryMsg as PairQueryMsg};
-use astroport::querier::query_supply;
-use astroport_pair::contract::get_share_in_assets;
+use astroport::liquidity_manager::{QueryMsg, RouterPath};
+use astroport::pair::{ExecuteMsg as PairExecuteMsg, QueryMsg as PairQueryMsg, SimulationResponse};
+use astroport::querier::{query_pair_fee_info, query_supply};
+use astroport::router::{QueryMsg as RouterQueryMsg, SimulateSwapOperationsResponse};
+use astroport_pair::contract::{compute_swap, get_share_in_assets};
 
 use crate::error::ContractError;
-use crate::utils::{stableswap_provide_simulation, xyk_provide_simulation};
+use crate::state::CONFIG;
+use crate::utils::{
+    concentrated_provide_simulation, stableswap_provide_simulation, xyk_provide_simulation,
+};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
@@ -23,6 +27,12 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             pair_addr,
             lp_tokens,
         } => simulate_withdraw(deps, pair_addr, lp_tokens),
+        QueryMsg::SimulateZapOut {
+            pair_addr,
+            lp_tokens,
+            target_asset,
+            router_path,
+        } => simulate_zap_out(deps, pair_addr, lp_tokens, target_asset, router_path),
     }
 }
 
@@ -108,7 +118,23 @@ fn simulate_provide(
                         .map_err(|err| StdError::generic_err(format!("{err}")))?,
                     )
                 }
-                PairType::Custom(..) => unimplemented!("not implemented yet"),
+                PairType::Custom(typ)
+                    if typ == "concentrated" || typ == "concentrated_inj_orderbook" =>
+                {
+                    to_binary(
+                        &concentrated_provide_simulation(
+                            deps.querier,
+                            &env,
+                            &pair_info,
+                            slippage_tolerance,
+                            assets,
+                        )
+                        .map_err(|err| StdError::generic_err(format!("{err}")))?,
+                    )
+                }
+                PairType::Custom(typ) => Err(StdError::generic_err(format!(
+                    "Provide simulation is not supported for {typ} pairs"
+                ))),
             }
         }
         _ => Err(StdError::generic_err("Invalid simulate message")),
@@ -131,6 +157,80 @@ fn simulate_withdraw(deps: Deps, pair_addr: String, lp_tokens: Uint128) -> StdRe
     to_binary(&assets)
 }
 
+/// Simulates a withdrawal into `target_asset`. For XYK pools the swap is simulated against the pool
+/// after the withdrawal. Other pools and router paths are simulated against their current state.
+fn simulate_zap_out(
+    deps: Deps,
+    pair_addr: String,
+    lp_tokens: Uint128,
+    target_asset: AssetInfo,
+    router_path: Option<RouterPath>,
+) -> StdResult<Binary> {
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+    let pair_info: PairInfo = deps
+        .querier
+        .query_wasm_smart(&pair_addr, &PairQueryMsg::Pair {})?;
+    if pair_info.asset_infos.len() != 2 {
+        return Err(StdError::generic_err(format!(
+            "{}",
+            ContractError::WrongPoolLength {}
+        )));
+    }
+    if !pair_info.asset_infos.contains(&target_asset) {
+        return Err(StdError::generic_err(format!(
+            "{}",
+            ContractError::AssetNotInPair(target_asset.to_string())
+        )));
+    }
+
+    let assets: Vec<Asset> = deps
+        .querier
+        .query_wasm_smart(&pair_addr, &PairQueryMsg::Share { amount: lp_tokens })?;
+    let (target, other): (Vec<_>, Vec<_>) = assets
+        .into_iter()
+        .partition(|asset| asset.info == target_asset);
+    let (target, other) = (&target[0], &other[0]);
+
+    let swapped_amount = if other.amount.is_zero() {
+        Uint128::zero()
+    } else if let Some(router_path) = router_path {
+        let res: SimulateSwapOperationsResponse = deps.querier.query_wasm_smart(
+            router_path.router,
+            &RouterQueryMsg::SimulateSwapOperations {
+                offer_amount: other.amount,
+                operations: router_path.operations,
+                referral: None,
+            },
+        )?;
+        res.amount
+    } else if pair_info.pair_type == (PairType::Xyk {}) {
+        let pools = pair_info.query_pools(&deps.querier, &pair_addr)?;
+        let offer_pool = pools.iter().find(|pool| pool.info == other.info).unwrap();
+        let ask_pool = pools.iter().find(|pool| pool.info == target_asset).unwrap();
+        let factory_addr = CONFIG.load(deps.storage)?.factory_addr;
+        let fee_info = query_pair_fee_info(&deps.querier, factory_addr, &pair_info)?;
+
+        let (return_amount, ..) = compute_swap(
+            offer_pool.amount - other.amount,
+            ask_pool.amount - target.amount,
+            other.amount,
+            fee_info.total_fee_rate,
+        )?;
+        return_amount
+    } else {
+        let res: SimulationResponse = deps.querier.query_wasm_smart(
+            &pair_addr,
+            &PairQueryMsg::Simulation {
+                offer_asset: other.clone(),
+                ask_asset_info: Some(target_asset),
+            },
+        )?;
+        res.return_amount
+    };
+
+    to_binary(&(target.amount + swapped_amount))
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -156,6 +256,7 @@ mod tests {
                 slippage_tolerance: Some(Decimal::from_str("0.02").unwrap()),
                 auto_stake: Some(true),
                 receiver: Some("wasm1...addr".to_string()),
+                deadline: None,
             },
         };
 
@@ -188,6 +289,7 @@ mod tests {
                 slippage_tolerance: Some(Decimal::from_str("0.02").unwrap()),
                 auto_stake: Some(true),
                 receiver: Some("wasm1...addr".to_string()),
+                deadline: None,
             },
             min_lp_to_receive: Some(100000u128.into()),
         };
@@ -198,12 +300,18 @@ mod tests {
         );
 
         let cw20hook_msg = Cw20HookMsg::WithdrawLiquidity {
-            pair_msg: PairCw20HookMsg::WithdrawLiquidity { assets: vec![] },
+            pair_msg: PairCw20HookMsg::WithdrawLiquidity {
+                assets: vec![],
+                deadline: None,
+            },
             min_assets_to_receive: vec![
                 native_asset_info("uusd".to_string()).with_balance(100000u128),
                 token_asset_info(Addr::unchecked("wasm1...cw20address".to_string()))
                     .with_balance(100000u128),
             ],
+            target_asset: None,
+            min_receive: None,
+            router_path: None,
         };
 
         let withdraw_msg = cw20::Cw20ExecuteMsg::Send {
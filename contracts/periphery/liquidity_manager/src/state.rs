@@ -2,7 +2,8 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, Uint128};
 use cw_storage_plus::Item;
 
-use astroport::asset::Asset;
+use astroport::asset::{Asset, AssetInfo};
+use astroport::liquidity_manager::RouterPath;
 
 #[cw_serde]
 pub struct Config {
@@ -20,8 +21,37 @@ pub enum ActionParams {
         min_lp_to_receive: Uint128,
     },
     Withdraw {
-        pair_addr: Addr,
         min_assets_to_receive: Vec<Asset>,
+        /// The pool asset balances of this contract before the withdrawal
+        balances_before: Vec<Asset>,
+    },
+    ZapIn {
+        pair_addr: Addr,
+        /// The part of the offer asset which is not swapped
+        offer_asset: Asset,
+        ask_asset_info: AssetInfo,
+        ask_balance_before: Uint128,
+        auto_stake: bool,
+        min_lp_to_receive: Option<Uint128>,
+        /// The address which receives leftovers
+        sender: Addr,
+    },
+    ZapOut {
+        pair_addr: Addr,
+        target_asset: AssetInfo,
+        min_receive: Uint128,
+        router_path: Option<RouterPath>,
+        /// The pool asset balances of this contract before the withdrawal
+        balances_before: Vec<Asset>,
+    },
+    MigrateLiquidity {
+        to_pair: Addr,
+        /// The pool asset balances of this contract before the withdrawal
+        balances_before: Vec<Asset>,
+        auto_stake: bool,
+        min_lp_out: Option<Uint128>,
+        /// The address which receives leftovers
+        sender: Addr,
     },
 }
 
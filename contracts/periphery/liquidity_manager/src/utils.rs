@@ -1,18 +1,52 @@
 use std::collections::HashMap;
 
-use cosmwasm_std::{Addr, Decimal, Decimal256, Env, QuerierWrapper, StdError, StdResult, Uint128};
+use cosmwasm_std::{
+    Addr, Decimal, Decimal256, Env, QuerierWrapper, StdError, StdResult, Uint128, Uint256,
+};
 
-use astroport::asset::{Asset, Decimal256Ext, DecimalAsset, PairInfo, MINIMUM_LIQUIDITY_AMOUNT};
+use astroport::asset::{
+    Asset, AssetInfo, AssetInfoExt, Decimal256Ext, DecimalAsset, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
+};
+use astroport::cosmwasm_ext::{AbsDiff, DecimalToInteger, IntegerToDecimal};
 use astroport::generator::QueryMsg as GeneratorQueryMsg;
+use astroport::pair::{PoolResponse, QueryMsg as PairQueryMsg, SimulationResponse};
 use astroport::querier::{query_supply, query_token_balance};
 use astroport::U256;
 use astroport_pair::{
-    contract::assert_slippage_tolerance, error::ContractError as PairContractError,
+    contract::{assert_slippage_tolerance, get_share_in_assets},
+    error::ContractError as PairContractError,
+};
+use astroport_pair_concentrated::error::ContractError as ConcentratedContractError;
+use astroport_pair_concentrated::math::{calc_d, get_xcp};
+use astroport_pair_concentrated::state::{PoolParams as ConcentratedPoolParams, PoolState};
+use astroport_pair_concentrated::utils::{
+    assert_slippage_tolerance as concentrated_slippage_tolerance, calc_provide_fee,
 };
 use astroport_pair_stable::error::ContractError as StableContractError;
 use astroport_pair_stable::math::compute_d;
 use astroport_pair_stable::state::Config as PairStableConfig;
-use astroport_pair_stable::utils::compute_current_amp;
+use astroport_pair_stable::utils::{compute_current_amp, Rates};
+
+/// LP tokens of concentrated pairs always have 6 decimals
+const CONCENTRATED_LP_TOKEN_PRECISION: u8 = 6;
+
+/// The part of the concentrated pair config used in provide simulations.
+/// Both `pair_concentrated` and `pair_concentrated_inj` store their config in this shape.
+#[derive(cosmwasm_schema::serde::Deserialize)]
+#[serde(crate = "cosmwasm_schema::serde")]
+pub struct ConcentratedPairConfig {
+    pub factory_addr: Addr,
+    pub pool_params: ConcentratedPoolParams,
+    pub pool_state: PoolState,
+}
+
+/// Returns the generator address from the factory config.
+pub fn query_generator(querier: QuerierWrapper, factory_addr: Addr) -> StdResult<Addr> {
+    astroport_factory::state::CONFIG
+        .query(&querier, factory_addr)?
+        .generator_address
+        .ok_or_else(|| StdError::generic_err("Generator address is not set in factory config"))
+}
 
 pub fn query_lp_amount(
     querier: QuerierWrapper,
@@ -22,22 +56,13 @@ pub fn query_lp_amount(
     user: &String,
 ) -> StdResult<Uint128> {
     if staked_in_generator {
-        let maybe_generator = astroport_factory::state::CONFIG
-            .query(&querier, factory_addr)?
-            .generator_address;
-        if let Some(generator_addr) = maybe_generator {
-            querier.query_wasm_smart(
-                generator_addr,
-                &GeneratorQueryMsg::Deposit {
-                    lp_token: lp_token_addr,
-                    user: user.to_string(),
-                },
-            )
-        } else {
-            Err(StdError::generic_err(
-                "Generator address is not set in factory config",
-            ))
-        }
+        querier.query_wasm_smart(
+            query_generator(querier, factory_addr)?,
+            &GeneratorQueryMsg::Deposit {
+                lp_token: lp_token_addr,
+                user: user.to_string(),
+            },
+        )
     } else {
         query_token_balance(&querier, lp_token_addr, user)
     }
@@ -110,6 +135,226 @@ pub fn xyk_provide_simulation(
     Ok(share)
 }
 
+/// Returns the part of `deposits` which is used by the XYK pair to mint LP tokens.
+/// The rest of the deposits would be kept by the pair because of its minting policy.
+pub fn xyk_deposits_share(
+    querier: QuerierWrapper,
+    pair_info: &PairInfo,
+    slippage_tolerance: Option<Decimal>,
+    deposits: &[Asset],
+) -> Result<Vec<Asset>, PairContractError> {
+    let pools = pair_info.query_pools(&querier, &pair_info.contract_addr)?;
+    // Initial provide is always fair because initial LP dictates the price
+    if (pools[0].amount * pools[1].amount).is_zero() {
+        return Ok(deposits.to_vec());
+    }
+
+    let predicted_lp_amount =
+        xyk_provide_simulation(querier, pair_info, slippage_tolerance, deposits.to_vec())?;
+
+    // Add user's deposits
+    let pools = pools
+        .into_iter()
+        .map(|mut pool| {
+            if let Some(deposit) = deposits.iter().find(|a| a.info.equal(&pool.info)) {
+                pool.amount += deposit.amount;
+            }
+            pool
+        })
+        .collect::<Vec<_>>();
+    let total_share = query_supply(&querier, &pair_info.liquidity_token)?;
+    let share = get_share_in_assets(
+        &pools,
+        predicted_lp_amount,
+        total_share + predicted_lp_amount,
+    );
+
+    Ok(deposits
+        .iter()
+        .map(|deposit| {
+            share
+                .iter()
+                .find(|a| a.info.equal(&deposit.info))
+                .cloned()
+                .unwrap_or_else(|| deposit.info.with_balance(0u8))
+        })
+        .collect())
+}
+
+/// Calculates the amount of the offer asset to swap in an XYK pool so that the rest of the offer
+/// asset and the swap output are in the pool ratio after the swap.
+///
+/// The pair charges commission from the swap output and only the maker fee leaves the pool,
+/// so the amount `s` solves `A * s^2 + s * (X * (2 - f) - a * g) - a * X = 0`, where `X` is the offer pool,
+/// `a` is the offer amount, `f` is the commission rate, `g = f * (1 - maker_fee_rate)` is the commission
+/// kept in the pool and `A = 1 - f + g`.
+pub fn xyk_zap_swap_amount(
+    offer_pool: Uint128,
+    offer_amount: Uint128,
+    commission_rate: Decimal,
+    maker_fee_rate: Decimal,
+) -> StdResult<Uint128> {
+    let fee = Decimal256::from(commission_rate);
+    let pool_fee = fee * (Decimal256::one() - Decimal256::from(maker_fee_rate));
+    let quad = Decimal256::one() - fee + pool_fee;
+    let ratio = Decimal256::from_ratio(offer_amount, offer_pool);
+    let two = Decimal256::from_integer(2u8);
+    let four = Decimal256::from_integer(4u8);
+
+    // b = 2 - f - a * g / X, which is negative for huge offer amounts
+    let (b, b_is_negative) = if two - fee >= ratio * pool_fee {
+        (two - fee - ratio * pool_fee, false)
+    } else {
+        (ratio * pool_fee + fee - two, true)
+    };
+    let root = (b * b + four * quad * ratio).sqrt();
+    let share = if b_is_negative { root + b } else { root - b };
+
+    let swap_amount = (Decimal256::from_integer(offer_pool) * share / (two * quad))
+        .to_uint128_with_precision(0u8)?;
+
+    Ok(swap_amount.min(offer_amount))
+}
+
+/// The maximum number of pair simulations used to find the zap swap amount
+const ZAP_SEARCH_ITERATIONS: u8 = 32;
+
+/// Finds the amount of the offer asset to swap so that the rest of the offer asset and the swap output
+/// are in the pool ratio after the swap. Uses a binary search over the pair's swap simulations,
+/// so it works for any pool curve.
+pub fn simulated_zap_swap_amount(
+    querier: QuerierWrapper,
+    pair_addr: &Addr,
+    offer_asset: &Asset,
+    offer_pool: Uint128,
+    ask_asset_info: &AssetInfo,
+    ask_pool: Uint128,
+) -> StdResult<Uint128> {
+    let mut low = Uint128::zero();
+    let mut high = offer_asset.amount;
+
+    for _ in 0..ZAP_SEARCH_ITERATIONS {
+        if high - low <= Uint128::one() {
+            break;
+        }
+
+        let swap_amount = low + (high - low) / Uint128::from(2u8);
+        let res: SimulationResponse = querier.query_wasm_smart(
+            pair_addr,
+            &PairQueryMsg::Simulation {
+                offer_asset: offer_asset.info.with_balance(swap_amount),
+                ask_asset_info: Some(ask_asset_info.clone()),
+            },
+        )?;
+
+        // Compare the deposit ratio (offer_amount - s) / return with the pool ratio after the swap
+        let deposit_side = Uint256::from(offer_asset.amount - swap_amount)
+            * Uint256::from(ask_pool.saturating_sub(res.return_amount));
+        let pool_side = Uint256::from(res.return_amount) * Uint256::from(offer_pool + swap_amount);
+
+        if deposit_side > pool_side {
+            low = swap_amount;
+        } else {
+            high = swap_amount;
+        }
+    }
+
+    Ok(low)
+}
+
+pub fn concentrated_provide_simulation(
+    querier: QuerierWrapper,
+    env: &Env,
+    pair_info: &PairInfo,
+    slippage_tolerance: Option<Decimal>,
+    deposits: Vec<Asset>,
+) -> Result<Uint128, ConcentratedContractError> {
+    let config: ConcentratedPairConfig = querier
+        .query_wasm_raw(&pair_info.contract_addr, b"config".as_slice())?
+        .map(|data| cosmwasm_std::from_slice(&data))
+        .transpose()?
+        .ok_or_else(|| StdError::generic_err("Failed to find config in pair storage"))?;
+    let price_state = &config.pool_state.price_state;
+
+    // Inj pair also accounts assets placed in the orderbook, thus pools must be queried from the pair
+    let PoolResponse {
+        assets: pools,
+        total_share,
+    } = querier.query_wasm_smart(&pair_info.contract_addr, &PairQueryMsg::Pool {})?;
+
+    let mut precisions = vec![];
+    let mut xp = vec![];
+    let mut deposit_amounts = vec![];
+    for pool in &pools {
+        let precision = pool.info.decimals(&querier, &config.factory_addr)?;
+        let deposit = deposits
+            .iter()
+            .find(|asset| asset.info.equal(&pool.info))
+            .map(|asset| asset.amount)
+            .unwrap_or_default();
+        xp.push(Decimal256::with_precision(pool.amount, precision)?);
+        deposit_amounts.push(Decimal256::with_precision(deposit, precision)?);
+        precisions.push(precision);
+    }
+    if let Some(asset) = deposits
+        .iter()
+        .find(|asset| !pair_info.asset_infos.contains(&asset.info))
+    {
+        return Err(ConcentratedContractError::InvalidAsset(
+            asset.info.to_string(),
+        ));
+    }
+
+    let total_share = total_share.to_decimal256(CONCENTRATED_LP_TOKEN_PRECISION)?;
+    // Initial provide can not be one-sided
+    if total_share.is_zero() && (deposit_amounts[0].is_zero() || deposit_amounts[1].is_zero()) {
+        return Err(ConcentratedContractError::InvalidZeroAmount {});
+    }
+
+    let mut new_xp = xp
+        .iter()
+        .zip(deposit_amounts.iter())
+        .map(|(pool, deposit)| *pool + *deposit)
+        .collect::<Vec<_>>();
+    new_xp[1] *= price_state.price_scale;
+
+    let amp_gamma = config.pool_state.get_amp_gamma(env);
+    let new_d = calc_d(&new_xp, &amp_gamma)?;
+
+    let share = if total_share.is_zero() {
+        let xcp = get_xcp(new_d, price_state.price_scale);
+        let share = xcp
+            .checked_sub(MINIMUM_LIQUIDITY_AMOUNT.to_decimal256(CONCENTRATED_LP_TOKEN_PRECISION)?)
+            .map_err(|_| ConcentratedContractError::MinimumLiquidityAmountError {})?;
+
+        // share cannot become zero after minimum liquidity subtraction
+        if share.is_zero() {
+            return Err(ConcentratedContractError::MinimumLiquidityAmountError {});
+        }
+
+        share
+    } else {
+        let mut old_xp = xp.clone();
+        old_xp[1] *= price_state.price_scale;
+        let old_d = calc_d(&old_xp, &amp_gamma)?;
+        let share = (total_share * new_d / old_d).saturating_sub(total_share);
+
+        let mut ideposits = deposit_amounts.clone();
+        ideposits[1] *= price_state.price_scale;
+
+        share * (Decimal256::one() - calc_provide_fee(&ideposits, &new_xp, &config.pool_params))
+    };
+
+    // The pair checks slippage only for imbalanced provides
+    let share_ratio = share / (total_share + share);
+    let balanced_share = new_xp[1] * share_ratio / price_state.price_scale;
+    if !deposit_amounts[1].diff(balanced_share).is_zero() {
+        concentrated_slippage_tolerance(&deposit_amounts, share, price_state, slippage_tolerance)?;
+    }
+
+    Ok(share.to_uint(CONCENTRATED_LP_TOKEN_PRECISION)?)
+}
+
 pub fn stableswap_provide_simulation(
     querier: QuerierWrapper,
     env: Env,
@@ -193,6 +438,18 @@ pub fn stableswap_provide_simulation(
         })
         .collect::<StdResult<Vec<(DecimalAsset, Decimal256)>>>()?;
 
+    // Pool balances and deposits are scaled by asset rates
+    let rates = Rates::query(querier, &env, &config)?;
+    let assets_collection = assets_collection
+        .iter()
+        .map(|(deposit, pool)| {
+            Ok((
+                rates.scale_asset(deposit)?,
+                rates.scale(&deposit.info, *pool)?,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
     let amp = compute_current_amp(&config, &env)?;
 
     // Invariant (D) after deposit added
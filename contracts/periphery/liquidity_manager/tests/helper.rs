@@ -9,7 +9,7 @@ use std::str::FromStr;
 use anyhow::Result as AnyResult;
 use cosmwasm_schema::serde::de::DeserializeOwned;
 use cosmwasm_std::{
-    coin, from_slice, to_binary, Addr, Coin, Decimal, Empty, StdError, StdResult, Uint128,
+    coin, from_slice, to_binary, Addr, Binary, Coin, Decimal, Empty, StdError, StdResult, Uint128,
 };
 use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
 use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
@@ -121,6 +121,37 @@ fn stable_pair_contract() -> Box<dyn Contract<Empty>> {
     )
 }
 
+fn pcl_pair_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_pair_concentrated::contract::execute,
+            astroport_pair_concentrated::contract::instantiate,
+            astroport_pair_concentrated::queries::query,
+        )
+        .with_reply_empty(astroport_pair_concentrated::contract::reply),
+    )
+}
+
+fn store_pair_code(app: &mut App, params: &PoolParams) -> (u64, PairType, Binary) {
+    match params {
+        PoolParams::Constant(inner) => (
+            app.store_code(xyk_pair_contract()),
+            PairType::Xyk {},
+            to_binary(inner).unwrap(),
+        ),
+        PoolParams::Stable(inner) => (
+            app.store_code(stable_pair_contract()),
+            PairType::Stable {},
+            to_binary(inner).unwrap(),
+        ),
+        PoolParams::Concentrated(inner) => (
+            app.store_code(pcl_pair_contract()),
+            PairType::Custom("concentrated".to_owned()),
+            to_binary(inner).unwrap(),
+        ),
+    }
+}
+
 fn coin_registry_contract() -> Box<dyn Contract<Empty>> {
     Box::new(ContractWrapper::new_with_empty(
         astroport_native_coin_registry::contract::execute,
@@ -204,25 +235,7 @@ impl Helper {
 
         let factory_code_id = app.store_code(factory_contract());
 
-        let (pair_code_id, pair_type, inner_params);
-        match &params {
-            PoolParams::Constant(inner) => {
-                pair_code_id = app.store_code(xyk_pair_contract());
-                pair_type = PairType::Xyk {};
-                inner_params = to_binary(inner).unwrap();
-            }
-            PoolParams::Stable(inner) => {
-                pair_code_id = app.store_code(stable_pair_contract());
-                pair_type = PairType::Stable {};
-                inner_params = to_binary(inner).unwrap();
-            }
-            PoolParams::Concentrated(_) => {
-                unimplemented!("Concentrated pool is not supported yet");
-                // pair_code_id = app.store_code(pcl_pair_contract());
-                // pair_type = PairType::Custom("concentrated".to_owned());
-                // inner_params = to_binary(inner).unwrap();
-            }
-        }
+        let (pair_code_id, pair_type, inner_params) = store_pair_code(&mut app, &params);
 
         let fake_maker = Addr::unchecked("fake_maker");
 
@@ -259,6 +272,7 @@ impl Helper {
                 pair_type: pair_type.clone(),
                 is_disabled: false,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             }],
             token_code_id,
             generator_address: None,
@@ -338,13 +352,19 @@ impl Helper {
             pair_type,
             asset_infos: asset_infos.clone(),
             init_params: Some(inner_params),
+            fee: None,
+            fee_tier: None,
         };
 
         app.execute_contract(owner.clone(), factory.clone(), &init_pair_msg, &[])?;
 
         let resp: PairInfo = app.wrap().query_wasm_smart(
             &factory,
-            &astroport::factory::QueryMsg::Pair { asset_infos },
+            &astroport::factory::QueryMsg::Pair {
+                asset_infos,
+                pair_type: None,
+                fee_tier: None,
+            },
         )?;
 
         Ok(Self {
@@ -360,6 +380,53 @@ impl Helper {
         })
     }
 
+    /// Creates one more pair with the same assets. Returns the new pair info.
+    pub fn create_pair(&mut self, params: PoolParams) -> AnyResult<PairInfo> {
+        let (code_id, pair_type, inner_params) = store_pair_code(&mut self.app, &params);
+        self.app.execute_contract(
+            self.owner.clone(),
+            self.factory.clone(),
+            &factory::ExecuteMsg::UpdatePairConfig {
+                config: PairConfig {
+                    code_id,
+                    pair_type: pair_type.clone(),
+                    total_fee_bps: 30,
+                    maker_fee_bps: 5000,
+                    is_disabled: false,
+                    is_generator_disabled: false,
+                    fee_tiers: vec![],
+                },
+            },
+            &[],
+        )?;
+
+        let asset_infos = self.assets.values().cloned().collect_vec();
+        self.app.execute_contract(
+            self.owner.clone(),
+            self.factory.clone(),
+            &factory::ExecuteMsg::CreatePair {
+                pair_type: pair_type.clone(),
+                asset_infos: asset_infos.clone(),
+                init_params: Some(inner_params),
+                fee: None,
+                fee_tier: None,
+            },
+            &[],
+        )?;
+
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.factory,
+                &factory::QueryMsg::Pair {
+                    asset_infos,
+                    pair_type: Some(pair_type),
+                    fee_tier: None,
+                },
+            )
+            .map_err(Into::into)
+    }
+
     pub fn simulate_provide(
         &self,
         slippage_tolerance: Option<Decimal>,
@@ -370,6 +437,7 @@ impl Helper {
             slippage_tolerance,
             auto_stake: None,
             receiver: None,
+            deadline: None,
         };
 
         self.app
@@ -429,6 +497,7 @@ impl Helper {
             slippage_tolerance,
             auto_stake: Some(auto_stake),
             receiver,
+            deadline: None,
         };
 
         if min_lp_receive.is_some() {
@@ -458,19 +527,55 @@ impl Helper {
         }
     }
 
+    pub fn zap_in(
+        &mut self,
+        sender: &Addr,
+        offer_asset: &Asset,
+        min_lp_receive: Option<Uint128>,
+        auto_stake: bool,
+    ) -> AnyResult<AppResponse> {
+        let funds = offer_asset.mock_coin_sent(
+            &mut self.app,
+            sender,
+            &self.liquidity_manager,
+            SendType::Allowance,
+        );
+
+        self.app.execute_contract(
+            sender.clone(),
+            self.liquidity_manager.clone(),
+            &ExecuteMsg::ZapIn {
+                pair_addr: self.pair_addr.to_string(),
+                offer_asset: offer_asset.clone(),
+                max_spread: Some(f64_to_dec(0.5)),
+                min_lp_to_receive: min_lp_receive,
+                auto_stake: Some(auto_stake),
+                receiver: None,
+                deadline: None,
+            },
+            &funds,
+        )
+    }
+
     pub fn withdraw_liquidity(
         &mut self,
         sender: &Addr,
         amount: u128,
         min_assets: Option<Vec<Asset>>,
     ) -> AnyResult<AppResponse> {
-        let pair_msg = PairCw20HookMsg::WithdrawLiquidity { assets: vec![] };
+        let pair_msg = PairCw20HookMsg::WithdrawLiquidity {
+            assets: vec![],
+            deadline: None,
+        };
         let (contract, msg);
         if let Some(min_assets_to_receive) = min_assets {
             contract = self.liquidity_manager.to_string();
             msg = to_binary(&Cw20HookMsg::WithdrawLiquidity {
                 pair_msg,
                 min_assets_to_receive,
+                target_asset: None,
+                min_receive: None,
+                router_path: None,
             })
             .unwrap();
         } else {
@@ -488,6 +593,102 @@ impl Helper {
             .execute_contract(sender.clone(), self.lp_token.clone(), &msg, &[])
     }
 
+    pub fn zap_out(
+        &mut self,
+        sender: &Addr,
+        amount: u128,
+        target_asset: &AssetInfo,
+        min_receive: Option<Uint128>,
+    ) -> AnyResult<AppResponse> {
+        let msg = Cw20ExecuteMsg::Send {
+            contract: self.liquidity_manager.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                pair_msg: PairCw20HookMsg::WithdrawLiquidity {
+                    assets: vec![],
+                    deadline: None,
+                },
+                min_assets_to_receive: vec![],
+                target_asset: Some(target_asset.clone()),
+                min_receive,
+                router_path: None,
+            })
+            .unwrap(),
+        };
+
+        self.app
+            .execute_contract(sender.clone(), self.lp_token.clone(), &msg, &[])
+    }
+
+    pub fn migrate_liquidity(
+        &mut self,
+        sender: &Addr,
+        to_pair: &Addr,
+        amount: u128,
+        staked: bool,
+        min_lp_out: Option<Uint128>,
+        auto_stake: bool,
+    ) -> AnyResult<AppResponse> {
+        if staked {
+            self.app.execute_contract(
+                sender.clone(),
+                self.generator.clone(),
+                &generator::ExecuteMsg::SetWithdrawAllowance {
+                    lp_token: self.lp_token.to_string(),
+                    spender: self.liquidity_manager.to_string(),
+                    amount: amount.into(),
+                },
+                &[],
+            )?;
+        } else {
+            self.app.execute_contract(
+                sender.clone(),
+                self.lp_token.clone(),
+                &Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: self.liquidity_manager.to_string(),
+                    amount: amount.into(),
+                    expires: None,
+                },
+                &[],
+            )?;
+        }
+
+        self.app.execute_contract(
+            sender.clone(),
+            self.liquidity_manager.clone(),
+            &ExecuteMsg::MigrateLiquidity {
+                from_pair: self.pair_addr.to_string(),
+                to_pair: to_pair.to_string(),
+                amount: amount.into(),
+                staked: Some(staked),
+                min_lp_out,
+                auto_stake: Some(auto_stake),
+                receiver: None,
+                deadline: None,
+            },
+            &[],
+        )
+    }
+
+    pub fn simulate_zap_out(
+        &self,
+        lp_tokens_amount: impl Into<Uint128>,
+        target_asset: &AssetInfo,
+    ) -> AnyResult<Uint128> {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.liquidity_manager,
+                &QueryMsg::SimulateZapOut {
+                    pair_addr: self.pair_addr.to_string(),
+                    lp_tokens: lp_tokens_amount.into(),
+                    target_asset: target_asset.clone(),
+                    router_path: None,
+                },
+            )
+            .map_err(Into::into)
+    }
+
     pub fn swap(
         &mut self,
         sender: &Addr,
@@ -504,6 +705,7 @@ impl Helper {
                         belief_price: None,
                         max_spread,
                         to: None,
+                        deadline: None,
                     })
                     .unwrap(),
                 };
@@ -525,6 +727,7 @@ impl Helper {
                     belief_price: None,
                     max_spread,
                     to: None,
+                    deadline: None,
                 };
 
                 self.app
@@ -656,10 +859,14 @@ impl Helper {
     }
 
     pub fn query_staked_lp(&self, user: &Addr) -> StdResult<Uint128> {
+        self.query_staked_lp_of(&self.lp_token, user)
+    }
+
+    pub fn query_staked_lp_of(&self, lp_token: &Addr, user: &Addr) -> StdResult<Uint128> {
         self.app.wrap().query_wasm_smart(
             &self.generator,
             &generator::QueryMsg::Deposit {
-                lp_token: self.lp_token.to_string(),
+                lp_token: lp_token.to_string(),
                 user: user.to_string(),
             },
         )
@@ -1,9 +1,14 @@
 #![cfg(not(tarpaulin_include))]
 
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_multi_test::Executor;
 
-use astroport::asset::{native_asset, AssetInfoExt};
+use astroport::asset::{native_asset, native_asset_info, AssetInfoExt};
+use astroport::generator::QueryMsg as GeneratorQueryMsg;
+use astroport::liquidity_manager::ExecuteMsg;
 use astroport::pair::{StablePoolParams, XYKPoolParams};
+use astroport::pair_concentrated::ConcentratedPoolParams;
+use astroport_generator::error::ContractError as GeneratorContractError;
 use astroport_liquidity_manager::error::ContractError;
 
 use crate::helper::{f64_to_dec, Helper, PoolParams, TestCoin};
@@ -170,6 +175,7 @@ fn test_stableswap_without_manager() {
         PoolParams::Stable(StablePoolParams {
             amp: 40,
             owner: None,
+            rate_sources: None,
         }),
     )
     .unwrap();
@@ -245,6 +251,7 @@ fn test_stableswap_with_manager() {
         PoolParams::Stable(StablePoolParams {
             amp: 40,
             owner: None,
+            rate_sources: None,
         }),
     )
     .unwrap();
@@ -314,6 +321,7 @@ fn test_auto_stake_and_receiver() {
         PoolParams::Stable(StablePoolParams {
             amp: 40,
             owner: None,
+            rate_sources: None,
         }),
     )
     .unwrap();
@@ -475,3 +483,604 @@ fn test_withdraw() {
         err.downcast().unwrap()
     );
 }
+
+#[test]
+fn test_zap_in() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("TEST")];
+    let mut helper = Helper::new(
+        &owner,
+        test_coins.clone(),
+        PoolParams::Constant(XYKPoolParams {
+            track_asset_balances: None,
+        }),
+    )
+    .unwrap();
+
+    let user = Addr::unchecked("user");
+    let zap_asset = helper.assets[&test_coins[0]].with_balance(1000_000000u128);
+    helper.give_me_money(&[zap_asset.clone()], &user);
+
+    // Zap into an empty pool is not possible as there is no price yet
+    let err = helper.zap_in(&user, &zap_asset, None, false).unwrap_err();
+    assert_eq!(ContractError::EmptyPool {}, err.downcast().unwrap());
+
+    helper
+        .provide_liquidity(
+            &owner,
+            &[
+                helper.assets[&test_coins[0]].with_balance(100_000_000000_u128),
+                helper.assets[&test_coins[1]].with_balance(100_000_000000_u128),
+            ],
+            None,
+        )
+        .unwrap();
+
+    let err = helper
+        .zap_in(&user, &zap_asset, Some(1000_000000u128.into()), false)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ProvideSlippageViolation(_, expected) if expected == Uint128::new(1000_000000)
+    ));
+
+    helper.zap_in(&user, &zap_asset, None, false).unwrap();
+
+    // Only rounding dust is sent back to the user
+    assert!(helper.coin_balance(&test_coins[0], &user) < 10);
+    assert!(helper.coin_balance(&test_coins[1], &user) < 10);
+    let manager = helper.liquidity_manager.clone();
+    assert_eq!(helper.coin_balance(&test_coins[0], &manager), 0);
+    assert_eq!(helper.coin_balance(&test_coins[1], &manager), 0);
+
+    // Withdrawn assets are worth nearly the same as the zapped amount minus swap fees and price impact
+    let lp_amount = helper.token_balance(&helper.lp_token, &user);
+    helper.withdraw_liquidity(&user, lp_amount, None).unwrap();
+    let total =
+        helper.coin_balance(&test_coins[0], &user) + helper.coin_balance(&test_coins[1], &user);
+    assert!(total > 990_000000, "total {total}");
+
+    // Zap with CW20 token and auto stake
+    let user2 = Addr::unchecked("user2");
+    let zap_asset = helper.assets[&test_coins[1]].with_balance(1000_000000u128);
+    helper.give_me_money(&[zap_asset.clone()], &user2);
+    helper
+        .zap_in(&user2, &zap_asset, Some(Uint128::one()), true)
+        .unwrap();
+
+    assert!(!helper.query_staked_lp(&user2).unwrap().is_zero());
+    assert!(helper.coin_balance(&test_coins[0], &user2) < 10);
+    assert!(helper.coin_balance(&test_coins[1], &user2) < 10);
+    assert_eq!(helper.coin_balance(&test_coins[0], &manager), 0);
+    assert_eq!(helper.coin_balance(&test_coins[1], &manager), 0);
+}
+
+#[test]
+fn test_zap_in_stableswap() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("UST")];
+    let mut helper = Helper::new(
+        &owner,
+        test_coins.clone(),
+        PoolParams::Stable(StablePoolParams {
+            amp: 40,
+            owner: None,
+            rate_sources: None,
+        }),
+    )
+    .unwrap();
+
+    helper
+        .provide_liquidity(
+            &owner,
+            &[
+                helper.assets[&test_coins[0]].with_balance(100_000_000000_u128),
+                helper.assets[&test_coins[1]].with_balance(50_000_000000_u128),
+            ],
+            None,
+        )
+        .unwrap();
+
+    let user = Addr::unchecked("user");
+    let zap_asset = helper.assets[&test_coins[0]].with_balance(1000_000000u128);
+    helper.give_me_money(&[zap_asset.clone()], &user);
+    helper.zap_in(&user, &zap_asset, None, false).unwrap();
+
+    // Stable pool uses all deposits so nothing is left
+    let manager = helper.liquidity_manager.clone();
+    assert_eq!(helper.coin_balance(&test_coins[0], &user), 0);
+    assert_eq!(helper.coin_balance(&test_coins[1], &user), 0);
+    assert_eq!(helper.coin_balance(&test_coins[0], &manager), 0);
+    assert_eq!(helper.coin_balance(&test_coins[1], &manager), 0);
+
+    let lp_amount = helper.token_balance(&helper.lp_token, &user);
+    helper.withdraw_liquidity(&user, lp_amount, None).unwrap();
+    let total =
+        helper.coin_balance(&test_coins[0], &user) + helper.coin_balance(&test_coins[1], &user);
+    assert!(total > 950_000000, "total {total}");
+}
+
+#[test]
+fn test_zap_out() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("TEST")];
+    let mut helper = Helper::new(
+        &owner,
+        test_coins.clone(),
+        PoolParams::Constant(XYKPoolParams {
+            track_asset_balances: None,
+        }),
+    )
+    .unwrap();
+
+    helper
+        .provide_liquidity(
+            &owner,
+            &[
+                helper.assets[&test_coins[0]].with_balance(100_000_000000_u128),
+                helper.assets[&test_coins[1]].with_balance(100_000_000000_u128),
+            ],
+            None,
+        )
+        .unwrap();
+
+    let user = Addr::unchecked("user");
+    let provide_assets = [
+        helper.assets[&test_coins[0]].with_balance(1000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(1000_000000u128),
+    ];
+    helper.give_me_money(&provide_assets, &user);
+    helper
+        .provide_liquidity(&user, &provide_assets, None)
+        .unwrap();
+    let lp_amount = helper.token_balance(&helper.lp_token, &user);
+
+    let target_asset = helper.assets[&test_coins[0]].clone();
+    let sim_amount = helper.simulate_zap_out(lp_amount, &target_asset).unwrap();
+
+    // Asset which is not in the pair can't be the target
+    let err = helper
+        .zap_out(
+            &user,
+            lp_amount,
+            &native_asset_info("random".to_string()),
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::AssetNotInPair("random".to_string()),
+        err.downcast().unwrap()
+    );
+
+    let err = helper
+        .zap_out(
+            &user,
+            lp_amount,
+            &target_asset,
+            Some(sim_amount + Uint128::one()),
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::WithdrawSlippageViolation {
+            asset_name: target_asset.to_string(),
+            received: sim_amount,
+            expected: sim_amount + Uint128::one(),
+        },
+        err.downcast().unwrap()
+    );
+
+    // Coins which were sent to the manager directly are not part of the zap-out
+    let manager = helper.liquidity_manager.clone();
+    let donation = [
+        helper.assets[&test_coins[0]].with_balance(1_000000u128),
+        helper.assets[&test_coins[1]].with_balance(2_000000u128),
+    ];
+    helper.give_me_money(&donation, &manager);
+
+    helper
+        .zap_out(&user, lp_amount, &target_asset, Some(sim_amount))
+        .unwrap();
+    assert_eq!(
+        helper.coin_balance(&test_coins[0], &user),
+        sim_amount.u128()
+    );
+    assert_eq!(helper.coin_balance(&test_coins[1], &user), 0);
+    assert_eq!(helper.token_balance(&helper.lp_token, &user), 0);
+
+    assert_eq!(helper.coin_balance(&test_coins[0], &manager), 1_000000);
+    assert_eq!(helper.coin_balance(&test_coins[1], &manager), 2_000000);
+}
+
+#[test]
+fn test_concentrated() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("FOO")];
+    let mut helper = Helper::new(
+        &owner,
+        test_coins.clone(),
+        PoolParams::Concentrated(ConcentratedPoolParams {
+            amp: f64_to_dec(40f64),
+            gamma: f64_to_dec(0.000145),
+            mid_fee: f64_to_dec(0.0026),
+            out_fee: f64_to_dec(0.0045),
+            fee_gamma: f64_to_dec(0.00023),
+            repeg_profit_threshold: f64_to_dec(0.000002),
+            min_price_scale_delta: f64_to_dec(0.000146),
+            price_scale: Decimal::from_ratio(2u8, 1u8),
+            ma_half_time: 600,
+            track_asset_balances: None,
+        }),
+    )
+    .unwrap();
+
+    // Initial provide is simulated precisely as well
+    let initial_assets = [
+        helper.assets[&test_coins[0]].with_balance(100_000_000000_u128),
+        helper.assets[&test_coins[1]].with_balance(50_000_000000_u128),
+    ];
+    let sim_lp_amount = helper.simulate_provide(None, &initial_assets).unwrap();
+    helper
+        .provide_liquidity(&owner, &initial_assets, Some(sim_lp_amount))
+        .unwrap();
+    assert_eq!(
+        helper.token_balance(&helper.lp_token, &owner),
+        sim_lp_amount.u128()
+    );
+
+    // Balanced provide
+    let user = Addr::unchecked("user");
+    let provide_assets = [
+        helper.assets[&test_coins[0]].with_balance(100_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000000u128),
+    ];
+    let sim_lp_amount = helper.simulate_provide(None, &provide_assets).unwrap();
+    helper.give_me_money(&provide_assets, &user);
+    helper
+        .provide_liquidity(&user, &provide_assets, Some(sim_lp_amount))
+        .unwrap();
+    let user_lp_bal = helper.token_balance(&helper.lp_token, &user);
+    assert_eq!(user_lp_bal, sim_lp_amount.u128());
+
+    // Imbalanced provide
+    let provide_assets = [
+        helper.assets[&test_coins[0]].with_balance(100_000000u128),
+        helper.assets[&test_coins[1]].with_balance(10_000000u128),
+    ];
+    let sim_lp_amount = helper
+        .simulate_provide(Some(f64_to_dec(0.02)), &provide_assets)
+        .unwrap();
+    helper.give_me_money(&provide_assets, &user);
+    helper
+        .provide_liquidity_with_slip_tolerance(
+            &user,
+            &provide_assets,
+            Some(f64_to_dec(0.02)),
+            Some(sim_lp_amount),
+            false,
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        helper.token_balance(&helper.lp_token, &user),
+        user_lp_bal + sim_lp_amount.u128()
+    );
+
+    // Simulation is a subject of the same slippage check as the real provide
+    let huge_provide = [
+        helper.assets[&test_coins[0]].with_balance(100_000_000000_u128),
+        helper.assets[&test_coins[1]].with_balance(1_000000u128),
+    ];
+    helper
+        .simulate_provide(Some(f64_to_dec(0.02)), &huge_provide)
+        .unwrap_err();
+
+    // Pool is imbalanced right before the victim's provide
+    let provide_assets = [
+        helper.assets[&test_coins[0]].with_balance(100_000000u128),
+        helper.assets[&test_coins[1]].with_balance(50_000000u128),
+    ];
+    let sim_lp_amount = helper.simulate_provide(None, &provide_assets).unwrap();
+
+    let attacker = Addr::unchecked("attacker");
+    let swap_asset = helper.assets[&test_coins[0]].with_balance(10_000_000000_u128);
+    helper.give_me_money(&[swap_asset.clone()], &attacker);
+    helper
+        .swap(&attacker, &swap_asset, Some(f64_to_dec(0.5)))
+        .unwrap();
+
+    let victim = Addr::unchecked("victim");
+    helper.give_me_money(&provide_assets, &victim);
+    let err = helper
+        .provide_liquidity_with_slip_tolerance(
+            &victim,
+            &provide_assets,
+            Some(f64_to_dec(0.5)),
+            Some(sim_lp_amount),
+            false,
+            None,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast().unwrap(),
+        ContractError::ProvideSlippageViolation(..)
+    ));
+
+    let manager = helper.liquidity_manager.clone();
+    assert_eq!(helper.coin_balance(&test_coins[0], &manager), 0);
+    assert_eq!(helper.coin_balance(&test_coins[1], &manager), 0);
+}
+
+#[test]
+fn test_migrate_liquidity() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("TEST")];
+    let mut helper = Helper::new(
+        &owner,
+        test_coins.clone(),
+        PoolParams::Constant(XYKPoolParams {
+            track_asset_balances: None,
+        }),
+    )
+    .unwrap();
+
+    helper
+        .provide_liquidity(
+            &owner,
+            &[
+                helper.assets[&test_coins[0]].with_balance(100_000_000000_u128),
+                helper.assets[&test_coins[1]].with_balance(100_000_000000_u128),
+            ],
+            None,
+        )
+        .unwrap();
+
+    let stable_pair = helper
+        .create_pair(PoolParams::Stable(StablePoolParams {
+            amp: 40,
+            owner: None,
+            rate_sources: None,
+        }))
+        .unwrap();
+
+    // Migrating to the same pair is not allowed
+    let xyk_pair = helper.pair_addr.clone();
+    let err = helper
+        .migrate_liquidity(&owner, &xyk_pair, 1000_000000, false, None, true)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidMigrationPair {},
+        err.downcast().unwrap()
+    );
+
+    // Owner moves half of the liquidity to the stable pair
+    let owner_lp_bal = helper.token_balance(&helper.lp_token, &owner);
+    let sim_withdraw = helper.simulate_withdraw(owner_lp_bal / 2).unwrap();
+    helper
+        .migrate_liquidity(
+            &owner,
+            &stable_pair.contract_addr,
+            owner_lp_bal / 2,
+            false,
+            Some(1u128.into()),
+            true,
+        )
+        .unwrap();
+    assert_eq!(
+        helper.token_balance(&helper.lp_token, &owner),
+        owner_lp_bal - owner_lp_bal / 2
+    );
+    let stable_pools = stable_pair
+        .query_pools(&helper.app.wrap(), &stable_pair.contract_addr)
+        .unwrap();
+    for pool in stable_pools {
+        let withdrawn = sim_withdraw
+            .iter()
+            .find(|asset| asset.info == pool.info)
+            .unwrap();
+        assert_eq!(pool.amount, withdrawn.amount);
+    }
+    let owner_staked = helper
+        .query_staked_lp_of(&stable_pair.liquidity_token, &owner)
+        .unwrap();
+    assert!(!owner_staked.is_zero());
+    assert_eq!(
+        helper.token_balance(&stable_pair.liquidity_token, &owner),
+        0
+    );
+
+    // User migrates without staking and the slippage check fails
+    let user = Addr::unchecked("user");
+    let provide_assets = [
+        helper.assets[&test_coins[0]].with_balance(100_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000000u128),
+    ];
+    helper.give_me_money(&provide_assets, &user);
+    helper
+        .provide_liquidity(&user, &provide_assets, None)
+        .unwrap();
+    let user_lp_bal = helper.token_balance(&helper.lp_token, &user);
+
+    let err = helper
+        .migrate_liquidity(
+            &user,
+            &stable_pair.contract_addr,
+            user_lp_bal,
+            false,
+            Some(1000_000000u128.into()),
+            false,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast().unwrap(),
+        ContractError::ProvideSlippageViolation(..)
+    ));
+
+    // Coins which were sent to the manager directly are not provided
+    let manager = helper.liquidity_manager.clone();
+    let donation = [
+        helper.assets[&test_coins[0]].with_balance(1_000000u128),
+        helper.assets[&test_coins[1]].with_balance(2_000000u128),
+    ];
+    helper.give_me_money(&donation, &manager);
+
+    let sim_withdraw = helper.simulate_withdraw(user_lp_bal).unwrap();
+    let stable_pools_before = stable_pair
+        .query_pools(&helper.app.wrap(), &stable_pair.contract_addr)
+        .unwrap();
+    helper
+        .migrate_liquidity(
+            &user,
+            &stable_pair.contract_addr,
+            user_lp_bal,
+            false,
+            Some(190_000000u128.into()),
+            false,
+        )
+        .unwrap();
+    assert_eq!(helper.token_balance(&helper.lp_token, &user), 0);
+    assert!(helper.token_balance(&stable_pair.liquidity_token, &user) >= 190_000000);
+    let stable_pools = stable_pair
+        .query_pools(&helper.app.wrap(), &stable_pair.contract_addr)
+        .unwrap();
+    for (pool, pool_before) in stable_pools.iter().zip(stable_pools_before.iter()) {
+        let withdrawn = sim_withdraw
+            .iter()
+            .find(|asset| asset.info == pool.info)
+            .unwrap();
+        assert_eq!(pool.amount, pool_before.amount + withdrawn.amount);
+    }
+
+    assert_eq!(helper.coin_balance(&test_coins[0], &manager), 1_000000);
+    assert_eq!(helper.coin_balance(&test_coins[1], &manager), 2_000000);
+    assert_eq!(helper.token_balance(&helper.lp_token, &manager), 0);
+}
+
+#[test]
+fn test_migrate_staked_liquidity() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("TEST")];
+    let mut helper = Helper::new(
+        &owner,
+        test_coins.clone(),
+        PoolParams::Constant(XYKPoolParams {
+            track_asset_balances: None,
+        }),
+    )
+    .unwrap();
+
+    helper
+        .provide_liquidity(
+            &owner,
+            &[
+                helper.assets[&test_coins[0]].with_balance(100_000_000000_u128),
+                helper.assets[&test_coins[1]].with_balance(100_000_000000_u128),
+            ],
+            None,
+        )
+        .unwrap();
+
+    let user = Addr::unchecked("user");
+    let provide_assets = [
+        helper.assets[&test_coins[0]].with_balance(100_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000000u128),
+    ];
+    helper.give_me_money(&provide_assets, &user);
+    helper
+        .provide_liquidity_with_slip_tolerance(
+            &user,
+            &provide_assets,
+            Some(f64_to_dec(0.5)),
+            None,
+            true,
+            None,
+        )
+        .unwrap();
+    let user_staked = helper.query_staked_lp(&user).unwrap();
+    assert_eq!(user_staked.u128(), 100_000000);
+    assert_eq!(helper.token_balance(&helper.lp_token, &user), 0);
+
+    let stable_pair = helper
+        .create_pair(PoolParams::Stable(StablePoolParams {
+            amp: 40,
+            owner: None,
+            rate_sources: None,
+        }))
+        .unwrap();
+
+    // Staked LP tokens can't be withdrawn without an allowance
+    let err = helper
+        .app
+        .execute_contract(
+            user.clone(),
+            helper.liquidity_manager.clone(),
+            &ExecuteMsg::MigrateLiquidity {
+                from_pair: helper.pair_addr.to_string(),
+                to_pair: stable_pair.contract_addr.to_string(),
+                amount: user_staked,
+                staked: Some(true),
+                min_lp_out: None,
+                auto_stake: None,
+                receiver: None,
+                deadline: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        GeneratorContractError::WithdrawAllowanceTooSmall {}.to_string()
+    );
+
+    // User moves half of the staked liquidity to the stable pair
+    let sim_withdraw = helper.simulate_withdraw(user_staked.u128() / 2).unwrap();
+    helper
+        .migrate_liquidity(
+            &user,
+            &stable_pair.contract_addr,
+            user_staked.u128() / 2,
+            true,
+            Some(1u128.into()),
+            true,
+        )
+        .unwrap();
+
+    assert_eq!(
+        helper.query_staked_lp(&user).unwrap(),
+        user_staked - user_staked / Uint128::new(2)
+    );
+    assert_eq!(helper.token_balance(&helper.lp_token, &user), 0);
+    let stable_pools = stable_pair
+        .query_pools(&helper.app.wrap(), &stable_pair.contract_addr)
+        .unwrap();
+    for pool in stable_pools {
+        let withdrawn = sim_withdraw
+            .iter()
+            .find(|asset| asset.info == pool.info)
+            .unwrap();
+        assert_eq!(pool.amount, withdrawn.amount);
+    }
+    let user_staked_stable = helper
+        .query_staked_lp_of(&stable_pair.liquidity_token, &user)
+        .unwrap();
+    assert!(!user_staked_stable.is_zero());
+
+    // The allowance is spent
+    let allowance: Uint128 = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &helper.generator,
+            &GeneratorQueryMsg::WithdrawAllowance {
+                lp_token: helper.lp_token.to_string(),
+                owner: user.to_string(),
+                spender: helper.liquidity_manager.to_string(),
+            },
+        )
+        .unwrap();
+    assert!(allowance.is_zero());
+
+    let manager = helper.liquidity_manager.clone();
+    assert_eq!(helper.coin_balance(&test_coins[0], &manager), 0);
+    assert_eq!(helper.coin_balance(&test_coins[1], &manager), 0);
+    assert_eq!(helper.token_balance(&helper.lp_token, &manager), 0);
+}
@@ -5,6 +5,7 @@ use crate::state::{
     get_precision, store_precisions, Config, PriceCumulativeLast, CONFIG, PRICE_LAST,
 };
 use astroport::asset::{Asset, AssetInfo};
+use astroport::factory::PairType;
 use astroport::oracle::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use astroport::pair::TWAP_PRECISION;
 use astroport::querier::query_pair_info;
@@ -39,7 +40,14 @@ pub fn instantiate(
     }
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    let pair_info = query_pair_info(&deps.querier, &factory_contract, &msg.asset_infos)?;
+    // The oracle tracks the x*y=k pool even if pools of other types exist for the assets
+    let pair_info = query_pair_info(
+        &deps.querier,
+        &factory_contract,
+        &msg.asset_infos,
+        Some(PairType::Xyk {}),
+        None,
+    )?;
 
     let config = Config {
         owner: info.sender,
@@ -101,12 +101,13 @@ impl WasmMockQuerier {
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
                 if contract_addr == "factory" {
                     match from_binary(&msg).unwrap() {
-                        Pair { asset_infos } => SystemResult::Ok(
+                        Pair { asset_infos, .. } => SystemResult::Ok(
                             to_binary(&PairInfo {
                                 asset_infos,
                                 contract_addr: Addr::unchecked("pair"),
                                 liquidity_token: Addr::unchecked("lp_token"),
                                 pair_type: PairType::Xyk {},
+                                fee_tier: None,
                             })
                             .into(),
                         ),
@@ -149,6 +149,7 @@ fn instantiate_contracts(mut router: &mut App, owner: Addr) -> (Addr, Addr, u64)
                 maker_fee_bps: 0,
                 is_disabled: false,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             },
             PairConfig {
                 code_id: pair_stable_code_id,
@@ -157,6 +158,7 @@ fn instantiate_contracts(mut router: &mut App, owner: Addr) -> (Addr, Addr, u64)
                 maker_fee_bps: 0,
                 is_disabled: false,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             },
         ],
         token_code_id: 1u64,
@@ -320,6 +322,7 @@ fn provide_liquidity(
             slippage_tolerance: None,
             auto_stake: None,
             receiver: None,
+            deadline: None,
         },
         &funds,
     )
@@ -359,6 +362,8 @@ fn create_pair(
                 pair_type: PairType::Xyk {},
                 asset_infos: asset_infos.clone(),
                 init_params: None,
+                fee: None,
+                fee_tier: None,
             },
             &[],
         )
@@ -384,6 +389,8 @@ fn create_pair(
             contract_addr: factory_instance.clone().to_string(),
             msg: to_binary(&astroport::factory::QueryMsg::Pair {
                 asset_infos: asset_infos.clone(),
+                pair_type: None,
+                fee_tier: None,
             })
             .unwrap(),
         }))
@@ -437,6 +444,7 @@ fn change_provide_liquidity(
                 slippage_tolerance: Some(Decimal::percent(50)),
                 auto_stake: None,
                 receiver: None,
+                deadline: None,
             },
             &vec![],
         )
@@ -506,6 +514,8 @@ fn consult() {
             contract_addr: factory_instance.clone().to_string(),
             msg: to_binary(&astroport::factory::QueryMsg::Pair {
                 asset_infos: asset_infos.clone(),
+                pair_type: None,
+                fee_tier: None,
             })
             .unwrap(),
         }))
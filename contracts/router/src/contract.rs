@@ -1,26 +1,37 @@
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Addr, Api, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Response, StdResult, Uint128, WasmMsg,
+    attr, coin, entry_point, from_binary, to_binary, Addr, Api, Binary, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, IbcMsg, IbcTimeout, MessageInfo, QuerierWrapper, Response, StdResult, Uint128,
+    WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
-use cw20::Cw20ReceiveMsg;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
-use astroport::asset::{addr_opt_validate, Asset, AssetInfo};
-use astroport::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
-use astroport::querier::query_pair_info;
+use astroport::asset::{addr_opt_validate, Asset, AssetInfo, PairInfo};
+use astroport::common::is_deadline_passed;
+use astroport::factory::PairType;
+use astroport::pair::{QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse};
+use astroport::querier::{query_pair_info, query_pairs_info};
 use astroport::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
-    SimulateSwapOperationsResponse, SwapOperation, MAX_SWAP_OPERATIONS,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, FindBestRouteResponse, IbcDestination, InstantiateMsg,
+    MigrateMsg, NativeSwapAdapterQueryMsg, QueryMsg, Referral, ReverseSwapOperationSimulation,
+    RouteSimulation, RouteSplit, SimulateReverseSwapOperationsResponse,
+    SimulateSplitSwapOperationsResponse, SimulateSwapOperationsResponse, SwapOperation,
+    SwapOperationSimulation, SwapRoute, DEFAULT_IBC_TIMEOUT, DEFAULT_MAX_HOPS,
+    MAX_REFERRAL_FEE_BPS, MAX_ROUTE_HOPS, MAX_SWAP_OPERATIONS,
 };
 
 use crate::error::ContractError;
-use crate::operations::execute_swap_operation;
+use crate::operations::{asset_into_swap_msg, execute_swap_operation, native_swap_msg};
 use crate::state::{Config, CONFIG};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "astroport-router";
 /// Contract version that is used for migration.
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The number of pairs read from the factory per query during route discovery.
+const PAIRS_QUERY_LIMIT: u32 = 30;
+/// The maximum number of factory pairs considered during route discovery.
+const ROUTE_PAIRS_LIMIT: u32 = 150;
 
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -32,10 +43,18 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    let max_referral_fee_bps = msg.max_referral_fee_bps.unwrap_or_default();
+    if max_referral_fee_bps > MAX_REFERRAL_FEE_BPS {
+        return Err(ContractError::InvalidMaxReferralFee {});
+    }
+
     CONFIG.save(
         deps.storage,
         &Config {
             astroport_factory: deps.api.addr_validate(&msg.astroport_factory)?,
+            cw20_ics20: addr_opt_validate(deps.api, &msg.cw20_ics20)?,
+            max_referral_fee_bps,
+            native_swap_adapter: addr_opt_validate(deps.api, &msg.native_swap_adapter)?,
         },
     )?;
 
@@ -51,10 +70,22 @@ pub fn instantiate(
 /// * **ExecuteMsg::ExecuteSwapOperations {
 ///             operations,
 ///             minimum_receive,
-///             to
+///             to,
+///             max_spread,
+///             deadline,
+///             ibc_destination,
+///             referral
 ///         }** Performs swap operations with the specified parameters.
 ///
-/// * **ExecuteMsg::ExecuteSwapOperation { operation, to }** Execute a single swap operation.
+/// * **ExecuteMsg::ExecuteSplitSwapOperations {
+///             routes,
+///             minimum_receive,
+///             to,
+///             max_spread,
+///             deadline
+///         }** Splits the offer amount between several routes and swaps it through each of them.
+///
+/// * **ExecuteMsg::ExecuteSwapOperation { operation, to, prev_balance }** Execute a single swap operation.
 ///
 /// * **ExecuteMsg::AssertMinimumReceive {
 ///             asset_info,
@@ -62,6 +93,19 @@ pub fn instantiate(
 ///             minimum_receive,
 ///             receiver
 ///         }** Checks if an ask amount is higher than or equal to the minimum amount to receive.
+///
+/// * **ExecuteMsg::ChargeReferralFee {
+///             asset_info,
+///             prev_balance,
+///             referral,
+///             receiver
+///         }** Sends the referral fee and transfers the rest of the swap output to the receiver.
+///
+/// * **ExecuteMsg::ForwardIbcTransfer {
+///             asset_info,
+///             prev_balance,
+///             destination
+///         }** Sends the swap output over IBC.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -70,27 +114,73 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, msg),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ExecuteSwapOperations {
             operations,
             minimum_receive,
             to,
             max_spread,
+            deadline,
+            ibc_destination,
+            referral,
         } => execute_swap_operations(
             deps,
             env,
             info.sender,
+            info.funds.iter().map(Asset::from).collect(),
             operations,
             minimum_receive,
             to,
             max_spread,
+            deadline,
+            ibc_destination,
+            referral,
         ),
+        ExecuteMsg::ExecuteSplitSwapOperations {
+            routes,
+            minimum_receive,
+            to,
+            max_spread,
+            deadline,
+        } => {
+            let offer_asset = match info.funds.as_slice() {
+                [coin] => Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: coin.denom.clone(),
+                    },
+                    amount: coin.amount,
+                },
+                _ => return Err(ContractError::InvalidOfferFunds {}),
+            };
+
+            execute_split_swap_operations(
+                deps,
+                env,
+                info.sender,
+                offer_asset,
+                routes,
+                minimum_receive,
+                to,
+                max_spread,
+                deadline,
+            )
+        }
         ExecuteMsg::ExecuteSwapOperation {
             operation,
             to,
             max_spread,
             single,
-        } => execute_swap_operation(deps, env, info, operation, to, max_spread, single),
+            prev_balance,
+        } => execute_swap_operation(
+            deps,
+            env,
+            info,
+            operation,
+            to,
+            max_spread,
+            single,
+            prev_balance,
+        ),
         ExecuteMsg::AssertMinimumReceive {
             asset_info,
             prev_balance,
@@ -103,6 +193,25 @@ pub fn execute(
             minimum_receive,
             deps.api.addr_validate(&receiver)?,
         ),
+        ExecuteMsg::ChargeReferralFee {
+            asset_info,
+            prev_balance,
+            referral,
+            receiver,
+        } => charge_referral_fee(
+            deps,
+            env,
+            info,
+            asset_info,
+            prev_balance,
+            referral,
+            receiver,
+        ),
+        ExecuteMsg::ForwardIbcTransfer {
+            asset_info,
+            prev_balance,
+            destination,
+        } => forward_ibc_transfer(deps, env, info, asset_info, prev_balance, destination),
     }
 }
 
@@ -112,6 +221,7 @@ pub fn execute(
 pub fn receive_cw20(
     deps: DepsMut,
     env: Env,
+    info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     match from_binary(&cw20_msg.msg)? {
@@ -120,14 +230,48 @@ pub fn receive_cw20(
             minimum_receive,
             to,
             max_spread,
+            deadline,
+            ibc_destination,
+            referral,
         } => execute_swap_operations(
             deps,
             env,
             Addr::unchecked(cw20_msg.sender),
+            vec![Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender,
+                },
+                amount: cw20_msg.amount,
+            }],
             operations,
             minimum_receive,
             to,
             max_spread,
+            deadline,
+            ibc_destination,
+            referral,
+        ),
+        Cw20HookMsg::ExecuteSplitSwapOperations {
+            routes,
+            minimum_receive,
+            to,
+            max_spread,
+            deadline,
+        } => execute_split_swap_operations(
+            deps,
+            env,
+            Addr::unchecked(cw20_msg.sender),
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender,
+                },
+                amount: cw20_msg.amount,
+            },
+            routes,
+            minimum_receive,
+            to,
+            max_spread,
+            deadline,
         ),
     }
 }
@@ -136,49 +280,465 @@ pub fn receive_cw20(
 ///
 /// * **sender** address that swaps tokens.
 ///
+/// * **received** assets sent to the router along with the swap. Only the offer asset among them is swapped.
+///
 /// * **operations** all swap operations to perform.
 ///
 /// * **minimum_receive** used to guarantee that the ask amount is above a minimum amount.
 ///
 /// * **to** recipient of the ask tokens.
+///
+/// * **deadline** the latest block time (in seconds) the swap operations can be executed at.
+///
+/// * **ibc_destination** if set, the ask tokens are sent over IBC instead of being transferred to `to`.
+/// Refunds of the transfer are sent back to the router and are never swapped by later operations.
+///
+/// * **referral** if set, a referral fee is deducted from the ask tokens.
 #[allow(clippy::too_many_arguments)]
 pub fn execute_swap_operations(
     deps: DepsMut,
     env: Env,
     sender: Addr,
+    received: Vec<Asset>,
     operations: Vec<SwapOperation>,
     minimum_receive: Option<Uint128>,
     to: Option<String>,
     max_spread: Option<Decimal>,
+    deadline: Option<u64>,
+    ibc_destination: Option<IbcDestination>,
+    referral: Option<Referral>,
 ) -> Result<Response, ContractError> {
+    if is_deadline_passed(&env.block, deadline) {
+        return Err(ContractError::DeadlineExpired {});
+    }
+
     assert_operations(deps.api, &operations)?;
 
-    let to = addr_opt_validate(deps.api, &to)?.unwrap_or(sender);
+    if let Some(referral) = &referral {
+        let config = CONFIG.load(deps.storage)?;
+        assert_referral(deps.api, &config, referral)?;
+    }
+
+    let offer_asset_info = operations[0].get_offer_asset_info();
     let target_asset_info = operations.last().unwrap().get_target_asset_info();
+    let to = if let Some(destination) = &ibc_destination {
+        if to.is_some() {
+            return Err(ContractError::IbcRecipientConflict {});
+        }
+        assert_ibc_destination(deps.as_ref(), &target_asset_info, destination)?;
+
+        // The router receives the ask tokens and forwards them over IBC afterwards
+        env.contract.address.clone()
+    } else {
+        addr_opt_validate(deps.api, &to)?.unwrap_or(sender)
+    };
     let operations_len = operations.len();
+    // The referral fee is taken from the swap output received by the router
+    let swap_receiver = if referral.is_some() {
+        env.contract.address.clone()
+    } else {
+        to.clone()
+    };
+
+    // Every operation only swaps what it received, so funds already held by the router,
+    // e.g. refunds of failed IBC transfers, are never swapped or forwarded
+    let offer_asset = Asset {
+        amount: received
+            .iter()
+            .filter(|asset| asset.info.equal(&offer_asset_info))
+            .map(|asset| asset.amount)
+            .sum(),
+        info: offer_asset_info,
+    };
 
     let mut messages = operations
         .into_iter()
         .enumerate()
         .map(|(operation_index, op)| {
+            let prev_balance = idle_balance(
+                &deps.querier,
+                &env,
+                &op.get_offer_asset_info(),
+                &offer_asset,
+            )?;
+
             Ok(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: env.contract.address.to_string(),
                 funds: vec![],
                 msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
                     operation: op,
                     to: if operation_index == operations_len - 1 {
-                        Some(to.to_string())
+                        Some(swap_receiver.to_string())
                     } else {
                         None
                     },
                     max_spread,
                     single: operations_len == 1,
+                    prev_balance,
                 })?,
             }))
         })
         .collect::<StdResult<Vec<CosmosMsg>>>()?;
 
+    // The referral fee and the IBC transfer only use the swap output
+    let router_balance = if referral.is_some() || ibc_destination.is_some() {
+        idle_balance(&deps.querier, &env, &target_asset_info, &offer_asset)?
+    } else {
+        Uint128::zero()
+    };
+
+    // The receiver balance is checked before the referral fee is charged as the fee
+    // message transfers the swap output to the receiver
+    let receiver_balance = match minimum_receive {
+        Some(_) => target_asset_info.query_pool(&deps.querier, &to)?,
+        None => Uint128::zero(),
+    };
+
+    if let Some(referral) = referral {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::ChargeReferralFee {
+                asset_info: target_asset_info.clone(),
+                prev_balance: router_balance,
+                referral,
+                receiver: to.to_string(),
+            })?,
+        }));
+    }
+
     // Execute minimum amount assertion
+    if let Some(minimum_receive) = minimum_receive {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+                asset_info: target_asset_info.clone(),
+                prev_balance: receiver_balance,
+                minimum_receive,
+                receiver: to.to_string(),
+            })?,
+        }));
+    }
+
+    if let Some(destination) = ibc_destination {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::ForwardIbcTransfer {
+                asset_info: target_asset_info,
+                prev_balance: router_balance,
+                destination,
+            })?,
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages))
+}
+
+/// Returns the router balance of an asset which doesn't belong to the swap being executed.
+///
+/// * **asset_info** asset to return the balance for.
+///
+/// * **offer_asset** asset sent to the router for the swap. It is already included in the router balance.
+fn idle_balance(
+    querier: &QuerierWrapper,
+    env: &Env,
+    asset_info: &AssetInfo,
+    offer_asset: &Asset,
+) -> StdResult<Uint128> {
+    let balance = asset_info.query_pool(querier, &env.contract.address)?;
+
+    if asset_info.equal(&offer_asset.info) {
+        Ok(balance.checked_sub(offer_asset.amount)?)
+    } else {
+        Ok(balance)
+    }
+}
+
+/// Checks that the referral address is valid and the referral fee doesn't exceed the maximum.
+///
+/// * **referral** referral to check.
+fn assert_referral(
+    api: &dyn Api,
+    config: &Config,
+    referral: &Referral,
+) -> Result<(), ContractError> {
+    api.addr_validate(&referral.address)?;
+
+    if referral.fee_bps > config.max_referral_fee_bps {
+        return Err(ContractError::ReferralFeeTooHigh {
+            fee_bps: referral.fee_bps,
+            max_fee_bps: config.max_referral_fee_bps,
+        });
+    }
+
+    Ok(())
+}
+
+/// Sends the referral fee from the swap output received by the router and transfers
+/// the rest to the receiver. If the receiver is the router itself, the rest stays in the router.
+///
+/// * **asset_info** the swap output asset.
+///
+/// * **prev_balance** the router balance of the swap output asset before the swap operations.
+///
+/// * **referral** the referral taking the fee.
+///
+/// * **receiver** address that receives the swap output net of the fee.
+fn charge_referral_fee(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    prev_balance: Uint128,
+    referral: Referral,
+    receiver: String,
+) -> Result<Response, ContractError> {
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let amount = asset_info
+        .query_pool(&deps.querier, &env.contract.address)?
+        .checked_sub(prev_balance)?;
+    let fee_amount = amount.multiply_ratio(referral.fee_bps, 10000u16);
+    let receiver_amount = amount - fee_amount;
+
+    let mut messages = vec![];
+    if !fee_amount.is_zero() {
+        messages.push(
+            Asset {
+                info: asset_info.clone(),
+                amount: fee_amount,
+            }
+            .into_msg(&referral.address)?,
+        );
+    }
+    if !receiver_amount.is_zero() && receiver != env.contract.address {
+        messages.push(
+            Asset {
+                info: asset_info,
+                amount: receiver_amount,
+            }
+            .into_msg(receiver)?,
+        );
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes([
+        attr("action", "charge_referral_fee"),
+        attr("referral", referral.address),
+        attr("referral_amount", fee_amount),
+    ]))
+}
+
+/// Checks that the swap output can be sent to the specified IBC destination.
+///
+/// * **asset_info** asset to send over IBC.
+///
+/// * **destination** IBC destination of the asset.
+fn assert_ibc_destination(
+    deps: Deps,
+    asset_info: &AssetInfo,
+    destination: &IbcDestination,
+) -> Result<(), ContractError> {
+    match asset_info {
+        AssetInfo::Token { .. } => {
+            if CONFIG.load(deps.storage)?.cw20_ics20.is_none() {
+                return Err(ContractError::Cw20Ics20NotSet {});
+            }
+        }
+        AssetInfo::NativeToken { .. } => {
+            if destination.memo.is_some() {
+                return Err(ContractError::IbcMemoNotSupported {});
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends the swap output received by the router over IBC net of the referral fee.
+/// CW20 tokens are sent via the CW20-ICS20 contract, native tokens via an ICS20 transfer.
+///
+/// * **asset_info** asset to send over IBC.
+///
+/// * **prev_balance** the router balance of the asset before the swap operations.
+///
+/// * **destination** IBC destination of the asset.
+fn forward_ibc_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    prev_balance: Uint128,
+    destination: IbcDestination,
+) -> Result<Response, ContractError> {
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let amount = asset_info
+        .query_pool(&deps.querier, &env.contract.address)?
+        .checked_sub(prev_balance)?;
+    let channel = destination.channel.clone();
+
+    let message = match &asset_info {
+        AssetInfo::Token { contract_addr } => {
+            let cw20_ics20 = CONFIG
+                .load(deps.storage)?
+                .cw20_ics20
+                .ok_or(ContractError::Cw20Ics20NotSet {})?;
+
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: cw20_ics20.to_string(),
+                    amount,
+                    msg: to_binary(&destination)?,
+                })?,
+            })
+        }
+        AssetInfo::NativeToken { denom } => CosmosMsg::Ibc(IbcMsg::Transfer {
+            channel_id: destination.channel,
+            to_address: destination.remote_address,
+            amount: coin(amount.u128(), denom),
+            timeout: IbcTimeout::with_timestamp(
+                env.block
+                    .time
+                    .plus_seconds(destination.timeout.unwrap_or(DEFAULT_IBC_TIMEOUT)),
+            ),
+        }),
+    };
+
+    Ok(Response::new().add_message(message).add_attributes([
+        attr("action", "forward_ibc_transfer"),
+        attr("asset", asset_info.to_string()),
+        attr("amount", amount),
+        attr("channel", channel),
+    ]))
+}
+
+/// Splits the offer amount between several routes and performs swap operations for each of them.
+/// Routes are executed one after another and the minimum amount to receive is checked against
+/// the total return of all routes.
+///
+/// * **sender** address that swaps tokens.
+///
+/// * **offer_asset** asset sent to the router along with the total amount to swap.
+///
+/// * **routes** routes to swap through.
+///
+/// * **minimum_receive** used to guarantee that the total ask amount is above a minimum amount.
+///
+/// * **to** recipient of the ask tokens.
+///
+/// * **deadline** the latest block time (in seconds) the swap operations can be executed at.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_split_swap_operations(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    routes: Vec<SwapRoute>,
+    minimum_receive: Option<Uint128>,
+    to: Option<String>,
+    max_spread: Option<Decimal>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    if is_deadline_passed(&env.block, deadline) {
+        return Err(ContractError::DeadlineExpired {});
+    }
+
+    let (offer_asset_info, target_asset_info) = assert_routes(deps.api, &routes)?;
+    if offer_asset.info != offer_asset_info {
+        return Err(ContractError::InvalidOfferFunds {});
+    }
+
+    let amounts = resolve_route_amounts(&routes, offer_asset.amount)?;
+    let to = addr_opt_validate(deps.api, &to)?.unwrap_or(sender);
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut messages = vec![];
+    for (route, amount) in routes.into_iter().zip(amounts) {
+        let operations_len = route.operations.len();
+        let mut operations = route.operations.into_iter();
+
+        // The first hop swaps the exact amount allocated to the route
+        let first_hop_to = if operations_len == 1 {
+            Some(to.to_string())
+        } else {
+            None
+        };
+        let first_hop_msg = match operations.next().unwrap() {
+            SwapOperation::AstroSwap {
+                offer_asset_info,
+                ask_asset_info,
+                pair_type,
+                fee_tier,
+            } => {
+                let pair_info = query_pair_info(
+                    &deps.querier,
+                    config.astroport_factory.clone(),
+                    &[offer_asset_info.clone(), ask_asset_info.clone()],
+                    pair_type,
+                    fee_tier,
+                )?;
+                asset_into_swap_msg(
+                    pair_info.contract_addr.to_string(),
+                    Asset {
+                        info: offer_asset_info,
+                        amount,
+                    },
+                    ask_asset_info,
+                    max_spread,
+                    first_hop_to,
+                    operations_len == 1,
+                )?
+            }
+            SwapOperation::NativeSwap {
+                offer_denom,
+                ask_denom,
+            } => native_swap_msg(
+                config.native_swap_adapter()?,
+                coin(amount.u128(), offer_denom),
+                ask_denom,
+                max_spread,
+                first_hop_to,
+                operations_len == 1,
+            )?,
+        };
+        messages.push(first_hop_msg);
+
+        // Subsequent hops swap everything received from the previous hop
+        for (operation_index, op) in operations.enumerate() {
+            let prev_balance = idle_balance(
+                &deps.querier,
+                &env,
+                &op.get_offer_asset_info(),
+                &offer_asset,
+            )?;
+
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: op,
+                    to: if operation_index == operations_len - 2 {
+                        Some(to.to_string())
+                    } else {
+                        None
+                    },
+                    max_spread,
+                    single: false,
+                    prev_balance,
+                })?,
+            }));
+        }
+    }
+
+    // Execute minimum amount assertion over all routes
     if let Some(minimum_receive) = minimum_receive {
         let receiver_balance = target_asset_info.query_pool(&deps.querier, &to)?;
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -233,6 +793,19 @@ fn assert_minimum_receive(
 ///             offer_amount,
 ///             operations,
 ///         }** Simulates one or multiple swap operations and returns the end result in a [`SimulateSwapOperationsResponse`] object.
+/// * **QueryMsg::SimulateReverseSwapOperations {
+///             ask_amount,
+///             operations,
+///         }** Returns the amount to offer for one or multiple swap operations in a [`SimulateReverseSwapOperationsResponse`] object.
+/// * **QueryMsg::SimulateSplitSwapOperations {
+///             offer_amount,
+///             routes,
+///         }** Simulates a swap split between several routes and returns the result in a [`SimulateSplitSwapOperationsResponse`] object.
+/// * **QueryMsg::FindBestRoute {
+///             offer_asset,
+///             ask_asset_info,
+///             max_hops,
+///         }** Looks for the route with the highest return and returns it in a [`FindBestRouteResponse`] object.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
@@ -240,10 +813,36 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
         QueryMsg::SimulateSwapOperations {
             offer_amount,
             operations,
+            referral,
         } => Ok(to_binary(&simulate_swap_operations(
             deps,
             offer_amount,
             operations,
+            referral,
+        )?)?),
+        QueryMsg::SimulateReverseSwapOperations {
+            ask_amount,
+            operations,
+        } => Ok(to_binary(&simulate_reverse_swap_operations(
+            deps, ask_amount, operations,
+        )?)?),
+        QueryMsg::SimulateSplitSwapOperations {
+            offer_amount,
+            routes,
+        } => Ok(to_binary(&simulate_split_swap_operations(
+            deps,
+            offer_amount,
+            routes,
+        )?)?),
+        QueryMsg::FindBestRoute {
+            offer_asset,
+            ask_asset_info,
+            max_hops,
+        } => Ok(to_binary(&find_best_route(
+            deps,
+            offer_asset,
+            ask_asset_info,
+            max_hops,
         )?)?),
     }
 }
@@ -253,6 +852,9 @@ pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
     let state = CONFIG.load(deps.storage)?;
     let resp = ConfigResponse {
         astroport_factory: state.astroport_factory.into_string(),
+        cw20_ics20: state.cw20_ics20.map(Addr::into_string),
+        max_referral_fee_bps: state.max_referral_fee_bps,
+        native_swap_adapter: state.native_swap_adapter.map(Addr::into_string),
     };
 
     Ok(resp)
@@ -260,17 +862,32 @@ pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
 
 /// Manages contract migration.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     let contract_version = get_contract_version(deps.storage)?;
 
     match contract_version.contract.as_ref() {
         "astroport-router" => match contract_version.version.as_ref() {
-            "1.0.0" | "1.1.0" | "1.1.1" => {}
+            "1.0.0" | "1.1.0" | "1.1.1" | "1.1.2" => {}
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
     };
 
+    let mut config = CONFIG.load(deps.storage)?;
+    if let Some(cw20_ics20) = msg.cw20_ics20 {
+        config.cw20_ics20 = Some(deps.api.addr_validate(&cw20_ics20)?);
+    }
+    if let Some(max_referral_fee_bps) = msg.max_referral_fee_bps {
+        if max_referral_fee_bps > MAX_REFERRAL_FEE_BPS {
+            return Err(ContractError::InvalidMaxReferralFee {});
+        }
+        config.max_referral_fee_bps = max_referral_fee_bps;
+    }
+    if let Some(native_swap_adapter) = msg.native_swap_adapter {
+        config.native_swap_adapter = Some(deps.api.addr_validate(&native_swap_adapter)?);
+    }
+    CONFIG.save(deps.storage, &config)?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::new()
@@ -282,36 +899,49 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
 
 /// Returns the end result of a simulation for one or multiple swap
 /// operations using a [`SimulateSwapOperationsResponse`] object.
+/// The result also contains a breakdown of every operation and the aggregate price impact.
 ///
 /// * **offer_amount** amount of offer assets being swapped.
 ///
 /// * **operations** is a vector that contains objects of type [`SwapOperation`].
 /// These are all the swap operations for which we perform a simulation.
+///
+/// * **referral** if set, the referral fee is deducted from the end result.
 fn simulate_swap_operations(
     deps: Deps,
     offer_amount: Uint128,
     operations: Vec<SwapOperation>,
+    referral: Option<Referral>,
 ) -> Result<SimulateSwapOperationsResponse, ContractError> {
     assert_operations(deps.api, &operations)?;
 
     let config = CONFIG.load(deps.storage)?;
-    let astroport_factory = config.astroport_factory;
+    if let Some(referral) = &referral {
+        assert_referral(deps.api, &config, referral)?;
+    }
     let mut return_amount = offer_amount;
+    let mut simulations = Vec::with_capacity(operations.len());
+    // The share of the output left after spread compared to swapping at spot prices
+    let mut spot_ratio = Decimal::one();
 
     for operation in operations.into_iter() {
-        match operation {
+        let (pair_address, res): (Addr, SimulationResponse) = match operation {
             SwapOperation::AstroSwap {
                 offer_asset_info,
                 ask_asset_info,
+                pair_type,
+                fee_tier,
             } => {
                 let pair_info = query_pair_info(
                     &deps.querier,
-                    astroport_factory.clone(),
+                    config.astroport_factory.clone(),
                     &[offer_asset_info.clone(), ask_asset_info.clone()],
+                    pair_type,
+                    fee_tier,
                 )?;
 
-                let res: SimulationResponse = deps.querier.query_wasm_smart(
-                    pair_info.contract_addr,
+                let res = deps.querier.query_wasm_smart(
+                    &pair_info.contract_addr,
                     &PairQueryMsg::Simulation {
                         offer_asset: Asset {
                             info: offer_asset_info.clone(),
@@ -321,19 +951,430 @@ fn simulate_swap_operations(
                     },
                 )?;
 
-                return_amount = res.return_amount;
+                (pair_info.contract_addr, res)
             }
-            SwapOperation::NativeSwap { .. } => {
-                return Err(ContractError::NativeSwapNotSupported {})
+            SwapOperation::NativeSwap {
+                offer_denom,
+                ask_denom,
+            } => {
+                let adapter = config.native_swap_adapter()?;
+                let res = deps.querier.query_wasm_smart(
+                    adapter,
+                    &NativeSwapAdapterQueryMsg::Simulation {
+                        offer: coin(return_amount.u128(), offer_denom),
+                        ask_denom,
+                    },
+                )?;
+
+                (adapter.clone(), res)
             }
+        };
+
+        let spot_return = res.return_amount + res.spread_amount + res.commission_amount;
+        if !spot_return.is_zero() {
+            spot_ratio *=
+                Decimal::from_ratio(res.return_amount + res.commission_amount, spot_return);
         }
+
+        simulations.push(SwapOperationSimulation {
+            pair_address,
+            offer_amount: return_amount,
+            return_amount: res.return_amount,
+            spread_amount: res.spread_amount,
+            commission_amount: res.commission_amount,
+        });
+        return_amount = res.return_amount;
     }
 
+    let referral_amount = referral
+        .map(|referral| return_amount.multiply_ratio(referral.fee_bps, 10000u16))
+        .unwrap_or_default();
+
     Ok(SimulateSwapOperationsResponse {
-        amount: return_amount,
+        amount: return_amount - referral_amount,
+        referral_amount,
+        operations: simulations,
+        price_impact: Decimal::one() - spot_ratio,
+    })
+}
+
+/// Returns the amount of offer assets required to receive `ask_amount` from one or multiple
+/// swap operations using a [`SimulateReverseSwapOperationsResponse`] object.
+/// Operations are simulated backwards, starting from the last one.
+///
+/// * **ask_amount** amount of ask assets to receive.
+///
+/// * **operations** is a vector that contains objects of type [`SwapOperation`].
+/// These are all the swap operations for which we perform a simulation.
+fn simulate_reverse_swap_operations(
+    deps: Deps,
+    ask_amount: Uint128,
+    operations: Vec<SwapOperation>,
+) -> Result<SimulateReverseSwapOperationsResponse, ContractError> {
+    assert_operations(deps.api, &operations)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut simulations = Vec::with_capacity(operations.len());
+    let mut required_amount = ask_amount;
+
+    for operation in operations.into_iter().rev() {
+        let (pair_address, res): (Addr, ReverseSimulationResponse) = match operation {
+            SwapOperation::AstroSwap {
+                offer_asset_info,
+                ask_asset_info,
+                pair_type,
+                fee_tier,
+            } => {
+                let pair_info = query_pair_info(
+                    &deps.querier,
+                    config.astroport_factory.clone(),
+                    &[offer_asset_info.clone(), ask_asset_info.clone()],
+                    pair_type,
+                    fee_tier,
+                )?;
+
+                let res = deps.querier.query_wasm_smart(
+                    &pair_info.contract_addr,
+                    &PairQueryMsg::ReverseSimulation {
+                        offer_asset_info: Some(offer_asset_info),
+                        ask_asset: Asset {
+                            info: ask_asset_info,
+                            amount: required_amount,
+                        },
+                    },
+                )?;
+
+                (pair_info.contract_addr, res)
+            }
+            SwapOperation::NativeSwap {
+                offer_denom,
+                ask_denom,
+            } => {
+                let adapter = config.native_swap_adapter()?;
+                let res = deps.querier.query_wasm_smart(
+                    adapter,
+                    &NativeSwapAdapterQueryMsg::ReverseSimulation {
+                        offer_denom,
+                        ask: coin(required_amount.u128(), ask_denom),
+                    },
+                )?;
+
+                (adapter.clone(), res)
+            }
+        };
+
+        simulations.push(ReverseSwapOperationSimulation {
+            pair_address,
+            offer_amount: res.offer_amount,
+            ask_amount: required_amount,
+            spread_amount: res.spread_amount,
+            commission_amount: res.commission_amount,
+        });
+        required_amount = res.offer_amount;
+    }
+
+    simulations.reverse();
+
+    Ok(SimulateReverseSwapOperationsResponse {
+        offer_amount: required_amount,
+        operations: simulations,
+    })
+}
+
+/// Returns the result of a simulation for a swap split between several routes
+/// using a [`SimulateSplitSwapOperationsResponse`] object.
+/// Every route is simulated independently, so the result doesn't account for routes
+/// sharing the same pools.
+///
+/// * **offer_amount** total amount of offer assets being swapped.
+///
+/// * **routes** routes to swap through.
+fn simulate_split_swap_operations(
+    deps: Deps,
+    offer_amount: Uint128,
+    routes: Vec<SwapRoute>,
+) -> Result<SimulateSplitSwapOperationsResponse, ContractError> {
+    assert_routes(deps.api, &routes)?;
+    let amounts = resolve_route_amounts(&routes, offer_amount)?;
+
+    let routes = routes
+        .into_iter()
+        .zip(amounts)
+        .map(|(route, offer_amount)| {
+            let res = simulate_swap_operations(deps, offer_amount, route.operations, None)?;
+            Ok(RouteSimulation {
+                offer_amount,
+                return_amount: res.amount,
+            })
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    Ok(SimulateSplitSwapOperationsResponse {
+        amount: routes.iter().map(|route| route.return_amount).sum(),
+        routes,
+    })
+}
+
+/// Looks for the swap operations that return the most ask tokens using a [`FindBestRouteResponse`] object.
+/// Routes are extended one hop at a time and, for every asset reached at a given depth,
+/// only the route returning the most tokens is extended further.
+///
+/// * **offer_asset** asset that is swapped along with the amount to swap.
+///
+/// * **ask_asset_info** asset that is swapped to.
+///
+/// * **max_hops** maximum number of hops in a route.
+fn find_best_route(
+    deps: Deps,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    max_hops: Option<u32>,
+) -> Result<FindBestRouteResponse, ContractError> {
+    offer_asset.info.check(deps.api)?;
+    ask_asset_info.check(deps.api)?;
+
+    if offer_asset.info.equal(&ask_asset_info) {
+        return Err(ContractError::DoublingAssetsPath {
+            offer_asset: offer_asset.info.to_string(),
+            ask_asset: ask_asset_info.to_string(),
+        });
+    }
+
+    let max_hops = max_hops
+        .unwrap_or(DEFAULT_MAX_HOPS)
+        .clamp(1, MAX_ROUTE_HOPS);
+    let config = CONFIG.load(deps.storage)?;
+    let pools = query_swap_pools(&deps.querier, &config.astroport_factory)?;
+
+    let mut best_route: Option<FindBestRouteResponse> = None;
+    let mut routes = vec![(offer_asset.clone(), vec![])];
+
+    for _ in 0..max_hops {
+        let mut next_routes: Vec<(Asset, Vec<SwapOperation>)> = vec![];
+
+        for (asset, operations) in &routes {
+            for pair in &pools {
+                let (first, second) = match pair.asset_infos.as_slice() {
+                    [first, second] => (first, second),
+                    _ => continue,
+                };
+                let next_asset = if *first == asset.info {
+                    second
+                } else if *second == asset.info {
+                    first
+                } else {
+                    continue;
+                };
+
+                // Only simple paths are searched so that every pool is used at most once
+                if operations
+                    .iter()
+                    .any(|op| op.get_offer_asset_info() == *next_asset)
+                {
+                    continue;
+                }
+
+                let return_amount = match deps.querier.query_wasm_smart::<SimulationResponse>(
+                    &pair.contract_addr,
+                    &PairQueryMsg::Simulation {
+                        offer_asset: asset.clone(),
+                        ask_asset_info: Some(next_asset.clone()),
+                    },
+                ) {
+                    Ok(res) if !res.return_amount.is_zero() => res.return_amount,
+                    _ => continue,
+                };
+
+                let mut next_operations = operations.clone();
+                next_operations.push(SwapOperation::AstroSwap {
+                    offer_asset_info: asset.info.clone(),
+                    ask_asset_info: next_asset.clone(),
+                    pair_type: Some(pair.pair_type.clone()),
+                    fee_tier: pair.fee_tier,
+                });
+
+                if next_asset == &ask_asset_info {
+                    if best_route
+                        .as_ref()
+                        .map_or(true, |best| return_amount > best.amount)
+                    {
+                        best_route = Some(FindBestRouteResponse {
+                            operations: next_operations,
+                            amount: return_amount,
+                        });
+                    }
+                    continue;
+                }
+
+                let next_asset = Asset {
+                    info: next_asset.clone(),
+                    amount: return_amount,
+                };
+                match next_routes
+                    .iter_mut()
+                    .find(|(route_asset, _)| route_asset.info == next_asset.info)
+                {
+                    Some(route) if route.0.amount >= return_amount => {}
+                    Some(route) => *route = (next_asset, next_operations),
+                    None => next_routes.push((next_asset, next_operations)),
+                }
+            }
+        }
+
+        routes = next_routes;
+    }
+
+    best_route.ok_or_else(|| ContractError::RouteNotFound {
+        offer_asset: offer_asset.info.to_string(),
+        ask_asset: ask_asset_info.to_string(),
     })
 }
 
+/// Returns the pools the router can swap through.
+/// At most [`ROUTE_PAIRS_LIMIT`] pairs are read from the factory. Only pairs with two assets are used.
+/// Every pair is addressed by its pair type and fee tier in the found swap operations.
+fn query_swap_pools(querier: &QuerierWrapper, factory: &Addr) -> StdResult<Vec<PairInfo>> {
+    let mut pools: Vec<PairInfo> = vec![];
+    let mut start_after: Option<Vec<AssetInfo>> = None;
+    let mut start_after_pair_type: Option<PairType> = None;
+    let mut start_after_fee_tier: Option<u16> = None;
+    let mut pairs_read = 0;
+
+    while pairs_read < ROUTE_PAIRS_LIMIT {
+        let pairs = query_pairs_info(
+            querier,
+            factory.clone(),
+            start_after,
+            start_after_pair_type,
+            start_after_fee_tier,
+            Some(PAIRS_QUERY_LIMIT.min(ROUTE_PAIRS_LIMIT - pairs_read)),
+        )?
+        .pairs;
+        pairs_read += pairs.len() as u32;
+
+        pools.extend(
+            pairs
+                .iter()
+                .filter(|pair| pair.asset_infos.len() == 2)
+                .cloned(),
+        );
+
+        match pairs.last() {
+            Some(last) if pairs.len() == PAIRS_QUERY_LIMIT as usize => {
+                start_after = Some(last.asset_infos.clone());
+                start_after_pair_type = Some(last.pair_type.clone());
+                start_after_fee_tier = last.fee_tier;
+            }
+            _ => break,
+        }
+    }
+
+    Ok(pools)
+}
+
+/// Validates split routes and returns their common offer and ask assets.
+///
+/// * **routes** is a vector that contains objects of type [`SwapRoute`]. These are all the routes we check.
+fn assert_routes(
+    api: &dyn Api,
+    routes: &[SwapRoute],
+) -> Result<(AssetInfo, AssetInfo), ContractError> {
+    if routes.is_empty() {
+        return Err(ContractError::MustProvideOperations {});
+    }
+
+    let operations_len: usize = routes.iter().map(|route| route.operations.len()).sum();
+    if operations_len > MAX_SWAP_OPERATIONS {
+        return Err(ContractError::SwapLimitExceeded {});
+    }
+
+    let mut assets: Option<(AssetInfo, AssetInfo)> = None;
+    for route in routes {
+        assert_operations(api, &route.operations)?;
+
+        let offer_asset = route.operations[0].get_offer_asset_info();
+        let ask_asset = route.operations.last().unwrap().get_target_asset_info();
+
+        if route
+            .operations
+            .iter()
+            .skip(1)
+            .any(|op| op.get_offer_asset_info() == offer_asset)
+        {
+            return Err(ContractError::InvalidSplitRoutes(format!(
+                "{offer_asset} can't be swapped in the middle of a route"
+            )));
+        }
+
+        match &assets {
+            Some((first_offer, first_ask)) => {
+                if *first_offer != offer_asset || *first_ask != ask_asset {
+                    return Err(ContractError::InvalidSplitRoutes(
+                        "all routes must have the same offer and ask assets".to_string(),
+                    ));
+                }
+            }
+            None => assets = Some((offer_asset, ask_asset)),
+        }
+    }
+
+    Ok(assets.unwrap())
+}
+
+/// Splits the total offer amount between routes.
+/// Rounding leftovers of percentage splits are added to the last route with a percentage split.
+///
+/// * **routes** routes for which amounts are calculated.
+///
+/// * **total** total offer amount.
+fn resolve_route_amounts(
+    routes: &[SwapRoute],
+    total: Uint128,
+) -> Result<Vec<Uint128>, ContractError> {
+    let mut amounts = routes
+        .iter()
+        .map(|route| match route.split {
+            RouteSplit::Percent(percent) => total * percent,
+            RouteSplit::Amount(amount) => amount,
+        })
+        .collect::<Vec<_>>();
+
+    let allocated = amounts
+        .iter()
+        .try_fold(Uint128::zero(), |acc, amount| acc.checked_add(*amount))?;
+    let leftover = total.checked_sub(allocated).map_err(|_| {
+        ContractError::InvalidSplitRoutes(format!(
+            "routes allocate {allocated} while only {total} was offered"
+        ))
+    })?;
+
+    let percent_routes = routes
+        .iter()
+        .filter(|route| matches!(route.split, RouteSplit::Percent(_)))
+        .count();
+    if !leftover.is_zero() && leftover.u128() >= percent_routes as u128 {
+        return Err(ContractError::InvalidSplitRoutes(format!(
+            "routes allocate {allocated} while {total} was offered"
+        )));
+    }
+
+    if !leftover.is_zero() {
+        let last_percent_route = routes
+            .iter()
+            .rposition(|route| matches!(route.split, RouteSplit::Percent(_)))
+            .unwrap();
+        amounts[last_percent_route] += leftover;
+    }
+
+    if amounts.iter().any(|amount| amount.is_zero()) {
+        return Err(ContractError::InvalidSplitRoutes(
+            "every route must swap a non-zero amount".to_string(),
+        ));
+    }
+
+    Ok(amounts)
+}
+
 /// Validates swap operations.
 ///
 /// * **operations** is a vector that contains objects of type [`SwapOperation`]. These are all the swap operations we check.
@@ -350,15 +1391,8 @@ fn assert_operations(api: &dyn Api, operations: &[SwapOperation]) -> Result<(),
     let mut prev_ask_asset: Option<AssetInfo> = None;
 
     for operation in operations {
-        let (offer_asset, ask_asset) = match operation {
-            SwapOperation::AstroSwap {
-                offer_asset_info,
-                ask_asset_info,
-            } => (offer_asset_info.clone(), ask_asset_info.clone()),
-            SwapOperation::NativeSwap { .. } => {
-                return Err(ContractError::NativeSwapNotSupported {})
-            }
-        };
+        let offer_asset = operation.get_offer_asset_info();
+        let ask_asset = operation.get_target_asset_info();
 
         offer_asset.check(api)?;
         ask_asset.check(api)?;
@@ -410,6 +1444,8 @@ mod testing {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: Addr::unchecked("asset0001"),
                         },
+                        pair_type: None,
+                        fee_tier: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: AssetInfo::Token {
@@ -418,6 +1454,8 @@ mod testing {
                         ask_asset_info: AssetInfo::NativeToken {
                             denom: "uluna".to_string(),
                         },
+                        pair_type: None,
+                        fee_tier: None,
                     },
                 ]
             )
@@ -437,6 +1475,8 @@ mod testing {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: Addr::unchecked("asset0001"),
                         },
+                        pair_type: None,
+                        fee_tier: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: AssetInfo::Token {
@@ -445,6 +1485,8 @@ mod testing {
                         ask_asset_info: AssetInfo::NativeToken {
                             denom: "uluna".to_string(),
                         },
+                        pair_type: None,
+                        fee_tier: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: AssetInfo::NativeToken {
@@ -453,6 +1495,8 @@ mod testing {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: Addr::unchecked("asset0002"),
                         },
+                        pair_type: None,
+                        fee_tier: None,
                     },
                 ]
             )
@@ -472,6 +1516,8 @@ mod testing {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: Addr::unchecked("asset0001"),
                         },
+                        pair_type: None,
+                        fee_tier: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: AssetInfo::Token {
@@ -480,6 +1526,8 @@ mod testing {
                         ask_asset_info: AssetInfo::NativeToken {
                             denom: "uaud".to_string(),
                         },
+                        pair_type: None,
+                        fee_tier: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: AssetInfo::NativeToken {
@@ -488,6 +1536,8 @@ mod testing {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: Addr::unchecked("asset0002"),
                         },
+                        pair_type: None,
+                        fee_tier: None,
                     },
                 ]
             )
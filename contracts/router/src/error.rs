@@ -1,3 +1,4 @@
+use astroport::router::MAX_REFERRAL_FEE_BPS;
 use cosmwasm_std::{OverflowError, StdError, Uint128};
 use thiserror::Error;
 
@@ -38,6 +39,36 @@ pub enum ContractError {
     #[error("Native swap operations are not supported!")]
     NativeSwapNotSupported {},
 
+    #[error("Invalid split routes: {0}")]
+    InvalidSplitRoutes(String),
+
+    #[error("No route found from {offer_asset} to {ask_asset}")]
+    RouteNotFound {
+        offer_asset: String,
+        ask_asset: String,
+    },
+
+    #[error("Sent funds don't match the offer asset of the routes")]
+    InvalidOfferFunds {},
+
+    #[error("Can't specify both a recipient and an IBC destination")]
+    IbcRecipientConflict {},
+
+    #[error("CW20-ICS20 contract is not set")]
+    Cw20Ics20NotSet {},
+
+    #[error("IBC memo is only supported for CW20 tokens")]
+    IbcMemoNotSupported {},
+
+    #[error("Referral fee of {fee_bps} bps exceeds the maximum of {max_fee_bps} bps")]
+    ReferralFeeTooHigh { fee_bps: u16, max_fee_bps: u16 },
+
+    #[error("Maximum referral fee can't exceed {} bps", MAX_REFERRAL_FEE_BPS)]
+    InvalidMaxReferralFee {},
+
+    #[error("Transaction deadline has passed")]
+    DeadlineExpired {},
+
     #[error("Contract can't be migrated!")]
     MigrationError {},
 }
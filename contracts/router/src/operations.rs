@@ -1,9 +1,10 @@
 use astroport::asset::{Asset, AssetInfo};
 use astroport::pair::ExecuteMsg as PairExecuteMsg;
 use astroport::querier::{query_balance, query_pair_info, query_token_balance};
-use astroport::router::SwapOperation;
+use astroport::router::{NativeSwapAdapterExecuteMsg, SwapOperation};
 use cosmwasm_std::{
-    to_binary, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response, StdResult, WasmMsg,
+    coin, to_binary, Addr, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response,
+    StdResult, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 
@@ -17,6 +18,9 @@ use crate::state::CONFIG;
 /// * **to** address that receives the ask assets.
 ///
 /// * **single** defines whether this swap is single or part of a multi hop route.
+///
+/// * **prev_balance** the router balance of the offer asset which doesn't belong to the swap.
+#[allow(clippy::too_many_arguments)]
 pub fn execute_swap_operation(
     deps: DepsMut,
     env: Env,
@@ -25,21 +29,26 @@ pub fn execute_swap_operation(
     to: Option<String>,
     max_spread: Option<Decimal>,
     single: bool,
+    prev_balance: Uint128,
 ) -> Result<Response, ContractError> {
     if env.contract.address != info.sender {
         return Err(ContractError::Unauthorized {});
     }
 
+    let config = CONFIG.load(deps.storage)?;
     let message = match operation {
         SwapOperation::AstroSwap {
             offer_asset_info,
             ask_asset_info,
+            pair_type,
+            fee_tier,
         } => {
-            let config = CONFIG.load(deps.storage)?;
             let pair_info = query_pair_info(
                 &deps.querier,
                 config.astroport_factory,
                 &[offer_asset_info.clone(), ask_asset_info.clone()],
+                pair_type,
+                fee_tier,
             )?;
 
             let amount = match &offer_asset_info {
@@ -49,7 +58,8 @@ pub fn execute_swap_operation(
                 AssetInfo::Token { contract_addr } => {
                     query_token_balance(&deps.querier, contract_addr, env.contract.address)?
                 }
-            };
+            }
+            .checked_sub(prev_balance)?;
             let offer_asset = Asset {
                 info: offer_asset_info,
                 amount,
@@ -64,7 +74,21 @@ pub fn execute_swap_operation(
                 single,
             )?
         }
-        SwapOperation::NativeSwap { .. } => return Err(ContractError::NativeSwapNotSupported {}),
+        SwapOperation::NativeSwap {
+            offer_denom,
+            ask_denom,
+        } => {
+            let amount = query_balance(&deps.querier, env.contract.address, &offer_denom)?
+                .checked_sub(prev_balance)?;
+            native_swap_msg(
+                config.native_swap_adapter()?,
+                coin(amount.u128(), offer_denom),
+                ask_denom,
+                max_spread,
+                to,
+                single,
+            )?
+        }
     };
 
     Ok(Response::new().add_message(message))
@@ -110,6 +134,7 @@ pub fn asset_into_swap_msg(
                 belief_price,
                 max_spread,
                 to,
+                deadline: None,
             })?,
         })),
         AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -123,8 +148,45 @@ pub fn asset_into_swap_msg(
                     belief_price,
                     max_spread,
                     to,
+                    deadline: None,
                 })?,
             })?,
         })),
     }
 }
+
+/// Creates a message of type [`CosmosMsg`] representing a native swap operation.
+///
+/// * **adapter** native swap adapter performing the swap.
+///
+/// * **offer** coin that is swapped.
+///
+/// * **ask_denom** denom that is swapped to.
+///
+/// * **max_spread** max spread enforced for the swap.
+///
+/// * **to** address that receives the ask assets.
+///
+/// * **single** defines whether this swap is single or part of a multi hop route.
+pub fn native_swap_msg(
+    adapter: &Addr,
+    offer: Coin,
+    ask_denom: String,
+    max_spread: Option<Decimal>,
+    to: Option<String>,
+    single: bool,
+) -> StdResult<CosmosMsg> {
+    // Disabling spread assertion if this swap is part of a multi hop route
+    let belief_price = if single { None } else { Some(Decimal::MAX) };
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: adapter.to_string(),
+        funds: vec![offer],
+        msg: to_binary(&NativeSwapAdapterExecuteMsg::Swap {
+            ask_denom,
+            belief_price,
+            max_spread,
+            to,
+        })?,
+    }))
+}
@@ -2,6 +2,8 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::Addr;
 use cw_storage_plus::Item;
 
+use crate::error::ContractError;
+
 /// Stores the contract config at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
 
@@ -10,4 +12,20 @@ pub const CONFIG: Item<Config> = Item::new("config");
 pub struct Config {
     /// The factory contract address
     pub astroport_factory: Addr,
+    /// The CW20-ICS20 contract used to send CW20 tokens over IBC
+    pub cw20_ics20: Option<Addr>,
+    /// The maximum referral fee in bps
+    #[serde(default)]
+    pub max_referral_fee_bps: u16,
+    /// The native swap adapter used to execute native swap operations
+    pub native_swap_adapter: Option<Addr>,
+}
+
+impl Config {
+    /// Returns the native swap adapter or an error if native swaps are disabled
+    pub fn native_swap_adapter(&self) -> Result<&Addr, ContractError> {
+        self.native_swap_adapter
+            .as_ref()
+            .ok_or(ContractError::NativeSwapNotSupported {})
+    }
 }
@@ -8,13 +8,16 @@ use std::collections::HashMap;
 
 use astroport::asset::{Asset, AssetInfo, PairInfo};
 use astroport::factory::PairType;
-use astroport::pair::SimulationResponse;
+use astroport::pair::{ReverseSimulationResponse, SimulationResponse};
+use astroport::router::NativeSwapAdapterQueryMsg;
 use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 
 #[cw_serde]
 pub enum QueryMsg {
     Pair {
         asset_infos: Vec<AssetInfo>,
+        pair_type: Option<PairType>,
+        fee_tier: Option<u16>,
     },
     Simulation {
         offer_asset: Asset,
@@ -123,6 +126,8 @@ impl WasmMockQuerier {
                     || contract_addr.to_string().starts_with("asset")
                 {
                     self.handle_cw20(&contract_addr, &msg)
+                } else if contract_addr == "nativeswapadapter" {
+                    self.handle_native_swap_adapter(&msg)
                 } else {
                     self.handle_default(&msg)
                 }
@@ -133,7 +138,7 @@ impl WasmMockQuerier {
 
     fn handle_default(&self, msg: &Binary) -> QuerierResult {
         match from_binary(&msg).unwrap() {
-            QueryMsg::Pair { asset_infos } => {
+            QueryMsg::Pair { asset_infos, .. } => {
                 let key = asset_infos[0].to_string() + asset_infos[1].to_string().as_str();
                 match self.astroport_factory_querier.pairs.get(&key) {
                     Some(v) => SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
@@ -148,6 +153,7 @@ impl WasmMockQuerier {
                             },
                         ],
                         pair_type: PairType::Xyk {},
+                        fee_tier: None,
                     }))),
                     None => SystemResult::Err(SystemError::InvalidRequest {
                         error: "No pair info exists".to_string(),
@@ -165,6 +171,25 @@ impl WasmMockQuerier {
         }
     }
 
+    fn handle_native_swap_adapter(&self, msg: &Binary) -> QuerierResult {
+        match from_binary(&msg).unwrap() {
+            NativeSwapAdapterQueryMsg::Simulation { offer, .. } => {
+                SystemResult::Ok(ContractResult::from(to_binary(&SimulationResponse {
+                    return_amount: offer.amount,
+                    commission_amount: Uint128::zero(),
+                    spread_amount: Uint128::zero(),
+                })))
+            }
+            NativeSwapAdapterQueryMsg::ReverseSimulation { ask, .. } => SystemResult::Ok(
+                ContractResult::from(to_binary(&ReverseSimulationResponse {
+                    offer_amount: ask.amount,
+                    commission_amount: Uint128::zero(),
+                    spread_amount: Uint128::zero(),
+                })),
+            ),
+        }
+    }
+
     fn handle_cw20(&self, contract_addr: &String, msg: &Binary) -> QuerierResult {
         match from_binary(&msg).unwrap() {
             Cw20QueryMsg::TokenInfo {} => {
@@ -1,16 +1,21 @@
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
-use cosmwasm_std::{from_binary, to_binary, Addr, Coin, ReplyOn, SubMsg, Uint128, WasmMsg};
+use cosmwasm_std::{
+    coin, from_binary, to_binary, Addr, Coin, CosmosMsg, Decimal, IbcMsg, IbcTimeout, ReplyOn,
+    SubMsg, Uint128, WasmMsg,
+};
 
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query};
 use crate::error::ContractError;
 use crate::testing::mock_querier::mock_dependencies;
 
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
 use astroport::asset::{native_asset_info, AssetInfo};
 use astroport::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    SimulateSwapOperationsResponse, SwapOperation, MAX_SWAP_OPERATIONS,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, IbcDestination, InstantiateMsg, MigrateMsg,
+    NativeSwapAdapterExecuteMsg, QueryMsg, SimulateSwapOperationsResponse, SwapOperation,
+    SwapOperationSimulation, MAX_SWAP_OPERATIONS,
 };
 
 #[test]
@@ -19,6 +24,9 @@ fn proper_initialization() {
 
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        cw20_ics20: None,
+        max_referral_fee_bps: None,
+        native_swap_adapter: None,
     };
 
     let env = mock_env();
@@ -33,11 +41,49 @@ fn proper_initialization() {
     assert_eq!("astroportfactory", config.astroport_factory.as_str());
 }
 
+#[test]
+fn migrate_from_previous_version() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        cw20_ics20: None,
+        max_referral_fee_bps: None,
+        native_swap_adapter: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    set_contract_version(deps.as_mut().storage, "astroport-router", "1.1.2").unwrap();
+
+    let msg = MigrateMsg {
+        cw20_ics20: Some("cw20ics20".to_string()),
+        max_referral_fee_bps: Some(100),
+        native_swap_adapter: Some("adapter".to_string()),
+    };
+    migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.cw20_ics20, Some("cw20ics20".to_string()));
+    assert_eq!(config.max_referral_fee_bps, 100);
+    assert_eq!(config.native_swap_adapter, Some("adapter".to_string()));
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        env!("CARGO_PKG_VERSION")
+    );
+
+    // The current version can't be migrated again
+    let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+    assert_eq!(err, ContractError::MigrationError {});
+}
+
 #[test]
 fn execute_swap_operations() {
     let mut deps = mock_dependencies(&[]);
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        cw20_ics20: None,
+        max_referral_fee_bps: None,
+        native_swap_adapter: None,
     };
 
     let env = mock_env();
@@ -51,6 +97,9 @@ fn execute_swap_operations() {
         minimum_receive: None,
         to: None,
         max_spread: None,
+        deadline: None,
+        ibc_destination: None,
+        referral: None,
     };
 
     let env = mock_env();
@@ -67,6 +116,8 @@ fn execute_swap_operations() {
                 ask_asset_info: AssetInfo::Token {
                     contract_addr: Addr::unchecked("asset0001"),
                 },
+                pair_type: None,
+                fee_tier: None,
             },
             SwapOperation::AstroSwap {
                 offer_asset_info: AssetInfo::Token {
@@ -75,6 +126,8 @@ fn execute_swap_operations() {
                 ask_asset_info: AssetInfo::NativeToken {
                     denom: "uluna".to_string(),
                 },
+                pair_type: None,
+                fee_tier: None,
             },
             SwapOperation::AstroSwap {
                 offer_asset_info: AssetInfo::NativeToken {
@@ -83,11 +136,16 @@ fn execute_swap_operations() {
                 ask_asset_info: AssetInfo::Token {
                     contract_addr: Addr::unchecked("asset0002"),
                 },
+                pair_type: None,
+                fee_tier: None,
             },
         ],
         minimum_receive: Some(Uint128::from(1000000u128)),
         to: None,
         max_spread: None,
+        deadline: None,
+        ibc_destination: None,
+        referral: None,
     };
 
     let env = mock_env();
@@ -108,10 +166,13 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::Token {
                                 contract_addr: Addr::unchecked("asset0001"),
                             },
+                            pair_type: None,
+                            fee_tier: None,
                         },
                         to: None,
                         max_spread: None,
-                        single: false
+                        single: false,
+                        prev_balance: Uint128::zero(),
                     })
                     .unwrap(),
                 }
@@ -132,10 +193,13 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::NativeToken {
                                 denom: "uluna".to_string(),
                             },
+                            pair_type: None,
+                            fee_tier: None,
                         },
                         to: None,
                         max_spread: None,
-                        single: false
+                        single: false,
+                        prev_balance: Uint128::zero(),
                     })
                     .unwrap(),
                 }
@@ -156,10 +220,13 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::Token {
                                 contract_addr: Addr::unchecked("asset0002"),
                             },
+                            pair_type: None,
+                            fee_tier: None,
                         },
                         to: Some(String::from("addr0000")),
                         max_spread: None,
-                        single: false
+                        single: false,
+                        prev_balance: Uint128::zero(),
                     })
                     .unwrap(),
                 }
@@ -202,6 +269,8 @@ fn execute_swap_operations() {
                     ask_asset_info: AssetInfo::Token {
                         contract_addr: Addr::unchecked("asset0001"),
                     },
+                    pair_type: None,
+                    fee_tier: None,
                 },
                 SwapOperation::AstroSwap {
                     offer_asset_info: AssetInfo::Token {
@@ -210,6 +279,8 @@ fn execute_swap_operations() {
                     ask_asset_info: AssetInfo::NativeToken {
                         denom: "uluna".to_string(),
                     },
+                    pair_type: None,
+                    fee_tier: None,
                 },
                 SwapOperation::AstroSwap {
                     offer_asset_info: AssetInfo::NativeToken {
@@ -218,11 +289,16 @@ fn execute_swap_operations() {
                     ask_asset_info: AssetInfo::Token {
                         contract_addr: Addr::unchecked("asset0002"),
                     },
+                    pair_type: None,
+                    fee_tier: None,
                 },
             ],
             minimum_receive: None,
             to: Some(String::from("addr0002")),
             max_spread: None,
+            deadline: None,
+            ibc_destination: None,
+            referral: None,
         })
         .unwrap(),
     });
@@ -245,10 +321,13 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::Token {
                                 contract_addr: Addr::unchecked("asset0001"),
                             },
+                            pair_type: None,
+                            fee_tier: None,
                         },
                         to: None,
                         max_spread: None,
-                        single: false
+                        single: false,
+                        prev_balance: Uint128::zero(),
                     })
                     .unwrap(),
                 }
@@ -269,10 +348,13 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::NativeToken {
                                 denom: "uluna".to_string(),
                             },
+                            pair_type: None,
+                            fee_tier: None,
                         },
                         to: None,
                         max_spread: None,
-                        single: false
+                        single: false,
+                        prev_balance: Uint128::zero(),
                     })
                     .unwrap(),
                 }
@@ -293,10 +375,13 @@ fn execute_swap_operations() {
                             ask_asset_info: AssetInfo::Token {
                                 contract_addr: Addr::unchecked("asset0002"),
                             },
+                            pair_type: None,
+                            fee_tier: None,
                         },
                         to: Some(String::from("addr0002")),
                         max_spread: None,
-                        single: false
+                        single: false,
+                        prev_balance: Uint128::zero(),
                     })
                     .unwrap(),
                 }
@@ -314,6 +399,9 @@ fn execute_swap_operation() {
     let mut deps = mock_dependencies(&[]);
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        cw20_ics20: None,
+        max_referral_fee_bps: None,
+        native_swap_adapter: None,
     };
 
     let env = mock_env();
@@ -349,10 +437,13 @@ fn execute_swap_operation() {
             ask_asset_info: AssetInfo::NativeToken {
                 denom: "uusd".to_string(),
             },
+            pair_type: None,
+            fee_tier: None,
         },
         to: Some(String::from("addr0000")),
         max_spread: None,
         single: true,
+        prev_balance: Uint128::zero(),
     };
     let env = mock_env();
     let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
@@ -371,6 +462,7 @@ fn execute_swap_operation() {
                         belief_price: None,
                         max_spread: None,
                         to: Some(String::from("addr0000")),
+                        deadline: None,
                     })
                     .unwrap()
                 })
@@ -390,6 +482,9 @@ fn query_buy_with_routes() {
 
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        cw20_ics20: None,
+        max_referral_fee_bps: None,
+        native_swap_adapter: None,
     };
 
     let env = mock_env();
@@ -408,6 +503,8 @@ fn query_buy_with_routes() {
                 ask_asset_info: AssetInfo::Token {
                     contract_addr: Addr::unchecked("asset0000"),
                 },
+                pair_type: None,
+                fee_tier: None,
             },
             SwapOperation::AstroSwap {
                 offer_asset_info: AssetInfo::Token {
@@ -416,8 +513,11 @@ fn query_buy_with_routes() {
                 ask_asset_info: AssetInfo::NativeToken {
                     denom: "uluna".to_string(),
                 },
+                pair_type: None,
+                fee_tier: None,
             },
         ],
+        referral: None,
     };
     deps.querier.with_astroport_pairs(&[
         (&"ukrwasset0000".to_string(), &String::from("pair0000")),
@@ -426,17 +526,28 @@ fn query_buy_with_routes() {
 
     let res: SimulateSwapOperationsResponse =
         from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
-    assert_eq!(
-        res,
-        SimulateSwapOperationsResponse {
-            amount: Uint128::from(1000000u128)
-        }
-    );
-
     assert_eq!(
         res,
         SimulateSwapOperationsResponse {
             amount: Uint128::from(1000000u128),
+            referral_amount: Uint128::zero(),
+            operations: vec![
+                SwapOperationSimulation {
+                    pair_address: Addr::unchecked("pair0000"),
+                    offer_amount: Uint128::from(1000000u128),
+                    return_amount: Uint128::from(1000000u128),
+                    spread_amount: Uint128::zero(),
+                    commission_amount: Uint128::zero(),
+                },
+                SwapOperationSimulation {
+                    pair_address: Addr::unchecked("pair0001"),
+                    offer_amount: Uint128::from(1000000u128),
+                    return_amount: Uint128::from(1000000u128),
+                    spread_amount: Uint128::zero(),
+                    commission_amount: Uint128::zero(),
+                },
+            ],
+            price_impact: Decimal::zero(),
         }
     );
 }
@@ -530,6 +641,9 @@ fn assert_maximum_receive_swap_operations() {
     let mut deps = mock_dependencies(&[]);
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        cw20_ics20: None,
+        max_referral_fee_bps: None,
+        native_swap_adapter: None,
     };
 
     let env = mock_env();
@@ -549,6 +663,9 @@ fn assert_maximum_receive_swap_operations() {
         minimum_receive: None,
         to: None,
         max_spread: None,
+        deadline: None,
+        ibc_destination: None,
+        referral: None,
     };
 
     let env = mock_env();
@@ -557,3 +674,332 @@ fn assert_maximum_receive_swap_operations() {
 
     assert_eq!(res, ContractError::SwapLimitExceeded {});
 }
+
+#[test]
+fn execute_swap_operations_with_ibc_destination() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        cw20_ics20: None,
+        max_referral_fee_bps: None,
+        native_swap_adapter: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let destination = IbcDestination {
+        channel: "channel-0".to_string(),
+        remote_address: "remote0000".to_string(),
+        timeout: None,
+        memo: None,
+    };
+    let swap_msg = |ask_asset_info: AssetInfo, to: Option<String>, memo: Option<String>| {
+        ExecuteMsg::ExecuteSwapOperations {
+            operations: vec![SwapOperation::AstroSwap {
+                offer_asset_info: native_asset_info("ukrw".to_string()),
+                ask_asset_info,
+                pair_type: None,
+                fee_tier: None,
+            }],
+            minimum_receive: Some(Uint128::from(1000000u128)),
+            to,
+            max_spread: None,
+            deadline: None,
+            ibc_destination: Some(IbcDestination {
+                memo,
+                ..destination.clone()
+            }),
+            referral: None,
+        }
+    };
+    let cw20_ask = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset0000"),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        swap_msg(cw20_ask.clone(), None, None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Cw20Ics20NotSet {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        swap_msg(
+            native_asset_info("uluna".to_string()),
+            Some("addr0000".to_string()),
+            None,
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IbcRecipientConflict {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        swap_msg(
+            native_asset_info("uluna".to_string()),
+            None,
+            Some("memo".to_string()),
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IbcMemoNotSupported {});
+
+    // The router receives the swap output and forwards it after the minimum receive assertion.
+    // Tokens already held by the router are not forwarded
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[coin(500000u128, "uluna")],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        swap_msg(native_asset_info("uluna".to_string()), None, None),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: SwapOperation::AstroSwap {
+                        offer_asset_info: native_asset_info("ukrw".to_string()),
+                        ask_asset_info: native_asset_info("uluna".to_string()),
+                        pair_type: None,
+                        fee_tier: None,
+                    },
+                    to: Some(String::from(MOCK_CONTRACT_ADDR)),
+                    max_spread: None,
+                    single: true,
+                    prev_balance: Uint128::zero(),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+                    asset_info: native_asset_info("uluna".to_string()),
+                    prev_balance: Uint128::new(500000u128),
+                    minimum_receive: Uint128::from(1000000u128),
+                    receiver: String::from(MOCK_CONTRACT_ADDR),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ForwardIbcTransfer {
+                    asset_info: native_asset_info("uluna".to_string()),
+                    prev_balance: Uint128::new(500000u128),
+                    destination,
+                })
+                .unwrap(),
+            }),
+        ]
+    );
+}
+
+#[test]
+fn forward_ibc_transfer() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        cw20_ics20: Some(String::from("cw20ics20")),
+        max_referral_fee_bps: None,
+        native_swap_adapter: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[coin(1000000u128, "uluna")],
+    )]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &Uint128::new(2000000u128),
+        )],
+    )]);
+
+    let destination = IbcDestination {
+        channel: "channel-0".to_string(),
+        remote_address: "remote0000".to_string(),
+        timeout: Some(100),
+        memo: None,
+    };
+
+    // Only the swap output is forwarded, the router already held 400000 uluna before the swap
+    let msg = ExecuteMsg::ForwardIbcTransfer {
+        asset_info: native_asset_info("uluna".to_string()),
+        prev_balance: Uint128::new(400000u128),
+        destination: destination.clone(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let env = mock_env();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Ibc(IbcMsg::Transfer {
+            channel_id: "channel-0".to_string(),
+            to_address: "remote0000".to_string(),
+            amount: coin(600000u128, "uluna"),
+            timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(100)),
+        }))]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::ForwardIbcTransfer {
+            asset_info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            prev_balance: Uint128::zero(),
+            destination: destination.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: String::from("cw20ics20"),
+                amount: Uint128::new(2000000u128),
+                msg: to_binary(&destination).unwrap(),
+            })
+            .unwrap(),
+        })]
+    );
+}
+
+#[test]
+fn native_swap_operations() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        cw20_ics20: None,
+        max_referral_fee_bps: None,
+        native_swap_adapter: None,
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[coin(1000000u128, "uluna")],
+    )]);
+
+    let operation = SwapOperation::NativeSwap {
+        offer_denom: "uluna".to_string(),
+        ask_denom: "uusd".to_string(),
+    };
+
+    // Native swaps are rejected when no adapter is configured
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::ExecuteSwapOperation {
+            operation: operation.clone(),
+            to: Some(String::from("addr0000")),
+            max_spread: None,
+            single: true,
+            prev_balance: Uint128::zero(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NativeSwapNotSupported {});
+
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        cw20_ics20: None,
+        max_referral_fee_bps: None,
+        native_swap_adapter: Some(String::from("nativeswapadapter")),
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::ExecuteSwapOperation {
+            operation: operation.clone(),
+            to: Some(String::from("addr0000")),
+            max_spread: None,
+            single: true,
+            prev_balance: Uint128::zero(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("nativeswapadapter"),
+            funds: vec![coin(1000000u128, "uluna")],
+            msg: to_binary(&NativeSwapAdapterExecuteMsg::Swap {
+                ask_denom: "uusd".to_string(),
+                belief_price: None,
+                max_spread: None,
+                to: Some(String::from("addr0000")),
+            })
+            .unwrap(),
+        })]
+    );
+
+    let res: SimulateSwapOperationsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateSwapOperations {
+                offer_amount: Uint128::from(1000000u128),
+                operations: vec![operation],
+                referral: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.operations,
+        vec![SwapOperationSimulation {
+            pair_address: Addr::unchecked("nativeswapadapter"),
+            offer_amount: Uint128::from(1000000u128),
+            return_amount: Uint128::from(1000000u128),
+            spread_amount: Uint128::zero(),
+            commission_amount: Uint128::zero(),
+        }]
+    );
+}
@@ -78,6 +78,7 @@ impl FactoryHelper {
                     maker_fee_bps: 0,
                     is_disabled: false,
                     is_generator_disabled: false,
+                    fee_tiers: vec![],
                 },
                 PairConfig {
                     code_id: pair_code_id,
@@ -86,6 +87,7 @@ impl FactoryHelper {
                     maker_fee_bps: 0,
                     is_disabled: false,
                     is_generator_disabled: false,
+                    fee_tiers: vec![],
                 },
             ],
             token_code_id: cw20_token_code_id,
@@ -136,6 +138,8 @@ impl FactoryHelper {
             pair_type,
             asset_infos,
             init_params,
+            fee: None,
+            fee_tier: None,
         };
 
         router.execute_contract(sender.clone(), self.factory.clone(), &msg, &[])
@@ -149,7 +153,7 @@ impl FactoryHelper {
         tokens: [&Addr; 2],
         init_params: Option<Binary>,
     ) -> AnyResult<Addr> {
-        self.create_pair(router, sender, pair_type, tokens, init_params)?;
+        self.create_pair(router, sender, pair_type.clone(), tokens, init_params)?;
 
         let asset_infos = vec![
             AssetInfo::Token {
@@ -164,6 +168,8 @@ impl FactoryHelper {
             self.factory.clone(),
             &QueryMsg::Pair {
                 asset_infos: asset_infos.clone(),
+                pair_type: Some(pair_type),
+                fee_tier: None,
             },
         )?;
 
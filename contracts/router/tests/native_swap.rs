@@ -0,0 +1,156 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use astroport::router::{
+    ExecuteMsg, InstantiateMsg, NativeSwapAdapterExecuteMsg, QueryMsg,
+    SimulateReverseSwapOperationsResponse, SimulateSwapOperationsResponse, SwapOperation,
+};
+use astroport_mocks::cw_multi_test::{BasicApp, ContractWrapper, Executor};
+use astroport_mocks::{astroport_address, MockNativeSwapAdapterBuilder};
+use astroport_router::contract::{execute, instantiate, query};
+use cosmwasm_std::{coin, Addr, Decimal, Uint128};
+
+#[test]
+fn native_swap_through_adapter() {
+    let astroport = astroport_address();
+    let user = Addr::unchecked("user");
+
+    let app = Rc::new(RefCell::new(BasicApp::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &astroport,
+                vec![coin(1_000_000000, "uinj"), coin(1_000_000000, "uusdt")],
+            )
+            .unwrap();
+    })));
+
+    let adapter = MockNativeSwapAdapterBuilder::new(&app)
+        .with_rate(Decimal::percent(50))
+        .instantiate();
+    // The adapter pays out the ask coins from its own balance
+    app.borrow_mut()
+        .send_tokens(
+            astroport.clone(),
+            adapter.address.clone(),
+            &[coin(1_000_000000, "uusdt")],
+        )
+        .unwrap();
+    app.borrow_mut()
+        .send_tokens(astroport.clone(), user.clone(), &[coin(10_000000, "uinj")])
+        .unwrap();
+
+    let router_code = app
+        .borrow_mut()
+        .store_code(Box::new(ContractWrapper::new_with_empty(
+            execute,
+            instantiate,
+            query,
+        )));
+    let router = app
+        .borrow_mut()
+        .instantiate_contract(
+            router_code,
+            astroport.clone(),
+            &InstantiateMsg {
+                astroport_factory: "factory".to_string(),
+                cw20_ics20: None,
+                max_referral_fee_bps: None,
+                native_swap_adapter: Some(adapter.address.to_string()),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let operations = vec![SwapOperation::NativeSwap {
+        offer_denom: "uinj".to_string(),
+        ask_denom: "uusdt".to_string(),
+    }];
+
+    let simulation: SimulateSwapOperationsResponse = app
+        .borrow()
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount: Uint128::new(10_000000),
+                operations: operations.clone(),
+                referral: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(simulation.amount, Uint128::new(5_000000));
+    assert_eq!(simulation.operations[0].pair_address, adapter.address);
+
+    let reverse_simulation: SimulateReverseSwapOperationsResponse = app
+        .borrow()
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateReverseSwapOperations {
+                ask_amount: Uint128::new(5_000000),
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(reverse_simulation.offer_amount, Uint128::new(10_000000));
+    assert_eq!(
+        reverse_simulation.operations[0].pair_address,
+        adapter.address
+    );
+
+    app.borrow_mut()
+        .execute_contract(
+            user.clone(),
+            router.clone(),
+            &ExecuteMsg::ExecuteSwapOperations {
+                operations,
+                minimum_receive: Some(simulation.amount),
+                to: None,
+                max_spread: None,
+                deadline: None,
+                ibc_destination: None,
+                referral: None,
+            },
+            &[coin(10_000000, "uinj")],
+        )
+        .unwrap();
+
+    let balance = |address: &Addr, denom: &str| {
+        app.borrow()
+            .wrap()
+            .query_balance(address, denom)
+            .unwrap()
+            .amount
+    };
+    assert_eq!(balance(&user, "uinj"), Uint128::zero());
+    assert_eq!(balance(&user, "uusdt"), Uint128::new(5_000000));
+    assert_eq!(balance(&router, "uinj"), Uint128::zero());
+    assert_eq!(balance(&adapter.address, "uinj"), Uint128::new(10_000000));
+
+    // The adapter enforces the slippage limits
+    app.borrow_mut()
+        .send_tokens(astroport.clone(), user.clone(), &[coin(10_000000, "uinj")])
+        .unwrap();
+    let err = app
+        .borrow_mut()
+        .execute_contract(
+            user.clone(),
+            adapter.address.clone(),
+            &NativeSwapAdapterExecuteMsg::Swap {
+                ask_denom: "uusdt".to_string(),
+                belief_price: Some(Decimal::one()),
+                max_spread: Some(Decimal::percent(1)),
+                to: None,
+            },
+            &[coin(10_000000, "uinj")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Operation exceeds max spread limit"
+    );
+}
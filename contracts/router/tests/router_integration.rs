@@ -5,7 +5,7 @@ mod factory_helper;
 use crate::factory_helper::{instantiate_token, mint, FactoryHelper};
 use astroport::asset::token_asset_info;
 use astroport::factory::PairType;
-use astroport::router::{ExecuteMsg, InstantiateMsg, SwapOperation};
+use astroport::router::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, SwapOperation};
 use cosmwasm_std::{to_binary, Addr, Empty, StdError};
 use cw20::Cw20ExecuteMsg;
 use cw_multi_test::{App, Contract, ContractWrapper, Executor};
@@ -47,6 +47,9 @@ fn router_does_not_enforce_spread_assertion() {
             owner.clone(),
             &InstantiateMsg {
                 astroport_factory: helper.factory.to_string(),
+                cw20_ics20: None,
+                max_referral_fee_bps: None,
+                native_swap_adapter: None,
             },
             &[],
             "router",
@@ -67,15 +70,22 @@ fn router_does_not_enforce_spread_assertion() {
                     SwapOperation::AstroSwap {
                         offer_asset_info: token_asset_info(token_x.clone()),
                         ask_asset_info: token_asset_info(token_y.clone()),
+                        pair_type: None,
+                        fee_tier: None,
                     },
                     SwapOperation::AstroSwap {
                         offer_asset_info: token_asset_info(token_y.clone()),
                         ask_asset_info: token_asset_info(token_z.clone()),
+                        pair_type: None,
+                        fee_tier: None,
                     },
                 ],
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                deadline: None,
+                ibc_destination: None,
+                referral: None,
             })
             .unwrap(),
         },
@@ -96,10 +106,15 @@ fn router_does_not_enforce_spread_assertion() {
                     operations: vec![SwapOperation::AstroSwap {
                         offer_asset_info: token_asset_info(token_x.clone()),
                         ask_asset_info: token_asset_info(token_y.clone()),
+                        pair_type: None,
+                        fee_tier: None,
                     }],
                     minimum_receive: None,
                     to: None,
                     max_spread: None,
+                    deadline: None,
+                    ibc_destination: None,
+                    referral: None,
                 })
                 .unwrap(),
             },
@@ -148,6 +163,9 @@ fn test_swap_route() {
             owner.clone(),
             &InstantiateMsg {
                 astroport_factory: helper.factory.to_string(),
+                cw20_ics20: None,
+                max_referral_fee_bps: None,
+                native_swap_adapter: None,
             },
             &[],
             "router",
@@ -167,6 +185,8 @@ fn test_swap_route() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: inj.clone(),
             },
+            pair_type: None,
+            fee_tier: None,
         },
         SwapOperation::AstroSwap {
             offer_asset_info: AssetInfo::Token {
@@ -175,6 +195,8 @@ fn test_swap_route() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: osmo.clone(),
             },
+            pair_type: None,
+            fee_tier: None,
         },
     ];
 
@@ -185,6 +207,7 @@ fn test_swap_route() {
             &QueryMsg::SimulateSwapOperations {
                 offer_amount: swap_amount,
                 operations: swap_operations.clone(),
+                referral: None,
             },
         )
         .unwrap_err();
@@ -206,6 +229,8 @@ fn test_swap_route() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: inj.clone(),
             },
+            pair_type: None,
+            fee_tier: None,
         },
         SwapOperation::AstroSwap {
             offer_asset_info: AssetInfo::Token {
@@ -214,6 +239,8 @@ fn test_swap_route() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: osmo.clone(),
             },
+            pair_type: None,
+            fee_tier: None,
         },
         SwapOperation::AstroSwap {
             offer_asset_info: AssetInfo::Token {
@@ -222,6 +249,8 @@ fn test_swap_route() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: atom.clone(),
             },
+            pair_type: None,
+            fee_tier: None,
         },
         SwapOperation::AstroSwap {
             offer_asset_info: AssetInfo::Token {
@@ -230,6 +259,8 @@ fn test_swap_route() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: osmo.clone(),
             },
+            pair_type: None,
+            fee_tier: None,
         },
     ];
 
@@ -241,6 +272,7 @@ fn test_swap_route() {
             &QueryMsg::SimulateSwapOperations {
                 offer_amount: swap_amount,
                 operations: swap_operations.clone(),
+                referral: None,
             },
         )
         .unwrap();
@@ -278,6 +310,9 @@ fn test_swap_route() {
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                deadline: None,
+                ibc_destination: None,
+                referral: None,
             })
             .unwrap(),
         },
@@ -365,6 +400,9 @@ fn test_swap_route() {
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                deadline: None,
+                ibc_destination: None,
+                referral: None,
             })
             .unwrap(),
         },
@@ -404,7 +442,7 @@ fn test_swap_route() {
             },
         )
         .unwrap();
-    assert_eq!(balance_res.balance, Uint128::new(19992001));
+    assert_eq!(balance_res.balance, Uint128::new(19992000));
 
     // Query router contract balance
     let balance_res: BalanceResponse = app
@@ -427,7 +465,7 @@ fn test_swap_route() {
             },
         )
         .unwrap();
-    assert_eq!(balance_res.balance, Uint128::zero());
+    assert_eq!(balance_res.balance, Uint128::new(donated_atom));
 
     let balance_res: BalanceResponse = app
         .wrap()
@@ -453,10 +491,15 @@ fn test_swap_route() {
                     ask_asset_info: AssetInfo::Token {
                         contract_addr: atom.clone(),
                     },
+                    pair_type: None,
+                    fee_tier: None,
                 }],
                 minimum_receive: Some(Uint128::new(9_997_000)),
                 to: None,
                 max_spread: None,
+                deadline: None,
+                ibc_destination: None,
+                referral: None,
             },
             &[],
         )
@@ -504,7 +547,7 @@ fn test_swap_route() {
         .unwrap();
     assert_eq!(balance_res.balance, Uint128::zero());
 
-    // double check router contract have no funds left
+    // double check router contract have no funds left except for the donation, which is never swapped
     let balance_res: BalanceResponse = app
         .wrap()
         .query_wasm_smart(
@@ -525,7 +568,7 @@ fn test_swap_route() {
             },
         )
         .unwrap();
-    assert_eq!(balance_res.balance, Uint128::zero());
+    assert_eq!(balance_res.balance, Uint128::new(donated_atom));
 
     let balance_res: BalanceResponse = app
         .wrap()
@@ -589,6 +632,9 @@ fn test_swap_route() {
                 minimum_receive: Some(donated_atom),
                 to: None,
                 max_spread: None,
+                deadline: None,
+                ibc_destination: None,
+                referral: None,
             })
             .unwrap(),
         },
@@ -643,10 +689,15 @@ fn test_swap_route() {
                     ask_asset_info: AssetInfo::Token {
                         contract_addr: atom.clone(),
                     },
+                    pair_type: None,
+                    fee_tier: None,
                 }],
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                deadline: None,
+                ibc_destination: None,
+                referral: None,
             },
             &[],
         )
@@ -680,3 +731,838 @@ fn test_swap_route() {
     let profit = balance_res.balance.saturating_sub(donated_atom);
     println!("Attacker2's profit: {:?}", profit);
 }
+
+#[test]
+fn router_enforces_deadline() {
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+
+    let pair = helper
+        .create_pair_with_addr(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&token_x, &token_y],
+            None,
+        )
+        .unwrap();
+    mint(&mut app, &owner, &token_x, 100_000_000000, &pair).unwrap();
+    mint(&mut app, &owner, &token_y, 100_000_000000, &pair).unwrap();
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                cw20_ics20: None,
+                max_referral_fee_bps: None,
+                native_swap_adapter: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    mint(&mut app, &owner, &token_x, 2_000000, &owner).unwrap();
+    let now = app.block_info().time.seconds();
+    let swap_msg = |deadline: u64| Cw20ExecuteMsg::Send {
+        contract: router.to_string(),
+        amount: 1_000000u128.into(),
+        msg: to_binary(&Cw20HookMsg::ExecuteSwapOperations {
+            operations: vec![SwapOperation::AstroSwap {
+                offer_asset_info: token_asset_info(token_x.clone()),
+                ask_asset_info: token_asset_info(token_y.clone()),
+                pair_type: None,
+                fee_tier: None,
+            }],
+            minimum_receive: None,
+            to: None,
+            max_spread: None,
+            deadline: Some(deadline),
+            ibc_destination: None,
+            referral: None,
+        })
+        .unwrap(),
+    };
+
+    let err = app
+        .execute_contract(owner.clone(), token_x.clone(), &swap_msg(now - 1), &[])
+        .unwrap_err();
+    assert_eq!(
+        astroport_router::error::ContractError::DeadlineExpired {},
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(owner.clone(), token_x.clone(), &swap_msg(now), &[])
+        .unwrap();
+}
+
+#[test]
+fn test_split_swap_routes() {
+    use astroport::router::{QueryMsg, RouteSplit, SimulateSplitSwapOperationsResponse, SwapRoute};
+    use cosmwasm_std::{Decimal, Uint128};
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+    let token_z = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOZ", None);
+
+    for (a, b) in [
+        (&token_x, &token_y),
+        (&token_x, &token_z),
+        (&token_z, &token_y),
+    ] {
+        let pair = helper
+            .create_pair_with_addr(&mut app, &owner, PairType::Xyk {}, [a, b], None)
+            .unwrap();
+        mint(&mut app, &owner, a, 100_000_000000, &pair).unwrap();
+        mint(&mut app, &owner, b, 100_000_000000, &pair).unwrap();
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                cw20_ics20: None,
+                max_referral_fee_bps: None,
+                native_swap_adapter: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let routes = vec![
+        SwapRoute {
+            operations: vec![SwapOperation::AstroSwap {
+                offer_asset_info: token_asset_info(token_x.clone()),
+                ask_asset_info: token_asset_info(token_y.clone()),
+                pair_type: None,
+                fee_tier: None,
+            }],
+            split: RouteSplit::Percent(Decimal::percent(50)),
+        },
+        SwapRoute {
+            operations: vec![
+                SwapOperation::AstroSwap {
+                    offer_asset_info: token_asset_info(token_x.clone()),
+                    ask_asset_info: token_asset_info(token_z.clone()),
+                    pair_type: None,
+                    fee_tier: None,
+                },
+                SwapOperation::AstroSwap {
+                    offer_asset_info: token_asset_info(token_z.clone()),
+                    ask_asset_info: token_asset_info(token_y.clone()),
+                    pair_type: None,
+                    fee_tier: None,
+                },
+            ],
+            split: RouteSplit::Percent(Decimal::percent(50)),
+        },
+    ];
+
+    let offer_amount = Uint128::new(1_000_000001);
+    let simulation: SimulateSplitSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSplitSwapOperations {
+                offer_amount,
+                routes: routes.clone(),
+            },
+        )
+        .unwrap();
+    // Rounding leftover goes to the last route
+    assert_eq!(simulation.routes[0].offer_amount.u128(), 500_000000);
+    assert_eq!(simulation.routes[1].offer_amount.u128(), 500_000001);
+    assert_eq!(
+        simulation.amount,
+        simulation.routes[0].return_amount + simulation.routes[1].return_amount
+    );
+
+    mint(&mut app, &owner, &token_x, 2 * offer_amount.u128(), &owner).unwrap();
+    let swap_msg = |minimum_receive: Uint128| Cw20ExecuteMsg::Send {
+        contract: router.to_string(),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::ExecuteSplitSwapOperations {
+            routes: routes.clone(),
+            minimum_receive: Some(minimum_receive),
+            to: None,
+            max_spread: None,
+            deadline: None,
+        })
+        .unwrap(),
+    };
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            token_x.clone(),
+            &swap_msg(simulation.amount + Uint128::one()),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        astroport_router::error::ContractError::AssertionMinimumReceive {
+            receive: simulation.amount + Uint128::one(),
+            amount: simulation.amount,
+        },
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        token_x.clone(),
+        &swap_msg(simulation.amount),
+        &[],
+    )
+    .unwrap();
+
+    let balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &token_y,
+            &Cw20QueryMsg::Balance {
+                address: owner.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance.balance, simulation.amount);
+
+    // Routes must share the same ask asset
+    let mut invalid_routes = routes.clone();
+    invalid_routes[1].operations.pop();
+    let err = app
+        .wrap()
+        .query_wasm_smart::<SimulateSplitSwapOperationsResponse>(
+            &router,
+            &QueryMsg::SimulateSplitSwapOperations {
+                offer_amount,
+                routes: invalid_routes,
+            },
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("all routes must have the same offer and ask assets"));
+}
+
+#[test]
+fn test_find_best_route() {
+    use astroport::asset::Asset;
+    use astroport::router::{FindBestRouteResponse, QueryMsg, SimulateSwapOperationsResponse};
+
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+    let token_z = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOZ", None);
+    let token_w = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOW", None);
+
+    // The direct pool is much shallower than the pools of the route through Z
+    for (a, b, liq) in [
+        (&token_x, &token_y, 1_000_000000),
+        (&token_x, &token_z, 100_000_000000),
+        (&token_z, &token_y, 100_000_000000),
+    ] {
+        let pair = helper
+            .create_pair_with_addr(&mut app, &owner, PairType::Xyk {}, [a, b], None)
+            .unwrap();
+        mint(&mut app, &owner, a, liq, &pair).unwrap();
+        mint(&mut app, &owner, b, liq, &pair).unwrap();
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                cw20_ics20: None,
+                max_referral_fee_bps: None,
+                native_swap_adapter: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let find_best_route = |app: &App, ask: &Addr, max_hops: Option<u32>| {
+        app.wrap().query_wasm_smart::<FindBestRouteResponse>(
+            &router,
+            &QueryMsg::FindBestRoute {
+                offer_asset: Asset {
+                    info: token_asset_info(token_x.clone()),
+                    amount: 500_000000u128.into(),
+                },
+                ask_asset_info: token_asset_info(ask.clone()),
+                max_hops,
+            },
+        )
+    };
+
+    let best_route = find_best_route(&app, &token_y, None).unwrap();
+    assert_eq!(
+        best_route.operations,
+        vec![
+            SwapOperation::AstroSwap {
+                offer_asset_info: token_asset_info(token_x.clone()),
+                ask_asset_info: token_asset_info(token_z.clone()),
+                pair_type: Some(PairType::Xyk {}),
+                fee_tier: None,
+            },
+            SwapOperation::AstroSwap {
+                offer_asset_info: token_asset_info(token_z.clone()),
+                ask_asset_info: token_asset_info(token_y.clone()),
+                pair_type: Some(PairType::Xyk {}),
+                fee_tier: None,
+            },
+        ]
+    );
+    let simulation: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount: 500_000000u128.into(),
+                operations: best_route.operations.clone(),
+                referral: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(best_route.amount, simulation.amount);
+
+    // The number of hops is capped
+    assert_eq!(
+        find_best_route(&app, &token_y, Some(50)).unwrap(),
+        best_route
+    );
+
+    // Only the direct pool is available with a single hop
+    let best_route = find_best_route(&app, &token_y, Some(1)).unwrap();
+    assert_eq!(
+        best_route.operations,
+        vec![SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_x.clone()),
+            ask_asset_info: token_asset_info(token_y.clone()),
+            pair_type: Some(PairType::Xyk {}),
+            fee_tier: None,
+        }]
+    );
+
+    let err = find_best_route(&app, &token_w, None).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(format!(
+            "Querier contract error: {}",
+            astroport_router::error::ContractError::RouteNotFound {
+                offer_asset: token_x.to_string(),
+                ask_asset: token_w.to_string(),
+            }
+        ))
+    );
+}
+
+#[test]
+fn swap_through_chosen_pair_type() {
+    use astroport::router::{QueryMsg, SimulateSwapOperationsResponse};
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+
+    // Two pairs of different types are registered for the same assets
+    let mut pairs = vec![];
+    for (typ, liq) in [
+        (PairType::Xyk {}, 1_000_000000),
+        (PairType::Stable {}, 100_000_000000),
+    ] {
+        let pair = helper
+            .create_pair_with_addr(&mut app, &owner, typ.clone(), [&token_x, &token_y], None)
+            .unwrap();
+        mint(&mut app, &owner, &token_x, liq, &pair).unwrap();
+        mint(&mut app, &owner, &token_y, liq, &pair).unwrap();
+        pairs.push((typ, pair));
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                cw20_ics20: None,
+                max_referral_fee_bps: None,
+                native_swap_adapter: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let operations = |pair_type: Option<PairType>| {
+        vec![SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_x.clone()),
+            ask_asset_info: token_asset_info(token_y.clone()),
+            pair_type,
+            fee_tier: None,
+        }]
+    };
+    let simulate = |app: &App, pair_type: Option<PairType>| {
+        app.wrap()
+            .query_wasm_smart::<SimulateSwapOperationsResponse>(
+                &router,
+                &QueryMsg::SimulateSwapOperations {
+                    offer_amount: 100_000000u128.into(),
+                    operations: operations(pair_type),
+                    referral: None,
+                },
+            )
+            .unwrap()
+    };
+
+    let xyk_simulation = simulate(&app, Some(PairType::Xyk {}));
+    let stable_simulation = simulate(&app, Some(PairType::Stable {}));
+    assert!(xyk_simulation.amount < stable_simulation.amount);
+    assert_eq!(xyk_simulation.operations[0].pair_address, pairs[0].1);
+    assert_eq!(stable_simulation.operations[0].pair_address, pairs[1].1);
+    // Without the pair type the pair with the lowest pair type key is used
+    assert_eq!(simulate(&app, None), stable_simulation);
+
+    let user = Addr::unchecked("user");
+    mint(&mut app, &owner, &token_x, 100_000000, &user).unwrap();
+    app.execute_contract(
+        user.clone(),
+        token_x.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: router.to_string(),
+            amount: 100_000000u128.into(),
+            msg: to_binary(&ExecuteMsg::ExecuteSwapOperations {
+                operations: operations(Some(PairType::Xyk {})),
+                minimum_receive: Some(xyk_simulation.amount),
+                to: None,
+                max_spread: None,
+                deadline: None,
+                ibc_destination: None,
+                referral: None,
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let balance = |app: &App, address: &Addr| {
+        app.wrap()
+            .query_wasm_smart::<BalanceResponse>(
+                &token_y,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+    };
+    assert_eq!(balance(&app, &user), xyk_simulation.amount);
+    assert_eq!(
+        balance(&app, &pairs[0].1).u128(),
+        1_000_000000 - xyk_simulation.amount.u128()
+    );
+    assert_eq!(balance(&app, &pairs[1].1).u128(), 100_000_000000);
+}
+
+#[test]
+fn test_simulate_reverse_swap_operations() {
+    use astroport::router::{
+        QueryMsg, SimulateReverseSwapOperationsResponse, SimulateSwapOperationsResponse,
+    };
+    use cosmwasm_std::Uint128;
+
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+    let token_z = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOZ", None);
+
+    for (a, b) in [(&token_x, &token_y), (&token_y, &token_z)] {
+        let pair = helper
+            .create_pair_with_addr(&mut app, &owner, PairType::Xyk {}, [a, b], None)
+            .unwrap();
+        mint(&mut app, &owner, a, 100_000_000000, &pair).unwrap();
+        mint(&mut app, &owner, b, 100_000_000000, &pair).unwrap();
+    }
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                cw20_ics20: None,
+                max_referral_fee_bps: None,
+                native_swap_adapter: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let operations = vec![
+        SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_x.clone()),
+            ask_asset_info: token_asset_info(token_y.clone()),
+            pair_type: None,
+            fee_tier: None,
+        },
+        SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(token_y.clone()),
+            ask_asset_info: token_asset_info(token_z.clone()),
+            pair_type: None,
+            fee_tier: None,
+        },
+    ];
+
+    let ask_amount = Uint128::new(1_000_000000);
+    let reverse_simulation: SimulateReverseSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateReverseSwapOperations {
+                ask_amount,
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
+
+    let hops = &reverse_simulation.operations;
+    assert_eq!(hops.len(), 2);
+    assert_eq!(hops[1].ask_amount, ask_amount);
+    assert_eq!(hops[0].ask_amount, hops[1].offer_amount);
+    assert_eq!(reverse_simulation.offer_amount, hops[0].offer_amount);
+    assert!(hops
+        .iter()
+        .all(|hop| !hop.spread_amount.is_zero() && !hop.commission_amount.is_zero()));
+
+    // Offering the required amount returns at least the requested amount
+    let simulation: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount: reverse_simulation.offer_amount,
+                operations,
+                referral: None,
+            },
+        )
+        .unwrap();
+    assert!(simulation.amount >= ask_amount);
+
+    // Forward simulation contains a breakdown of every operation
+    assert_eq!(simulation.operations.len(), 2);
+    assert_eq!(
+        simulation.operations[0].offer_amount,
+        reverse_simulation.offer_amount
+    );
+    assert_eq!(
+        simulation.operations[1].offer_amount,
+        simulation.operations[0].return_amount
+    );
+    assert_eq!(simulation.operations[1].return_amount, simulation.amount);
+    assert!(!simulation.price_impact.is_zero());
+
+    // Both breakdowns report the pairs used for every operation
+    assert_ne!(hops[0].pair_address, hops[1].pair_address);
+    for (hop, operation) in hops.iter().zip(simulation.operations.iter()) {
+        assert_eq!(hop.pair_address, operation.pair_address);
+    }
+}
+
+#[test]
+fn test_referral_fee() {
+    use astroport::router::{QueryMsg, Referral, SimulateSwapOperationsResponse};
+    use cosmwasm_std::Uint128;
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let referral = Addr::unchecked("referral");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+
+    let pair = helper
+        .create_pair_with_addr(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&token_x, &token_y],
+            None,
+        )
+        .unwrap();
+    mint(&mut app, &owner, &token_x, 100_000_000000, &pair).unwrap();
+    mint(&mut app, &owner, &token_y, 100_000_000000, &pair).unwrap();
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                cw20_ics20: None,
+                max_referral_fee_bps: Some(100),
+                native_swap_adapter: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let operations = vec![SwapOperation::AstroSwap {
+        offer_asset_info: token_asset_info(token_x.clone()),
+        ask_asset_info: token_asset_info(token_y.clone()),
+        pair_type: None,
+        fee_tier: None,
+    }];
+    let referral_msg = |fee_bps: u16| Referral {
+        address: referral.to_string(),
+        fee_bps,
+    };
+
+    let simulation: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount: 1_000000u128.into(),
+                operations: operations.clone(),
+                referral: Some(referral_msg(100)),
+            },
+        )
+        .unwrap();
+    let gross_amount = simulation.operations[0].return_amount;
+    assert_eq!(
+        simulation.referral_amount,
+        gross_amount.multiply_ratio(1u8, 100u8)
+    );
+    assert_eq!(simulation.amount + simulation.referral_amount, gross_amount);
+
+    mint(&mut app, &owner, &token_x, 2_000000, &owner).unwrap();
+    let swap_msg = |fee_bps: u16| Cw20ExecuteMsg::Send {
+        contract: router.to_string(),
+        amount: 1_000000u128.into(),
+        msg: to_binary(&Cw20HookMsg::ExecuteSwapOperations {
+            operations: operations.clone(),
+            minimum_receive: Some(simulation.amount),
+            to: None,
+            max_spread: None,
+            deadline: None,
+            ibc_destination: None,
+            referral: Some(referral_msg(fee_bps)),
+        })
+        .unwrap(),
+    };
+
+    let err = app
+        .execute_contract(owner.clone(), token_x.clone(), &swap_msg(101), &[])
+        .unwrap_err();
+    assert_eq!(
+        astroport_router::error::ContractError::ReferralFeeTooHigh {
+            fee_bps: 101,
+            max_fee_bps: 100
+        },
+        err.downcast().unwrap()
+    );
+
+    // Tokens already held by the router are not used to pay the referral fee
+    mint(&mut app, &owner, &token_y, 5_000000, &router).unwrap();
+
+    app.execute_contract(owner.clone(), token_x.clone(), &swap_msg(100), &[])
+        .unwrap();
+
+    let balance = |address: &Addr| {
+        app.wrap()
+            .query_wasm_smart::<BalanceResponse>(
+                &token_y,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+    };
+    assert_eq!(balance(&owner), simulation.amount);
+    assert_eq!(balance(&referral), simulation.referral_amount);
+    assert_eq!(balance(&router), Uint128::new(5_000000));
+}
+
+#[test]
+fn ibc_refunds_are_not_swept() {
+    use cosmwasm_std::Uint128;
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+
+    let mut app = App::default();
+
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
+    let token_y = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOY", None);
+
+    let pair = helper
+        .create_pair_with_addr(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [&token_x, &token_y],
+            None,
+        )
+        .unwrap();
+    mint(&mut app, &owner, &token_x, 100_000_000000, &pair).unwrap();
+    mint(&mut app, &owner, &token_y, 100_000_000000, &pair).unwrap();
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+                cw20_ics20: None,
+                max_referral_fee_bps: None,
+                native_swap_adapter: None,
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let balance = |app: &App, token: &Addr, address: &Addr| {
+        app.wrap()
+            .query_wasm_smart::<BalanceResponse>(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+    };
+    let swap = |offer: &Addr, ask: &Addr| SwapOperation::AstroSwap {
+        offer_asset_info: token_asset_info(offer.clone()),
+        ask_asset_info: token_asset_info(ask.clone()),
+        pair_type: None,
+        fee_tier: None,
+    };
+    let swap_msg = |operations: Vec<SwapOperation>| Cw20HookMsg::ExecuteSwapOperations {
+        operations,
+        minimum_receive: None,
+        to: None,
+        max_spread: None,
+        deadline: None,
+        ibc_destination: None,
+        referral: None,
+    };
+
+    // The router sends IBC transfers, so refunds of timed out or failed transfers land in it
+    let refund = Uint128::new(5_000000);
+    mint(&mut app, &owner, &token_y, refund.u128(), &router).unwrap();
+
+    // A swap from the refunded asset only swaps the tokens sent along with it
+    mint(&mut app, &owner, &token_y, 1_000000, &user).unwrap();
+    app.execute_contract(
+        user.clone(),
+        token_y.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: router.to_string(),
+            amount: 1_000000u128.into(),
+            msg: to_binary(&swap_msg(vec![swap(&token_y, &token_x)])).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        balance(&app, &token_y, &pair),
+        Uint128::new(100_000_000000 + 1_000000)
+    );
+    assert_eq!(balance(&app, &token_y, &router), refund);
+
+    // A route through the refunded asset only swaps what the previous hop returned
+    mint(&mut app, &owner, &token_x, 1_000000, &user).unwrap();
+    app.execute_contract(
+        user.clone(),
+        token_x.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: router.to_string(),
+            amount: 1_000000u128.into(),
+            msg: to_binary(&swap_msg(vec![
+                swap(&token_x, &token_y),
+                swap(&token_y, &token_x),
+            ]))
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(balance(&app, &token_x, &router), Uint128::zero());
+    assert_eq!(balance(&app, &token_y, &router), refund);
+
+    // Nobody can swap the refund without sending tokens
+    let err = app
+        .execute_contract(
+            user.clone(),
+            router.clone(),
+            &ExecuteMsg::ExecuteSwapOperations {
+                operations: vec![swap(&token_y, &token_x)],
+                minimum_receive: None,
+                to: None,
+                max_spread: None,
+                deadline: None,
+                ibc_destination: None,
+                referral: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Invalid zero amount");
+    assert_eq!(balance(&app, &token_y, &router), refund);
+}
@@ -39,6 +39,7 @@ use crate::state::{
     accumulate_pool_proxy_rewards, query_lp_balance, update_proxy_asset, update_user_balance,
     update_virtual_amount, CompatibleLoader, CHECKPOINT_GENERATORS_LIMIT, CONFIG, DEFAULT_LIMIT,
     MAX_LIMIT, OWNERSHIP_PROPOSAL, POOL_INFO, PROXY_REWARDS_HOLDER, PROXY_REWARD_ASSET, USER_INFO,
+    WITHDRAW_ALLOWANCES,
 };
 
 /// Contract name that is used for migration.
@@ -112,6 +113,11 @@ pub fn instantiate(
 ///
 /// * **ExecuteMsg::Withdraw { lp_token, amount }** Withdraw LP tokens from the Generator.
 ///
+/// * **ExecuteMsg::SetWithdrawAllowance { lp_token, spender, amount }** Allows another address to withdraw
+/// the sender's LP tokens.
+///
+/// * **ExecuteMsg::WithdrawFrom { lp_token, owner, amount }** Withdraw LP tokens of another user using their allowance.
+///
 /// * **ExecuteMsg::EmergencyWithdraw { lp_token }** Withdraw LP tokens without caring about reward claiming.
 /// TO BE USED IN EMERGENCY SITUATIONS ONLY.
 ///
@@ -219,7 +225,49 @@ pub fn execute(
                 Some(vec![lp_token.clone()]),
                 ExecuteOnReply::Withdraw {
                     lp_token,
-                    account: info.sender,
+                    account: info.sender.clone(),
+                    recipient: info.sender,
+                    amount,
+                },
+            )
+        }
+        ExecuteMsg::SetWithdrawAllowance {
+            lp_token,
+            spender,
+            amount,
+        } => set_withdraw_allowance(deps, info, lp_token, spender, amount),
+        ExecuteMsg::WithdrawFrom {
+            lp_token,
+            owner,
+            amount,
+        } => {
+            if amount.is_zero() {
+                return Err(ContractError::ZeroWithdraw {});
+            }
+            let lp_token = deps.api.addr_validate(&lp_token)?;
+            let owner = deps.api.addr_validate(&owner)?;
+
+            let key = (&lp_token, &owner, &info.sender);
+            let allowance = WITHDRAW_ALLOWANCES
+                .may_load(deps.storage, key)?
+                .unwrap_or_default();
+            let allowance = allowance
+                .checked_sub(amount)
+                .map_err(|_| ContractError::WithdrawAllowanceTooSmall {})?;
+            if allowance.is_zero() {
+                WITHDRAW_ALLOWANCES.remove(deps.storage, key);
+            } else {
+                WITHDRAW_ALLOWANCES.save(deps.storage, key, &allowance)?;
+            }
+
+            update_rewards_and_execute(
+                deps.branch(),
+                env,
+                Some(vec![lp_token.clone()]),
+                ExecuteOnReply::Withdraw {
+                    lp_token,
+                    account: owner,
+                    recipient: info.sender,
                     amount,
                 },
             )
@@ -589,20 +637,12 @@ pub fn execute_setup_pools(
         }
 
         // If a pair gets deregistered from the factory, we should raise error.
-        let _: PairInfo = deps
-            .querier
-            .query_wasm_smart(
-                &cfg.factory,
-                &FactoryQueryMsg::Pair {
-                    asset_infos: pair_info.asset_infos.clone(),
-                },
-            )
-            .map_err(|_| {
-                ContractError::Std(StdError::generic_err(format!(
-                    "The pair is not registered: {}-{}",
-                    pair_info.asset_infos[0], pair_info.asset_infos[1]
-                )))
-            })?;
+        if !is_pair_registered(&deps.querier, &cfg.factory, &pair_info) {
+            return Err(ContractError::Std(StdError::generic_err(format!(
+                "The pair is not registered: {}-{}",
+                pair_info.asset_infos[0], pair_info.asset_infos[1]
+            ))));
+        }
 
         setup_pools.push((pool_addr, alloc_point));
     }
@@ -757,8 +797,9 @@ fn handle_callback(
         ExecuteOnReply::Withdraw {
             lp_token,
             account,
+            recipient,
             amount,
-        } => withdraw(deps, env, lp_token, account, amount),
+        } => withdraw(deps, env, lp_token, account, recipient, amount),
         ExecuteOnReply::SetTokensPerBlock { amount } => set_tokens_per_block(deps, env, amount),
         ExecuteOnReply::MigrateProxy {
             lp_addr,
@@ -1123,7 +1164,9 @@ pub fn deposit(
 ///
 /// * **lp_token** LP token to withdraw.
 ///
-/// * **account** user whose LP tokens we withdraw.
+/// * **account** user whose LP tokens we withdraw. Pending rewards are sent to this address.
+///
+/// * **recipient** address which receives the withdrawn LP tokens.
 ///
 /// * **amount** amount of LP tokens to withdraw.
 pub fn withdraw(
@@ -1131,6 +1174,7 @@ pub fn withdraw(
     env: Env,
     lp_token: Addr,
     account: Addr,
+    recipient: Addr,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     let user = USER_INFO
@@ -1154,14 +1198,14 @@ pub fn withdraw(
             contract_addr: proxy.to_string(),
             funds: vec![],
             msg: to_binary(&ProxyExecuteMsg::Withdraw {
-                account: account.to_string(),
+                account: recipient.to_string(),
                 amount,
             })?,
         },
         None => WasmMsg::Execute {
             contract_addr: lp_token.to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: account.to_string(),
+                recipient: recipient.to_string(),
                 amount,
             })?,
             funds: vec![],
@@ -1196,6 +1240,36 @@ pub fn withdraw(
         .add_attribute("action", "withdraw")
         .add_attribute("amount", amount))
 }
+/// Sets the amount of LP tokens `spender` is allowed to withdraw on behalf of the sender.
+///
+/// * **lp_token** LP token which the allowance is set for.
+///
+/// * **spender** address which is allowed to withdraw the LP tokens.
+///
+/// * **amount** maximum amount of LP tokens to withdraw. Zero removes the allowance.
+fn set_withdraw_allowance(
+    deps: DepsMut,
+    info: MessageInfo,
+    lp_token: String,
+    spender: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let lp_token = deps.api.addr_validate(&lp_token)?;
+    let spender = deps.api.addr_validate(&spender)?;
+
+    let key = (&lp_token, &info.sender, &spender);
+    if amount.is_zero() {
+        WITHDRAW_ALLOWANCES.remove(deps.storage, key);
+    } else {
+        WITHDRAW_ALLOWANCES.save(deps.storage, key, &amount)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_withdraw_allowance")
+        .add_attribute("spender", spender)
+        .add_attribute("amount", amount))
+}
+
 /// Withdraw LP tokens without caring about rewards. TO BE USED IN EMERGENCY SITUATIONS ONLY.
 ///
 /// * **lp_token** LP token to withdraw.
@@ -1585,6 +1659,9 @@ fn move_to_proxy(
 ///
 /// * **QueryMsg::Deposit { lp_token, user }** Returns the amount of LP tokens staked by a user in a specific generator.
 ///
+/// * **QueryMsg::WithdrawAllowance { lp_token, owner, spender }** Returns the amount of LP tokens a spender is allowed
+/// to withdraw on behalf of a user.
+///
 /// * **QueryMsg::PendingToken { lp_token, user }** Returns the amount of pending rewards a user earned using
 /// a [`PendingTokenResponse`] object.
 ///
@@ -1615,6 +1692,19 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
                 .count();
             Ok(to_binary(&length)?)
         }
+        QueryMsg::WithdrawAllowance {
+            lp_token,
+            owner,
+            spender,
+        } => {
+            let lp_token = deps.api.addr_validate(&lp_token)?;
+            let owner = deps.api.addr_validate(&owner)?;
+            let spender = deps.api.addr_validate(&spender)?;
+            let allowance = WITHDRAW_ALLOWANCES
+                .may_load(deps.storage, (&lp_token, &owner, &spender))?
+                .unwrap_or_default();
+            Ok(to_binary(&allowance)?)
+        }
         QueryMsg::UserVirtualAmount { lp_token, user } => {
             Ok(to_binary(&query_virtual_amount(deps, lp_token, user)?)?)
         }
@@ -1990,6 +2080,36 @@ pub fn get_alloc_point(pools: &[(Addr, Uint128)], lp_token: &Addr) -> Uint128 {
         .unwrap_or_else(Uint128::zero)
 }
 
+/// Checks whether a pair is registered in the factory either with its pair type fees
+/// or with one of the pair type fee tiers.
+fn is_pair_registered(querier: &QuerierWrapper, factory: &Addr, pair_info: &PairInfo) -> bool {
+    // Factories without fee tiers do not support this query
+    let fee_tiers: Vec<u16> = querier
+        .query_wasm_smart(
+            factory,
+            &FactoryQueryMsg::FeeTiers {
+                pair_type: pair_info.pair_type.clone(),
+            },
+        )
+        .unwrap_or_default();
+
+    std::iter::once(None)
+        .chain(fee_tiers.into_iter().map(Some))
+        .any(|fee_tier| {
+            querier
+                .query_wasm_smart::<PairInfo>(
+                    factory,
+                    &FactoryQueryMsg::Pair {
+                        asset_infos: pair_info.asset_infos.clone(),
+                        pair_type: Some(pair_info.pair_type.clone()),
+                        fee_tier,
+                    },
+                )
+                .map(|registered_pair| registered_pair.contract_addr == pair_info.contract_addr)
+                .unwrap_or(false)
+        })
+}
+
 /// Creates pool if it is allowed in the factory.
 pub fn create_pool(
     deps: DepsMut,
@@ -2045,7 +2165,7 @@ pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response,
                     migration::fix_neutron_users_reward_indexes(&mut deps)?;
                 }
             }
-            "2.3.1" => {}
+            "2.3.1" | "2.3.2" => {}
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
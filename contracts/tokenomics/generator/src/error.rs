@@ -46,6 +46,9 @@ pub enum ContractError {
     #[error("You can not withdraw 0 LP tokens.")]
     ZeroWithdraw {},
 
+    #[error("Withdraw allowance is too small")]
+    WithdrawAllowanceTooSmall {},
+
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
 }
@@ -33,6 +33,11 @@ pub const POOL_INFO: Map<&Addr, PoolInfo> = Map::new("pool_info");
 ///
 /// The first key is an LP token address, the second key is a depositor address.
 pub const USER_INFO: Map<(&Addr, &Addr), UserInfoV2> = Map::new("user_info");
+/// Contains the amounts of staked LP tokens which spenders are allowed to withdraw.
+///
+/// The first key is an LP token address, the second key is a staker address, the third key is a spender address.
+pub const WITHDRAW_ALLOWANCES: Map<(&Addr, &Addr, &Addr), Uint128> =
+    Map::new("withdraw_allowances");
 /// Old USER_INFO storage interface for backward compatibility
 pub const OLD_USER_INFO: Map<(&Addr, &Addr), UserInfo> = Map::new("user_info");
 /// Previous proxy rewards holder
@@ -1140,6 +1140,7 @@ fn disabling_pool() {
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: true,
+            fee_tiers: vec![],
         },
     };
 
@@ -1169,6 +1170,7 @@ fn disabling_pool() {
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         },
     };
 
@@ -3156,6 +3158,8 @@ fn setup_pools() {
                     denom: "eur".to_string(),
                 },
             ],
+            pair_type: PairType::Xyk {},
+            fee_tier: None,
         },
         &[],
     )
@@ -3344,6 +3348,7 @@ fn deactivate_pools_by_pair_types() {
             to_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_sources: None,
             })
             .unwrap(),
         ),
@@ -3430,6 +3435,7 @@ fn deactivate_pools_by_pair_types() {
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: true,
+            fee_tiers: vec![],
         },
     };
 
@@ -3533,6 +3539,7 @@ fn deactivate_pools_by_pair_types() {
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         },
     };
 
@@ -3938,6 +3945,7 @@ fn instantiate_factory(
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         }],
         token_code_id,
         fee_address: None,
@@ -3955,6 +3963,7 @@ fn instantiate_factory(
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         });
     }
 
@@ -4285,6 +4294,8 @@ fn create_pair(
             pair_type: pair_type.unwrap_or_else(|| PairType::Xyk {}),
             asset_infos: assets.clone(),
             init_params: init_param,
+            fee: None,
+            fee_tier: None,
         },
         &[],
     )
@@ -4296,6 +4307,8 @@ fn create_pair(
             factory,
             &FactoryQueryMsg::Pair {
                 asset_infos: assets,
+                pair_type: None,
+                fee_tier: None,
             },
         )
         .unwrap();
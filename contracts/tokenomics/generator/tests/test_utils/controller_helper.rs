@@ -60,6 +60,7 @@ impl ControllerHelper {
                 maker_fee_bps: 10,
                 is_disabled: false,
                 is_generator_disabled: false,
+                fee_tiers: vec![],
             }],
             token_code_id: escrow_helper.astro_token_code_id,
             fee_address: None,
@@ -256,13 +257,19 @@ impl ControllerHelper {
                 pair_type: PairType::Xyk {},
                 asset_infos: asset_infos.clone(),
                 init_params: None,
+                fee: None,
+                fee_tier: None,
             },
             &[],
         )?;
 
         let res: PairInfo = router.wrap().query_wasm_smart(
             self.factory.clone(),
-            &astroport::factory::QueryMsg::Pair { asset_infos },
+            &astroport::factory::QueryMsg::Pair {
+                asset_infos,
+                pair_type: None,
+                fee_tier: None,
+            },
         )?;
 
         Ok(res.liquidity_token)
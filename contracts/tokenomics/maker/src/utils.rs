@@ -71,6 +71,7 @@ pub fn build_swap_msg(
                 belief_price: None,
                 max_spread: Some(max_spread),
                 to: None,
+                deadline: None,
             })?,
             funds: vec![offer_asset.as_coin()?],
         }))
@@ -85,6 +86,7 @@ pub fn build_swap_msg(
                     belief_price: None,
                     max_spread: Some(max_spread),
                     to: None,
+                    deadline: None,
                 })?,
             })?,
             funds: vec![],
@@ -175,7 +177,8 @@ pub fn validate_bridge(
 }
 
 /// This function checks that there is a pool to swap between `from` and `to`. In case of success
-/// returns [`PairInfo`] of selected pool.
+/// returns [`PairInfo`] of selected pool. If several pools exist for the assets, the factory picks
+/// the one with the lowest pair type key.
 ///
 /// * **factory_contract** address of the factory contract.
 ///
@@ -192,6 +195,8 @@ pub fn get_pool(
         querier,
         factory_contract.clone(),
         &[from.clone(), to.clone()],
+        None,
+        None,
     )
     .map_err(|_| ContractError::InvalidBridgeNoPool(from.to_string(), to.to_string()))
 }
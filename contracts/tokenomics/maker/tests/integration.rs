@@ -173,6 +173,7 @@ fn instantiate_contracts(
             maker_fee_bps: 0,
             is_disabled: false,
             is_generator_disabled: false,
+            fee_tiers: vec![],
         }],
         token_code_id: 1u64,
         fee_address: None,
@@ -380,6 +381,8 @@ fn create_pair(
                 pair_type: pair_type.unwrap_or(PairType::Xyk {}),
                 asset_infos: asset_infos.clone(),
                 init_params: None,
+                fee: None,
+                fee_tier: None,
             },
             &[],
         )
@@ -394,6 +397,8 @@ fn create_pair(
             contract_addr: factory_instance.clone().to_string(),
             msg: to_binary(&astroport::factory::QueryMsg::Pair {
                 asset_infos: asset_infos.clone(),
+                pair_type: None,
+                fee_tier: None,
             })
             .unwrap(),
         }))
@@ -442,6 +447,7 @@ fn create_pair(
                 slippage_tolerance: None,
                 auto_stake: None,
                 receiver: None,
+                deadline: None,
             },
             &funds,
         )
@@ -2565,6 +2571,8 @@ fn collect_3pools() {
                     token_asset_info(usdc_token_instance),
                     native_asset_info(uluna_asset),
                 ],
+                pair_type: None,
+                fee_tier: None,
             },
         )
         .unwrap();
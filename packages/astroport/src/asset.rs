@@ -513,6 +513,9 @@ pub struct PairInfo {
     pub liquidity_token: Addr,
     /// The pool type (xyk, stableswap etc) available in [`PairType`]
     pub pair_type: PairType,
+    /// The fee tier the pair was created with. Only the factory returns it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_tier: Option<u16>,
 }
 
 impl PairInfo {
@@ -1,6 +1,7 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    attr, Addr, Api, CustomQuery, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    attr, Addr, Api, BlockInfo, CustomQuery, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult,
 };
 use cw_storage_plus::Item;
 
@@ -138,3 +139,8 @@ where
 pub fn validate_addresses(api: &dyn Api, admins: &[String]) -> StdResult<Vec<Addr>> {
     admins.iter().map(|addr| api.addr_validate(addr)).collect()
 }
+
+/// Returns `true` if the optional `deadline` (block time in seconds) has already passed.
+pub fn is_deadline_passed(block: &BlockInfo, deadline: Option<u64>) -> bool {
+    matches!(deadline, Some(deadline) if block.time.seconds() > deadline)
+}
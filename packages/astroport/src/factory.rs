@@ -70,6 +70,10 @@ pub struct PairConfig {
     /// Setting this to true means that pairs of this type will not be able
     /// to get an ASTRO generator
     pub is_generator_disabled: bool,
+    /// Additional total fees (in bps) which pairs of this type can be created with.
+    /// Pairs with the same assets but different fee tiers are separate pools
+    #[serde(default)]
+    pub fee_tiers: Vec<u16>,
 }
 
 impl PairConfig {
@@ -77,6 +81,34 @@ impl PairConfig {
     pub fn valid_fee_bps(&self) -> bool {
         self.total_fee_bps <= MAX_TOTAL_FEE_BPS && self.maker_fee_bps <= MAX_MAKER_FEE_BPS
     }
+
+    /// This method is used to check that fee tiers are unique and do not exceed the maximum fee.
+    pub fn valid_fee_tiers(&self) -> bool {
+        let mut fee_tiers = self.fee_tiers.clone();
+        fee_tiers.sort_unstable();
+        fee_tiers.dedup();
+
+        fee_tiers.len() == self.fee_tiers.len()
+            && fee_tiers
+                .iter()
+                .all(|fee_tier| *fee_tier <= MAX_TOTAL_FEE_BPS)
+    }
+}
+
+/// This structure stores the fees charged by a specific pair.
+#[cw_serde]
+pub struct PairFee {
+    /// The total fees (in bps) charged by the pair
+    pub total_fee_bps: u16,
+    /// The amount of fees (in bps) collected by the Maker contract from the pair
+    pub maker_fee_bps: u16,
+}
+
+impl PairFee {
+    /// This method is used to check fee bps.
+    pub fn valid_fee_bps(&self) -> bool {
+        self.total_fee_bps <= MAX_TOTAL_FEE_BPS && self.maker_fee_bps <= MAX_MAKER_FEE_BPS
+    }
 }
 
 /// This structure stores the basic settings for creating a new factory contract.
@@ -127,11 +159,60 @@ pub enum ExecuteMsg {
         asset_infos: Vec<AssetInfo>,
         /// Optional binary serialised parameters for custom pool types
         init_params: Option<Binary>,
+        /// Fees which override the pair type fees for the new pair. Only the owner can set them
+        fee: Option<PairFee>,
+        /// The fee tier (total fee in bps) of the new pair. Must be one of the pair type fee tiers.
+        /// If not specified, the pair uses the pair type fees
+        fee_tier: Option<u16>,
+    },
+    /// UpdatePairFee sets or removes fees which override the pair type fees for a specific pair.
+    UpdatePairFee {
+        /// The pair contract address
+        pair_addr: String,
+        /// The new pair fees. If not specified, the pair falls back to its pair type fees
+        fee: Option<PairFee>,
+    },
+    /// UpdateGuardian sets or removes the guardian which can pause pairs.
+    UpdateGuardian {
+        /// The new guardian
+        guardian: UpdateAddr,
+    },
+    /// Pause halts swaps and liquidity provision for the specified pairs. Liquidity can still be withdrawn.
+    Pause {
+        /// The pairs to pause
+        target: PauseTarget,
+    },
+    /// Unpause resumes swaps and liquidity provision for the specified pairs.
+    Unpause {
+        /// The pairs to unpause
+        target: PauseTarget,
+    },
+    /// MigratePairs migrates the next batch of pairs of a specific type to a new code ID.
+    /// The factory must be the admin of the pairs.
+    MigratePairs {
+        /// The type of pairs to migrate
+        pair_type: PairType,
+        /// The code ID to migrate the pairs to
+        new_code_id: u64,
+        /// The migration message sent to each pair
+        msg: Binary,
+        /// The number of pairs to migrate in this batch
+        limit: Option<u32>,
+    },
+    /// MigrateLegacyPairs moves the next batch of pairs registered before version 1.7.0
+    /// to the current storage layout.
+    MigrateLegacyPairs {
+        /// The number of pairs to move in this batch
+        limit: Option<u32>,
     },
     /// Deregister removes a previously created pair.
     Deregister {
         /// The assets for which we deregister a pool
         asset_infos: Vec<AssetInfo>,
+        /// The type of the pair to deregister
+        pair_type: PairType,
+        /// The fee tier of the pair to deregister. Only used together with `pair_type`
+        fee_tier: Option<u16>,
     },
     /// ProposeNewOwner creates a proposal to change contract ownership.
     /// The validity period for the proposal is set in the `expires_in` variable.
@@ -159,12 +240,22 @@ pub enum QueryMsg {
     Pair {
         /// The assets for which we return a pair
         asset_infos: Vec<AssetInfo>,
+        /// The type of the pair to return. If not specified, the pair with the lowest pair type key
+        /// among the pairs registered for the given assets is returned
+        pair_type: Option<PairType>,
+        /// The fee tier of the pair to return. Only used together with `pair_type`
+        fee_tier: Option<u16>,
     },
     /// Pairs returns an array of pairs and their information according to the specified parameters in `start_after` and `limit` variables.
     #[returns(PairsResponse)]
     Pairs {
         /// The pair item to start reading from. It is an [`Option`] type that accepts [`AssetInfo`] elements.
         start_after: Option<Vec<AssetInfo>>,
+        /// The pair type of the `start_after` pair. If not specified, all pairs
+        /// for the `start_after` assets are skipped
+        start_after_pair_type: Option<PairType>,
+        /// The fee tier of the `start_after` pair. Only used together with `start_after_pair_type`
+        start_after_fee_tier: Option<u16>,
         /// The number of pairs to read and return. It is an [`Option`] type.
         limit: Option<u32>,
     },
@@ -173,10 +264,44 @@ pub enum QueryMsg {
     FeeInfo {
         /// The pair type for which we return fee information. Pair type is a [`PairType`] struct
         pair_type: PairType,
+        /// The pair address. If the pair has its own fees, they are returned instead of the pair type fees
+        pair_addr: Option<String>,
     },
     /// Returns a vector that contains blacklisted pair types
     #[returns(Vec<PairType>)]
     BlacklistedPairTypes {},
+    /// Returns the fee tiers (total fees in bps) available for a pair type
+    #[returns(Vec<u16>)]
+    FeeTiers { pair_type: PairType },
+    /// PauseInfo returns the current state of the pause registry
+    #[returns(PauseInfoResponse)]
+    PauseInfo {},
+    /// IsPaused returns whether swaps and liquidity provision are paused for a specific pair
+    #[returns(bool)]
+    IsPaused {
+        /// The type of the pair
+        pair_type: PairType,
+        /// The pair contract address
+        pair_addr: String,
+    },
+    /// PairsMigration returns the state of the latest pairs migration
+    #[returns(Option<PairsMigrationResponse>)]
+    PairsMigration {
+        /// The maximum number of pending and failed pairs to return
+        limit: Option<u32>,
+    },
+    /// PairsByAsset returns an array of pairs which contain the specified asset.
+    #[returns(PairsResponse)]
+    PairsByAsset {
+        /// The asset for which we return pairs
+        asset_info: AssetInfo,
+        /// Only pairs of this type are returned if specified
+        pair_type: Option<PairType>,
+        /// The pair contract address to start reading from
+        start_after: Option<String>,
+        /// The number of pairs to read and return
+        limit: Option<u32>,
+    },
 }
 
 /// A custom struct for each query response that returns general contract settings/configs.
@@ -222,6 +347,56 @@ pub struct FeeInfoResponse {
     pub maker_fee_bps: u16,
 }
 
+/// This enum describes the pairs which can be paused.
+#[cw_serde]
+pub enum PauseTarget {
+    /// All pairs
+    Global {},
+    /// All pairs of a specific type
+    PairType(PairType),
+    /// A specific pair
+    Pair(String),
+}
+
+/// A custom struct for each query response that returns the state of the pause registry.
+#[cw_serde]
+pub struct PauseInfoResponse {
+    /// The address which can pause pairs besides the owner
+    pub guardian: Option<Addr>,
+    /// Whether all pairs are paused
+    pub global: bool,
+    /// Paused pair types
+    pub pair_types: Vec<PairType>,
+    /// Paused pairs
+    pub pairs: Vec<Addr>,
+}
+
+/// This enum describes the result of migrating a specific pair.
+#[cw_serde]
+pub enum PairMigrationStatus {
+    /// The pair was migrated
+    Migrated {},
+    /// The pair failed to migrate
+    Failed {
+        /// The migration error
+        error: String,
+    },
+}
+
+/// A custom struct for each query response that returns the state of a pairs migration.
+#[cw_serde]
+pub struct PairsMigrationResponse {
+    /// The type of pairs being migrated
+    pub pair_type: PairType,
+    /// The code ID the pairs are migrated to
+    pub new_code_id: u64,
+    /// Pairs which were not migrated yet
+    pub pending: Vec<Addr>,
+    /// Pairs which failed to migrate together with their errors. They are retried
+    /// once there are no pending pairs left
+    pub failed: Vec<(Addr, String)>,
+}
+
 /// This is an enum used for setting and removing a contract address.
 #[cw_serde]
 pub enum UpdateAddr {
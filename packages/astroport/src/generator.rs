@@ -70,6 +70,26 @@ pub enum ExecuteMsg {
         /// The amount to withdraw
         amount: Uint128,
     },
+    /// Allows `spender` to withdraw up to `amount` of the LP tokens staked by the sender.
+    /// A zero amount removes the allowance
+    SetWithdrawAllowance {
+        /// The address of the LP token
+        lp_token: String,
+        /// The address which is allowed to withdraw the LP tokens
+        spender: String,
+        /// The maximum amount of LP tokens to withdraw
+        amount: Uint128,
+    },
+    /// Withdraw LP tokens staked by `owner` using the allowance set by `owner`.
+    /// LP tokens are sent to the sender while pending rewards are sent to `owner`
+    WithdrawFrom {
+        /// The address of the LP token to withdraw
+        lp_token: String,
+        /// The account whose LP tokens are withdrawn
+        owner: String,
+        /// The amount to withdraw
+        amount: Uint128,
+    },
     /// Withdraw LP tokens from the Generator without withdrawing outstanding rewards
     EmergencyWithdraw {
         /// The address of the LP token to withdraw
@@ -168,8 +188,10 @@ pub enum ExecuteOnReply {
     Withdraw {
         /// The LP tokens to withdraw
         lp_token: Addr,
-        /// The account that receives the withdrawn LP tokens
+        /// The account whose LP tokens are withdrawn. It receives the pending rewards
         account: Addr,
+        /// The account that receives the withdrawn LP tokens
+        recipient: Addr,
         /// The amount of tokens to withdraw
         amount: Uint128,
     },
@@ -212,6 +234,13 @@ pub enum QueryMsg {
     /// Deposit returns the LP token amount deposited in a specific generator
     #[returns(Uint128)]
     Deposit { lp_token: String, user: String },
+    /// Returns the amount of LP tokens `spender` is allowed to withdraw on behalf of `owner`
+    #[returns(Uint128)]
+    WithdrawAllowance {
+        lp_token: String,
+        owner: String,
+        spender: String,
+    },
     /// Returns the current virtual amount in a specific generator
     #[returns(Uint128)]
     UserVirtualAmount { lp_token: String, user: String },
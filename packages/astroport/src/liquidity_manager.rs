@@ -1,15 +1,20 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 
-use crate::asset::Asset;
+use crate::asset::{Asset, AssetInfo};
 use crate::pair::{Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg};
+use crate::router::SwapOperation;
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub astroport_factory: String,
 }
 
+/// We currently take no arguments for migrations.
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub enum ExecuteMsg {
     ProvideLiquidity {
@@ -17,6 +22,40 @@ pub enum ExecuteMsg {
         pair_msg: PairExecuteMsg,
         min_lp_to_receive: Option<Uint128>,
     },
+    /// Provides liquidity from a single asset. A part of the offer asset is swapped through the pair
+    /// so that both pool assets are deposited in the pool ratio. Leftovers are sent back to the sender.
+    /// CW20 offer assets are pulled from the sender, so an allowance must be set beforehand.
+    ZapIn {
+        pair_addr: String,
+        offer_asset: Asset,
+        /// The maximum spread of the internal swap
+        max_spread: Option<Decimal>,
+        min_lp_to_receive: Option<Uint128>,
+        auto_stake: Option<bool>,
+        receiver: Option<String>,
+        /// The latest block time (in seconds) the transaction can be executed at
+        deadline: Option<u64>,
+    },
+    /// Moves liquidity from one pair to another pair with the same assets in a single transaction.
+    /// LP tokens of `from_pair` are pulled from the sender, so an allowance must be set beforehand.
+    /// Withdrawn assets which the new pair would not accept are sent back to the sender.
+    MigrateLiquidity {
+        from_pair: String,
+        to_pair: String,
+        /// The amount of `from_pair` LP tokens to migrate
+        amount: Uint128,
+        /// Whether the LP tokens are withdrawn from the sender's stake in the generator.
+        /// The sender must allow this contract to withdraw them with the generator `set_withdraw_allowance`
+        /// message instead of setting a CW20 allowance. Defaults to false
+        staked: Option<bool>,
+        /// The minimum amount of `to_pair` LP tokens to receive
+        min_lp_out: Option<Uint128>,
+        /// Whether the new LP tokens are staked in the generator. Defaults to true
+        auto_stake: Option<bool>,
+        receiver: Option<String>,
+        /// The latest block time (in seconds) the transaction can be executed at
+        deadline: Option<u64>,
+    },
     Receive(Cw20ReceiveMsg),
 }
 
@@ -27,9 +66,26 @@ pub enum Cw20HookMsg {
         pair_msg: PairCw20HookMsg,
         #[serde(default)]
         min_assets_to_receive: Vec<Asset>,
+        /// Swaps the withdrawn assets into this pool asset (zap-out).
+        /// `min_assets_to_receive` must be empty in this case
+        target_asset: Option<AssetInfo>,
+        /// The minimum amount of `target_asset` to receive
+        min_receive: Option<Uint128>,
+        /// The router path used to swap the other pool asset into `target_asset`.
+        /// Defaults to a swap through the same pair
+        router_path: Option<RouterPath>,
     },
 }
 
+/// This structure describes a swap path through the Astroport router.
+#[cw_serde]
+pub struct RouterPath {
+    /// The router contract address
+    pub router: String,
+    /// The swap operations. They must start with the swapped asset and end with the target asset
+    pub operations: Vec<SwapOperation>,
+}
+
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
@@ -43,4 +99,12 @@ pub enum QueryMsg {
         pair_addr: String,
         lp_tokens: Uint128,
     },
+    /// Simulates a withdrawal into a single pool asset and returns its amount
+    #[returns(Uint128)]
+    SimulateZapOut {
+        pair_addr: String,
+        lp_tokens: Uint128,
+        target_asset: AssetInfo,
+        router_path: Option<RouterPath>,
+    },
 }
@@ -3,8 +3,13 @@ use cosmwasm_schema::{cw_serde, QueryResponses};
 
 use crate::asset::{Asset, AssetInfo, PairInfo};
 
-use cosmwasm_std::{Addr, Binary, Decimal, Uint128, Uint64};
+use cosmwasm_std::{
+    to_binary, Addr, Binary, CosmosMsg, Decimal, QuerierWrapper, StdError, StdResult, Storage,
+    Uint128, Uint256, Uint64, WasmMsg,
+};
 use cw20::Cw20ReceiveMsg;
+use cw_storage_plus::Item;
+use thiserror::Error;
 
 /// The default swap slippage
 pub const DEFAULT_SLIPPAGE: &str = "0.005";
@@ -14,6 +19,11 @@ pub const MAX_ALLOWED_SLIPPAGE: &str = "0.5";
 /// Decimal precision for TWAP results
 pub const TWAP_PRECISION: u8 = 6;
 
+/// The default flash loan fee (in bps)
+pub const DEFAULT_FLASH_LOAN_FEE_BPS: u16 = 9;
+/// The maximum allowed flash loan fee (in bps)
+pub const MAX_FLASH_LOAN_FEE_BPS: u16 = 1_000;
+
 /// This structure describes the parameters used for creating a contract.
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -42,6 +52,8 @@ pub enum ExecuteMsg {
         auto_stake: Option<bool>,
         /// The receiver of LP tokens
         receiver: Option<String>,
+        /// The latest block time (in seconds) the transaction can be executed at
+        deadline: Option<u64>,
     },
     /// Swap performs a swap in the pool
     Swap {
@@ -50,9 +62,34 @@ pub enum ExecuteMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// The latest block time (in seconds) the transaction can be executed at
+        deadline: Option<u64>,
+    },
+    /// SwapExactOut swaps the native coin sent with the message to the exact amount of the ask asset.
+    /// Unused offer assets are sent back to the sender
+    SwapExactOut {
+        /// The asset to receive
+        ask_asset: Asset,
+        /// The maximum amount of the offer asset which can be spent
+        max_offer_amount: Uint128,
+        /// The receiver of the ask asset
+        to: Option<String>,
+        /// The latest block time (in seconds) the transaction can be executed at
+        deadline: Option<u64>,
     },
     /// Update the pair configuration
     UpdateConfig { params: Binary },
+    /// FlashLoan sends the specified assets from the pool reserves to the receiver contract and calls it
+    /// with [`FlashLoanReceiverMsg`]. The borrowed assets together with the flash loan fee must be returned
+    /// to the pool by the end of the receiver call
+    FlashLoan {
+        /// The assets to borrow
+        assets: Vec<Asset>,
+        /// The contract which receives borrowed assets
+        receiver: String,
+        /// Arbitrary data passed to the receiver
+        data: Binary,
+    },
     /// ProposeNewOwner creates a proposal to change contract ownership.
     /// The validity period for the proposal is set in the `expires_in` variable.
     ProposeNewOwner {
@@ -67,6 +104,31 @@ pub enum ExecuteMsg {
     ClaimOwnership {},
 }
 
+/// This structure describes the message a flash loan receiver is called with.
+/// Native assets are attached to the call, CW20 tokens are transferred right before it.
+#[cw_serde]
+pub struct FlashLoanReceiverMsg {
+    /// The borrowed assets
+    pub amounts: Vec<Asset>,
+    /// Fees which must be paid on top of the borrowed assets
+    pub fee: Vec<Asset>,
+    /// Arbitrary data passed by the borrower
+    pub data: Binary,
+}
+
+impl FlashLoanReceiverMsg {
+    /// Serializes the message wrapped into [`FlashLoanReceiverExecuteMsg`]
+    pub fn into_binary(self) -> StdResult<Binary> {
+        to_binary(&FlashLoanReceiverExecuteMsg::FlashLoan(self))
+    }
+}
+
+/// This enum describes the execute message a flash loan receiver must handle.
+#[cw_serde]
+pub enum FlashLoanReceiverExecuteMsg {
+    FlashLoan(FlashLoanReceiverMsg),
+}
+
 /// This structure describes a CW20 hook message.
 #[cw_serde]
 pub enum Cw20HookMsg {
@@ -76,11 +138,25 @@ pub enum Cw20HookMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// The latest block time (in seconds) the transaction can be executed at
+        deadline: Option<u64>,
+    },
+    /// Swap a part of the sent tokens to the exact amount of the ask asset and send the rest back
+    SwapExactOut {
+        ask_asset: Asset,
+        max_offer_amount: Uint128,
+        to: Option<String>,
+        /// The latest block time (in seconds) the transaction can be executed at
+        deadline: Option<u64>,
     },
     /// Withdraw liquidity from the pool
     WithdrawLiquidity {
+        /// The amount of each pool asset to withdraw. If empty, the withdrawal is balanced.
+        /// Otherwise unused LP tokens are sent back
         #[serde(default)]
         assets: Vec<Asset>,
+        /// The latest block time (in seconds) the transaction can be executed at
+        deadline: Option<u64>,
     },
 }
 
@@ -127,6 +203,17 @@ pub enum QueryMsg {
     /// Query price from observations
     #[returns(OracleObservation)]
     Observe { seconds_ago: u64 },
+    /// Query price of `base_asset` denominated in `quote_asset` from observations.
+    /// Used by pools with more than two assets
+    #[returns(OracleObservation)]
+    ObservePair {
+        base_asset: AssetInfo,
+        quote_asset: AssetInfo,
+        seconds_ago: u64,
+    },
+    /// Returns the flash loan fee (in bps)
+    #[returns(u16)]
+    FlashLoanFee {},
 }
 
 /// This struct is used to return a query result with the total amount of LP tokens and assets in a specific pool.
@@ -210,6 +297,8 @@ pub struct XYKPoolConfig {
 pub enum XYKPoolUpdateParams {
     /// Enables asset balances tracking over blocks.
     EnableAssetBalancesTracking,
+    /// Updates the flash loan fee.
+    UpdateFlashLoanFee { fee_bps: u16 },
 }
 
 /// This structure holds stableswap pool parameters.
@@ -219,6 +308,44 @@ pub struct StablePoolParams {
     pub amp: u64,
     /// The contract owner
     pub owner: Option<String>,
+    /// Optional redemption rate sources for the pool assets.
+    /// Pool balances are scaled by asset rates. Assets without a rate source have a rate of 1
+    pub rate_sources: Option<Vec<AssetRateSource>>,
+}
+
+/// This structure binds a pool asset with its redemption rate source.
+#[cw_serde]
+pub struct AssetRateSource {
+    /// The pool asset
+    pub asset_info: AssetInfo,
+    /// The source of the asset redemption rate
+    pub source: RateSource,
+}
+
+/// This enum describes the available redemption rate sources for a stableswap pool asset.
+#[cw_serde]
+pub enum RateSource {
+    /// The rate never changes
+    Constant { rate: Decimal },
+    /// The rate is queried from a contract which must respond with a [`RateResponse`] object
+    Contract {
+        /// The rate provider contract address
+        contract_addr: String,
+        /// Binary serialised query message sent to the rate provider contract
+        query_msg: Binary,
+        /// The number of seconds a fetched rate is cached for
+        cache_period: u64,
+        /// The maximum age (in seconds) of a cached rate which is still used
+        /// if the rate provider contract fails to respond
+        max_staleness: u64,
+    },
+}
+
+/// This structure describes the response a rate provider contract must return.
+#[cw_serde]
+pub struct RateResponse {
+    /// The redemption rate of the asset
+    pub rate: Decimal,
 }
 
 /// This structure stores a stableswap pool's configuration.
@@ -233,6 +360,212 @@ pub struct StablePoolConfig {
 pub enum StablePoolUpdateParams {
     StartChangingAmp { next_amp: u64, next_amp_time: u64 },
     StopChangingAmp {},
+    UpdateFlashLoanFee { fee_bps: u16 },
+    UpdateRateSources { rate_sources: Vec<AssetRateSource> },
+}
+
+/// This structure stores the state of a flash loan which is currently in progress.
+#[cw_serde]
+pub struct FlashLoanState {
+    /// Pool balances of the borrowed assets before the loan was issued
+    pub balances_before: Vec<Asset>,
+    /// Fees which must be paid on top of the borrowed assets
+    pub fees: Vec<Asset>,
+}
+
+/// Stores the flash loan which is currently in progress. The pair is locked while it is set
+pub const FLASH_LOAN: Item<FlashLoanState> = Item::new("flash_loan");
+
+/// This enum describes flash loan errors shared by all pair contracts.
+#[derive(Error, Debug, PartialEq)]
+pub enum FlashLoanError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Flash loan is in progress. The pair is locked until it is repaid")]
+    InProgress {},
+
+    #[error(
+        "Flash loan of {asset} was not repaid. Expected balance: {expected}, actual: {actual}"
+    )]
+    NotRepaid {
+        asset: String,
+        expected: Uint128,
+        actual: Uint128,
+    },
+
+    #[error("Invalid flash loan assets: {0}")]
+    InvalidAssets(String),
+
+    #[error("Invalid flash loan receiver: {0}")]
+    InvalidReceiver(String),
+}
+
+/// Returns an error if a flash loan is in progress and the pair is locked.
+pub fn assert_no_flash_loan(storage: &dyn Storage) -> Result<(), FlashLoanError> {
+    if FLASH_LOAN.may_load(storage)?.is_some() {
+        return Err(FlashLoanError::InProgress {});
+    }
+
+    Ok(())
+}
+
+/// Calculates the flash loan fee for the specified amount. The fee is rounded up,
+/// so any non-zero loan pays at least 1 unit of the borrowed asset unless **fee_bps** is zero.
+pub fn flash_loan_fee(amount: Uint128, fee_bps: u16) -> StdResult<Uint128> {
+    let denominator = Uint256::from(10_000u128);
+    let fee = (amount.full_mul(fee_bps) + denominator - Uint256::one()) / denominator;
+
+    fee.try_into().map_err(StdError::from)
+}
+
+/// Validates the assets to borrow against the pool balances, calculates the fees and locks the pair.
+/// Returns the stored [`FlashLoanState`].
+///
+/// The receiver can't be the pair itself, its LP token or any CW20 token in the pool,
+/// otherwise the pair would call them on the borrower's behalf.
+///
+/// * **pair_info** describes the pair issuing the loan.
+///
+/// * **receiver** is the contract which receives borrowed assets.
+///
+/// * **pools** are the current pool balances.
+///
+/// * **assets** are the assets to borrow.
+///
+/// * **fee_bps** is the flash loan fee (in bps).
+pub fn start_flash_loan(
+    storage: &mut dyn Storage,
+    pair_info: &PairInfo,
+    receiver: &Addr,
+    pools: &[Asset],
+    assets: &[Asset],
+    fee_bps: u16,
+) -> Result<FlashLoanState, FlashLoanError> {
+    if receiver == pair_info.contract_addr
+        || receiver == pair_info.liquidity_token
+        || pair_info.asset_infos.iter().any(
+            |info| matches!(info, AssetInfo::Token { contract_addr } if contract_addr == receiver),
+        )
+    {
+        return Err(FlashLoanError::InvalidReceiver(receiver.to_string()));
+    }
+
+    if assets.is_empty() {
+        return Err(FlashLoanError::InvalidAssets(
+            "at least one asset must be specified".to_string(),
+        ));
+    }
+
+    let mut balances_before = vec![];
+    let mut fees = vec![];
+    for (i, asset) in assets.iter().enumerate() {
+        if assets[..i].iter().any(|a| a.info.equal(&asset.info)) {
+            return Err(FlashLoanError::InvalidAssets(format!(
+                "{} is specified more than once",
+                asset.info
+            )));
+        }
+        if asset.amount.is_zero() {
+            return Err(FlashLoanError::InvalidAssets(format!(
+                "zero amount of {}",
+                asset.info
+            )));
+        }
+
+        let pool = pools
+            .iter()
+            .find(|pool| pool.info.equal(&asset.info))
+            .ok_or_else(|| {
+                FlashLoanError::InvalidAssets(format!("{} does not belong to the pool", asset.info))
+            })?;
+        if asset.amount > pool.amount {
+            return Err(FlashLoanError::InvalidAssets(format!(
+                "{} exceeds the pool balance",
+                asset
+            )));
+        }
+
+        balances_before.push(pool.clone());
+        fees.push(Asset {
+            info: asset.info.clone(),
+            amount: flash_loan_fee(asset.amount, fee_bps)?,
+        });
+    }
+
+    let loan = FlashLoanState {
+        balances_before,
+        fees,
+    };
+    FLASH_LOAN.save(storage, &loan)?;
+
+    Ok(loan)
+}
+
+/// Builds the messages which send the borrowed assets to the receiver and call it with [`FlashLoanReceiverMsg`].
+/// CW20 tokens are transferred first, native coins are attached to the receiver call.
+pub fn flash_loan_messages(
+    receiver: &Addr,
+    assets: Vec<Asset>,
+    loan: FlashLoanState,
+    data: Binary,
+) -> StdResult<(Vec<CosmosMsg>, WasmMsg)> {
+    let mut messages = vec![];
+    let mut funds = vec![];
+    for asset in &assets {
+        match &asset.info {
+            AssetInfo::NativeToken { .. } => funds.push(asset.as_coin()?),
+            AssetInfo::Token { .. } => messages.push(asset.clone().into_msg(receiver)?),
+        }
+    }
+    funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+    let callback = WasmMsg::Execute {
+        contract_addr: receiver.to_string(),
+        msg: FlashLoanReceiverMsg {
+            amounts: assets,
+            fee: loan.fees,
+            data,
+        }
+        .into_binary()?,
+        funds,
+    };
+
+    Ok((messages, callback))
+}
+
+/// Checks that the flash loan in progress was repaid together with the fee and unlocks the pair.
+/// Returns the finished [`FlashLoanState`] together with the current pool balances of the borrowed assets.
+///
+/// * **pool_addr** is the address of the pair contract.
+pub fn finish_flash_loan(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    pool_addr: &Addr,
+) -> Result<(FlashLoanState, Vec<Uint128>), FlashLoanError> {
+    let loan = FLASH_LOAN.load(storage)?;
+    FLASH_LOAN.remove(storage);
+
+    let balances = loan
+        .balances_before
+        .iter()
+        .zip(loan.fees.iter())
+        .map(|(before, fee)| {
+            let expected = before.amount.checked_add(fee.amount)?;
+            let actual = before.info.query_pool(querier, pool_addr)?;
+            if actual < expected {
+                return Err(FlashLoanError::NotRepaid {
+                    asset: before.info.to_string(),
+                    expected,
+                    actual,
+                });
+            }
+
+            Ok(actual)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((loan, balances))
 }
 
 #[cfg(test)]
@@ -295,6 +628,37 @@ mod tests {
     #[test]
     fn check_empty_vec_deserialization() {
         let variant: Cw20HookMsg = from_slice(br#"{"withdraw_liquidity": {} }"#).unwrap();
-        assert_eq!(variant, Cw20HookMsg::WithdrawLiquidity { assets: vec![] });
+        assert_eq!(
+            variant,
+            Cw20HookMsg::WithdrawLiquidity {
+                assets: vec![],
+                deadline: None
+            }
+        );
+    }
+
+    #[test]
+    fn flash_loan_fee_rounds_up() {
+        assert_eq!(flash_loan_fee(Uint128::new(1), 9).unwrap(), Uint128::new(1));
+        assert_eq!(
+            flash_loan_fee(Uint128::new(1_000), 9).unwrap(),
+            Uint128::new(1)
+        );
+        assert_eq!(
+            flash_loan_fee(Uint128::new(10_000), 9).unwrap(),
+            Uint128::new(9)
+        );
+        assert_eq!(
+            flash_loan_fee(Uint128::new(10_001), 9).unwrap(),
+            Uint128::new(10)
+        );
+        assert_eq!(
+            flash_loan_fee(Uint128::new(1_000), 0).unwrap(),
+            Uint128::zero()
+        );
+        assert_eq!(
+            flash_loan_fee(Uint128::MAX, MAX_FLASH_LOAN_FEE_BPS).unwrap(),
+            Uint128::MAX / Uint128::new(10) + Uint128::new(1)
+        );
     }
 }
@@ -38,6 +38,8 @@ pub enum ExecuteMsg {
         auto_stake: Option<bool>,
         /// The receiver of LP tokens
         receiver: Option<String>,
+        /// The latest block time (in seconds) the transaction can be executed at
+        deadline: Option<u64>,
     },
     /// Swap performs a swap in the pool
     Swap {
@@ -45,6 +47,8 @@ pub enum ExecuteMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// The latest block time (in seconds) the transaction can be executed at
+        deadline: Option<u64>,
     },
     /// Update the pair configuration
     UpdateConfig { params: Binary },
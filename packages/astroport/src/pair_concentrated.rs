@@ -68,6 +68,8 @@ pub enum ConcentratedPoolUpdateParams {
     StopChangingAmpGamma {},
     /// Enable asset balances tracking
     EnableAssetBalancesTracking {},
+    /// Updates the flash loan fee
+    UpdateFlashLoanFee { fee_bps: u16 },
 }
 
 /// This structure stores a CL pool's configuration.
@@ -144,6 +146,9 @@ pub enum QueryMsg {
     /// Query price from observations
     #[returns(OracleObservation)]
     Observe { seconds_ago: u64 },
+    /// Returns the flash loan fee (in bps)
+    #[returns(u16)]
+    FlashLoanFee {},
 }
 
 #[cw_serde]
@@ -0,0 +1,127 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Decimal, Decimal256, Uint128, Uint64};
+
+use crate::asset::PairInfo;
+use crate::asset::{Asset, AssetInfo};
+use crate::observation::OracleObservation;
+use crate::pair::{
+    ConfigResponse, CumulativePricesResponse, PoolResponse, ReverseSimulationResponse,
+    SimulationResponse,
+};
+
+/// This structure holds tricrypto pool parameters.
+#[cw_serde]
+pub struct TricryptoPoolParams {
+    /// Amplification coefficient affects trades close to price_scales
+    pub amp: Decimal,
+    /// Affects how gradual the curve changes from constant sum to constant product
+    /// as price moves away from price scales. Low values mean more gradual.
+    pub gamma: Decimal,
+    /// The minimum fee, charged when pool is fully balanced
+    pub mid_fee: Decimal,
+    /// The maximum fee, charged when pool is imbalanced
+    pub out_fee: Decimal,
+    /// Parameter that defines how gradual the fee changes from fee_mid to fee_out
+    /// based on distance from price_scales.
+    pub fee_gamma: Decimal,
+    /// Minimum profit before initiating a new repeg
+    pub repeg_profit_threshold: Decimal,
+    /// Minimum amount to change price_scales when repegging.
+    pub min_price_scale_delta: Decimal,
+    /// Initial prices of the 2nd and the 3rd assets denominated in the 1st asset.
+    /// I.e. 1 x\[i + 1] = price_scales\[i] * x\[0].
+    pub price_scales: Vec<Decimal>,
+    /// Half-time used for calculating the price oracle.
+    pub ma_half_time: u64,
+    /// Whether asset balances are tracked over blocks or not.
+    /// They will not be tracked if the parameter is ignored.
+    /// It can not be disabled later once enabled.
+    pub track_asset_balances: Option<bool>,
+}
+
+/// This structure stores a tricrypto pool's configuration.
+#[cw_serde]
+pub struct TricryptoPoolConfig {
+    /// Amplification coefficient affects trades close to price_scales
+    pub amp: Decimal,
+    /// Affects how gradual the curve changes from constant sum to constant product
+    /// as price moves away from price scales. Low values mean more gradual.
+    pub gamma: Decimal,
+    /// The minimum fee, charged when pool is fully balanced
+    pub mid_fee: Decimal,
+    /// The maximum fee, charged when pool is imbalanced
+    pub out_fee: Decimal,
+    /// Parameter that defines how gradual the fee changes from fee_mid to fee_out
+    /// based on distance from price_scales.
+    pub fee_gamma: Decimal,
+    /// Minimum profit before initiating a new repeg
+    pub repeg_profit_threshold: Decimal,
+    /// Minimum amount to change price_scales when repegging.
+    pub min_price_scale_delta: Decimal,
+    /// Prices of the 2nd and the 3rd assets denominated in the 1st asset.
+    /// I.e. 1 x\[i + 1] = price_scales\[i] * x\[0].
+    pub price_scales: Vec<Decimal>,
+    /// Half-time used for calculating the price oracle.
+    pub ma_half_time: u64,
+    /// Whether asset balances are tracked over blocks or not.
+    pub track_asset_balances: bool,
+}
+
+/// This structure describes the query messages available in the contract.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns information about a pair
+    #[returns(PairInfo)]
+    Pair {},
+    /// Returns information about a pool
+    #[returns(PoolResponse)]
+    Pool {},
+    /// Returns contract configuration
+    #[returns(ConfigResponse)]
+    Config {},
+    /// Returns information about the share of the pool in a vector that contains objects of type [`Asset`].
+    #[returns(Vec<Asset>)]
+    Share { amount: Uint128 },
+    /// Returns information about a swap simulation
+    #[returns(SimulationResponse)]
+    Simulation {
+        offer_asset: Asset,
+        ask_asset_info: Option<AssetInfo>,
+    },
+    /// Returns information about a reverse swap simulation
+    #[returns(ReverseSimulationResponse)]
+    ReverseSimulation {
+        offer_asset_info: Option<AssetInfo>,
+        ask_asset: Asset,
+    },
+    /// Returns information about the cumulative prices
+    #[returns(CumulativePricesResponse)]
+    CumulativePrices {},
+    /// Returns current D invariant
+    #[returns(Decimal256)]
+    ComputeD {},
+    /// Query LP token virtual price
+    #[returns(Decimal256)]
+    LpPrice {},
+    /// Returns the balance of the specified asset that was in the pool just preceding the moment
+    /// of the specified block height creation.
+    #[returns(Option<Uint128>)]
+    AssetBalanceAt {
+        asset_info: AssetInfo,
+        block_height: Uint64,
+    },
+    /// Query price of the first two assets from observations
+    #[returns(OracleObservation)]
+    Observe { seconds_ago: u64 },
+    /// Query price of `base_asset` denominated in `quote_asset` from observations
+    #[returns(OracleObservation)]
+    ObservePair {
+        base_asset: AssetInfo,
+        quote_asset: AssetInfo,
+        seconds_ago: u64,
+    },
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
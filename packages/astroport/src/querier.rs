@@ -172,8 +172,13 @@ pub fn query_fee_info<C>(
 where
     C: CustomQuery,
 {
-    let res: FeeInfoResponse =
-        querier.query_wasm_smart(factory_contract, &FactoryQueryMsg::FeeInfo { pair_type })?;
+    let res: FeeInfoResponse = querier.query_wasm_smart(
+        factory_contract,
+        &FactoryQueryMsg::FeeInfo {
+            pair_type,
+            pair_addr: None,
+        },
+    )?;
 
     Ok(FeeInfo {
         fee_address: res.fee_address,
@@ -182,16 +187,70 @@ where
     })
 }
 
+/// Returns the fee information for a specific pair. Fees set for the pair in the factory
+/// take precedence over its pair type fees.
+///
+/// * **pair_info** pair we query information for.
+pub fn query_pair_fee_info<C>(
+    querier: &QuerierWrapper<C>,
+    factory_contract: impl Into<String>,
+    pair_info: &PairInfo,
+) -> StdResult<FeeInfo>
+where
+    C: CustomQuery,
+{
+    let res: FeeInfoResponse = querier.query_wasm_smart(
+        factory_contract,
+        &FactoryQueryMsg::FeeInfo {
+            pair_type: pair_info.pair_type.clone(),
+            pair_addr: Some(pair_info.contract_addr.to_string()),
+        },
+    )?;
+
+    Ok(FeeInfo {
+        fee_address: res.fee_address,
+        total_fee_rate: Decimal::from_ratio(res.total_fee_bps, 10000u16),
+        maker_fee_rate: Decimal::from_ratio(res.maker_fee_bps, 10000u16),
+    })
+}
+
+/// Returns whether swaps and liquidity provision are paused for a pair in the factory pause registry.
+pub fn query_pair_paused<C>(
+    querier: &QuerierWrapper<C>,
+    factory_contract: impl Into<String>,
+    pair_info: &PairInfo,
+) -> StdResult<bool>
+where
+    C: CustomQuery,
+{
+    querier.query_wasm_smart(
+        factory_contract,
+        &FactoryQueryMsg::IsPaused {
+            pair_type: pair_info.pair_type.clone(),
+            pair_addr: pair_info.contract_addr.to_string(),
+        },
+    )
+}
+
 /// Accepts two tokens as input and returns a pair's information.
+///
+/// * **pair_type** is the type of the pair to return. If it is not specified, the factory returns
+/// the pair with the lowest pair type key among all pairs registered for the given assets.
+///
+/// * **fee_tier** is the fee tier of the pair to return. It is only used together with **pair_type**.
 pub fn query_pair_info(
     querier: &QuerierWrapper,
     factory_contract: impl Into<String>,
     asset_infos: &[AssetInfo],
+    pair_type: Option<PairType>,
+    fee_tier: Option<u16>,
 ) -> StdResult<PairInfo> {
     querier.query_wasm_smart(
         factory_contract,
         &FactoryQueryMsg::Pair {
             asset_infos: asset_infos.to_vec(),
+            pair_type,
+            fee_tier,
         },
     )
 }
@@ -202,11 +261,18 @@ pub fn query_pairs_info(
     querier: &QuerierWrapper,
     factory_contract: impl Into<String>,
     start_after: Option<Vec<AssetInfo>>,
+    start_after_pair_type: Option<PairType>,
+    start_after_fee_tier: Option<u16>,
     limit: Option<u32>,
 ) -> StdResult<PairsResponse> {
     querier.query_wasm_smart(
         factory_contract,
-        &FactoryQueryMsg::Pairs { start_after, limit },
+        &FactoryQueryMsg::Pairs {
+            start_after,
+            start_after_pair_type,
+            start_after_fee_tier,
+            limit,
+        },
     )
 }
 
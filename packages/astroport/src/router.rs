@@ -1,17 +1,60 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 
-use crate::asset::AssetInfo;
+use crate::asset::{Asset, AssetInfo};
+use crate::factory::PairType;
+use crate::pair::{ReverseSimulationResponse, SimulationResponse};
 
 pub const MAX_SWAP_OPERATIONS: usize = 50;
+/// The default number of hops used by route discovery
+pub const DEFAULT_MAX_HOPS: u32 = 3;
+/// The maximum number of hops used by route discovery
+pub const MAX_ROUTE_HOPS: u32 = 4;
+/// The default timeout (in seconds) of native IBC transfers
+pub const DEFAULT_IBC_TIMEOUT: u64 = 600;
+/// The upper limit for the maximum referral fee configured in the router (10%)
+pub const MAX_REFERRAL_FEE_BPS: u16 = 1000;
 
 /// This structure holds the parameters used for creating a contract.
 #[cw_serde]
 pub struct InstantiateMsg {
     /// The astroport factory contract address
     pub astroport_factory: String,
+    /// The CW20-ICS20 contract used to send CW20 tokens over IBC
+    pub cw20_ics20: Option<String>,
+    /// The maximum referral fee in bps. Referral fees are disabled if not specified
+    pub max_referral_fee_bps: Option<u16>,
+    /// The native swap adapter used to execute [`SwapOperation::NativeSwap`] operations.
+    /// Native swaps are disabled if not specified
+    pub native_swap_adapter: Option<String>,
+}
+
+/// This structure describes an integrator taking a fee from the swap output.
+#[cw_serde]
+pub struct Referral {
+    /// The address receiving the referral fee
+    pub address: String,
+    /// The share of the swap output taken as a fee, in bps
+    pub fee_bps: u16,
+}
+
+/// This structure describes where the swap output is sent over IBC.
+/// It has the same layout as the CW20-ICS20 `TransferMsg`.
+/// The router is the sender of the transfer, so refunds of timed out or failed transfers are
+/// sent back to the router and are not returned to the swapper.
+#[cw_serde]
+pub struct IbcDestination {
+    /// The local channel to send the tokens on
+    pub channel: String,
+    /// The remote address to send the tokens to
+    pub remote_address: String,
+    /// How long the packet lives in seconds. For CW20 tokens the CW20-ICS20 default timeout is used
+    /// if not specified, for native tokens [`DEFAULT_IBC_TIMEOUT`] is used
+    pub timeout: Option<u64>,
+    /// An optional memo to add to the IBC transfer. Only supported for CW20 tokens
+    pub memo: Option<String>,
 }
 
 /// This enum describes a swap operation.
@@ -30,10 +73,26 @@ pub enum SwapOperation {
         offer_asset_info: AssetInfo,
         /// Information about the asset we swap to
         ask_asset_info: AssetInfo,
+        /// The type of the pair to swap in. If not specified, the factory picks the pair
+        /// with the lowest pair type key among the pairs registered for the assets
+        pair_type: Option<PairType>,
+        /// The fee tier of the pair to swap in. Only used together with `pair_type`
+        fee_tier: Option<u16>,
     },
 }
 
 impl SwapOperation {
+    pub fn get_offer_asset_info(&self) -> AssetInfo {
+        match self {
+            SwapOperation::NativeSwap { offer_denom, .. } => AssetInfo::NativeToken {
+                denom: offer_denom.clone(),
+            },
+            SwapOperation::AstroSwap {
+                offer_asset_info, ..
+            } => offer_asset_info.clone(),
+        }
+    }
+
     pub fn get_target_asset_info(&self) -> AssetInfo {
         match self {
             SwapOperation::NativeSwap { ask_denom, .. } => AssetInfo::NativeToken {
@@ -44,6 +103,56 @@ impl SwapOperation {
     }
 }
 
+/// This enum describes which part of the total offer amount is swapped through a route.
+#[cw_serde]
+pub enum RouteSplit {
+    /// A share of the total offer amount, e.g. 0.25 for 25%
+    Percent(Decimal),
+    /// An exact amount of offer tokens
+    Amount(Uint128),
+}
+
+/// This structure describes a single route of a split swap.
+#[cw_serde]
+pub struct SwapRoute {
+    /// The swap operations to perform along the route
+    pub operations: Vec<SwapOperation>,
+    /// The part of the total offer amount swapped through the route
+    pub split: RouteSplit,
+}
+
+/// This structure describes the execute messages a native swap adapter must handle.
+/// An adapter wraps a chain-specific swap module (e.g. the Injective exchange module)
+/// and is used by the router to execute [`SwapOperation::NativeSwap`] operations.
+#[cw_serde]
+pub enum NativeSwapAdapterExecuteMsg {
+    /// Swaps the single coin sent along with the message.
+    /// The spread is checked the same way an Astroport pair checks it
+    Swap {
+        /// The denom to swap to
+        ask_denom: String,
+        /// The expected price of the ask coin in offer coins. If not specified, the spread is
+        /// checked against the swap module's spot price
+        belief_price: Option<Decimal>,
+        /// The maximum spread allowed for the swap. Defaults to the pair default slippage
+        max_spread: Option<Decimal>,
+        /// The recipient of the ask tokens. Defaults to the sender
+        to: Option<String>,
+    },
+}
+
+/// This structure describes the query messages a native swap adapter must handle.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum NativeSwapAdapterQueryMsg {
+    /// Simulates a swap of `offer` to `ask_denom`
+    #[returns(SimulationResponse)]
+    Simulation { offer: Coin, ask_denom: String },
+    /// Returns the amount of `offer_denom` to swap in order to receive `ask`
+    #[returns(ReverseSimulationResponse)]
+    ReverseSimulation { offer_denom: String, ask: Coin },
+}
+
 /// This structure describes the execute messages available in the contract.
 #[cw_serde]
 pub enum ExecuteMsg {
@@ -55,6 +164,26 @@ pub enum ExecuteMsg {
         minimum_receive: Option<Uint128>,
         to: Option<String>,
         max_spread: Option<Decimal>,
+        /// The latest block time (in seconds) the transaction can be executed at
+        deadline: Option<u64>,
+        /// Sends the swap output over IBC instead of transferring it to `to`
+        ibc_destination: Option<IbcDestination>,
+        /// Takes a referral fee from the swap output
+        referral: Option<Referral>,
+    },
+    /// ExecuteSplitSwapOperations splits the offer amount between several routes and swaps it through each of them.
+    /// The minimum amount of tokens to receive is checked against the total return of all routes
+    ExecuteSplitSwapOperations {
+        /// The routes to swap through. All of them must start with the same offer asset and end with the same ask asset
+        routes: Vec<SwapRoute>,
+        /// The minimum total amount of tokens to get from all routes
+        minimum_receive: Option<Uint128>,
+        /// The recipient
+        to: Option<String>,
+        /// Max spread
+        max_spread: Option<Decimal>,
+        /// The latest block time (in seconds) the swap operations can be executed at
+        deadline: Option<u64>,
     },
 
     /// Internal use
@@ -64,6 +193,9 @@ pub enum ExecuteMsg {
         to: Option<String>,
         max_spread: Option<Decimal>,
         single: bool,
+        /// The router balance of the offer asset which doesn't belong to the swap.
+        /// Only the amount above it is swapped
+        prev_balance: Uint128,
     },
     /// Internal use
     /// AssertMinimumReceive checks that a receiver will get a minimum amount of tokens from a swap
@@ -73,6 +205,22 @@ pub enum ExecuteMsg {
         minimum_receive: Uint128,
         receiver: String,
     },
+    /// Internal use
+    /// ChargeReferralFee sends the referral fee from the swap output received by the router
+    /// and transfers the rest to the receiver
+    ChargeReferralFee {
+        asset_info: AssetInfo,
+        prev_balance: Uint128,
+        referral: Referral,
+        receiver: String,
+    },
+    /// Internal use
+    /// ForwardIbcTransfer sends the swap output received by the router over IBC
+    ForwardIbcTransfer {
+        asset_info: AssetInfo,
+        prev_balance: Uint128,
+        destination: IbcDestination,
+    },
 }
 
 #[cw_serde]
@@ -86,6 +234,24 @@ pub enum Cw20HookMsg {
         to: Option<String>,
         /// Max spread
         max_spread: Option<Decimal>,
+        /// The latest block time (in seconds) the swap operations can be executed at
+        deadline: Option<u64>,
+        /// Sends the swap output over IBC instead of transferring it to `to`
+        ibc_destination: Option<IbcDestination>,
+        /// Takes a referral fee from the swap output
+        referral: Option<Referral>,
+    },
+    ExecuteSplitSwapOperations {
+        /// The routes to swap through. All of them must start with the same offer asset and end with the same ask asset
+        routes: Vec<SwapRoute>,
+        /// The minimum total amount of tokens to get from all routes
+        minimum_receive: Option<Uint128>,
+        /// The recipient
+        to: Option<String>,
+        /// Max spread
+        max_spread: Option<Decimal>,
+        /// The latest block time (in seconds) the swap operations can be executed at
+        deadline: Option<u64>,
     },
 }
 
@@ -103,6 +269,37 @@ pub enum QueryMsg {
         offer_amount: Uint128,
         /// The swap operations to perform, each swap involving a specific pool
         operations: Vec<SwapOperation>,
+        /// The referral fee to deduct from the swap output
+        referral: Option<Referral>,
+    },
+    /// SimulateReverseSwapOperations returns the amount of tokens to offer in order to
+    /// receive the specified amount of tokens from multi-hop swap operations
+    #[returns(SimulateReverseSwapOperationsResponse)]
+    SimulateReverseSwapOperations {
+        /// The amount of tokens to receive
+        ask_amount: Uint128,
+        /// The swap operations to perform, each swap involving a specific pool
+        operations: Vec<SwapOperation>,
+    },
+    /// SimulateSplitSwapOperations simulates a swap split between several routes
+    #[returns(SimulateSplitSwapOperationsResponse)]
+    SimulateSplitSwapOperations {
+        /// The total amount of tokens to swap
+        offer_amount: Uint128,
+        /// The routes to swap through
+        routes: Vec<SwapRoute>,
+    },
+    /// FindBestRoute looks for the swap operations that return the most ask tokens
+    /// among the pairs registered in the factory
+    #[returns(FindBestRouteResponse)]
+    FindBestRoute {
+        /// The asset to swap
+        offer_asset: Asset,
+        /// The asset to swap to
+        ask_asset_info: AssetInfo,
+        /// The maximum number of hops in a route. Defaults to [`DEFAULT_MAX_HOPS`]
+        /// and can't exceed [`MAX_ROUTE_HOPS`]
+        max_hops: Option<u32>,
     },
 }
 
@@ -111,16 +308,100 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     /// The Astroport factory contract address
     pub astroport_factory: String,
+    /// The CW20-ICS20 contract used to send CW20 tokens over IBC
+    pub cw20_ics20: Option<String>,
+    /// The maximum referral fee in bps
+    pub max_referral_fee_bps: u16,
+    /// The native swap adapter used to execute native swap operations
+    pub native_swap_adapter: Option<String>,
 }
 
 /// This structure describes a custom struct to return a query response containing the end amount of a swap simulation
 #[cw_serde]
 pub struct SimulateSwapOperationsResponse {
-    /// The amount of tokens received in a swap simulation
+    /// The amount of tokens received in a swap simulation, net of the referral fee
+    pub amount: Uint128,
+    /// The referral fee deducted from the swap output
+    pub referral_amount: Uint128,
+    /// Simulation results of every swap operation in the same order as the operations in the query
+    pub operations: Vec<SwapOperationSimulation>,
+    /// The share of the output lost to spread compared to swapping at spot prices, fees excluded
+    pub price_impact: Decimal,
+}
+
+/// This structure describes the result of a simulation of a single swap operation
+#[cw_serde]
+pub struct SwapOperationSimulation {
+    /// The pair used for the swap operation
+    pub pair_address: Addr,
+    /// The amount of tokens offered
+    pub offer_amount: Uint128,
+    /// The amount of tokens returned
+    pub return_amount: Uint128,
+    /// The spread used in the swap operation
+    pub spread_amount: Uint128,
+    /// The amount of fees charged by the swap operation
+    pub commission_amount: Uint128,
+}
+
+/// This structure describes the result of a reverse simulation of a single swap operation
+#[cw_serde]
+pub struct ReverseSwapOperationSimulation {
+    /// The pair used for the swap operation
+    pub pair_address: Addr,
+    /// The amount of tokens to offer
+    pub offer_amount: Uint128,
+    /// The amount of tokens to receive
+    pub ask_amount: Uint128,
+    /// The spread used in the swap operation
+    pub spread_amount: Uint128,
+    /// The amount of fees charged by the swap operation
+    pub commission_amount: Uint128,
+}
+
+/// This structure describes a custom struct to return a query response containing the result of a reverse swap simulation
+#[cw_serde]
+pub struct SimulateReverseSwapOperationsResponse {
+    /// The amount of tokens to offer in order to receive the requested amount
+    pub offer_amount: Uint128,
+    /// Simulation results of every swap operation in the same order as the operations in the query
+    pub operations: Vec<ReverseSwapOperationSimulation>,
+}
+
+/// This structure describes the result of a swap simulation through a single route
+#[cw_serde]
+pub struct RouteSimulation {
+    /// The amount of tokens swapped through the route
+    pub offer_amount: Uint128,
+    /// The amount of tokens received from the route
+    pub return_amount: Uint128,
+}
+
+/// This structure describes a custom struct to return a query response containing the end amount of a split swap simulation
+#[cw_serde]
+pub struct SimulateSplitSwapOperationsResponse {
+    /// The total amount of tokens received from all routes
+    pub amount: Uint128,
+    /// Simulation results of every route in the same order as the routes in the query
+    pub routes: Vec<RouteSimulation>,
+}
+
+/// This structure describes a custom struct to return a query response containing the best route found
+#[cw_serde]
+pub struct FindBestRouteResponse {
+    /// The swap operations to perform
+    pub operations: Vec<SwapOperation>,
+    /// The expected amount of ask tokens
     pub amount: Uint128,
 }
 
 /// This structure describes a migration message.
-/// We currently take no arguments for migrations.
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// The CW20-ICS20 contract used to send CW20 tokens over IBC
+    pub cw20_ics20: Option<String>,
+    /// The maximum referral fee in bps
+    pub max_referral_fee_bps: Option<u16>,
+    /// The native swap adapter used to execute native swap operations
+    pub native_swap_adapter: Option<String>,
+}
@@ -233,6 +233,7 @@ fn query_astroport_pair_contract() {
             contract_addr: Addr::unchecked("pair0000"),
             liquidity_token: Addr::unchecked("liquidity0000"),
             pair_type: PairType::Xyk {},
+            fee_tier: None,
         },
     )]);
 
@@ -247,6 +248,8 @@ fn query_astroport_pair_contract() {
                 denom: "uusd".to_string(),
             },
         ],
+        None,
+        None,
     )
     .unwrap();
 
@@ -271,6 +274,7 @@ fn test_format_lp_token_name() {
             contract_addr: Addr::unchecked("pair0000"),
             liquidity_token: Addr::unchecked("liquidity0000"),
             pair_type: PairType::Xyk {},
+            fee_tier: None,
         },
     )]);
 
@@ -285,6 +289,8 @@ fn test_format_lp_token_name() {
                 denom: "uusd".to_string(),
             },
         ],
+        None,
+        None,
     )
     .unwrap();
 
@@ -0,0 +1,90 @@
+use std::fmt::Debug;
+
+use astroport::asset::Asset;
+use astroport::pair::{ExecuteMsg, FlashLoanReceiverExecuteMsg};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    from_binary, to_binary, Api, Binary, CustomQuery, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdResult, Storage, WasmMsg,
+};
+use cw_multi_test::{
+    Bank, Contract, ContractWrapper, Distribution, Executor, Gov, Ibc, Module, Staking,
+};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+
+use crate::WKApp;
+
+/// Describes what the borrower does with a flash loan. It is passed to the pair as the loan data.
+#[cw_serde]
+pub enum BorrowerAction {
+    /// Sends the specified assets back to the pair
+    Repay { assets: Vec<Asset> },
+    /// Tries to swap in the pair during the flash loan
+    Swap { offer_asset: Asset },
+}
+
+fn instantiate(_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+/// Handles a flash loan issued by the pair which is the message sender.
+fn execute(
+    _deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: FlashLoanReceiverExecuteMsg,
+) -> StdResult<Response> {
+    let FlashLoanReceiverExecuteMsg::FlashLoan(loan) = msg;
+    let pair = info.sender;
+
+    match from_binary(&loan.data)? {
+        BorrowerAction::Repay { assets } => {
+            let messages = assets
+                .into_iter()
+                .map(|asset| asset.into_msg(&pair))
+                .collect::<StdResult<Vec<_>>>()?;
+
+            Ok(Response::new().add_messages(messages))
+        }
+        BorrowerAction::Swap { offer_asset } => Ok(Response::new().add_message(WasmMsg::Execute {
+            contract_addr: pair.to_string(),
+            msg: to_binary(&ExecuteMsg::Swap {
+                offer_asset: offer_asset.clone(),
+                ask_asset_info: None,
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                deadline: None,
+            })?,
+            funds: vec![offer_asset.as_coin()?],
+        })),
+    }
+}
+
+fn query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_binary(&Empty {})
+}
+
+/// Returns a mock contract which borrows assets from pairs via flash loans.
+pub fn borrower_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new_with_empty(execute, instantiate, query))
+}
+
+pub fn store_code<B, A, S, C, X, D, I, G>(app: &WKApp<B, A, S, C, X, D, I, G>) -> u64
+where
+    B: Bank,
+    A: Api,
+    S: Storage,
+    C: Module,
+    X: Staking,
+    D: Distribution,
+    I: Ibc,
+    G: Gov,
+    C::ExecT: Clone + Debug + PartialEq + JsonSchema + DeserializeOwned + 'static,
+    C::QueryT: CustomQuery + DeserializeOwned + 'static,
+{
+    let contract = Box::new(ContractWrapper::new_with_empty(execute, instantiate, query));
+
+    app.borrow_mut().store_code(contract)
+}
@@ -78,6 +78,7 @@ where
                 is_generator_disabled: false,
                 total_fee_bps: 30,
                 maker_fee_bps: 3333,
+                fee_tiers: vec![],
             },
             PairConfig {
                 code_id: stable_code_id,
@@ -86,6 +87,7 @@ where
                 is_generator_disabled: false,
                 total_fee_bps: 5,
                 maker_fee_bps: 5000,
+                fee_tiers: vec![],
             },
             PairConfig {
                 code_id: concentrated_code_id,
@@ -94,6 +96,7 @@ where
                 is_generator_disabled: false,
                 total_fee_bps: 30,
                 maker_fee_bps: 3333,
+                fee_tiers: vec![],
             },
         ];
 
@@ -187,6 +190,8 @@ where
                     pair_type: PairType::Xyk {},
                     asset_infos: asset_infos.to_vec(),
                     init_params: None,
+                    fee: None,
+                    fee_tier: None,
                 },
                 &[],
             )
@@ -200,6 +205,8 @@ where
                 &self.address,
                 &QueryMsg::Pair {
                     asset_infos: asset_infos.to_vec(),
+                    pair_type: None,
+                    fee_tier: None,
                 },
             )
             .unwrap();
@@ -221,6 +228,7 @@ where
         let default_params = StablePoolParams {
             amp: 100,
             owner: Some(astroport.to_string()),
+            rate_sources: None,
         };
 
         self.app
@@ -232,6 +240,8 @@ where
                     pair_type: PairType::Stable {},
                     asset_infos: asset_infos.to_vec(),
                     init_params: Some(to_binary(init_params.unwrap_or(&default_params)).unwrap()),
+                    fee: None,
+                    fee_tier: None,
                 },
                 &[],
             )
@@ -245,6 +255,8 @@ where
                 &self.address,
                 &QueryMsg::Pair {
                     asset_infos: asset_infos.to_vec(),
+                    pair_type: None,
+                    fee_tier: None,
                 },
             )
             .unwrap();
@@ -299,6 +311,8 @@ where
                     pair_type: PairType::Custom("concentrated".to_owned()),
                     asset_infos: asset_infos.to_vec(),
                     init_params: Some(to_binary(init_params.unwrap_or(&default_params)).unwrap()),
+                    fee: None,
+                    fee_tier: None,
                 },
                 &[],
             )
@@ -312,6 +326,8 @@ where
                 &self.address,
                 &QueryMsg::Pair {
                     asset_infos: asset_infos.to_vec(),
+                    pair_type: None,
+                    fee_tier: None,
                 },
             )
             .unwrap();
@@ -4,9 +4,11 @@ use std::{cell::RefCell, rc::Rc};
 
 use cosmwasm_std::Addr;
 
+pub mod borrower;
 pub mod coin_registry;
 pub mod factory;
 pub mod generator;
+pub mod native_swap_adapter;
 pub mod pair;
 pub mod pair_concentrated;
 pub mod pair_concentrated_inj;
@@ -26,9 +28,11 @@ pub fn astroport_address() -> Addr {
 pub use cw_multi_test;
 use cw_multi_test::{App, Module, WasmKeeper};
 pub use {
+    borrower::{borrower_contract, BorrowerAction},
     coin_registry::{MockCoinRegistry, MockCoinRegistryBuilder},
     factory::{MockFactory, MockFactoryBuilder},
     generator::{MockGenerator, MockGeneratorBuilder},
+    native_swap_adapter::{MockNativeSwapAdapter, MockNativeSwapAdapterBuilder},
     pair::{MockXykPair, MockXykPairBuilder},
     pair_concentrated::{MockConcentratedPair, MockConcentratedPairBuilder},
     pair_concentrated_inj::{MockConcentratedPairInj, MockConcentratedPairInjBuilder},
@@ -0,0 +1,199 @@
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use astroport::pair::{
+    ReverseSimulationResponse, SimulationResponse, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE,
+};
+use astroport::router::{NativeSwapAdapterExecuteMsg, NativeSwapAdapterQueryMsg};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    coins, from_slice, to_binary, to_vec, Addr, Api, BankMsg, Binary, CustomQuery, Decimal, Deps,
+    DepsMut, Env, Fraction, MessageInfo, Response, StdError, StdResult, Storage, Uint128,
+};
+use cw_multi_test::{Bank, ContractWrapper, Distribution, Executor, Gov, Ibc, Module, Staking};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+
+use crate::{astroport_address, WKApp, ASTROPORT};
+
+const RATE_KEY: &[u8] = b"rate";
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// The amount of ask coins returned for one offer coin
+    pub rate: Decimal,
+}
+
+fn load_rate(storage: &dyn Storage) -> StdResult<Decimal> {
+    from_slice(&storage.get(RATE_KEY).unwrap_or_default())
+}
+
+fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    deps.storage.set(RATE_KEY, &to_vec(&msg.rate)?);
+
+    Ok(Response::new())
+}
+
+/// Checks the spread of a swap the same way Astroport pairs do. Swaps at a fixed rate have
+/// no spread of their own, so only the belief price can be violated.
+fn assert_max_spread(
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+) -> StdResult<()> {
+    let max_spread = max_spread.unwrap_or(Decimal::from_str(DEFAULT_SLIPPAGE)?);
+    if max_spread > Decimal::from_str(MAX_ALLOWED_SLIPPAGE)? {
+        return Err(StdError::generic_err(
+            "Provided spread amount exceeds allowed limit",
+        ));
+    }
+
+    if let Some(belief_price) = belief_price {
+        let expected_return = offer_amount
+            * belief_price
+                .inv()
+                .ok_or_else(|| StdError::generic_err("Belief price must not be zero!"))?;
+        let spread_amount = expected_return.saturating_sub(return_amount);
+
+        if return_amount < expected_return
+            && Decimal::from_ratio(spread_amount, expected_return) > max_spread
+        {
+            return Err(StdError::generic_err("Operation exceeds max spread limit"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Swaps the offer coin at a fixed rate. The adapter must hold enough ask coins.
+fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: NativeSwapAdapterExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        NativeSwapAdapterExecuteMsg::Swap {
+            ask_denom,
+            belief_price,
+            max_spread,
+            to,
+        } => {
+            let offer = match info.funds.as_slice() {
+                [coin] => coin,
+                _ => return Err(StdError::generic_err("Exactly one coin must be sent")),
+            };
+            let return_amount = offer.amount * load_rate(deps.storage)?;
+            assert_max_spread(belief_price, max_spread, offer.amount, return_amount)?;
+
+            Ok(Response::new().add_message(BankMsg::Send {
+                to_address: to.unwrap_or_else(|| info.sender.to_string()),
+                amount: coins(return_amount.u128(), ask_denom),
+            }))
+        }
+    }
+}
+
+fn query(deps: Deps, _env: Env, msg: NativeSwapAdapterQueryMsg) -> StdResult<Binary> {
+    let rate = load_rate(deps.storage)?;
+
+    match msg {
+        NativeSwapAdapterQueryMsg::Simulation { offer, .. } => to_binary(&SimulationResponse {
+            return_amount: offer.amount * rate,
+            spread_amount: Uint128::zero(),
+            commission_amount: Uint128::zero(),
+        }),
+        NativeSwapAdapterQueryMsg::ReverseSimulation { ask, .. } => {
+            to_binary(&ReverseSimulationResponse {
+                offer_amount: ask
+                    .amount
+                    .multiply_ratio(rate.denominator(), rate.numerator()),
+                spread_amount: Uint128::zero(),
+                commission_amount: Uint128::zero(),
+            })
+        }
+    }
+}
+
+pub fn store_code<B, A, S, C, X, D, I, G>(app: &WKApp<B, A, S, C, X, D, I, G>) -> u64
+where
+    B: Bank,
+    A: Api,
+    S: Storage,
+    C: Module,
+    X: Staking,
+    D: Distribution,
+    I: Ibc,
+    G: Gov,
+    C::ExecT: Clone + Debug + PartialEq + JsonSchema + DeserializeOwned + 'static,
+    C::QueryT: CustomQuery + DeserializeOwned + 'static,
+{
+    let contract = Box::new(ContractWrapper::new_with_empty(execute, instantiate, query));
+
+    app.borrow_mut().store_code(contract)
+}
+
+pub struct MockNativeSwapAdapterBuilder<B, A, S, C: Module, X, D, I, G> {
+    pub app: WKApp<B, A, S, C, X, D, I, G>,
+    pub rate: Decimal,
+}
+
+impl<B, A, S, C, X, D, I, G> MockNativeSwapAdapterBuilder<B, A, S, C, X, D, I, G>
+where
+    B: Bank,
+    A: Api,
+    S: Storage,
+    C: Module,
+    X: Staking,
+    D: Distribution,
+    I: Ibc,
+    G: Gov,
+    C::ExecT: Clone + Debug + PartialEq + JsonSchema + DeserializeOwned + 'static,
+    C::QueryT: CustomQuery + DeserializeOwned + 'static,
+{
+    pub fn new(app: &WKApp<B, A, S, C, X, D, I, G>) -> Self {
+        Self {
+            app: app.clone(),
+            rate: Decimal::one(),
+        }
+    }
+
+    pub fn with_rate(mut self, rate: Decimal) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn instantiate(self) -> MockNativeSwapAdapter<B, A, S, C, X, D, I, G> {
+        let code_id = store_code(&self.app);
+        let astroport = astroport_address();
+
+        let address = self
+            .app
+            .borrow_mut()
+            .instantiate_contract(
+                code_id,
+                astroport,
+                &InstantiateMsg { rate: self.rate },
+                &[],
+                "Astroport Native Swap Adapter",
+                Some(ASTROPORT.to_owned()),
+            )
+            .unwrap();
+
+        MockNativeSwapAdapter {
+            app: self.app,
+            address,
+        }
+    }
+}
+
+pub struct MockNativeSwapAdapter<B, A, S, C: Module, X, D, I, G> {
+    pub app: WKApp<B, A, S, C, X, D, I, G>,
+    pub address: Addr,
+}
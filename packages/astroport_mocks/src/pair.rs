@@ -149,6 +149,7 @@ where
                     slippage_tolerance,
                     auto_stake: Some(auto_stake),
                     receiver: receiver.into(),
+                    deadline: None,
                 },
                 &coins,
             )
@@ -116,6 +116,7 @@ where
                             total_fee_bps: 30,
                             maker_fee_bps: 3333,
                             is_generator_disabled: false,
+                            fee_tiers: vec![],
                         },
                     },
                     &[],
@@ -156,6 +157,8 @@ where
                     pair_type: PairType::Custom("concentrated_inj_orderbook".to_owned()),
                     asset_infos: self.asset_infos.to_vec(),
                     init_params: Some(to_binary(init_params.unwrap_or(&default_params)).unwrap()),
+                    fee: None,
+                    fee_tier: None,
                 },
                 &[],
             )
@@ -169,6 +172,8 @@ where
                 &factory.address,
                 &FactoryQueryMsg::Pair {
                     asset_infos: self.asset_infos.to_vec(),
+                    pair_type: None,
+                    fee_tier: None,
                 },
             )
             .unwrap();
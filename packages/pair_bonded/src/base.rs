@@ -1,6 +1,7 @@
 use crate::error::ContractError;
 use crate::state::CONFIG;
 use astroport::asset::{addr_opt_validate, Asset, AssetInfo, PairInfo};
+use astroport::common::is_deadline_passed;
 use astroport::factory::PairType;
 use astroport::pair::{
     ConfigResponse, CumulativePricesResponse, Cw20HookMsg, InstantiateMsg, PoolResponse,
@@ -44,6 +45,7 @@ pub trait PairBonded<'a> {
                 liquidity_token: Addr::unchecked(""),
                 asset_infos: msg.asset_infos.clone(),
                 pair_type: PairType::Custom(String::from("Bonded")),
+                fee_tier: None,
             },
             factory_addr: deps.api.addr_validate(&msg.factory_addr)?,
         };
@@ -99,7 +101,14 @@ pub trait PairBonded<'a> {
                 belief_price,
                 max_spread,
                 to,
-            } => self.execute_swap(deps, env, info, offer_asset, belief_price, max_spread, to),
+                deadline,
+            } => {
+                if is_deadline_passed(&env.block, deadline) {
+                    return Err(ContractError::DeadlineExpired {});
+                }
+
+                self.execute_swap(deps, env, info, offer_asset, belief_price, max_spread, to)
+            }
             ExecuteMsg::AssertAndSend {
                 offer_asset,
                 ask_asset_info,
@@ -168,8 +177,13 @@ pub trait PairBonded<'a> {
                 belief_price,
                 max_spread,
                 to,
+                deadline,
                 ..
             } => {
+                if is_deadline_passed(&env.block, deadline) {
+                    return Err(ContractError::DeadlineExpired {});
+                }
+
                 // Only asset contract can execute this message
                 let mut authorized = false;
                 let config = CONFIG.load(deps.storage)?;
@@ -203,7 +217,9 @@ pub trait PairBonded<'a> {
                     to_addr,
                 )
             }
-            Cw20HookMsg::WithdrawLiquidity { .. } => Err(ContractError::NotSupported {}),
+            Cw20HookMsg::SwapExactOut { .. } | Cw20HookMsg::WithdrawLiquidity { .. } => {
+                Err(ContractError::NotSupported {})
+            }
         }
     }
 
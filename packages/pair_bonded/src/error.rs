@@ -31,6 +31,9 @@ pub enum ContractError {
     #[error("Operation is not supported for this pool.")]
     NotSupported {},
 
+    #[error("Transaction deadline has passed")]
+    DeadlineExpired {},
+
     #[error("Failed to migrate the contract")]
     MigrationError {},
 }